@@ -0,0 +1,14 @@
+fn main() {
+  #[cfg(feature = "grpc")]
+  compile("rlldp.proto");
+  #[cfg(feature = "gnmi")]
+  compile("gnmi.proto");
+}
+
+// protox instead of protoc so building doesn't need anything besides cargo
+#[cfg(any(feature = "grpc", feature = "gnmi"))]
+fn compile(file: &str) {
+  println!("cargo:rerun-if-changed=proto/{file}");
+  let descriptors = protox::compile([file], ["proto"]).unwrap();
+  tonic_build::configure().compile_fds(descriptors).unwrap();
+}
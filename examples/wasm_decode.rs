@@ -0,0 +1,35 @@
+// decode(bytes) for browsers, takes a whole ethernet frame and returns the du as json:
+// {"protocol": "lldp", "source": "..", "system_name": .., "port_id": .., "decoded": ".."} or {"error": ".."}
+
+use rlldp::prelude::*;
+use serde_json::{json, Value};
+
+pub fn decode_json(frame: &[u8]) -> Value {
+  let (frame, du) = match DataUnit::decode_frame(frame) {
+    Ok(x) => x,
+    Err(err) => return json!({ "error": err.to_string() }),
+  };
+
+  json!({
+    "protocol": match frame.protocol {
+      Protocol::Cdp => "cdp",
+      Protocol::Fdp => "fdp",
+      Protocol::Lldp => "lldp",
+      Protocol::Mndp => "mndp",
+      Protocol::Sonmp => "sonmp",
+    },
+    "source": MacAddress(frame.source).to_string(),
+    "ttl": du.time_to_live(),
+    "system_name": du.system_name(),
+    "port_id": du.port_id().map(|x| x.to_string()),
+    "management_address": du.management_address(),
+    "port_vlan_id": du.port_vlan_id(),
+    "decoded": format!("{du:#?}"),
+  })
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub fn decode(frame: &[u8]) -> String {
+  decode_json(frame).to_string()
+}
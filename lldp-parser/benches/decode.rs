@@ -0,0 +1,158 @@
+use std::{borrow::Cow, net::Ipv4Addr};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use lldp_parser::{
+  cdp,
+  lldp::{
+    du::{DataUnit, Org},
+    tlv::{
+      decode_list,
+      org::{
+        dot3::{AutoNegotiationCapability, AutoNegotiationStatus, MacPhyStatus},
+        CustomOrgTlv,
+      },
+      Capabilities, CapabilityFlags, ChassisId, ManagementAddress, ManagementInterfaceKind, NetworkAddress, PortId,
+    },
+  },
+};
+
+const MED: [u8; 3] = [0x00, 0x12, 0xbb];
+const DOT1: [u8; 3] = [0x00, 0x80, 0xc2];
+
+fn custom(org: [u8; 3], subtype: u8, data: &[u8]) -> CustomOrgTlv<'static> {
+  CustomOrgTlv {
+    org,
+    subtype,
+    data: Cow::Owned(data.to_vec()),
+  }
+}
+
+// what a switch port sends with nothing extra turned on
+fn small() -> DataUnit<'static> {
+  DataUnit {
+    chassis_id: ChassisId::MacAddress([0x00, 0x1c, 0x73, 0x11, 0x22, 0x33]),
+    port_id: PortId::InterfaceName("Ethernet12".into()),
+    time_to_live: 120,
+    port_description: Some("uplink to access-3".into()),
+    system_name: Some("leaf-1".into()),
+    system_description: Some("Arista Networks EOS version 4.28.3M running on an Arista DCS-7050SX3-48YC8".into()),
+    capabilities: Some(Capabilities {
+      capabilities: CapabilityFlags::BRIDGE | CapabilityFlags::ROUTER,
+      enabled_capabilities: CapabilityFlags::BRIDGE,
+    }),
+    management_address: vec![ManagementAddress {
+      address: NetworkAddress::Ip(Ipv4Addr::new(10, 0, 0, 1).into()),
+      interface_subtype: ManagementInterfaceKind::IfIndex,
+      interface_number: 999999,
+      oid: "".into(),
+    }],
+    org: Org::default(),
+  }
+}
+
+// a desk phone, most of it lldp-med
+fn phone() -> DataUnit<'static> {
+  let mut du = small();
+  du.chassis_id = ChassisId::NetworkAddress(NetworkAddress::Ip(Ipv4Addr::new(10, 1, 2, 3).into()));
+  du.port_id = PortId::MacAddress([0x00, 0x04, 0xf2, 0xaa, 0xbb, 0xcc]);
+  du.org.dot3.mac_phy_status = Some(MacPhyStatus {
+    status: AutoNegotiationStatus::SUPPORTED | AutoNegotiationStatus::ENABLED,
+    advertised: AutoNegotiationCapability::B_10_BASE_T_FD,
+    mau: 30.into(),
+  });
+  du.org.custom = vec![
+    custom(MED, 1, &[0x00, 0x33, 0x03]),
+    custom(MED, 2, &[0x01, 0x00, 0x64, 0xb8]),
+    custom(MED, 2, &[0x02, 0x00, 0x64, 0xa0]),
+    custom(
+      MED,
+      3,
+      &[
+        0x02, 0x1b, 0x02, 0x55, 0x53, 0x01, 0x02, 0x43, 0x41, 0x03, 0x09, 0x4c, 0x6f,
+      ],
+    ),
+    custom(MED, 4, &[0x53, 0x00, 0x3c]),
+    custom(MED, 5, b"1.0"),
+    custom(MED, 6, b"sip78xx.14-2-1-0001-14"),
+    custom(MED, 7, b"14.2.1"),
+    custom(MED, 8, b"FCH2235ABCD"),
+    custom(MED, 9, b"Cisco Systems, Inc."),
+    custom(MED, 10, b"CP-7841"),
+    custom(MED, 11, b"0"),
+  ];
+  du
+}
+
+// ieee dcbx on a converged nic: ets configuration and recommendation, pfc and application priority
+fn dcbx() -> DataUnit<'static> {
+  let mut du = small();
+  du.org.dot1.port_vlan_id = Some(1);
+  du.org.custom = vec![
+    custom(
+      DOT1,
+      9,
+      &[
+        0x08, 0x00, 0x00, 0x11, 0x11, 0x32, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x02, 0x00, 0x00,
+      ],
+    ),
+    custom(
+      DOT1,
+      10,
+      &[
+        0x00, 0x00, 0x00, 0x11, 0x11, 0x32, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x02, 0x00, 0x00,
+      ],
+    ),
+    custom(DOT1, 11, &[0x08, 0x08]),
+    custom(DOT1, 12, &[0x00, 0x61, 0x89, 0x06, 0x71, 0x0c, 0xbc]),
+  ];
+  du
+}
+
+fn encoded(du: DataUnit<'static>) -> Vec<u8> {
+  let mut buf = Vec::new();
+  du.encode(&mut buf);
+  buf
+}
+
+fn lldp(c: &mut Criterion) {
+  for (name, du) in [("small", small()), ("phone", phone()), ("dcbx", dcbx())] {
+    let buf = encoded(du.clone());
+    c.bench_function(&format!("lldp decode {name}"), |b| {
+      b.iter(|| DataUnit::decode(black_box(&buf)))
+    });
+    c.bench_function(&format!("lldp decode_list {name}"), |b| {
+      b.iter(|| decode_list(black_box(&buf)))
+    });
+    c.bench_function(&format!("lldp encode {name}"), |b| {
+      b.iter_batched(
+        || (du.clone(), Vec::with_capacity(buf.len())),
+        |(du, mut out)| {
+          du.encode(&mut out);
+          out
+        },
+        BatchSize::SmallInput,
+      )
+    });
+  }
+}
+
+fn cdp(c: &mut Criterion) {
+  let du = cdp::DataUnit {
+    time_to_live: 180,
+    device_id: Some("access-3.example.net".into()),
+    addresses: vec![Ipv4Addr::new(10, 0, 0, 3).into()],
+    capabilities: Some(cdp::tlv::CapabilityFlags::SWITCH | cdp::tlv::CapabilityFlags::IGMP),
+    software_version: Some("Cisco IOS Software, C2960X Software (C2960X-UNIVERSALK9-M), Version 15.2(7)E4".into()),
+    platform: Some("cisco WS-C2960X-48FPD-L".into()),
+    port_id: Some("GigabitEthernet1/0/24".into()),
+    duplex: Some(cdp::tlv::Duplex::Full),
+    native_vlan: Some(100),
+  };
+  let mut buf = Vec::new();
+  du.encode(&mut buf);
+
+  c.bench_function("cdp decode", |b| b.iter(|| cdp::DataUnit::decode(black_box(&buf))));
+}
+
+criterion_group!(benches, lldp, cdp);
+criterion_main!(benches);
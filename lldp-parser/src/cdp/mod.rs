@@ -1,14 +1,18 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, net::IpAddr};
 
 use thiserror::Error;
 use tracing::warn;
 
-use self::tlv::{Duplex, RawTlvError};
-use crate::cdp::tlv::{RawTlv, Tlv};
+use self::tlv::{CapabilityFlags, Duplex, RawTlvError};
+use crate::{
+  cdp::tlv::{RawTlv, Tlv},
+  DecodeReport,
+};
 
 pub mod tlv;
 
 #[derive(Debug, Clone, Error)]
+#[non_exhaustive]
 pub enum DataUnitError {
   #[error("buffer too short")]
   BufferTooShort,
@@ -22,6 +26,8 @@ pub enum DataUnitError {
 pub struct DataUnit<'a> {
   pub time_to_live: u8,
   pub device_id: Option<Cow<'a, str>>,
+  pub addresses: Vec<IpAddr>,
+  pub capabilities: Option<CapabilityFlags>,
   pub software_version: Option<Cow<'a, str>>,
   pub platform: Option<Cow<'a, str>>,
   pub port_id: Option<Cow<'a, str>>,
@@ -34,6 +40,8 @@ impl<'a> DataUnit<'a> {
     DataUnit {
       time_to_live: self.time_to_live,
       device_id: self.device_id.map(|x| Cow::Owned(x.into_owned())),
+      addresses: self.addresses,
+      capabilities: self.capabilities,
       software_version: self.software_version.map(|x| Cow::Owned(x.into_owned())),
       platform: self.platform.map(|x| Cow::Owned(x.into_owned())),
       port_id: self.port_id.map(|x| Cow::Owned(x.into_owned())),
@@ -43,6 +51,10 @@ impl<'a> DataUnit<'a> {
   }
 
   pub fn decode(buf: &'a [u8]) -> Result<Self, DataUnitError> {
+    Self::decode_with_report(buf).map(|(du, _)| du)
+  }
+
+  pub fn decode_with_report(buf: &'a [u8]) -> Result<(Self, DecodeReport), DataUnitError> {
     if buf.len() < 4 {
       return Err(DataUnitError::BufferTooShort);
     }
@@ -59,6 +71,8 @@ impl<'a> DataUnit<'a> {
     let mut du = Self {
       time_to_live,
       device_id: None,
+      addresses: Vec::new(),
+      capabilities: None,
       software_version: None,
       platform: None,
       port_id: None,
@@ -66,11 +80,14 @@ impl<'a> DataUnit<'a> {
       native_vlan: None,
     };
 
+    let mut report = DecodeReport::default();
     let mut buf = &buf[4..];
     while !buf.is_empty() {
       let raw = RawTlv::decode(buf)?;
       buf = &buf[raw.total_len()..];
-      match Tlv::decode(raw) {
+      let result = Tlv::decode(raw);
+      report.record(&result);
+      match result {
         Ok(Tlv::DeviceId(new)) => {
           if let Some(old) = du.device_id.take() {
             warn!(?old, ?new, "duplicate device id");
@@ -78,6 +95,15 @@ impl<'a> DataUnit<'a> {
           du.device_id = Some(new);
         }
 
+        Ok(Tlv::Addresses(new)) => du.addresses.extend(new),
+
+        Ok(Tlv::Capabilities(new)) => {
+          if let Some(old) = du.capabilities.take() {
+            warn!(?old, ?new, "duplicate capabilities");
+          }
+          du.capabilities = Some(new);
+        }
+
         Ok(Tlv::PortId(new)) => {
           if let Some(old) = du.port_id.take() {
             warn!(?old, ?new, "duplicate port id");
@@ -117,6 +143,89 @@ impl<'a> DataUnit<'a> {
       }
     }
 
-    Ok(du)
+    Ok((du, report))
   }
+
+  pub fn encode(self, buf: &mut Vec<u8>) {
+    let start = buf.len();
+    buf.extend([2, self.time_to_live, 0, 0]);
+
+    let tlvs = [
+      self.device_id.map(Tlv::DeviceId),
+      (!self.addresses.is_empty()).then_some(Tlv::Addresses(self.addresses)),
+      self.port_id.map(Tlv::PortId),
+      self.capabilities.map(Tlv::Capabilities),
+      self.software_version.map(Tlv::SoftwareVersion),
+      self.platform.map(Tlv::Platform),
+      self.native_vlan.map(Tlv::NativeVlan),
+      self.duplex.map(Tlv::Duplex),
+    ];
+
+    for x in tlvs.into_iter().flatten() {
+      x.encode(buf);
+    }
+
+    let checksum = checksum(&buf[start..]);
+    buf[start + 2..start + 4].copy_from_slice(&checksum.to_be_bytes());
+  }
+}
+
+// the ip checksum, except cisco sign extends a trailing odd byte
+pub fn checksum(buf: &[u8]) -> u16 {
+  let mut chunks = buf.chunks_exact(2);
+  let mut sum: u32 = chunks.by_ref().map(|x| u16::from_be_bytes([x[0], x[1]]) as u32).sum();
+
+  if let [last] = chunks.remainder() {
+    if last & 0x80 != 0 {
+      sum += 0xff00 + *last as u32 - 1;
+    } else {
+      sum += (*last as u32) << 8;
+    }
+  }
+
+  while sum > 0xffff {
+    sum = (sum >> 16) + (sum & 0xffff);
+  }
+  !(sum as u16)
+}
+
+#[test]
+fn encode_decode() {
+  use std::net::Ipv4Addr;
+
+  let du = DataUnit {
+    time_to_live: 180,
+    device_id: Some("switch".into()),
+    addresses: vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))],
+    capabilities: Some(CapabilityFlags::SWITCH | CapabilityFlags::IGMP),
+    software_version: Some("version".into()),
+    platform: Some("platform".into()),
+    port_id: Some("GigabitEthernet0/1".into()),
+    duplex: Some(Duplex::Full),
+    native_vlan: Some(10),
+  };
+
+  let mut buf = Vec::new();
+  du.clone().encode(&mut buf);
+  assert_eq!(DataUnit::decode(&buf).unwrap(), du);
+  // a correct checksum sums to zero
+  assert_eq!(checksum(&buf), 0);
+}
+
+#[test]
+fn reports_skipped_tlvs() {
+  let du = DataUnit {
+    device_id: Some("switch".into()),
+    ..crate::fixtures::cdp_du()
+  };
+
+  let mut buf = Vec::new();
+  du.clone().encode(&mut buf);
+  // vtp domain, which isn't decoded, and a native vlan one byte short
+  buf.extend([0x00, 0x09, 0x00, 0x05, b'a']);
+  buf.extend([0x00, 0x0a, 0x00, 0x05, 0x01]);
+
+  let (decoded, report) = DataUnit::decode_with_report(&buf).unwrap();
+  assert_eq!(decoded, du);
+  assert_eq!((report.tlvs_total, report.tlvs_unknown, report.tlvs_errored), (3, 1, 1));
 }
@@ -1,46 +1,39 @@
-use std::{borrow::Cow, cmp::Ordering};
+use std::{
+  borrow::Cow,
+  net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
 
-use thiserror::Error;
+use bitflags::bitflags;
 
-#[derive(Debug, Clone, Error)]
-pub enum RawTlvError {
-  #[error("buffer too short")]
-  BufferTooShort,
-}
-
-#[derive(Debug, Clone, Error)]
-pub enum TlvDecodeError {
-  #[error("buffer too short")]
-  BufferTooShort,
-  #[error("buffer too long")]
-  BufferTooLong,
-  #[error("bytes after end")]
-  BytesAfterEnd,
-  #[error("unknown tlv '{0}'")]
-  UnknownTlv(u16),
-}
+use crate::error::exact;
+pub use crate::error::{RawTlvError, TlvDecodeError};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TlvKind {
   DeviceId,
+  Addresses,
   PortId,
+  Capabilities,
   SoftwareVersion,
   Platform,
   NativeVlan,
   Duplex,
+  // everything cisco hasn't documented or we don't decode, kept so it round-trips
+  Unknown(u16),
 }
 
-impl TryFrom<u16> for TlvKind {
-  type Error = u16;
-  fn try_from(value: u16) -> Result<Self, u16> {
+impl From<u16> for TlvKind {
+  fn from(value: u16) -> Self {
     match value {
-      0x0001 => Ok(Self::DeviceId),
-      0x0003 => Ok(Self::PortId),
-      0x0005 => Ok(Self::SoftwareVersion),
-      0x0006 => Ok(Self::Platform),
-      0x000a => Ok(Self::NativeVlan),
-      0x000b => Ok(Self::Duplex),
-      x => Err(x),
+      0x0001 => Self::DeviceId,
+      0x0002 => Self::Addresses,
+      0x0003 => Self::PortId,
+      0x0004 => Self::Capabilities,
+      0x0005 => Self::SoftwareVersion,
+      0x0006 => Self::Platform,
+      0x000a => Self::NativeVlan,
+      0x000b => Self::Duplex,
+      x => Self::Unknown(x),
     }
   }
 }
@@ -49,11 +42,14 @@ impl From<TlvKind> for u16 {
   fn from(value: TlvKind) -> Self {
     match value {
       TlvKind::DeviceId => 0x0001,
+      TlvKind::Addresses => 0x0002,
       TlvKind::PortId => 0x0003,
+      TlvKind::Capabilities => 0x0004,
       TlvKind::SoftwareVersion => 0x0005,
       TlvKind::Platform => 0x0006,
       TlvKind::NativeVlan => 0x000a,
       TlvKind::Duplex => 0x000b,
+      TlvKind::Unknown(x) => x,
     }
   }
 }
@@ -65,21 +61,27 @@ pub struct RawTlv<'a> {
 }
 
 impl<'a> RawTlv<'a> {
-  pub(super) fn total_len(&self) -> usize {
+  pub fn total_len(&self) -> usize {
     self.payload.len() + 4
   }
 
-  pub(super) fn decode(buf: &'a [u8]) -> Result<Self, RawTlvError> {
+  pub fn decode(buf: &'a [u8]) -> Result<Self, RawTlvError> {
     if buf.len() < 4 {
       return Err(RawTlvError::BufferTooShort);
     }
 
     let ty = u16::from_be_bytes(buf[0..2].try_into().unwrap());
     let len = u16::from_be_bytes(buf[2..4].try_into().unwrap());
-    let len = (len as usize) - 4;
+    // the length includes the header, so anything shorter is malformed
+    let Some(len) = (len as usize).checked_sub(4) else {
+      return Err(RawTlvError::InvalidLength(len.into()));
+    };
 
-    if buf.len() < len {
-      return Err(RawTlvError::BufferTooShort);
+    if buf.len() < 4 + len {
+      return Err(RawTlvError::Truncated {
+        len,
+        available: buf.len() - 4,
+      });
     }
 
     let payload = &buf[4..4 + len];
@@ -91,13 +93,86 @@ impl<'a> RawTlv<'a> {
 #[derive(Debug, Clone)]
 pub enum Tlv<'a> {
   DeviceId(Cow<'a, str>),
+  Addresses(Vec<IpAddr>),
   PortId(Cow<'a, str>),
+  Capabilities(CapabilityFlags),
   SoftwareVersion(Cow<'a, str>),
   Platform(Cow<'a, str>),
   NativeVlan(u16),
   Duplex(Duplex),
 }
 
+bitflags! {
+  #[repr(transparent)]
+  #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  pub struct CapabilityFlags: u32 {
+    const ROUTER              = 0x01;
+    const TRANSPARENT_BRIDGE  = 0x02;
+    const SOURCE_ROUTE_BRIDGE = 0x04;
+    const SWITCH              = 0x08;
+    const HOST                = 0x10;
+    const IGMP                = 0x20;
+    const REPEATER            = 0x40;
+  }
+}
+
+const CAPABILITY_NAMES: [(CapabilityFlags, &str); 7] = [
+  (CapabilityFlags::ROUTER, "Router"),
+  (CapabilityFlags::TRANSPARENT_BRIDGE, "Transparent Bridge"),
+  (CapabilityFlags::SOURCE_ROUTE_BRIDGE, "Source Route Bridge"),
+  (CapabilityFlags::SWITCH, "Switch"),
+  (CapabilityFlags::HOST, "Host"),
+  (CapabilityFlags::IGMP, "IGMP"),
+  (CapabilityFlags::REPEATER, "Repeater"),
+];
+
+impl CapabilityFlags {
+  // in bit order, bits cisco hasn't named are skipped
+  pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+    CAPABILITY_NAMES
+      .iter()
+      .filter(|(flag, _)| self.contains(*flag))
+      .map(|(_, name)| *name)
+  }
+}
+
+// protocol type 1 is an nlpid, 2 is an 802.2 llc/snap header
+const ADDRESS_NLPID_IPV4: (u8, &[u8]) = (1, &[0xcc]);
+const ADDRESS_SNAP_IPV6: (u8, &[u8]) = (2, &[0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00, 0x86, 0xdd]);
+
+fn decode_addresses(buf: &[u8]) -> Result<Vec<IpAddr>, TlvDecodeError> {
+  if buf.len() < 4 {
+    return Err(TlvDecodeError::BufferTooShort);
+  }
+
+  let count = u32::from_be_bytes(buf[0..4].try_into().unwrap());
+  let mut buf = &buf[4..];
+  let mut out = Vec::new();
+  for _ in 0..count {
+    let (&ty, rest) = buf.split_first().ok_or(TlvDecodeError::BufferTooShort)?;
+    let (&proto_len, rest) = rest.split_first().ok_or(TlvDecodeError::BufferTooShort)?;
+    let proto = rest.get(..proto_len as usize).ok_or(TlvDecodeError::BufferTooShort)?;
+    let rest = &rest[proto_len as usize..];
+    let addr_len = rest.get(..2).ok_or(TlvDecodeError::BufferTooShort)?;
+    let addr_len = u16::from_be_bytes(addr_len.try_into().unwrap()) as usize;
+    let addr = rest.get(2..2 + addr_len).ok_or(TlvDecodeError::BufferTooShort)?;
+    buf = &rest[2 + addr_len..];
+
+    // anything that isn't ip, like decnet or appletalk, is skipped
+    if (ty, proto) == ADDRESS_NLPID_IPV4 && addr_len == 4 {
+      out.push(IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(addr).unwrap())));
+    } else if (ty, proto) == ADDRESS_SNAP_IPV6 && addr_len == 16 {
+      out.push(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(addr).unwrap())));
+    }
+  }
+
+  if !buf.is_empty() {
+    return Err(TlvDecodeError::BytesAfterEnd);
+  }
+
+  Ok(out)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Duplex {
   Half,
@@ -105,29 +180,111 @@ pub enum Duplex {
 }
 
 impl<'a> Tlv<'a> {
-  pub(super) fn decode(raw: RawTlv<'a>) -> Result<Self, TlvDecodeError> {
-    let kind = raw.ty.try_into().map_err(TlvDecodeError::UnknownTlv)?;
-    match kind {
+  pub fn decode(raw: RawTlv<'a>) -> Result<Self, TlvDecodeError> {
+    match TlvKind::from(raw.ty) {
       TlvKind::DeviceId => Ok(Self::DeviceId(String::from_utf8_lossy(raw.payload))),
+      TlvKind::Addresses => Ok(Self::Addresses(decode_addresses(raw.payload)?)),
       TlvKind::PortId => Ok(Self::PortId(String::from_utf8_lossy(raw.payload))),
+      TlvKind::Capabilities => Ok(Self::Capabilities(CapabilityFlags::from_bits_retain(
+        u32::from_be_bytes(exact(raw.payload)?),
+      ))),
       TlvKind::SoftwareVersion => Ok(Self::SoftwareVersion(String::from_utf8_lossy(raw.payload))),
       TlvKind::Platform => Ok(Self::Platform(String::from_utf8_lossy(raw.payload))),
-      TlvKind::NativeVlan => match raw.payload.len().cmp(&2) {
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Equal => Ok(Self::NativeVlan(u16::from_be_bytes(raw.payload.try_into().unwrap()))),
+      TlvKind::NativeVlan => Ok(Self::NativeVlan(u16::from_be_bytes(exact(raw.payload)?))),
+      TlvKind::Duplex => match exact(raw.payload)? {
+        [0] => Ok(Self::Duplex(Duplex::Half)),
+        _ => Ok(Self::Duplex(Duplex::Full)),
       },
-      TlvKind::Duplex => match raw.payload.len().cmp(&1) {
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Equal => {
-          if raw.payload[0] == 0 {
-            Ok(Self::Duplex(Duplex::Half))
-          } else {
-            Ok(Self::Duplex(Duplex::Full))
-          }
+      TlvKind::Unknown(x) => Err(TlvDecodeError::UnknownTlv(x)),
+    }
+  }
+
+  pub fn kind(&self) -> TlvKind {
+    match self {
+      Self::DeviceId(_) => TlvKind::DeviceId,
+      Self::Addresses(_) => TlvKind::Addresses,
+      Self::PortId(_) => TlvKind::PortId,
+      Self::Capabilities(_) => TlvKind::Capabilities,
+      Self::SoftwareVersion(_) => TlvKind::SoftwareVersion,
+      Self::Platform(_) => TlvKind::Platform,
+      Self::NativeVlan(_) => TlvKind::NativeVlan,
+      Self::Duplex(_) => TlvKind::Duplex,
+    }
+  }
+
+  pub fn encoded_size(&self) -> usize {
+    match self {
+      Self::DeviceId(x) | Self::PortId(x) | Self::SoftwareVersion(x) | Self::Platform(x) => x.len(),
+      Self::Addresses(x) => {
+        4 + x.iter().fold(0, |acc, x| match x {
+          IpAddr::V4(_) => acc + 2 + ADDRESS_NLPID_IPV4.1.len() + 2 + 4,
+          IpAddr::V6(_) => acc + 2 + ADDRESS_SNAP_IPV6.1.len() + 2 + 16,
+        })
+      }
+      Self::Capabilities(_) => 4,
+      Self::NativeVlan(_) => 2,
+      Self::Duplex(_) => 1,
+    }
+  }
+
+  pub fn encode(&self, buf: &mut Vec<u8>) {
+    let ty: u16 = self.kind().into();
+    let len = self.encoded_size();
+    buf.reserve(len + 4);
+
+    // unlike lldp, the length includes the header
+    buf.extend(ty.to_be_bytes());
+    buf.extend((len as u16 + 4).to_be_bytes());
+
+    match self {
+      Self::DeviceId(x) | Self::PortId(x) | Self::SoftwareVersion(x) | Self::Platform(x) => buf.extend(x.as_bytes()),
+      Self::Addresses(x) => {
+        buf.extend((x.len() as u32).to_be_bytes());
+        for addr in x {
+          let ((ty, proto), octets) = match addr {
+            IpAddr::V4(x) => (ADDRESS_NLPID_IPV4, x.octets().to_vec()),
+            IpAddr::V6(x) => (ADDRESS_SNAP_IPV6, x.octets().to_vec()),
+          };
+          buf.push(ty);
+          buf.push(proto.len() as u8);
+          buf.extend(proto);
+          buf.extend((octets.len() as u16).to_be_bytes());
+          buf.extend(octets);
         }
-      },
+      }
+      Self::Capabilities(x) => buf.extend(x.bits().to_be_bytes()),
+      Self::NativeVlan(x) => buf.extend(x.to_be_bytes()),
+      Self::Duplex(x) => buf.push(match x {
+        Duplex::Half => 0,
+        Duplex::Full => 1,
+      }),
     }
   }
 }
+
+#[test]
+fn encode_decode_addresses() {
+  let tlv = Tlv::Addresses(vec![
+    IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+    IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+  ]);
+
+  let mut buf = Vec::new();
+  tlv.encode(&mut buf);
+  assert_eq!(buf.len(), tlv.encoded_size() + 4);
+
+  let raw = RawTlv::decode(&buf).unwrap();
+  let Ok(Tlv::Addresses(decoded)) = Tlv::decode(raw) else {
+    panic!("expected addresses");
+  };
+  let Tlv::Addresses(expected) = tlv else { unreachable!() };
+  assert_eq!(decoded, expected);
+}
+
+#[test]
+fn kinds_round_trip() {
+  for x in [0x0001, 0x0009, 0x000b, 0x0016, 0xffff] {
+    assert_eq!(u16::from(TlvKind::from(x)), x);
+  }
+  assert_eq!(TlvKind::from(0x0009), TlvKind::Unknown(0x0009));
+}
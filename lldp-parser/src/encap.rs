@@ -0,0 +1,107 @@
+pub const ETHER_TYPE_IPV4: u16 = 0x0800;
+pub const ETHER_TYPE_IPV6: u16 = 0x86dd;
+
+const IP_PROTO_GRE: u8 = 47;
+
+const GRE_PROTO_TEB: u16 = 0x6558;
+const GRE_PROTO_ERSPAN_II: u16 = 0x88be;
+const GRE_PROTO_ERSPAN_III: u16 = 0x22eb;
+
+const GRE_FLAG_CHECKSUM: u16 = 0x8000;
+const GRE_FLAG_KEY: u16 = 0x2000;
+const GRE_FLAG_SEQUENCE: u16 = 0x1000;
+
+// returns the mirrored ethernet frame carried in a gre or erspan packet
+pub fn decapsulate(ether_type: u16, buf: &[u8]) -> Option<&[u8]> {
+  let gre = match ether_type {
+    ETHER_TYPE_IPV4 => {
+      let header_len = (*buf.first()? & 0x0f) as usize * 4;
+      if buf.len() < 20 || header_len < 20 || buf[9] != IP_PROTO_GRE {
+        return None;
+      }
+      buf.get(header_len..)?
+    }
+    // extension headers aren't followed, mirror sessions don't use them
+    ETHER_TYPE_IPV6 => {
+      if buf.len() < 40 || buf[6] != IP_PROTO_GRE {
+        return None;
+      }
+      &buf[40..]
+    }
+    _ => return None,
+  };
+
+  if gre.len() < 4 {
+    return None;
+  }
+
+  let flags = u16::from_be_bytes(gre[0..2].try_into().unwrap());
+  let proto = u16::from_be_bytes(gre[2..4].try_into().unwrap());
+  let mut offset = 4;
+  for flag in [GRE_FLAG_CHECKSUM, GRE_FLAG_KEY, GRE_FLAG_SEQUENCE] {
+    if flags & flag != 0 {
+      offset += 4;
+    }
+  }
+  let payload = gre.get(offset..)?;
+
+  match proto {
+    GRE_PROTO_TEB => Some(payload),
+    // type i has no sequence number and no erspan header, type ii always has both
+    GRE_PROTO_ERSPAN_II if flags & GRE_FLAG_SEQUENCE == 0 => Some(payload),
+    GRE_PROTO_ERSPAN_II => payload.get(8..),
+    GRE_PROTO_ERSPAN_III => {
+      let header = payload.get(..12)?;
+      // the o flag announces an 8 byte platform specific subheader
+      if header[11] & 0x01 != 0 {
+        payload.get(20..)
+      } else {
+        payload.get(12..)
+      }
+    }
+    _ => None,
+  }
+}
+
+#[test]
+fn decapsulate_erspan() {
+  let inner = [0xaa; 14];
+
+  let mut ipv4 = vec![
+    0x45,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    64,
+    IP_PROTO_GRE,
+    0,
+    0,
+    10,
+    0,
+    0,
+    1,
+    10,
+    0,
+    0,
+    2,
+  ];
+  ipv4.extend_from_slice(&[0x10, 0x00, 0x88, 0xbe, 0, 0, 0, 1]);
+  ipv4.extend_from_slice(&[0x10, 0x01, 0x00, 0x01, 0, 0, 0, 0]);
+  ipv4.extend_from_slice(&inner);
+  assert_eq!(decapsulate(ETHER_TYPE_IPV4, &ipv4), Some(&inner[..]));
+
+  let mut ipv6 = vec![0x60, 0, 0, 0, 0, 0, IP_PROTO_GRE, 64];
+  ipv6.extend_from_slice(&[0; 32]);
+  ipv6.extend_from_slice(&[0x00, 0x00, 0x22, 0xeb]);
+  ipv6.extend_from_slice(&[0x20, 0x01, 0x00, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+  ipv6.extend_from_slice(&[0; 8]);
+  ipv6.extend_from_slice(&inner);
+  assert_eq!(decapsulate(ETHER_TYPE_IPV6, &ipv6), Some(&inner[..]));
+
+  ipv4[9] = 17;
+  assert_eq!(decapsulate(ETHER_TYPE_IPV4, &ipv4), None);
+}
@@ -0,0 +1,65 @@
+use thiserror::Error;
+
+// lldp and cdp tlvs fail the same ways, both tlv modules re-export these
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum RawTlvError {
+  #[error("buffer too short")]
+  BufferTooShort,
+  #[error("tlv needs {len} bytes but only {available} are left")]
+  Truncated { len: usize, available: usize },
+  #[error("tlv length {0} is shorter than its header")]
+  InvalidLength(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum TlvDecodeError {
+  #[error("buffer too short")]
+  BufferTooShort,
+  #[error("buffer too long")]
+  BufferTooLong,
+  #[error("bytes after end")]
+  BytesAfterEnd,
+  #[error("expected {expected} bytes, got {actual}")]
+  WrongLength { expected: usize, actual: usize },
+  #[error("unknown chassis id subtype '{0}'")]
+  UnknownChassisIdSubtype(u8),
+  #[error("unknown port id subtype '{0}'")]
+  UnknownPortIdSubtype(u8),
+  #[error("unknown management interface subtype '{0}'")]
+  UnknownManagementInterfaceSubtype(u8),
+  #[error("unknown tlv '{0}'")]
+  UnknownTlv(u16),
+  #[error("unknown subtype '{subtype}' of organization {:02x}-{:02x}-{:02x}", .oui[0], .oui[1], .oui[2])]
+  UnknownOrgSubtype { oui: [u8; 3], subtype: u8 },
+  #[error("unsupported organization {:02x}-{:02x}-{:02x}", .0[0], .0[1], .0[2])]
+  UnsupportedOui([u8; 3]),
+}
+
+// for the fixed size values, which are wrong whichever way the length is off
+pub(crate) fn exact<const N: usize>(buf: &[u8]) -> Result<[u8; N], TlvDecodeError> {
+  buf.try_into().map_err(|_| TlvDecodeError::WrongLength {
+    expected: N,
+    actual: buf.len(),
+  })
+}
+
+#[test]
+fn describes_errors() {
+  let err = TlvDecodeError::UnknownOrgSubtype {
+    oui: [0x00, 0x80, 0xc2],
+    subtype: 9,
+  };
+  assert_eq!(err.to_string(), "unknown subtype '9' of organization 00-80-c2");
+  assert_eq!(
+    TlvDecodeError::UnsupportedOui([0x00, 0x12, 0xbb]).to_string(),
+    "unsupported organization 00-12-bb"
+  );
+  assert_eq!(exact::<2>(&[1, 2]), Ok([1, 2]));
+  assert_eq!(
+    exact::<2>(&[1, 2, 3]),
+    Err(TlvDecodeError::WrongLength { expected: 2, actual: 3 })
+  );
+}
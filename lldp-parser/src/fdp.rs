@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+
+use thiserror::Error;
+use tracing::warn;
+
+use crate::{
+  cdp::tlv::{RawTlv, RawTlvError, Tlv, TlvDecodeError},
+  error::exact,
+};
+
+// fdp reuses the cdp tlv layout, but only shares the string tlvs with it
+const TLV_DEVICE_ID: u16 = 0x0001;
+const TLV_PORT_ID: u16 = 0x0003;
+const TLV_SOFTWARE_VERSION: u16 = 0x0005;
+const TLV_PLATFORM: u16 = 0x0006;
+const TLV_TAG: u16 = 0x0102;
+
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum DataUnitError {
+  #[error("buffer too short")]
+  BufferTooShort,
+  #[error("unknown fdp version '{0}'")]
+  UnknownFdpVersion(u8),
+  #[error("failed to decode tlv: '{0}'")]
+  RawTlvError(#[from] RawTlvError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataUnit<'a> {
+  pub time_to_live: u8,
+  pub device_id: Option<Cow<'a, str>>,
+  pub software_version: Option<Cow<'a, str>>,
+  pub platform: Option<Cow<'a, str>>,
+  pub port_id: Option<Cow<'a, str>>,
+  pub native_vlan: Option<u16>,
+}
+
+fn decode_tag(payload: &[u8]) -> Result<u16, TlvDecodeError> {
+  Ok(u16::from_be_bytes(exact(payload)?))
+}
+
+fn set<T: std::fmt::Debug>(field: &mut Option<T>, new: T, name: &str) {
+  if let Some(old) = field.take() {
+    warn!(?old, ?new, "duplicate {name}");
+  }
+  *field = Some(new);
+}
+
+impl<'a> DataUnit<'a> {
+  pub fn to_static(self) -> DataUnit<'static> {
+    DataUnit {
+      time_to_live: self.time_to_live,
+      device_id: self.device_id.map(|x| Cow::Owned(x.into_owned())),
+      software_version: self.software_version.map(|x| Cow::Owned(x.into_owned())),
+      platform: self.platform.map(|x| Cow::Owned(x.into_owned())),
+      port_id: self.port_id.map(|x| Cow::Owned(x.into_owned())),
+      native_vlan: self.native_vlan,
+    }
+  }
+
+  pub fn decode(buf: &'a [u8]) -> Result<Self, DataUnitError> {
+    if buf.len() < 4 {
+      return Err(DataUnitError::BufferTooShort);
+    }
+
+    let version = buf[0];
+    if version != 1 {
+      return Err(DataUnitError::UnknownFdpVersion(version));
+    }
+
+    let mut du = Self {
+      time_to_live: buf[1],
+      device_id: None,
+      software_version: None,
+      platform: None,
+      port_id: None,
+      native_vlan: None,
+    };
+
+    let mut buf = &buf[4..];
+    while !buf.is_empty() {
+      let raw = RawTlv::decode(buf)?;
+      buf = &buf[raw.total_len()..];
+      match raw.ty {
+        TLV_DEVICE_ID | TLV_PORT_ID | TLV_SOFTWARE_VERSION | TLV_PLATFORM => match Tlv::decode(raw) {
+          Ok(Tlv::DeviceId(new)) => set(&mut du.device_id, new, "device id"),
+          Ok(Tlv::PortId(new)) => set(&mut du.port_id, new, "port id"),
+          Ok(Tlv::SoftwareVersion(new)) => set(&mut du.software_version, new, "software version"),
+          Ok(Tlv::Platform(new)) => set(&mut du.platform, new, "platform"),
+          Ok(_) => unreachable!(),
+          Err(err) => warn!(%err, "failed to decode tlv"),
+        },
+        TLV_TAG => match decode_tag(raw.payload) {
+          Ok(new) => set(&mut du.native_vlan, new, "native vlan"),
+          Err(err) => warn!(%err, "failed to decode tlv"),
+        },
+        // net address, capabilities and vlan map
+        _ => {}
+      }
+    }
+
+    Ok(du)
+  }
+}
+
+#[test]
+fn decode_fdp() {
+  let mut buf = vec![1, 180, 0, 0];
+  for (ty, payload) in [
+    (TLV_DEVICE_ID, &b"icx7150"[..]),
+    (TLV_PORT_ID, b"ethernet1/1/1"),
+    (0x0002, &[0, 0, 0, 1, 0xcc, 4, 0, 0, 0, 0, 0, 0, 0, 0]),
+    (TLV_PLATFORM, b"ICX7150-24P"),
+    (TLV_TAG, &[0, 100]),
+  ] {
+    buf.extend_from_slice(&ty.to_be_bytes());
+    buf.extend_from_slice(&(payload.len() as u16 + 4).to_be_bytes());
+    buf.extend_from_slice(payload);
+  }
+
+  let du = DataUnit::decode(&buf).unwrap();
+  assert_eq!(du.time_to_live, 180);
+  assert_eq!(du.device_id.as_deref(), Some("icx7150"));
+  assert_eq!(du.port_id.as_deref(), Some("ethernet1/1/1"));
+  assert_eq!(du.platform.as_deref(), Some("ICX7150-24P"));
+  assert_eq!(du.software_version, None);
+  assert_eq!(du.native_vlan, Some(100));
+
+  buf[0] = 2;
+  assert!(DataUnit::decode(&buf).is_err());
+}
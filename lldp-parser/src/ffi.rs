@@ -0,0 +1,68 @@
+// the decode api for uniffi, as flat owned records since the borrowed du types can't cross the boundary.
+// bindings are generated from the built library:
+//   cargo rustc -p lldp-parser --features uniffi --crate-type cdylib
+//   uniffi-bindgen generate --library target/debug/liblldp_parser.so --language kotlin --out-dir out
+
+use thiserror::Error;
+
+use crate::{DataUnit, Protocol};
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct Neighbor {
+  pub protocol: Protocol,
+  // source mac of the frame, aa:bb:cc:dd:ee:ff
+  pub source: String,
+  // lldp only, the other protocols identify themselves by system name
+  pub chassis_id: Option<String>,
+  pub port_id: Option<String>,
+  pub system_name: Option<String>,
+  pub management_address: Option<String>,
+  pub time_to_live: u16,
+  pub port_vlan_id: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum DecodeError {
+  #[error("not a discovery protocol frame")]
+  UnknownProtocol,
+  #[error("{0}")]
+  InvalidDataUnit(String),
+}
+
+// a whole ethernet frame, vlan tags and all
+#[uniffi::export]
+pub fn decode_frame(frame: Vec<u8>) -> Result<Neighbor, DecodeError> {
+  let (frame, du) = DataUnit::decode_frame(&frame).map_err(|err| match err {
+    crate::frame::FrameError::UnknownProtocol => DecodeError::UnknownProtocol,
+    err => DecodeError::InvalidDataUnit(err.to_string()),
+  })?;
+
+  let source: Vec<_> = frame.source.iter().map(|x| format!("{x:02x}")).collect();
+  Ok(Neighbor {
+    protocol: frame.protocol,
+    source: source.join(":"),
+    chassis_id: match &du {
+      DataUnit::Lldp(x) => Some(x.chassis_id.to_string()),
+      _ => None,
+    },
+    port_id: du.port_id().map(|x| x.to_string()),
+    system_name: du.system_name().map(|x| x.to_string()),
+    management_address: du.management_address().map(|x| x.to_string()),
+    time_to_live: du.time_to_live(),
+    port_vlan_id: du.port_vlan_id(),
+  })
+}
+
+#[test]
+fn decodes_for_bindings() {
+  let mut buf = vec![0x01, 0x00, 0x0c, 0xcc, 0xcc, 0xcc, 0, 0, 0, 0, 0, 1, 0x00, 0x15];
+  buf.extend_from_slice(&[0xaa, 0xaa, 0x03, 0x00, 0x00, 0x0c, 0x20, 0x00]);
+  buf.extend_from_slice(&[2, 180, 0, 0, 0x00, 0x01, 0x00, 0x05, b'a']);
+
+  let neighbor = decode_frame(buf).unwrap();
+  assert_eq!(neighbor.protocol, Protocol::Cdp);
+  assert_eq!(neighbor.source, "00:00:00:00:00:01");
+  assert_eq!(neighbor.system_name.as_deref(), Some("a"));
+  assert_eq!(decode_frame(vec![0; 20]), Err(DecodeError::UnknownProtocol));
+}
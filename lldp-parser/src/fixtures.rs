@@ -0,0 +1,36 @@
+// the smallest valid dus, tests override what they care about with struct update syntax
+use crate::{
+  cdp,
+  lldp::{
+    du::{DataUnit as LldpDu, Org},
+    tlv::{ChassisId, PortId},
+  },
+};
+
+pub(crate) fn lldp_du() -> LldpDu<'static> {
+  LldpDu {
+    chassis_id: ChassisId::Local("chassis".into()),
+    port_id: PortId::Local("port".into()),
+    time_to_live: 120,
+    port_description: None,
+    system_name: None,
+    system_description: None,
+    capabilities: None,
+    management_address: Vec::new(),
+    org: Org::default(),
+  }
+}
+
+pub(crate) fn cdp_du() -> cdp::DataUnit<'static> {
+  cdp::DataUnit {
+    time_to_live: 180,
+    device_id: None,
+    addresses: Vec::new(),
+    capabilities: None,
+    software_version: None,
+    platform: None,
+    port_id: None,
+    duplex: None,
+    native_vlan: None,
+  }
+}
@@ -0,0 +1,166 @@
+use thiserror::Error;
+
+use crate::{
+  encap::{self, ETHER_TYPE_IPV4, ETHER_TYPE_IPV6},
+  DataUnit, DataUnitError, Protocol,
+};
+
+const ETHER_TYPE_LLDP: u16 = 0x88cc;
+// 802.1q, 802.1ad and the pre-standard qinq tpid
+const VLAN_TPIDS: [u16; 3] = [0x8100, 0x88a8, 0x9100];
+const CDP_SNAP_HEADER: [u8; 8] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x0c, 0x20, 0x00];
+const FDP_SNAP_HEADER: [u8; 8] = [0xaa, 0xaa, 0x03, 0x00, 0xe0, 0x52, 0x20, 0x00];
+// hello and flatnet hello share the nortel oui and only differ in the pid
+const SONMP_SNAP_HEADERS: [[u8; 8]; 2] = [
+  [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x81, 0x01, 0xa2],
+  [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x81, 0x01, 0xa1],
+];
+
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum FrameError {
+  #[error("not a discovery protocol frame")]
+  UnknownProtocol,
+  #[error(transparent)]
+  DataUnit(#[from] DataUnitError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VlanTag {
+  pub tpid: u16,
+  pub tci: u16,
+}
+
+impl VlanTag {
+  pub fn vid(&self) -> u16 {
+    self.tci & 0x0fff
+  }
+
+  pub fn pcp(&self) -> u8 {
+    (self.tci >> 13) as u8
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame<'a> {
+  pub destination: [u8; 6],
+  pub source: [u8; 6],
+  // raw tag stack, outermost first
+  pub tags: &'a [u8],
+  pub protocol: Protocol,
+  pub payload: &'a [u8],
+}
+
+impl<'a> Frame<'a> {
+  pub fn parse(buf: &'a [u8]) -> Option<Self> {
+    if buf.len() < 14 {
+      return None;
+    }
+
+    let destination = buf[0..6].try_into().unwrap();
+    let source = buf[6..12].try_into().unwrap();
+    let mut ether_type = u16::from_be_bytes(buf[12..14].try_into().unwrap());
+    let mut offset = 14;
+
+    while VLAN_TPIDS.contains(&ether_type) {
+      ether_type = u16::from_be_bytes(buf.get(offset + 2..offset + 4)?.try_into().unwrap());
+      offset += 4;
+    }
+
+    let tags = &buf[12..offset - 2];
+    let payload = &buf[offset..];
+
+    if ether_type == ETHER_TYPE_IPV4 || ether_type == ETHER_TYPE_IPV6 {
+      // frames mirrored over gre/erspan are reported as if they were captured directly
+      return Self::parse(encap::decapsulate(ether_type, payload)?);
+    }
+
+    let (protocol, payload) = if ether_type == ETHER_TYPE_LLDP {
+      (Protocol::Lldp, payload)
+    } else if ether_type > 1500 {
+      return None;
+    } else {
+      // 802.3 length field followed by an llc/snap header, anything past the length is padding
+      let payload = &payload[..payload.len().min(ether_type as usize)];
+      if payload.starts_with(&CDP_SNAP_HEADER) {
+        (Protocol::Cdp, &payload[8..])
+      } else if payload.starts_with(&FDP_SNAP_HEADER) {
+        (Protocol::Fdp, &payload[8..])
+      } else if SONMP_SNAP_HEADERS.iter().any(|x| payload.starts_with(x)) {
+        (Protocol::Sonmp, &payload[8..])
+      } else {
+        return None;
+      }
+    };
+
+    Some(Self {
+      destination,
+      source,
+      tags,
+      protocol,
+      payload,
+    })
+  }
+
+  pub fn vlans(&self) -> impl Iterator<Item = VlanTag> + 'a {
+    self.tags.chunks_exact(4).map(|x| VlanTag {
+      tpid: u16::from_be_bytes([x[0], x[1]]),
+      tci: u16::from_be_bytes([x[2], x[3]]),
+    })
+  }
+}
+
+impl<'a> DataUnit<'a> {
+  pub fn decode_frame(buf: &'a [u8]) -> Result<(Frame<'a>, Self), FrameError> {
+    let frame = Frame::parse(buf).ok_or(FrameError::UnknownProtocol)?;
+    let du = Self::decode(frame.protocol, frame.payload)?;
+    Ok((frame, du))
+  }
+}
+
+#[test]
+fn decode_cdp_frame() {
+  let mut buf = vec![0x01, 0x00, 0x0c, 0xcc, 0xcc, 0xcc, 0, 0, 0, 0, 0, 1, 0x00, 0x15];
+  buf.extend_from_slice(&CDP_SNAP_HEADER);
+  buf.extend_from_slice(&[2, 180, 0, 0, 0x00, 0x01, 0x00, 0x05, b'a']);
+
+  let (frame, du) = DataUnit::decode_frame(&buf).unwrap();
+  assert_eq!(frame.source, [0, 0, 0, 0, 0, 1]);
+  assert_eq!(frame.vlans().count(), 0);
+  assert_eq!(du.protocol(), Protocol::Cdp);
+  assert_eq!(du.system_name().map(|x| x.as_ref()), Some("a"));
+
+  buf[12..14].copy_from_slice(&0x0800u16.to_be_bytes());
+  assert!(matches!(DataUnit::decode_frame(&buf), Err(FrameError::UnknownProtocol)));
+}
+
+#[test]
+fn parse_gre_mirrored_frame() {
+  let mut buf = vec![2, 0, 0, 0, 0, 2, 2, 0, 0, 0, 0, 3, 0x08, 0x00];
+  buf.extend_from_slice(&[0x45, 0, 0, 0, 0, 0, 0, 0, 64, 47, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2]);
+  buf.extend_from_slice(&[0x00, 0x00, 0x65, 0x58]);
+  buf.extend_from_slice(&[
+    0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 0, 0, 0, 0, 0, 1, 0x88, 0xcc, 0xde, 0xad,
+  ]);
+
+  let frame = Frame::parse(&buf).unwrap();
+  assert_eq!(frame.source, [0, 0, 0, 0, 0, 1]);
+  assert_eq!(frame.protocol, Protocol::Lldp);
+  assert_eq!(frame.payload, &[0xde, 0xad]);
+}
+
+#[test]
+fn parse_qinq_frame() {
+  let mut buf = vec![0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 0, 0, 0, 0, 0, 1];
+  buf.extend_from_slice(&[0x88, 0xa8, 0x00, 0x0a, 0x81, 0x00, 0xa0, 0x64, 0x88, 0xcc, 0xde, 0xad]);
+
+  let frame = Frame::parse(&buf).unwrap();
+  let vlans: Vec<_> = frame.vlans().collect();
+  assert_eq!(vlans.len(), 2);
+  assert_eq!((vlans[0].tpid, vlans[0].vid()), (0x88a8, 10));
+  assert_eq!((vlans[1].tpid, vlans[1].vid(), vlans[1].pcp()), (0x8100, 100, 5));
+  assert_eq!(frame.protocol, Protocol::Lldp);
+  assert_eq!(frame.payload, &[0xde, 0xad]);
+
+  assert!(Frame::parse(&buf[..17]).is_none());
+}
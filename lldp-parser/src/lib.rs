@@ -1,56 +1,158 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, net::IpAddr};
+
+use thiserror::Error;
 
 pub mod cdp;
+pub mod encap;
+mod error;
+#[cfg(feature = "uniffi")]
+pub mod ffi;
+pub use error::{RawTlvError, TlvDecodeError};
+pub mod fdp;
+#[cfg(test)]
+mod fixtures;
+pub mod frame;
 pub mod lldp;
+pub mod mndp;
+#[cfg(feature = "oui")]
+pub mod oui;
+pub mod render;
+mod report;
+pub use report::DecodeReport;
+pub mod sonmp;
 
 use cdp::DataUnit as CdpDu;
-use lldp::{du::DataUnit as LLdpDu, tlv::PortId};
+use fdp::DataUnit as FdpDu;
+use lldp::{du::DataUnit as LLdpDu, tlv::PortId, Msap};
+use mndp::DataUnit as MndpDu;
+use sonmp::DataUnit as SonmpDu;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 pub enum Protocol {
   Cdp,
+  Fdp,
   Lldp,
+  Mndp,
+  Sonmp,
+}
+
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum DataUnitError {
+  #[error("failed to decode cdp du: {0}")]
+  Cdp(#[from] cdp::DataUnitError),
+  #[error("failed to decode fdp du: {0}")]
+  Fdp(#[from] fdp::DataUnitError),
+  #[error("failed to decode lldp du: {0}")]
+  Lldp(#[from] lldp::du::DataUnitError),
+  #[error("failed to decode mndp du: {0}")]
+  Mndp(#[from] mndp::DataUnitError),
+  #[error("failed to decode sonmp du: {0}")]
+  Sonmp(#[from] sonmp::DataUnitError),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DataUnit<'a> {
   Cdp(CdpDu<'a>),
+  Fdp(FdpDu<'a>),
   Lldp(LLdpDu<'a>),
+  Mndp(MndpDu<'a>),
+  Sonmp(SonmpDu),
 }
 
 impl<'a> DataUnit<'a> {
+  pub fn decode(protocol: Protocol, buf: &'a [u8]) -> Result<Self, DataUnitError> {
+    match protocol {
+      Protocol::Cdp => Ok(CdpDu::decode(buf)?.into()),
+      Protocol::Fdp => Ok(FdpDu::decode(buf)?.into()),
+      Protocol::Lldp => Ok(LLdpDu::decode(buf)?.into()),
+      Protocol::Mndp => Ok(MndpDu::decode(buf)?.into()),
+      Protocol::Sonmp => Ok(SonmpDu::decode(buf)?.into()),
+    }
+  }
+
+  // only lldp and cdp count their tlvs, the report is empty for the rest
+  pub fn decode_with_report(protocol: Protocol, buf: &'a [u8]) -> Result<(Self, DecodeReport), DataUnitError> {
+    match protocol {
+      Protocol::Cdp => {
+        let (du, report) = CdpDu::decode_with_report(buf)?;
+        Ok((du.into(), report))
+      }
+      Protocol::Lldp => {
+        let (du, report) = LLdpDu::decode_with(buf, &Default::default())?;
+        Ok((du.into(), report))
+      }
+      _ => Ok((Self::decode(protocol, buf)?, DecodeReport::default())),
+    }
+  }
+
   pub fn protocol(&self) -> Protocol {
     match self {
       Self::Cdp(_) => Protocol::Cdp,
+      Self::Fdp(_) => Protocol::Fdp,
       Self::Lldp(_) => Protocol::Lldp,
+      Self::Mndp(_) => Protocol::Mndp,
+      Self::Sonmp(_) => Protocol::Sonmp,
     }
   }
 
   pub fn to_static(self) -> DataUnit<'static> {
     match self {
       Self::Cdp(x) => DataUnit::Cdp(x.to_static()),
+      Self::Fdp(x) => DataUnit::Fdp(x.to_static()),
       Self::Lldp(x) => DataUnit::Lldp(x.to_static()),
+      Self::Mndp(x) => DataUnit::Mndp(x.to_static()),
+      Self::Sonmp(x) => DataUnit::Sonmp(x),
     }
   }
 
   pub fn time_to_live(&self) -> u16 {
     match self {
       Self::Cdp(x) => x.time_to_live as _,
+      Self::Fdp(x) => x.time_to_live as _,
       Self::Lldp(x) => x.time_to_live,
+      Self::Mndp(_) => mndp::TIME_TO_LIVE,
+      Self::Sonmp(_) => sonmp::TIME_TO_LIVE,
     }
   }
 
   pub fn system_name(&self) -> Option<&Cow<'a, str>> {
     match self {
       Self::Cdp(x) => x.device_id.as_ref(),
+      Self::Fdp(x) => x.device_id.as_ref(),
       Self::Lldp(x) => x.system_name.as_ref(),
+      Self::Mndp(x) => x.identity.as_ref(),
+      Self::Sonmp(_) => None,
+    }
+  }
+
+  pub fn management_address(&self) -> Option<IpAddr> {
+    match self {
+      Self::Cdp(x) => x.addresses.first().copied(),
+      Self::Fdp(_) => None,
+      Self::Lldp(x) => x
+        .primary_ipv4()
+        .map(IpAddr::V4)
+        .or_else(|| x.primary_ipv6().map(IpAddr::V6)),
+      Self::Mndp(x) => x
+        .ipv4_address
+        .map(IpAddr::V4)
+        .or_else(|| x.ipv6_address.map(IpAddr::V6)),
+      Self::Sonmp(x) => Some(IpAddr::V4(x.ip_address)),
     }
   }
 
   pub fn port_vlan_id(&self) -> Option<u16> {
     match self {
       Self::Cdp(x) => x.native_vlan,
+      Self::Fdp(x) => x.native_vlan,
       Self::Lldp(x) => x.org.dot1.port_vlan_id,
+      Self::Mndp(_) => None,
+      Self::Sonmp(_) => None,
     }
   }
 
@@ -60,7 +162,24 @@ impl<'a> DataUnit<'a> {
         let port_id = x.port_id.clone()?;
         Some(PortId::InterfaceName(port_id))
       }
+      Self::Fdp(x) => {
+        let port_id = x.port_id.clone()?;
+        Some(PortId::InterfaceName(port_id))
+      }
       Self::Lldp(x) => Some(x.port_id.clone()),
+      Self::Mndp(x) => {
+        let port_id = x.interface_name.clone()?;
+        Some(PortId::InterfaceName(port_id))
+      }
+      Self::Sonmp(x) => Some(PortId::Local(format!("{:06x}", x.segment_id).into())),
+    }
+  }
+
+  // only lldp has a chassis id, the other protocols are told apart by source mac
+  pub fn msap(&self) -> Option<Msap<'a>> {
+    match self {
+      Self::Lldp(x) => Some(x.msap()),
+      _ => None,
     }
   }
 }
@@ -76,3 +195,21 @@ impl<'a> From<CdpDu<'a>> for DataUnit<'a> {
     Self::Cdp(value)
   }
 }
+
+impl<'a> From<FdpDu<'a>> for DataUnit<'a> {
+  fn from(value: FdpDu<'a>) -> Self {
+    Self::Fdp(value)
+  }
+}
+
+impl<'a> From<MndpDu<'a>> for DataUnit<'a> {
+  fn from(value: MndpDu<'a>) -> Self {
+    Self::Mndp(value)
+  }
+}
+
+impl<'a> From<SonmpDu> for DataUnit<'a> {
+  fn from(value: SonmpDu) -> Self {
+    Self::Sonmp(value)
+  }
+}
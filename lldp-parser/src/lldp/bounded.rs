@@ -0,0 +1,180 @@
+use std::borrow::Cow;
+
+use heapless::Vec;
+use thiserror::Error;
+use tracing::warn;
+
+use super::{
+  du::Dot3,
+  tlv::{
+    org::{dot1, dot3, LLDP_TLV_ORG_DOT1},
+    Capabilities, ChassisId, CustomOrgTlv, ManagementAddress, OrgTlv, PortId, RawTlv, RawTlvError, Tlv, TlvKind,
+  },
+};
+
+// same as du::DataUnit, except nothing is allocated: the lists hold at most N entries and every string borrows
+// from the frame. invalid utf-8 is an error here rather than being replaced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundedDataUnit<'a, const N: usize> {
+  pub chassis_id: ChassisId<'a>,
+  pub port_id: PortId<'a>,
+  pub time_to_live: u16,
+  pub port_description: Option<Cow<'a, str>>,
+  pub system_name: Option<Cow<'a, str>>,
+  pub system_description: Option<Cow<'a, str>>,
+  pub capabilities: Option<Capabilities>,
+  pub management_address: Vec<ManagementAddress<'a>, N>,
+  pub port_vlan_id: Option<u16>,
+  pub vlan_name: Vec<(u16, Cow<'a, str>), N>,
+  pub dot3: Dot3,
+  pub custom: Vec<CustomOrgTlv<'a>, N>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum BoundedDataUnitError {
+  #[error("missing chassis id")]
+  MissingChassisId,
+  #[error("missing port id")]
+  MissingPortId,
+  #[error("missing time to live")]
+  MissingTimeToLive,
+  #[error("failed to decode tlv: '{0}'")]
+  RawTlvError(#[from] RawTlvError),
+  #[error("invalid utf-8 in {0:?} tlv")]
+  InvalidUtf8(TlvKind),
+  #[error("more than {capacity} {kind:?} tlvs")]
+  CapacityExceeded { kind: TlvKind, capacity: usize },
+}
+
+// the bytes Tlv::decode would run through from_utf8_lossy, which allocates when they aren't valid
+fn text<'a>(raw: &RawTlv<'a>) -> Option<&'a [u8]> {
+  let payload = raw.payload;
+  match TlvKind::from(raw.ty) {
+    TlvKind::PortDescription | TlvKind::SystemName | TlvKind::SystemDescription => Some(payload),
+    TlvKind::ChassisId => matches!(payload.first(), Some(1 | 2 | 3 | 6 | 7)).then(|| &payload[1..]),
+    TlvKind::PortId => matches!(payload.first(), Some(1 | 2 | 5 | 7)).then(|| &payload[1..]),
+    TlvKind::ManagementAddress => payload.first().and_then(|x| payload.get(*x as usize + 7..)),
+    TlvKind::Org if payload.starts_with(&LLDP_TLV_ORG_DOT1) && payload.get(3) == Some(&3) => payload.get(7..),
+    _ => None,
+  }
+}
+
+fn push<T, const N: usize>(list: &mut Vec<T, N>, x: T, kind: TlvKind) -> Result<(), BoundedDataUnitError> {
+  list
+    .push(x)
+    .map_err(|_| BoundedDataUnitError::CapacityExceeded { kind, capacity: N })
+}
+
+impl<'a, const N: usize> BoundedDataUnit<'a, N> {
+  pub fn decode(mut buf: &'a [u8]) -> Result<Self, BoundedDataUnitError> {
+    let mut chassis_id = None;
+    let mut port_id = None;
+    let mut time_to_live = None;
+    let mut du = Self {
+      chassis_id: ChassisId::Local(Cow::Borrowed("")),
+      port_id: PortId::Local(Cow::Borrowed("")),
+      time_to_live: 0,
+      port_description: None,
+      system_name: None,
+      system_description: None,
+      capabilities: None,
+      management_address: Vec::new(),
+      port_vlan_id: None,
+      vlan_name: Vec::new(),
+      dot3: Dot3::default(),
+      custom: Vec::new(),
+    };
+
+    // duplicates are handled like du::DataUnit, the last one wins
+    while !buf.is_empty() {
+      let raw = RawTlv::decode(buf)?;
+      buf = &buf[raw.total_len()..];
+      if raw.ty == 0 {
+        // anything after is padding
+        break;
+      }
+
+      let kind = TlvKind::from(raw.ty);
+      if text(&raw).is_some_and(|x| std::str::from_utf8(x).is_err()) {
+        return Err(BoundedDataUnitError::InvalidUtf8(kind));
+      }
+
+      let tlv = match Tlv::decode(raw) {
+        Ok(x) => x,
+        Err(err) => {
+          warn!(%err, "failed to decode tlv");
+          continue;
+        }
+      };
+
+      match tlv {
+        Tlv::End => {}
+        Tlv::ChassisId(x) => chassis_id = Some(x),
+        Tlv::PortId(x) => port_id = Some(x),
+        Tlv::TimeToLive(x) => time_to_live = Some(x),
+        Tlv::PortDescription(x) => du.port_description = Some(x),
+        Tlv::SystemName(x) => du.system_name = Some(x),
+        Tlv::SystemDescription(x) => du.system_description = Some(x),
+        Tlv::Capabilities(x) => du.capabilities = Some(x),
+        Tlv::ManagementAddress(x) => push(&mut du.management_address, x, kind)?,
+        Tlv::Org(OrgTlv::Dot1(dot1::Tlv::PortVlanId(x))) => du.port_vlan_id = Some(x),
+        Tlv::Org(OrgTlv::Dot1(dot1::Tlv::VlanName(x, y))) => push(&mut du.vlan_name, (x, y), kind)?,
+        Tlv::Org(OrgTlv::Dot3(dot3::Tlv::MacPhyStatus(x))) => du.dot3.mac_phy_status = Some(x),
+        Tlv::Org(OrgTlv::Dot3(dot3::Tlv::Power(x))) => du.dot3.power = Some(x),
+        Tlv::Org(OrgTlv::Custom(x)) => push(&mut du.custom, x, kind)?,
+      }
+    }
+
+    du.chassis_id = chassis_id.ok_or(BoundedDataUnitError::MissingChassisId)?;
+    du.port_id = port_id.ok_or(BoundedDataUnitError::MissingPortId)?;
+    du.time_to_live = time_to_live.ok_or(BoundedDataUnitError::MissingTimeToLive)?;
+    Ok(du)
+  }
+}
+
+#[test]
+fn decodes_without_allocating() {
+  use super::du::DataUnit;
+
+  let mut buf = std::vec::Vec::new();
+  DataUnit {
+    port_id: PortId::InterfaceName("eth0".into()),
+    system_name: Some("switch".into()),
+    org: super::du::Org {
+      dot1: super::du::Dot1 {
+        port_vlan_id: Some(10),
+        vlan_name: vec![(10, "users".into()), (20, "voice".into())],
+      },
+      ..Default::default()
+    },
+    ..crate::fixtures::lldp_du()
+  }
+  .encode(&mut buf);
+
+  let du = BoundedDataUnit::<2>::decode(&buf).unwrap();
+  assert!(matches!(du.system_name, Some(Cow::Borrowed("switch"))));
+  assert!(matches!(du.port_id, PortId::InterfaceName(Cow::Borrowed("eth0"))));
+  assert_eq!(du.port_vlan_id, Some(10));
+  assert_eq!(du.vlan_name.len(), 2);
+
+  let mut padded = buf.clone();
+  padded.resize(buf.len() + 3, 0);
+  assert_eq!(BoundedDataUnit::<2>::decode(&padded), Ok(du.clone()));
+
+  assert_eq!(
+    BoundedDataUnit::<1>::decode(&buf),
+    Err(BoundedDataUnitError::CapacityExceeded {
+      kind: TlvKind::Org,
+      capacity: 1
+    })
+  );
+
+  // system name "sw\xff"
+  let mut bad = buf.clone();
+  bad.splice(0..0, [0x0a, 0x03, b's', b'w', 0xff]);
+  assert_eq!(
+    BoundedDataUnit::<2>::decode(&bad),
+    Err(BoundedDataUnitError::InvalidUtf8(TlvKind::SystemName))
+  );
+}
@@ -1,15 +1,24 @@
-use std::borrow::Cow;
+use std::{
+  borrow::Cow,
+  fmt::Debug,
+  net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
 
 use thiserror::Error;
 use tracing::warn;
 
 use super::tlv::{
-  decode_list,
   org::{dot1, dot3},
-  Capabilities, ChassisId, ManagementAddress, OrgTlv, PortId, RawTlvError, Tlv,
+  Capabilities, ChassisId, CustomOrgTlv, ManagementAddress, NetworkAddress, OrgTlv, PortId, RawTlv, RawTlvError, Tlv,
+  TlvDecodeError, TlvKind,
 };
+use crate::DecodeReport;
+
+const LLDP_TLV_ORG_MED: [u8; 3] = [0x00, 0x12, 0xbb];
+const MED_APPLICATION_VOICE: u8 = 1;
 
 #[derive(Debug, Clone, Error)]
+#[non_exhaustive]
 pub enum DataUnitError {
   #[error("missing chassis id")]
   MissingChassisId,
@@ -19,6 +28,55 @@ pub enum DataUnitError {
   MissingTimeToLive,
   #[error("failed to decode tlv: '{0}'")]
   RawTlvError(#[from] RawTlvError),
+  #[error("duplicate {0:?} tlv")]
+  DuplicateTlv(TlvKind),
+}
+
+// what to do when a tlv that may only appear once shows up again
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DuplicatePolicy {
+  KeepFirst,
+  #[default]
+  KeepLast,
+  Error,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DecodeOptions {
+  pub duplicates: DuplicatePolicy,
+  // org tlvs from an oui that isn't decoded are skipped as UnsupportedOui instead of kept in org.custom
+  pub strict_org: bool,
+  // drop management addresses identical to one already seen, interface and oid included
+  pub dedup_management_addresses: bool,
+}
+
+// things a peer got wrong that didn't stop the du from decoding
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DecodeWarning {
+  Duplicate { kind: TlvKind, policy: DuplicatePolicy },
+}
+
+fn set_once<T: Debug>(
+  slot: &mut Option<T>,
+  new: T,
+  kind: TlvKind,
+  policy: DuplicatePolicy,
+  warnings: &mut Vec<DecodeWarning>,
+) -> Result<(), DataUnitError> {
+  let Some(old) = slot else {
+    *slot = Some(new);
+    return Ok(());
+  };
+
+  warn!(?kind, ?old, ?new, ?policy, "duplicate tlv");
+  match policy {
+    DuplicatePolicy::KeepFirst => {}
+    DuplicatePolicy::KeepLast => *old = new,
+    DuplicatePolicy::Error => return Err(DataUnitError::DuplicateTlv(kind)),
+  }
+  warnings.push(DecodeWarning::Duplicate { kind, policy });
+  Ok(())
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -38,6 +96,7 @@ pub struct DataUnit<'a> {
 pub struct Org<'a> {
   pub dot1: Dot1<'a>,
   pub dot3: Dot3,
+  pub custom: Vec<CustomOrgTlv<'a>>,
 }
 
 impl<'a> Org<'a> {
@@ -45,6 +104,7 @@ impl<'a> Org<'a> {
     Org {
       dot1: self.dot1.to_static(),
       dot3: self.dot3,
+      custom: self.custom.into_iter().map(CustomOrgTlv::to_static).collect(),
     }
   }
 }
@@ -71,6 +131,7 @@ impl<'a> Dot1<'a> {
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct Dot3 {
   pub mac_phy_status: Option<dot3::MacPhyStatus>,
+  pub power: Option<dot3::Power>,
 }
 
 impl<'a> DataUnit<'a> {
@@ -93,7 +154,30 @@ impl<'a> DataUnit<'a> {
   }
 
   pub fn decode(buf: &'a [u8]) -> Result<Self, DataUnitError> {
-    let list = decode_list(buf)?;
+    Self::decode_with(buf, &DecodeOptions::default()).map(|(du, _)| du)
+  }
+
+  pub fn decode_with(mut buf: &'a [u8], options: &DecodeOptions) -> Result<(Self, DecodeReport), DataUnitError> {
+    let mut report = DecodeReport::default();
+    let mut list = Vec::new();
+    while !buf.is_empty() {
+      let raw = RawTlv::decode(buf)?;
+      buf = &buf[raw.total_len()..];
+      let end = raw.ty == 0;
+      let result = Tlv::decode(raw).and_then(|tlv| match tlv {
+        Tlv::Org(OrgTlv::Custom(x)) if options.strict_org => Err(TlvDecodeError::UnsupportedOui(x.org)),
+        tlv => Ok(tlv),
+      });
+      report.record(&result);
+      match result {
+        Ok(tlv) => list.push(tlv),
+        Err(err) => warn!(%err, "failed to decode tlv"),
+      }
+      if end {
+        report.padding = buf.len();
+        break;
+      }
+    }
 
     let mut chassis_id = None;
     let mut port_id = None;
@@ -105,82 +189,35 @@ impl<'a> DataUnit<'a> {
     let mut management_address = Vec::new();
     let mut org = Org::default();
 
+    let policy = options.duplicates;
     for tlv in list {
+      let kind = tlv.kind();
       match tlv {
         Tlv::End => {}
-
-        Tlv::ChassisId(new) => {
-          if let Some(old) = chassis_id.take() {
-            warn!(?old, ?new, "duplicate chassis id");
-          }
-          chassis_id = Some(new);
-        }
-
-        Tlv::PortId(new) => {
-          if let Some(old) = port_id.take() {
-            warn!(?old, ?new, "duplicate port id");
-          }
-          port_id = Some(new);
-        }
-
-        Tlv::TimeToLive(new) => {
-          if let Some(old) = time_to_live.take() {
-            warn!(?old, ?new, "duplicate time to live");
-          }
-          time_to_live = Some(new);
-        }
-
-        Tlv::PortDescription(new) => {
-          if let Some(old) = port_description.take() {
-            warn!(?old, ?new, "duplicate port description");
-          }
-          port_description = Some(new);
-        }
-
-        Tlv::SystemName(new) => {
-          if let Some(old) = system_name.take() {
-            warn!(?old, ?new, "duplicate system name");
-          }
-          system_name = Some(new);
-        }
-
-        Tlv::SystemDescription(new) => {
-          if let Some(old) = system_description.take() {
-            warn!(?old, ?new, "duplicate system description");
-          }
-          system_description = Some(new);
-        }
-
-        Tlv::Capabilities(new) => {
-          if let Some(old) = capabilities.take() {
-            warn!(?old, ?new, "duplicate system capabilities");
-          }
-          capabilities = Some(new);
-        }
-
+        Tlv::ChassisId(new) => set_once(&mut chassis_id, new, kind, policy, &mut report.warnings)?,
+        Tlv::PortId(new) => set_once(&mut port_id, new, kind, policy, &mut report.warnings)?,
+        Tlv::TimeToLive(new) => set_once(&mut time_to_live, new, kind, policy, &mut report.warnings)?,
+        Tlv::PortDescription(new) => set_once(&mut port_description, new, kind, policy, &mut report.warnings)?,
+        Tlv::SystemName(new) => set_once(&mut system_name, new, kind, policy, &mut report.warnings)?,
+        Tlv::SystemDescription(new) => set_once(&mut system_description, new, kind, policy, &mut report.warnings)?,
+        Tlv::Capabilities(new) => set_once(&mut capabilities, new, kind, policy, &mut report.warnings)?,
+        Tlv::ManagementAddress(x) if options.dedup_management_addresses && management_address.contains(&x) => {}
         Tlv::ManagementAddress(x) => management_address.push(x),
-
         Tlv::Org(OrgTlv::Dot1(dot1::Tlv::PortVlanId(new))) => {
-          if let Some(old) = org.dot1.port_vlan_id.take() {
-            warn!(?old, ?new, "duplicate vlan id");
-          }
-          org.dot1.port_vlan_id = Some(new);
+          set_once(&mut org.dot1.port_vlan_id, new, kind, policy, &mut report.warnings)?
         }
-
         Tlv::Org(OrgTlv::Dot1(dot1::Tlv::VlanName(x, y))) => org.dot1.vlan_name.push((x, y)),
-
         Tlv::Org(OrgTlv::Dot3(dot3::Tlv::MacPhyStatus(new))) => {
-          if let Some(old) = org.dot3.mac_phy_status.take() {
-            warn!(?old, ?new, "duplicate mac/phy status");
-          }
-          org.dot3.mac_phy_status = Some(new);
+          set_once(&mut org.dot3.mac_phy_status, new, kind, policy, &mut report.warnings)?
         }
-
-        _ => {}
+        Tlv::Org(OrgTlv::Dot3(dot3::Tlv::Power(new))) => {
+          set_once(&mut org.dot3.power, new, kind, policy, &mut report.warnings)?
+        }
+        Tlv::Org(OrgTlv::Custom(x)) => org.custom.push(x),
       }
     }
 
-    Ok(Self {
+    let du = Self {
       chassis_id: chassis_id.ok_or(DataUnitError::MissingChassisId)?,
       port_id: port_id.ok_or(DataUnitError::MissingPortId)?,
       time_to_live: time_to_live.ok_or(DataUnitError::MissingTimeToLive)?,
@@ -190,95 +227,139 @@ impl<'a> DataUnit<'a> {
       capabilities,
       management_address,
       org,
-    })
+    };
+    Ok((du, report))
   }
 
-  pub fn encode(self, buf: &mut Vec<u8>) {
-    let chassis_id = Tlv::ChassisId(self.chassis_id);
-    let port_id = Tlv::PortId(self.port_id);
-    let ttl = Tlv::TimeToLive(self.time_to_live);
-    let port_description = self.port_description.map(Tlv::PortDescription);
-    let system_name = self.system_name.map(Tlv::SystemName);
-    let system_description = self.system_description.map(Tlv::SystemDescription);
-    let capabilities = self.capabilities.map(Tlv::Capabilities);
-    let management_address: Vec<_> = self
-      .management_address
-      .into_iter()
-      .map(Tlv::ManagementAddress)
-      .collect();
-
-    let org_dot1_vlan_id = self
-      .org
-      .dot1
-      .port_vlan_id
-      .map(|x| Tlv::Org(OrgTlv::Dot1(dot1::Tlv::PortVlanId(x))));
+  // the first of each family, in the order they were received
+  pub fn primary_ipv4(&self) -> Option<Ipv4Addr> {
+    self.management_address.iter().find_map(|x| match x.address {
+      NetworkAddress::Ip(IpAddr::V4(x)) => Some(x),
+      _ => None,
+    })
+  }
 
-    let org_dot1_vlan_name: Vec<_> = self
-      .org
-      .dot1
-      .vlan_name
-      .into_iter()
-      .map(|(x, y)| Tlv::Org(OrgTlv::Dot1(dot1::Tlv::VlanName(x, y))))
-      .collect();
+  pub fn primary_ipv6(&self) -> Option<Ipv6Addr> {
+    self.management_address.iter().find_map(|x| match x.address {
+      NetworkAddress::Ip(IpAddr::V6(x)) => Some(x),
+      _ => None,
+    })
+  }
 
-    let org_dot3_phy = self
+  // a tagged med voice policy wins. an untagged or priority tagged one puts voice on the port vlan, so that's the pvid.
+  // policies flagged unknown are skipped, and without any voice policy the pvid is only the data vlan, so there's none
+  pub fn voice_vlan(&self) -> Option<u16> {
+    let policy = self
       .org
-      .dot3
-      .mac_phy_status
-      .map(|x| Tlv::Org(OrgTlv::Dot3(dot3::Tlv::MacPhyStatus(x))));
-
-    let total_size = chassis_id.encoded_size()
-      + port_id.encoded_size()
-      + ttl.encoded_size()
-      + port_description.as_ref().map(|x| x.encoded_size()).unwrap_or_default()
-      + system_description
-        .as_ref()
-        .map(|x| x.encoded_size())
-        .unwrap_or_default()
-      + system_name.as_ref().map(|x| x.encoded_size()).unwrap_or_default()
-      + capabilities.as_ref().map(|x| x.encoded_size()).unwrap_or_default()
-      + management_address.iter().fold(0, |acc, x| acc + x.encoded_size())
-      + org_dot1_vlan_id.as_ref().map(|x| x.encoded_size()).unwrap_or_default()
-      + org_dot1_vlan_name.iter().fold(0, |acc, x| acc + x.encoded_size())
-      + org_dot3_phy.as_ref().map(|x| x.encoded_size()).unwrap_or_default();
-
-    buf.reserve(total_size);
-
-    chassis_id.encode(buf);
-    port_id.encode(buf);
-    ttl.encode(buf);
-
-    if let Some(x) = port_description {
-      x.encode(buf);
-    }
-
-    if let Some(x) = system_name {
-      x.encode(buf);
-    }
-
-    if let Some(x) = system_description {
-      x.encode(buf);
+      .custom
+      .iter()
+      .find_map(|x| match (x.org, x.subtype, &*x.data) {
+        (LLDP_TLV_ORG_MED, 2, &[MED_APPLICATION_VOICE, a, b, c]) if a & 0x80 == 0 => {
+          Some(u32::from_be_bytes([0, a, b, c]))
+        }
+        _ => None,
+      })?;
+    let tagged = policy & 0x40_0000 != 0;
+    match (policy >> 9) & 0xfff {
+      0 => self.org.dot1.port_vlan_id,
+      _ if !tagged => self.org.dot1.port_vlan_id,
+      vlan => Some(vlan as u16),
     }
+  }
 
-    if let Some(x) = capabilities {
-      x.encode(buf);
-    }
+  // in the order they're encoded, which is the order the fields are declared in
+  pub(super) fn into_tlvs(self) -> Vec<Tlv<'a>> {
+    let mut tlvs = vec![
+      Tlv::ChassisId(self.chassis_id),
+      Tlv::PortId(self.port_id),
+      Tlv::TimeToLive(self.time_to_live),
+    ];
+    tlvs.extend(self.port_description.map(Tlv::PortDescription));
+    tlvs.extend(self.system_name.map(Tlv::SystemName));
+    tlvs.extend(self.system_description.map(Tlv::SystemDescription));
+    tlvs.extend(self.capabilities.map(Tlv::Capabilities));
+    // ipv4, then ipv6, then the other families by number, each in the order they were given
+    let mut management_address = self.management_address;
+    management_address.sort_by_key(|x| x.address.kind());
+    tlvs.extend(management_address.into_iter().map(Tlv::ManagementAddress));
+
+    let org = self.org;
+    tlvs.extend(
+      org
+        .dot1
+        .port_vlan_id
+        .map(|x| Tlv::Org(OrgTlv::Dot1(dot1::Tlv::PortVlanId(x)))),
+    );
+    tlvs.extend(
+      org
+        .dot1
+        .vlan_name
+        .into_iter()
+        .map(|(x, y)| Tlv::Org(OrgTlv::Dot1(dot1::Tlv::VlanName(x, y)))),
+    );
+    tlvs.extend(
+      org
+        .dot3
+        .mac_phy_status
+        .map(|x| Tlv::Org(OrgTlv::Dot3(dot3::Tlv::MacPhyStatus(x)))),
+    );
+    tlvs.extend(org.dot3.power.map(|x| Tlv::Org(OrgTlv::Dot3(dot3::Tlv::Power(x)))));
+    tlvs.extend(org.custom.into_iter().map(|x| Tlv::Org(OrgTlv::Custom(x))));
+    tlvs
+  }
 
-    for x in management_address.into_iter() {
+  pub fn encode(self, buf: &mut Vec<u8>) {
+    let tlvs = self.into_tlvs();
+    buf.reserve(tlvs.iter().map(|x| x.encoded_size() + 2).sum());
+    for x in tlvs {
       x.encode(buf);
     }
+  }
 
-    if let Some(x) = org_dot1_vlan_id {
-      x.encode(buf);
+  // like encode, but drops optional tlvs until the du fits in mtu bytes and returns the dropped ones. they go in
+  // drop_priority order, the last of a kind first. the mandatory tlvs are always written, even if they alone
+  // don't fit
+  pub fn encode_bounded(self, buf: &mut Vec<u8>, mtu: usize) -> Vec<Tlv<'a>> {
+    let mut tlvs = self.into_tlvs();
+    let mut size: usize = tlvs.iter().map(|x| x.encoded_size() + 2).sum();
+
+    let mut dropped = Vec::new();
+    while size > mtu {
+      let Some((i, _)) = tlvs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, x)| Some((i, drop_priority(x)?)))
+        .min_by_key(|(i, priority)| (*priority, usize::MAX - i))
+      else {
+        break;
+      };
+
+      let tlv = tlvs.remove(i);
+      size -= tlv.encoded_size() + 2;
+      dropped.push(tlv);
     }
 
-    for x in org_dot1_vlan_name {
+    buf.reserve(size);
+    for x in tlvs {
       x.encode(buf);
     }
+    dropped
+  }
+}
 
-    if let Some(x) = org_dot3_phy {
-      x.encode(buf);
-    }
+// lowest goes first: org extensions, then the free text, then what identifies the neighbor beyond the msap.
+// None is never dropped
+fn drop_priority(tlv: &Tlv) -> Option<u8> {
+  match tlv {
+    Tlv::End | Tlv::ChassisId(_) | Tlv::PortId(_) | Tlv::TimeToLive(_) => None,
+    Tlv::Org(OrgTlv::Custom(_)) => Some(0),
+    Tlv::Org(OrgTlv::Dot1(dot1::Tlv::VlanName(..))) => Some(1),
+    Tlv::Org(_) => Some(2),
+    Tlv::SystemDescription(_) => Some(3),
+    Tlv::PortDescription(_) => Some(4),
+    Tlv::ManagementAddress(_) => Some(5),
+    Tlv::Capabilities(_) => Some(6),
+    Tlv::SystemName(_) => Some(7),
   }
 }
 
@@ -293,21 +374,16 @@ fn test_encode_decode(du: DataUnit) {
 
 #[test]
 fn basic_encode_decode() {
-  use std::net::{IpAddr, Ipv4Addr};
-
   use crate::lldp::tlv::{
     org::dot3::{AutoNegotiationCapability, AutoNegotiationStatus, MacPhyStatus, MauType},
-    ManagementInterfaceKind, NetworkAddress,
+    ManagementInterfaceKind,
   };
 
   test_encode_decode(DataUnit {
-    chassis_id: ChassisId::Local("chassis".into()),
-    port_id: PortId::Local("port".into()),
     time_to_live: 1234,
     port_description: Some("port_description".into()),
     system_name: Some("system_name".into()),
     system_description: Some("system_description".into()),
-    capabilities: None,
     management_address: vec![
       ManagementAddress {
         address: NetworkAddress::Ip(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))),
@@ -333,7 +409,200 @@ fn basic_encode_decode() {
           advertised: AutoNegotiationCapability::OTHER | AutoNegotiationCapability::B_1000_BASE_T_FD,
           mau: MauType::B1000BaseTFD,
         }),
+        power: None,
       },
+      custom: vec![CustomOrgTlv {
+        org: [0x00, 0x12, 0xbb],
+        subtype: 2,
+        data: vec![1, 2, 3, 4].into(),
+      }],
     },
+    ..crate::fixtures::lldp_du()
   })
 }
+
+#[test]
+fn duplicate_policies() {
+  let mut buf = Vec::new();
+  DataUnit {
+    system_name: Some("first".into()),
+    ..crate::fixtures::lldp_du()
+  }
+  .encode(&mut buf);
+  Tlv::SystemName("last".into()).encode(&mut buf);
+  // reserved type 9
+  buf.extend([9 << 1, 0]);
+
+  let decode = |duplicates| {
+    let options = DecodeOptions {
+      duplicates,
+      ..Default::default()
+    };
+    DataUnit::decode_with(&buf, &options)
+  };
+  let warning = |policy| DecodeWarning::Duplicate {
+    kind: TlvKind::SystemName,
+    policy,
+  };
+
+  let (du, report) = decode(DuplicatePolicy::KeepFirst).unwrap();
+  assert_eq!(du.system_name.as_deref(), Some("first"));
+  assert_eq!(report.warnings, [warning(DuplicatePolicy::KeepFirst)]);
+
+  let (du, report) = decode(DuplicatePolicy::KeepLast).unwrap();
+  assert_eq!(du.system_name.as_deref(), Some("last"));
+  assert_eq!(report.warnings, [warning(DuplicatePolicy::KeepLast)]);
+  assert_eq!((report.tlvs_total, report.tlvs_unknown, report.tlvs_errored), (6, 1, 0));
+  assert_eq!(DataUnit::decode(&buf).unwrap(), du);
+
+  assert!(matches!(
+    decode(DuplicatePolicy::Error),
+    Err(DataUnitError::DuplicateTlv(TlvKind::SystemName))
+  ));
+}
+
+#[test]
+fn encodes_within_mtu() {
+  let du = DataUnit {
+    port_description: Some("uplink".into()),
+    system_name: Some("switch".into()),
+    system_description: Some("a".repeat(200).into()),
+    org: Org {
+      custom: vec![CustomOrgTlv {
+        org: [0x00, 0x12, 0xbb],
+        subtype: 2,
+        data: vec![0; 100].into(),
+      }],
+      ..Default::default()
+    },
+    ..crate::fixtures::lldp_du()
+  };
+
+  let mut full = Vec::new();
+  assert!(du.clone().encode_bounded(&mut full, usize::MAX).is_empty());
+  let mut unbounded = Vec::new();
+  du.clone().encode(&mut unbounded);
+  assert_eq!(full, unbounded);
+
+  let mut buf = Vec::new();
+  let dropped = du.clone().encode_bounded(&mut buf, full.len() - 1);
+  assert!(matches!(dropped[..], [Tlv::Org(OrgTlv::Custom(_))]));
+  assert_eq!(buf.len(), full.len() - 106);
+
+  let mut buf = Vec::new();
+  let dropped = du.clone().encode_bounded(&mut buf, 100);
+  assert!(matches!(dropped[..], [Tlv::Org(_), Tlv::SystemDescription(_)]));
+  let decoded = DataUnit::decode(&buf).unwrap();
+  assert_eq!(decoded.system_name.as_deref(), Some("switch"));
+  assert_eq!(decoded.port_description.as_deref(), Some("uplink"));
+
+  let mut buf = Vec::new();
+  assert_eq!(du.encode_bounded(&mut buf, 0).len(), 4);
+  assert_eq!(buf.len(), 10 + 7 + 4);
+}
+
+#[test]
+fn strict_org_skips_custom_tlvs() {
+  let mut buf = Vec::new();
+  DataUnit {
+    org: Org {
+      custom: vec![CustomOrgTlv {
+        org: [0x00, 0x12, 0xbb],
+        subtype: 1,
+        data: vec![0, 0x33, 0x03].into(),
+      }],
+      ..Default::default()
+    },
+    ..crate::fixtures::lldp_du()
+  }
+  .encode(&mut buf);
+
+  let options = DecodeOptions {
+    strict_org: true,
+    ..Default::default()
+  };
+  let (du, report) = DataUnit::decode_with(&buf, &options).unwrap();
+  assert!(du.org.custom.is_empty());
+  assert_eq!(report.tlvs_unknown, 1);
+  assert_eq!(DataUnit::decode(&buf).unwrap().org.custom.len(), 1);
+}
+
+#[test]
+fn orders_management_addresses() {
+  use crate::lldp::tlv::ManagementInterfaceKind;
+
+  let address = |address| ManagementAddress {
+    address,
+    interface_subtype: ManagementInterfaceKind::IfIndex,
+    interface_number: 1,
+    oid: "".into(),
+  };
+  let mac = address(NetworkAddress::Other(6, vec![0, 1, 2, 3, 4, 5].into()));
+  let v6 = address(NetworkAddress::Ip("2001:db8::1".parse().unwrap()));
+  let v4 = address(NetworkAddress::Ip([10, 0, 0, 1].into()));
+
+  let mut buf = Vec::new();
+  DataUnit {
+    management_address: vec![mac.clone(), v6.clone(), v4.clone(), v4.clone()],
+    ..crate::fixtures::lldp_du()
+  }
+  .encode(&mut buf);
+
+  let du = DataUnit::decode(&buf).unwrap();
+  assert_eq!(du.management_address, [v4.clone(), v4.clone(), v6.clone(), mac.clone()]);
+  assert_eq!(du.primary_ipv4(), Some([10, 0, 0, 1].into()));
+  assert_eq!(du.primary_ipv6(), "2001:db8::1".parse().ok());
+
+  let options = DecodeOptions {
+    dedup_management_addresses: true,
+    ..Default::default()
+  };
+  let (du, _) = DataUnit::decode_with(&buf, &options).unwrap();
+  assert_eq!(du.management_address, [v4, v6, mac]);
+}
+
+#[test]
+fn finds_voice_vlan() {
+  let policy = |policy: u32| CustomOrgTlv {
+    org: LLDP_TLV_ORG_MED,
+    subtype: 2,
+    data: [&[MED_APPLICATION_VOICE][..], &policy.to_be_bytes()[1..]]
+      .concat()
+      .into(),
+  };
+  let mut du = crate::fixtures::lldp_du();
+  du.org.dot1.port_vlan_id = Some(10);
+  assert_eq!(du.voice_vlan(), None);
+
+  // tagged, vlan 100, priority 5, dscp 46
+  du.org.custom = vec![policy(0x40_0000 | 100 << 9 | 5 << 6 | 46)];
+  assert_eq!(du.voice_vlan(), Some(100));
+
+  du.org.custom = vec![policy(100 << 9)];
+  assert_eq!(du.voice_vlan(), Some(10));
+
+  du.org.custom = vec![policy(0x80_0000 | 0x40_0000 | 100 << 9)];
+  assert_eq!(du.voice_vlan(), None);
+}
+
+#[test]
+fn stops_at_end() {
+  let mut buf = Vec::new();
+  crate::fixtures::lldp_du().encode(&mut buf);
+  Tlv::End.encode(&mut buf);
+  let len = buf.len();
+  // an odd length, so the padding can't be read as more end tlvs either
+  buf.resize(len + 23, 0);
+
+  let (du, report) = DataUnit::decode_with(&buf, &DecodeOptions::default()).unwrap();
+  assert_eq!(du.time_to_live, 120);
+  assert_eq!(report.tlvs_total, 4);
+  assert_eq!(report.padding, 23);
+  assert_eq!(
+    DataUnit::decode_with(&buf[..len], &DecodeOptions::default())
+      .unwrap()
+      .1
+      .padding,
+    0
+  );
+}
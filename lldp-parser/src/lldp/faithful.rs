@@ -0,0 +1,175 @@
+use std::mem::discriminant;
+
+use super::{
+  du::{DataUnit, DataUnitError},
+  tlv::{OrgTlv, RawTlv, Tlv},
+};
+
+// a du that remembers the tlvs it was decoded from. encoding it unchanged gives back the original bytes,
+// including order, duplicates, unknown and malformed tlvs and the end tlv. changed fields are written in place
+// of the tlvs they came from, new ones go before the end tlv
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaithfulDataUnit<'a> {
+  pub du: DataUnit<'a>,
+  tlvs: Vec<Entry<'a>>,
+  // whatever followed the end tlv
+  padding: &'a [u8],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry<'a> {
+  raw: RawTlv<'a>,
+  // None for the end tlv and anything that didn't decode, those are always kept
+  tlv: Option<Tlv<'a>>,
+  // lost to a duplicate of itself, kept for as long as the one that won is
+  shadowed: bool,
+}
+
+// whether two tlvs fill the same field of the du
+fn same_slot(a: &Tlv, b: &Tlv) -> bool {
+  match (a, b) {
+    (Tlv::Org(OrgTlv::Dot1(a)), Tlv::Org(OrgTlv::Dot1(b))) => discriminant(a) == discriminant(b),
+    (Tlv::Org(OrgTlv::Dot3(a)), Tlv::Org(OrgTlv::Dot3(b))) => discriminant(a) == discriminant(b),
+    (Tlv::Org(OrgTlv::Custom(a)), Tlv::Org(OrgTlv::Custom(b))) => (a.org, a.subtype) == (b.org, b.subtype),
+    (Tlv::Org(_), Tlv::Org(_)) => false,
+    (a, b) => a.kind() == b.kind(),
+  }
+}
+
+// takes the first pending tlv equal to tlv
+fn take(pending: &mut [Option<Tlv<'_>>], tlv: &Tlv) -> bool {
+  match pending.iter_mut().find(|x| x.as_ref() == Some(tlv)) {
+    Some(x) => x.take().is_some(),
+    None => false,
+  }
+}
+
+impl<'a> DataUnit<'a> {
+  pub fn decode_faithful(buf: &'a [u8]) -> Result<FaithfulDataUnit<'a>, DataUnitError> {
+    let du = Self::decode(buf)?;
+
+    let mut unclaimed: Vec<_> = du.clone().into_tlvs().into_iter().map(Some).collect();
+    let mut tlvs = Vec::new();
+    let mut rest = buf;
+    while !rest.is_empty() {
+      let raw = RawTlv::decode(rest)?;
+      rest = &rest[raw.total_len()..];
+      let tlv = Tlv::decode(raw.clone()).ok().filter(|x| *x != Tlv::End);
+      let shadowed = tlv.as_ref().is_some_and(|x| !take(&mut unclaimed, x));
+      let end = raw.ty == 0;
+      tlvs.push(Entry { raw, tlv, shadowed });
+      if end {
+        break;
+      }
+    }
+
+    Ok(FaithfulDataUnit {
+      du,
+      tlvs,
+      padding: rest,
+    })
+  }
+}
+
+impl FaithfulDataUnit<'_> {
+  pub fn encode(&self, buf: &mut Vec<u8>) {
+    let mut pending: Vec<_> = self.du.clone().into_tlvs().into_iter().map(Some).collect();
+    let mut kept: Vec<_> = self
+      .tlvs
+      .iter()
+      .map(|x| match &x.tlv {
+        None => true,
+        Some(_) if x.shadowed => false,
+        Some(tlv) => take(&mut pending, tlv),
+      })
+      .collect();
+
+    for (i, x) in self.tlvs.iter().enumerate() {
+      if let (true, Some(tlv)) = (x.shadowed, &x.tlv) {
+        kept[i] = self
+          .tlvs
+          .iter()
+          .zip(&kept)
+          .any(|(y, kept)| *kept && !y.shadowed && y.tlv.as_ref().is_some_and(|y| same_slot(tlv, y)));
+      }
+    }
+
+    // whatever changed takes the place of the first tlv it replaced, anything new goes before the end tlv
+    let end = self.tlvs.iter().position(|x| x.raw.ty == 0).unwrap_or(self.tlvs.len());
+    let target = |tlv: &Tlv| {
+      self
+        .tlvs
+        .iter()
+        .zip(&kept)
+        .position(|(x, kept)| !kept && x.tlv.as_ref().is_some_and(|x| same_slot(tlv, x)))
+        .unwrap_or(end)
+    };
+    let mut placed: Vec<_> = pending.into_iter().flatten().map(|x| (target(&x), x)).collect();
+    placed.sort_by_key(|(i, _)| *i);
+
+    let mut placed = placed.into_iter().peekable();
+    for (i, x) in self.tlvs.iter().enumerate() {
+      while let Some((_, tlv)) = placed.next_if(|(at, _)| *at == i) {
+        tlv.encode(buf);
+      }
+      if kept[i] {
+        x.raw.encode(buf);
+      }
+    }
+    for (_, tlv) in placed {
+      tlv.encode(buf);
+    }
+    buf.extend_from_slice(self.padding);
+  }
+}
+
+#[test]
+fn re_encodes_byte_for_byte() {
+  use super::tlv::{ChassisId, CustomOrgTlv, PortId};
+
+  let mut buf = Vec::new();
+  Tlv::ChassisId(ChassisId::Local("chassis".into())).encode(&mut buf);
+  Tlv::PortId(PortId::Local("port".into())).encode(&mut buf);
+  Tlv::TimeToLive(120).encode(&mut buf);
+  Tlv::SystemName("first".into()).encode(&mut buf);
+  // reserved type 9, then a port description that isn't utf-8
+  buf.extend([9 << 1, 2, 0xab, 0xcd]);
+  buf.extend([4 << 1, 3, b'a', 0xff, b'b']);
+  Tlv::SystemName("last".into()).encode(&mut buf);
+  Tlv::Org(OrgTlv::Custom(CustomOrgTlv {
+    org: [0x00, 0x12, 0xbb],
+    subtype: 1,
+    data: vec![1, 2, 3].into(),
+  }))
+  .encode(&mut buf);
+  let unchanged_len = buf.len();
+  Tlv::End.encode(&mut buf);
+
+  let mut padded = buf.clone();
+  padded.resize(buf.len() + 5, 0);
+  let mut out = Vec::new();
+  DataUnit::decode_faithful(&padded).unwrap().encode(&mut out);
+  assert_eq!(out, padded);
+
+  let mut faithful = DataUnit::decode_faithful(&buf).unwrap();
+  let mut out = Vec::new();
+  faithful.encode(&mut out);
+  assert_eq!(out, buf);
+
+  // both system names give way to the new one, where the first of them was
+  faithful.du.system_name = Some("new".into());
+  faithful.du.time_to_live = 60;
+  faithful.du.port_description = None;
+  faithful.du.org.dot1.port_vlan_id = Some(10);
+  let mut out = Vec::new();
+  faithful.encode(&mut out);
+
+  let mut expected = buf[..17].to_vec();
+  Tlv::TimeToLive(60).encode(&mut expected);
+  Tlv::SystemName("new".into()).encode(&mut expected);
+  expected.extend([9 << 1, 2, 0xab, 0xcd]);
+  expected.extend_from_slice(&buf[unchanged_len - 9..unchanged_len]);
+  Tlv::Org(OrgTlv::Dot1(super::tlv::org::dot1::Tlv::PortVlanId(10))).encode(&mut expected);
+  Tlv::End.encode(&mut expected);
+  assert_eq!(out, expected);
+}
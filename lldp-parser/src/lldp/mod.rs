@@ -1,2 +1,7 @@
+#[cfg(feature = "heapless")]
+pub mod bounded;
 pub mod du;
+pub mod faithful;
+mod msap;
+pub use msap::Msap;
 pub mod tlv;
@@ -0,0 +1,47 @@
+use std::fmt::{self, Display};
+
+use super::{
+  du::DataUnit,
+  tlv::{ChassisId, PortId},
+};
+
+// the msap identifier from 802.1AB: what identifies a remote port, rather than the source mac which stacked
+// switches can share between members
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Msap<'a> {
+  pub chassis_id: ChassisId<'a>,
+  pub port_id: PortId<'a>,
+}
+
+impl Msap<'_> {
+  pub fn to_static(self) -> Msap<'static> {
+    Msap {
+      chassis_id: self.chassis_id.to_static(),
+      port_id: self.port_id.to_static(),
+    }
+  }
+}
+
+impl Display for Msap<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} / {}", self.chassis_id, self.port_id)
+  }
+}
+
+impl<'a> DataUnit<'a> {
+  pub fn msap(&self) -> Msap<'a> {
+    Msap {
+      chassis_id: self.chassis_id.clone(),
+      port_id: self.port_id.clone(),
+    }
+  }
+}
+
+#[test]
+fn displays_msap() {
+  let msap = Msap {
+    chassis_id: ChassisId::MacAddress([0, 0x11, 0x22, 0x33, 0x44, 0x55]),
+    port_id: PortId::InterfaceName("Gi1/0/1".into()),
+  };
+  assert_eq!(msap.to_string(), "00:11:22:33:44:55 / Gi1/0/1");
+}
@@ -1,12 +1,13 @@
 use std::{
   borrow::Cow,
-  cmp::Ordering,
+  fmt::{self, Display},
   net::{IpAddr, Ipv4Addr, Ipv6Addr},
 };
 
 use super::TlvDecodeError;
+use crate::error::exact;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum NetworkAddressKind {
   Ipv4,
   Ipv6,
@@ -33,12 +34,42 @@ impl From<NetworkAddressKind> for u8 {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum NetworkAddress<'a> {
   Ip(IpAddr),
   Other(u8, Cow<'a, [u8]>),
 }
 
+// {:#} gives "ip 10.0.0.1", or the iana address family for anything else
+impl Display for NetworkAddress<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Ip(x) if f.alternate() => write!(f, "ip {x}"),
+      Self::Ip(x) => x.fmt(f),
+      Self::Other(family, x) => {
+        if f.alternate() {
+          write!(f, "family {family} ")?;
+        }
+        fmt_bytes(f, x, ':')
+      }
+    }
+  }
+}
+
+pub(super) fn hex(buf: &[u8]) -> String {
+  buf.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+pub(super) fn fmt_bytes(f: &mut fmt::Formatter<'_>, buf: &[u8], separator: char) -> fmt::Result {
+  for (i, x) in buf.iter().enumerate() {
+    if i > 0 {
+      write!(f, "{separator}")?;
+    }
+    write!(f, "{x:02x}")?;
+  }
+  Ok(())
+}
+
 impl<'a> NetworkAddress<'a> {
   pub fn kind(&self) -> NetworkAddressKind {
     match self {
@@ -48,6 +79,16 @@ impl<'a> NetworkAddress<'a> {
     }
   }
 
+  // the iana family and the address bytes in hex, like "1:0a000001"
+  pub(super) fn canonical(&self) -> String {
+    let bytes = match self {
+      Self::Ip(IpAddr::V4(x)) => hex(&x.octets()),
+      Self::Ip(IpAddr::V6(x)) => hex(&x.octets()),
+      Self::Other(_, x) => hex(x),
+    };
+    format!("{}:{bytes}", u8::from(self.kind()))
+  }
+
   pub fn to_static(self) -> NetworkAddress<'static> {
     match self {
       Self::Ip(x) => NetworkAddress::Ip(x),
@@ -64,22 +105,8 @@ impl<'a> NetworkAddress<'a> {
     let buf = &buf[1..];
 
     match subtype {
-      NetworkAddressKind::Ipv4 => match buf.len().cmp(&4) {
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Equal => Ok(NetworkAddress::Ip(IpAddr::V4(Ipv4Addr::new(
-          buf[0], buf[1], buf[2], buf[3],
-        )))),
-      },
-
-      NetworkAddressKind::Ipv6 => match buf.len().cmp(&16) {
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Equal => {
-          let arr: [u8; 16] = buf[0..16].try_into().unwrap();
-          Ok(NetworkAddress::Ip(IpAddr::V6(Ipv6Addr::from(arr))))
-        }
-      },
+      NetworkAddressKind::Ipv4 => Ok(NetworkAddress::Ip(IpAddr::V4(Ipv4Addr::from(exact::<4>(buf)?)))),
+      NetworkAddressKind::Ipv6 => Ok(NetworkAddress::Ip(IpAddr::V6(Ipv6Addr::from(exact::<16>(buf)?)))),
 
       NetworkAddressKind::Unknown(x) => Ok(NetworkAddress::Other(x, Cow::Borrowed(buf))),
     }
@@ -1,8 +1,15 @@
-use std::{borrow::Cow, cmp::Ordering};
-
-use super::{NetworkAddress, TlvDecodeError};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use std::{
+  borrow::Cow,
+  fmt::{self, Display},
+};
+
+use super::{
+  address::{fmt_bytes, hex},
+  NetworkAddress, TlvDecodeError,
+};
+use crate::error::exact;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ChassisIdKind {
   Chassis,
   IfAlias,
@@ -43,7 +50,7 @@ impl From<ChassisIdKind> for u8 {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ChassisId<'a> {
   Chassis(Cow<'a, str>),
   InterfaceAlias(Cow<'a, str>),
@@ -54,6 +61,27 @@ pub enum ChassisId<'a> {
   Local(Cow<'a, str>),
 }
 
+// {:#} puts the subtype in front, like "mac 00:11:22:33:44:55"
+impl Display for ChassisId<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if f.alternate() {
+      // the address labels itself
+      if let Self::NetworkAddress(x) = self {
+        return write!(f, "{x:#}");
+      }
+      write!(f, "{} ", self.label())?;
+    }
+
+    match self {
+      Self::Chassis(x) | Self::InterfaceAlias(x) | Self::PortComponent(x) | Self::InterfaceName(x) | Self::Local(x) => {
+        f.write_str(x)
+      }
+      Self::MacAddress(x) => fmt_bytes(f, x, ':'),
+      Self::NetworkAddress(x) => x.fmt(f),
+    }
+  }
+}
+
 impl<'a> ChassisId<'a> {
   pub fn kind(&self) -> ChassisIdKind {
     match self {
@@ -67,6 +95,30 @@ impl<'a> ChassisId<'a> {
     }
   }
 
+  fn label(&self) -> &'static str {
+    match self {
+      Self::Chassis(_) => "chassis",
+      Self::InterfaceAlias(_) => "ifalias",
+      Self::PortComponent(_) => "port",
+      Self::MacAddress(_) => "mac",
+      Self::NetworkAddress(_) => "addr",
+      Self::InterfaceName(_) => "ifname",
+      Self::Local(_) => "local",
+    }
+  }
+
+  // same scheme as PortId::to_canonical_string, like "mac:001122334455"
+  pub fn to_canonical_string(&self) -> String {
+    let value = match self {
+      Self::Chassis(x) | Self::InterfaceAlias(x) | Self::PortComponent(x) | Self::InterfaceName(x) | Self::Local(x) => {
+        x.to_string()
+      }
+      Self::MacAddress(x) => hex(x),
+      Self::NetworkAddress(x) => x.canonical(),
+    };
+    format!("{}:{value}", self.label())
+  }
+
   pub fn to_static(self) -> ChassisId<'static> {
     match self {
       Self::Chassis(x) => ChassisId::Chassis(Cow::Owned(x.into_owned())),
@@ -95,14 +147,7 @@ impl<'a> ChassisId<'a> {
 
       ChassisIdKind::Addr => Ok(ChassisId::NetworkAddress(NetworkAddress::decode(buf)?)),
 
-      ChassisIdKind::LlAddr => match buf.len().cmp(&6) {
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Equal => {
-          let mac = buf[0..6].try_into().unwrap();
-          Ok(ChassisId::MacAddress(mac))
-        }
-      },
+      ChassisIdKind::LlAddr => Ok(ChassisId::MacAddress(exact(buf)?)),
     }
   }
 
@@ -132,6 +177,17 @@ impl<'a> ChassisId<'a> {
   }
 }
 
+#[test]
+fn displays_chassis_ids() {
+  let chassis_id = ChassisId::MacAddress([0, 0x11, 0x22, 0x33, 0x44, 0x55]);
+  assert_eq!(chassis_id.to_string(), "00:11:22:33:44:55");
+  assert_eq!(format!("{chassis_id:#}"), "mac 00:11:22:33:44:55");
+  assert_eq!(chassis_id.to_canonical_string(), "mac:001122334455");
+  let chassis_id = ChassisId::NetworkAddress(NetworkAddress::Ip([10, 0, 0, 1].into()));
+  assert_eq!(format!("{chassis_id:#}"), "ip 10.0.0.1");
+  assert_eq!(chassis_id.to_canonical_string(), "addr:1:0a000001");
+}
+
 #[test]
 fn basic_encode_decode() {
   use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
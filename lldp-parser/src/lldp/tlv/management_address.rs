@@ -1,9 +1,13 @@
-use std::{borrow::Cow, cmp::Ordering};
+use std::{
+  borrow::Cow,
+  cmp::Ordering,
+  fmt::{self, Display},
+};
 
 use super::TlvDecodeError;
 use crate::lldp::tlv::NetworkAddress;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ManagementInterfaceKind {
   Unknown,
   IfIndex,
@@ -40,6 +44,22 @@ pub struct ManagementAddress<'a> {
   pub oid: Cow<'a, str>,
 }
 
+// just the address, {:#} adds the interface it belongs to like "ip 10.0.0.1 ifindex 3"
+impl Display for ManagementAddress<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if !f.alternate() {
+      return self.address.fmt(f);
+    }
+
+    write!(f, "{:#}", self.address)?;
+    match self.interface_subtype {
+      ManagementInterfaceKind::Unknown => Ok(()),
+      ManagementInterfaceKind::IfIndex => write!(f, " ifindex {}", self.interface_number),
+      ManagementInterfaceKind::SysPort => write!(f, " sysport {}", self.interface_number),
+    }
+  }
+}
+
 impl<'a> ManagementAddress<'a> {
   pub fn to_static(self) -> ManagementAddress<'static> {
     ManagementAddress {
@@ -117,3 +137,15 @@ fn basic_encode_decode() {
     oid: Cow::Borrowed("foobarbaz"),
   }));
 }
+
+#[test]
+fn displays_addresses() {
+  let address = ManagementAddress {
+    address: NetworkAddress::Ip([10, 0, 0, 1].into()),
+    interface_subtype: ManagementInterfaceKind::IfIndex,
+    interface_number: 3,
+    oid: Cow::Borrowed(""),
+  };
+  assert_eq!(address.to_string(), "10.0.0.1");
+  assert_eq!(format!("{address:#}"), "ip 10.0.0.1 ifindex 3");
+}
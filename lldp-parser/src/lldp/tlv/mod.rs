@@ -1,8 +1,10 @@
-use std::{borrow::Cow, cmp::Ordering};
+use std::borrow::Cow;
 
-use thiserror::Error;
 use tracing::warn;
 
+use crate::error::exact;
+pub use crate::error::{RawTlvError, TlvDecodeError};
+
 mod address;
 pub use address::*;
 
@@ -33,23 +35,24 @@ pub enum TlvKind {
   Capabilities,
   ManagementAddress,
   Org,
+  // reserved types, kept so they round-trip
+  Unknown(u8),
 }
 
-impl TryFrom<u8> for TlvKind {
-  type Error = u8;
-  fn try_from(value: u8) -> Result<Self, u8> {
+impl From<u8> for TlvKind {
+  fn from(value: u8) -> Self {
     match value {
-      0 => Ok(Self::End),
-      1 => Ok(Self::ChassisId),
-      2 => Ok(Self::PortId),
-      3 => Ok(Self::TimeToLive),
-      4 => Ok(Self::PortDescription),
-      5 => Ok(Self::SystemName),
-      6 => Ok(Self::SystemDescription),
-      7 => Ok(Self::Capabilities),
-      8 => Ok(Self::ManagementAddress),
-      127 => Ok(Self::Org),
-      x => Err(x),
+      0 => Self::End,
+      1 => Self::ChassisId,
+      2 => Self::PortId,
+      3 => Self::TimeToLive,
+      4 => Self::PortDescription,
+      5 => Self::SystemName,
+      6 => Self::SystemDescription,
+      7 => Self::Capabilities,
+      8 => Self::ManagementAddress,
+      127 => Self::Org,
+      x => Self::Unknown(x),
     }
   }
 }
@@ -67,6 +70,7 @@ impl From<TlvKind> for u8 {
       TlvKind::Capabilities => 7,
       TlvKind::ManagementAddress => 8,
       TlvKind::Org => 127,
+      TlvKind::Unknown(x) => x,
     }
   }
 }
@@ -93,11 +97,11 @@ pub struct RawTlv<'a> {
 }
 
 impl<'a> RawTlv<'a> {
-  fn total_len(&self) -> usize {
+  pub fn total_len(&self) -> usize {
     self.payload.len() + 2
   }
 
-  fn decode(buf: &'a [u8]) -> Result<Self, RawTlvError> {
+  pub fn decode(buf: &'a [u8]) -> Result<Self, RawTlvError> {
     if buf.len() < 2 {
       return Err(RawTlvError::BufferTooShort);
     }
@@ -107,7 +111,10 @@ impl<'a> RawTlv<'a> {
     let tlv_len = payload_len + 2;
 
     if buf.len() < tlv_len {
-      return Err(RawTlvError::BufferTooShort);
+      return Err(RawTlvError::Truncated {
+        len: payload_len,
+        available: buf.len() - 2,
+      });
     }
 
     let payload = &buf[2..2 + payload_len];
@@ -117,6 +124,12 @@ impl<'a> RawTlv<'a> {
       payload,
     })
   }
+
+  pub fn encode(&self, buf: &mut Vec<u8>) {
+    let hdr = ((self.ty as u16) << 9) + (self.payload.len() as u16 & 0b00000001_11111111);
+    buf.extend(hdr.to_be_bytes());
+    buf.extend_from_slice(self.payload);
+  }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -165,34 +178,9 @@ impl<'a> Tlv<'a> {
   }
 }
 
-#[derive(Debug, Clone, Error)]
-pub enum RawTlvError {
-  #[error("buffer too short")]
-  BufferTooShort,
-}
-
-#[derive(Debug, Clone, Error)]
-pub enum TlvDecodeError {
-  #[error("buffer too short")]
-  BufferTooShort,
-  #[error("buffer too long")]
-  BufferTooLong,
-  #[error("bytes after end")]
-  BytesAfterEnd,
-  #[error("unknown chassis id subtype '{0}'")]
-  UnknownChassisIdSubtype(u8),
-  #[error("unknown port id subtype '{0}'")]
-  UnknownPortIdSubtype(u8),
-  #[error("unknown management interface subtype '{0}'")]
-  UnknownManagementInterfaceSubtype(u8),
-  #[error("unknown tlv '{0}'")]
-  UnknownTlv(u8),
-}
-
 impl<'a> Tlv<'a> {
-  fn decode(raw: RawTlv<'a>) -> Result<Self, TlvDecodeError> {
-    let kind = raw.ty.try_into().map_err(TlvDecodeError::UnknownTlv)?;
-    match kind {
+  pub fn decode(raw: RawTlv<'a>) -> Result<Self, TlvDecodeError> {
+    match TlvKind::from(raw.ty) {
       TlvKind::End => {
         if raw.payload.len() > 2 {
           Err(TlvDecodeError::BytesAfterEnd)
@@ -204,11 +192,7 @@ impl<'a> Tlv<'a> {
       TlvKind::ChassisId => ChassisId::decode(raw.payload).map(Tlv::ChassisId),
       TlvKind::PortId => PortId::decode(raw.payload).map(Tlv::PortId),
 
-      TlvKind::TimeToLive => match raw.payload.len().cmp(&2) {
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Equal => Ok(Tlv::TimeToLive(u16::from_be_bytes(raw.payload.try_into().unwrap()))),
-      },
+      TlvKind::TimeToLive => Ok(Tlv::TimeToLive(u16::from_be_bytes(exact(raw.payload)?))),
 
       TlvKind::PortDescription => Ok(Tlv::PortDescription(String::from_utf8_lossy(raw.payload))),
       TlvKind::SystemName => Ok(Tlv::SystemName(String::from_utf8_lossy(raw.payload))),
@@ -216,6 +200,7 @@ impl<'a> Tlv<'a> {
       TlvKind::Capabilities => Capabilities::decode(raw.payload).map(Tlv::Capabilities),
       TlvKind::ManagementAddress => ManagementAddress::decode(raw.payload).map(Tlv::ManagementAddress),
       TlvKind::Org => OrgTlv::decode(raw.payload).map(Tlv::Org),
+      TlvKind::Unknown(x) => Err(TlvDecodeError::UnknownTlv(x.into())),
     }
   }
 
@@ -265,6 +250,14 @@ fn test_encode_decode(tlv: Tlv) {
   assert_eq!(parsed_tlv, tlv);
 }
 
+#[test]
+fn kinds_round_trip() {
+  for x in 0..=127 {
+    assert_eq!(u8::from(TlvKind::from(x)), x);
+  }
+  assert_eq!(TlvKind::from(9), TlvKind::Unknown(9));
+}
+
 #[test]
 fn encode_decode_ttl() {
   test_encode_decode(Tlv::TimeToLive(1234));
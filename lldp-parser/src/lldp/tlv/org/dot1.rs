@@ -1,6 +1,7 @@
 use std::{borrow::Cow, cmp::Ordering};
 
-use crate::lldp::tlv::TlvDecodeError;
+use super::LLDP_TLV_ORG_DOT1;
+use crate::{error::exact, lldp::tlv::TlvDecodeError};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TlvKind {
@@ -56,13 +57,13 @@ impl<'a> Tlv<'a> {
   }
 
   pub(super) fn decode(subtype: u8, buf: &'a [u8]) -> Result<Self, TlvDecodeError> {
-    let kind = subtype.try_into().map_err(TlvDecodeError::UnknownTlv)?;
+    let unknown = |subtype| TlvDecodeError::UnknownOrgSubtype {
+      oui: LLDP_TLV_ORG_DOT1,
+      subtype,
+    };
+    let kind = subtype.try_into().map_err(unknown)?;
     match kind {
-      TlvKind::PortVlanId => match buf.len().cmp(&2) {
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Equal => Ok(Tlv::PortVlanId(u16::from_be_bytes(buf[0..2].try_into().unwrap()))),
-      },
+      TlvKind::PortVlanId => Ok(Tlv::PortVlanId(u16::from_be_bytes(exact(buf)?))),
 
       TlvKind::VlanName => {
         if buf.len() < 3 {
@@ -80,7 +81,7 @@ impl<'a> Tlv<'a> {
         }
       }
 
-      x => Err(TlvDecodeError::UnknownTlv(x.into())),
+      x => Err(unknown(x.into())),
     }
   }
 
@@ -1,11 +1,9 @@
-use std::{
-  cmp::Ordering,
-  fmt::{self, Debug},
-};
+use std::fmt::{self, Debug};
 
 use bitflags::bitflags;
 
-use crate::lldp::tlv::TlvDecodeError;
+use super::LLDP_TLV_ORG_DOT3;
+use crate::{error::exact, lldp::tlv::TlvDecodeError};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TlvKind {
@@ -42,42 +40,74 @@ impl From<TlvKind> for u8 {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Tlv {
   MacPhyStatus(MacPhyStatus),
+  Power(Power),
 }
 
 impl Tlv {
   pub fn kind(&self) -> TlvKind {
     match self {
       Self::MacPhyStatus(_) => TlvKind::MacPhyStatus,
+      Self::Power(_) => TlvKind::Power,
     }
   }
 
   pub(super) fn decode(subtype: u8, buf: &[u8]) -> Result<Self, TlvDecodeError> {
-    let kind: TlvKind = subtype.try_into().map_err(TlvDecodeError::UnknownTlv)?;
+    let unknown = |subtype| TlvDecodeError::UnknownOrgSubtype {
+      oui: LLDP_TLV_ORG_DOT3,
+      subtype,
+    };
+    let kind: TlvKind = subtype.try_into().map_err(unknown)?;
     match kind {
-      TlvKind::MacPhyStatus => match buf.len().cmp(&5) {
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Equal => {
-          let status = AutoNegotiationStatus::from_bits_retain(buf[0]);
-          let advertised =
-            AutoNegotiationCapability::from_bits_retain(u16::from_le_bytes(buf[1..3].try_into().unwrap()));
-          let mau = MauType::from(u16::from_be_bytes(buf[3..5].try_into().unwrap()));
-
-          Ok(Tlv::MacPhyStatus(MacPhyStatus {
-            status,
-            advertised,
-            mau,
-          }))
+      TlvKind::MacPhyStatus => {
+        let buf: [u8; 5] = exact(buf)?;
+        let status = AutoNegotiationStatus::from_bits_retain(buf[0]);
+        let advertised = AutoNegotiationCapability::from_bits_retain(u16::from_le_bytes(buf[1..3].try_into().unwrap()));
+        let mau = MauType::from(u16::from_be_bytes(buf[3..5].try_into().unwrap()));
+
+        Ok(Tlv::MacPhyStatus(MacPhyStatus {
+          status,
+          advertised,
+          mau,
+        }))
+      }
+
+      TlvKind::Power => {
+        if buf.len() < 3 {
+          return Err(TlvDecodeError::BufferTooShort);
         }
-      },
+        // 802.1ab stops after the class, 802.3at adds 5 bytes and 802.3bt more after that
+        let extension = match buf.len() {
+          3 => None,
+          4..=7 => {
+            return Err(TlvDecodeError::WrongLength {
+              expected: 8,
+              actual: buf.len(),
+            })
+          }
+          _ => Some(PowerExtension {
+            type_source_priority: buf[3],
+            requested: u16::from_be_bytes(buf[4..6].try_into().unwrap()),
+            allocated: u16::from_be_bytes(buf[6..8].try_into().unwrap()),
+            bt: buf[8..].to_vec(),
+          }),
+        };
+
+        Ok(Tlv::Power(Power {
+          support: PowerSupport::from_bits_retain(buf[0]),
+          pse_power_pair: buf[1],
+          power_class: buf[2],
+          extension,
+        }))
+      }
 
-      x => Err(TlvDecodeError::UnknownTlv(x.into())),
+      x => Err(unknown(x.into())),
     }
   }
 
   pub(super) fn encoded_size(&self) -> usize {
     let size = match self {
       Self::MacPhyStatus(_) => 5,
+      Self::Power(x) => 3 + x.extension.as_ref().map_or(0, |x| 5 + x.bt.len()),
     };
     size + 1
   }
@@ -91,6 +121,17 @@ impl Tlv {
         let mau: u16 = x.mau.into();
         buf.extend(mau.to_be_bytes());
       }
+      Self::Power(x) => {
+        buf.push(x.support.bits());
+        buf.push(x.pse_power_pair);
+        buf.push(x.power_class);
+        if let Some(ext) = &x.extension {
+          buf.push(ext.type_source_priority);
+          buf.extend(ext.requested.to_be_bytes());
+          buf.extend(ext.allocated.to_be_bytes());
+          buf.extend(&ext.bt);
+        }
+      }
     }
   }
 }
@@ -104,6 +145,23 @@ fn test_encode_decode() {
     advertised: AutoNegotiationCapability::OTHER | AutoNegotiationCapability::B_1000_BASE_T_FD,
     mau: MauType::B1000BaseTFD,
   }))));
+  test_encode_decode(BaseTlv::Org(OrgTlv::Dot3(Tlv::Power(Power {
+    support: PowerSupport::PSE | PowerSupport::SUPPORTED,
+    pse_power_pair: 1,
+    power_class: 1,
+    extension: None,
+  }))));
+  test_encode_decode(BaseTlv::Org(OrgTlv::Dot3(Tlv::Power(Power {
+    support: PowerSupport::SUPPORTED | PowerSupport::ENABLED,
+    pse_power_pair: 1,
+    power_class: 5,
+    extension: Some(PowerExtension {
+      type_source_priority: 0b0101_0001,
+      requested: 255,
+      allocated: 255,
+      bt: vec![],
+    }),
+  }))));
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -113,6 +171,84 @@ pub struct MacPhyStatus {
   pub mau: MauType,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Power {
+  pub support: PowerSupport,
+  pub pse_power_pair: u8,
+  // class 0 through 4, encoded plus one
+  pub power_class: u8,
+  pub extension: Option<PowerExtension>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PowerExtension {
+  pub type_source_priority: u8,
+  // both in 0.1 W
+  pub requested: u16,
+  pub allocated: u16,
+  // 802.3bt fields, kept as they came
+  pub bt: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerPortClass {
+  Pse,
+  Pd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PoeLevel {
+  Af,
+  At,
+  Bt,
+}
+
+impl Power {
+  pub fn port_class(&self) -> PowerPortClass {
+    if self.support.contains(PowerSupport::PSE) {
+      PowerPortClass::Pse
+    } else {
+      PowerPortClass::Pd
+    }
+  }
+
+  pub fn class(&self) -> Option<u8> {
+    match self.power_class {
+      1..=5 => Some(self.power_class - 1),
+      _ => None,
+    }
+  }
+
+  // without the 802.3at fields there is no way to ask for more than af
+  pub fn level(&self) -> PoeLevel {
+    match &self.extension {
+      None => PoeLevel::Af,
+      Some(x) if !x.bt.is_empty() => PoeLevel::Bt,
+      Some(x) if x.type_source_priority & 0x80 != 0 => PoeLevel::Af,
+      Some(_) => PoeLevel::At,
+    }
+  }
+
+  pub fn requested_watts(&self) -> Option<f32> {
+    self.extension.as_ref().map(|x| f32::from(x.requested) / 10.0)
+  }
+
+  pub fn allocated_watts(&self) -> Option<f32> {
+    self.extension.as_ref().map(|x| f32::from(x.allocated) / 10.0)
+  }
+}
+
+bitflags! {
+  #[repr(transparent)]
+  #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  pub struct PowerSupport: u8 {
+    const PSE                = 0b00000001;
+    const SUPPORTED          = 0b00000010;
+    const ENABLED            = 0b00000100;
+    const PAIRS_CONTROLLABLE = 0b00001000;
+  }
+}
+
 bitflags! {
   #[repr(transparent)]
   #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -522,3 +658,28 @@ impl fmt::Debug for MauType {
       .finish()
   }
 }
+
+#[test]
+fn classifies_power() {
+  let buf = [0x07, 0x01, 0x05, 0b0101_0001, 0x01, 0x2c, 0x00, 0xff];
+  let Ok(Tlv::Power(power)) = Tlv::decode(2, &buf) else {
+    panic!("expected a power tlv");
+  };
+  assert_eq!(power.port_class(), PowerPortClass::Pse);
+  assert_eq!(power.class(), Some(4));
+  assert_eq!(power.level(), PoeLevel::At);
+  assert_eq!(power.requested_watts(), Some(30.0));
+  assert_eq!(power.allocated_watts(), Some(25.5));
+
+  let Ok(Tlv::Power(power)) = Tlv::decode(2, &[0x02, 0x01, 0x01]) else {
+    panic!("expected a power tlv");
+  };
+  assert_eq!(power.port_class(), PowerPortClass::Pd);
+  assert_eq!(power.class(), Some(0));
+  assert_eq!(power.level(), PoeLevel::Af);
+  assert_eq!(power.requested_watts(), None);
+  assert_eq!(
+    Tlv::decode(2, &[0x02, 0x01, 0x01, 0x00]),
+    Err(TlvDecodeError::WrongLength { expected: 8, actual: 4 })
+  );
+}
@@ -24,6 +24,11 @@ impl<'a> OrgTlv<'a> {
     }
   }
 
+  #[cfg(feature = "oui")]
+  pub fn org_name(&self) -> Option<&'static str> {
+    crate::oui::vendor(self.org())
+  }
+
   pub fn to_static(self) -> OrgTlv<'static> {
     match self {
       Self::Dot1(x) => OrgTlv::Dot1(x.to_static()),
@@ -1,8 +1,15 @@
-use std::{borrow::Cow, cmp::Ordering};
-
-use super::{NetworkAddress, TlvDecodeError};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use std::{
+  borrow::Cow,
+  fmt::{self, Display},
+};
+
+use super::{
+  address::{fmt_bytes, hex},
+  NetworkAddress, TlvDecodeError,
+};
+use crate::error::exact;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PortIdKind {
   IfAlias,
   Port,
@@ -43,7 +50,7 @@ impl From<PortIdKind> for u8 {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PortId<'a> {
   InterfaceAlias(Cow<'a, str>),
   PortComponent(Cow<'a, str>),
@@ -54,6 +61,26 @@ pub enum PortId<'a> {
   Local(Cow<'a, str>),
 }
 
+// {:#} puts the subtype in front, like "ifname Gi1/0/1"
+impl Display for PortId<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if f.alternate() {
+      // the address labels itself
+      if let Self::NetworkAddress(x) = self {
+        return write!(f, "{x:#}");
+      }
+      write!(f, "{} ", self.label())?;
+    }
+
+    match self {
+      Self::InterfaceAlias(x) | Self::PortComponent(x) | Self::InterfaceName(x) | Self::Local(x) => f.write_str(x),
+      Self::MacAddress(x) => fmt_bytes(f, x, ':'),
+      Self::NetworkAddress(x) => x.fmt(f),
+      Self::AgentCircuitId(x) => fmt_bytes(f, x, ' '),
+    }
+  }
+}
+
 impl<'a> PortId<'a> {
   pub fn kind(&self) -> PortIdKind {
     match self {
@@ -67,6 +94,30 @@ impl<'a> PortId<'a> {
     }
   }
 
+  fn label(&self) -> &'static str {
+    match self {
+      Self::InterfaceAlias(_) => "ifalias",
+      Self::PortComponent(_) => "port",
+      Self::MacAddress(_) => "mac",
+      Self::NetworkAddress(_) => "addr",
+      Self::InterfaceName(_) => "ifname",
+      Self::AgentCircuitId(_) => "circuit-id",
+      Self::Local(_) => "local",
+    }
+  }
+
+  // stable across implementations for use as a key, binary ids are always plain lowercase hex
+  // like "mac:001122334455" or "addr:1:0a000001", text is kept as sent like "ifname:Gi1/0/1"
+  pub fn to_canonical_string(&self) -> String {
+    let value = match self {
+      Self::InterfaceAlias(x) | Self::PortComponent(x) | Self::InterfaceName(x) | Self::Local(x) => x.to_string(),
+      Self::MacAddress(x) => hex(x),
+      Self::NetworkAddress(x) => x.canonical(),
+      Self::AgentCircuitId(x) => hex(x),
+    };
+    format!("{}:{value}", self.label())
+  }
+
   pub fn to_static(self) -> PortId<'static> {
     match self {
       Self::InterfaceAlias(x) => PortId::InterfaceAlias(Cow::Owned(x.into_owned())),
@@ -96,14 +147,7 @@ impl<'a> PortId<'a> {
 
       PortIdKind::Addr => Ok(PortId::NetworkAddress(NetworkAddress::decode(buf)?)),
 
-      PortIdKind::LlAddr => match buf.len().cmp(&6) {
-        Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-        Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-        Ordering::Equal => {
-          let mac = buf[0..6].try_into().unwrap();
-          Ok(PortId::MacAddress(mac))
-        }
-      },
+      PortIdKind::LlAddr => Ok(PortId::MacAddress(exact(buf)?)),
     }
   }
 
@@ -133,6 +177,43 @@ impl<'a> PortId<'a> {
   }
 }
 
+#[test]
+fn displays_port_ids() {
+  let port_id = PortId::InterfaceName("Gi1/0/1".into());
+  assert_eq!(port_id.to_string(), "Gi1/0/1");
+  assert_eq!(format!("{port_id:#}"), "ifname Gi1/0/1");
+  assert_eq!(
+    format!("{:#}", PortId::AgentCircuitId(Cow::Borrowed(&[1, 2]))),
+    "circuit-id 01 02"
+  );
+}
+
+#[test]
+fn canonical_port_ids() {
+  use std::net::Ipv4Addr;
+
+  assert_eq!(
+    PortId::InterfaceName("Gi1/0/1".into()).to_canonical_string(),
+    "ifname:Gi1/0/1"
+  );
+  assert_eq!(
+    PortId::MacAddress([0, 0x11, 0x22, 0x33, 0x44, 0xaa]).to_canonical_string(),
+    "mac:0011223344aa"
+  );
+  assert_eq!(
+    PortId::AgentCircuitId(Cow::Borrowed(&[1, 0xab])).to_canonical_string(),
+    "circuit-id:01ab"
+  );
+  assert_eq!(
+    PortId::NetworkAddress(NetworkAddress::Ip(Ipv4Addr::new(10, 0, 0, 1).into())).to_canonical_string(),
+    "addr:1:0a000001"
+  );
+  assert_eq!(
+    PortId::NetworkAddress(NetworkAddress::Other(6, Cow::Borrowed(&[0xde, 0xad]))).to_canonical_string(),
+    "addr:6:dead"
+  );
+}
+
 #[test]
 fn basic_encode_decode() {
   use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
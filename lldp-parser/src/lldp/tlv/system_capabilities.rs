@@ -1,8 +1,9 @@
-use std::cmp::Ordering;
+use std::fmt::{self, Display};
 
 use bitflags::bitflags;
 
 use super::TlvDecodeError;
+use crate::error::exact;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Capabilities {
@@ -28,22 +29,63 @@ bitflags! {
   }
 }
 
-impl Capabilities {
-  pub(super) fn decode(buf: &[u8]) -> Result<Self, TlvDecodeError> {
-    match buf.len().cmp(&4) {
-      Ordering::Greater => Err(TlvDecodeError::BufferTooLong),
-      Ordering::Less => Err(TlvDecodeError::BufferTooShort),
-      Ordering::Equal => {
-        let capabilities = u16::from_be_bytes(buf[0..2].try_into().unwrap());
-        let capabilities = CapabilityFlags::from_bits_retain(capabilities);
-        let enabled_capabilities = u16::from_be_bytes(buf[2..4].try_into().unwrap());
-        let enabled_capabilities = CapabilityFlags::from_bits_retain(enabled_capabilities);
-        Ok(Capabilities {
-          capabilities,
-          enabled_capabilities,
-        })
+const NAMES: [(CapabilityFlags, &str); 11] = [
+  (CapabilityFlags::OTHER, "Other"),
+  (CapabilityFlags::REPEATER, "Repeater"),
+  (CapabilityFlags::BRIDGE, "Bridge"),
+  (CapabilityFlags::WLAN_ACCESS_POINT, "WLAN Access Point"),
+  (CapabilityFlags::ROUTER, "Router"),
+  (CapabilityFlags::TELEPHONE, "Telephone"),
+  (CapabilityFlags::DOCSIS, "DOCSIS"),
+  (CapabilityFlags::STATION, "Station"),
+  (CapabilityFlags::C_VLAN, "C-VLAN"),
+  (CapabilityFlags::S_VLAN, "S-VLAN"),
+  (CapabilityFlags::TWO_PORT_MAC_RELAY, "Two-Port MAC Relay"),
+];
+
+impl CapabilityFlags {
+  // in bit order, reserved bits are skipped
+  pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+    NAMES
+      .iter()
+      .filter(|(flag, _)| self.contains(*flag))
+      .map(|(_, name)| *name)
+  }
+}
+
+// "Bridge (enabled), Router", or "none"
+impl Display for Capabilities {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut names = NAMES
+      .iter()
+      .filter(|(flag, _)| self.capabilities.contains(*flag))
+      .peekable();
+    if names.peek().is_none() {
+      return f.write_str("none");
+    }
+
+    for (i, (flag, name)) in names.enumerate() {
+      if i > 0 {
+        f.write_str(", ")?;
+      }
+      f.write_str(name)?;
+      if self.enabled_capabilities.contains(*flag) {
+        f.write_str(" (enabled)")?;
       }
     }
+    Ok(())
+  }
+}
+
+impl Capabilities {
+  pub(super) fn decode(buf: &[u8]) -> Result<Self, TlvDecodeError> {
+    let buf: [u8; 4] = exact(buf)?;
+    let capabilities = CapabilityFlags::from_bits_retain(u16::from_be_bytes([buf[0], buf[1]]));
+    let enabled_capabilities = CapabilityFlags::from_bits_retain(u16::from_be_bytes([buf[2], buf[3]]));
+    Ok(Capabilities {
+      capabilities,
+      enabled_capabilities,
+    })
   }
 
   pub(super) fn encoded_size(&self) -> usize {
@@ -67,3 +109,15 @@ fn basic_encode_decode() {
     enabled_capabilities,
   }))
 }
+
+#[test]
+fn names_capabilities() {
+  let capabilities = Capabilities {
+    capabilities: CapabilityFlags::BRIDGE | CapabilityFlags::ROUTER | CapabilityFlags::from_bits_retain(0x8000),
+    enabled_capabilities: CapabilityFlags::BRIDGE,
+  };
+  let names: Vec<_> = capabilities.capabilities.names().collect();
+  assert_eq!(names, ["Bridge", "Router"]);
+  assert_eq!(capabilities.to_string(), "Bridge (enabled), Router");
+  assert_eq!(Capabilities::decode(&[0; 4]).unwrap().to_string(), "none");
+}
@@ -0,0 +1,144 @@
+use std::{
+  borrow::Cow,
+  net::{Ipv4Addr, Ipv6Addr},
+};
+
+use thiserror::Error;
+use tracing::warn;
+
+// routeros announces every 60 seconds and doesn't send a hold time
+pub const TIME_TO_LIVE: u16 = 120;
+
+const TLV_MAC_ADDRESS: u16 = 1;
+const TLV_IDENTITY: u16 = 5;
+const TLV_VERSION: u16 = 7;
+const TLV_PLATFORM: u16 = 8;
+const TLV_UPTIME: u16 = 10;
+const TLV_SOFTWARE_ID: u16 = 11;
+const TLV_BOARD: u16 = 12;
+const TLV_IPV6_ADDRESS: u16 = 15;
+const TLV_INTERFACE_NAME: u16 = 16;
+const TLV_IPV4_ADDRESS: u16 = 17;
+
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum DataUnitError {
+  #[error("buffer too short")]
+  BufferTooShort,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataUnit<'a> {
+  pub sequence: u16,
+  pub mac_address: Option<[u8; 6]>,
+  pub identity: Option<Cow<'a, str>>,
+  pub version: Option<Cow<'a, str>>,
+  pub platform: Option<Cow<'a, str>>,
+  pub uptime: Option<u32>,
+  pub software_id: Option<Cow<'a, str>>,
+  pub board: Option<Cow<'a, str>>,
+  pub interface_name: Option<Cow<'a, str>>,
+  pub ipv4_address: Option<Ipv4Addr>,
+  pub ipv6_address: Option<Ipv6Addr>,
+}
+
+impl<'a> DataUnit<'a> {
+  pub fn to_static(self) -> DataUnit<'static> {
+    DataUnit {
+      sequence: self.sequence,
+      mac_address: self.mac_address,
+      identity: self.identity.map(|x| Cow::Owned(x.into_owned())),
+      version: self.version.map(|x| Cow::Owned(x.into_owned())),
+      platform: self.platform.map(|x| Cow::Owned(x.into_owned())),
+      uptime: self.uptime,
+      software_id: self.software_id.map(|x| Cow::Owned(x.into_owned())),
+      board: self.board.map(|x| Cow::Owned(x.into_owned())),
+      interface_name: self.interface_name.map(|x| Cow::Owned(x.into_owned())),
+      ipv4_address: self.ipv4_address,
+      ipv6_address: self.ipv6_address,
+    }
+  }
+
+  pub fn decode(buf: &'a [u8]) -> Result<Self, DataUnitError> {
+    if buf.len() < 4 {
+      return Err(DataUnitError::BufferTooShort);
+    }
+
+    let mut du = Self {
+      sequence: u16::from_be_bytes(buf[2..4].try_into().unwrap()),
+      mac_address: None,
+      identity: None,
+      version: None,
+      platform: None,
+      uptime: None,
+      software_id: None,
+      board: None,
+      interface_name: None,
+      ipv4_address: None,
+      ipv6_address: None,
+    };
+
+    let mut buf = &buf[4..];
+    while !buf.is_empty() {
+      if buf.len() < 4 {
+        return Err(DataUnitError::BufferTooShort);
+      }
+
+      // unlike cdp, the length only covers the value
+      let ty = u16::from_be_bytes(buf[0..2].try_into().unwrap());
+      let len = u16::from_be_bytes(buf[2..4].try_into().unwrap()) as usize;
+      let Some(value) = buf.get(4..4 + len) else {
+        return Err(DataUnitError::BufferTooShort);
+      };
+      buf = &buf[4 + len..];
+
+      let string = || Some(String::from_utf8_lossy(value));
+      match (ty, len) {
+        (TLV_MAC_ADDRESS, 6) => du.mac_address = Some(value.try_into().unwrap()),
+        (TLV_IDENTITY, _) => du.identity = string(),
+        (TLV_VERSION, _) => du.version = string(),
+        (TLV_PLATFORM, _) => du.platform = string(),
+        // the only little endian field in the packet
+        (TLV_UPTIME, 4) => du.uptime = Some(u32::from_le_bytes(value.try_into().unwrap())),
+        (TLV_SOFTWARE_ID, _) => du.software_id = string(),
+        (TLV_BOARD, _) => du.board = string(),
+        (TLV_IPV6_ADDRESS, 16) => du.ipv6_address = Some(<[u8; 16]>::try_from(value).unwrap().into()),
+        (TLV_INTERFACE_NAME, _) => du.interface_name = string(),
+        (TLV_IPV4_ADDRESS, 4) => du.ipv4_address = Some(<[u8; 4]>::try_from(value).unwrap().into()),
+        (TLV_MAC_ADDRESS | TLV_UPTIME | TLV_IPV6_ADDRESS | TLV_IPV4_ADDRESS, _) => {
+          warn!(ty, len, "unexpected tlv length")
+        }
+        _ => {}
+      }
+    }
+
+    Ok(du)
+  }
+}
+
+#[test]
+fn decode_mndp() {
+  let mut buf = vec![0, 0, 0, 7];
+  for (ty, value) in [
+    (TLV_MAC_ADDRESS, &[0x4c, 0x5e, 0x0c, 0, 0, 1][..]),
+    (TLV_IDENTITY, b"MikroTik"),
+    (TLV_UPTIME, &[0x10, 0x0e, 0, 0]),
+    (TLV_INTERFACE_NAME, b"ether1"),
+    (TLV_IPV4_ADDRESS, &[192, 168, 88, 1]),
+    (14, &[1]),
+  ] {
+    buf.extend_from_slice(&ty.to_be_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value);
+  }
+
+  let du = DataUnit::decode(&buf).unwrap();
+  assert_eq!(du.sequence, 7);
+  assert_eq!(du.mac_address, Some([0x4c, 0x5e, 0x0c, 0, 0, 1]));
+  assert_eq!(du.identity.as_deref(), Some("MikroTik"));
+  assert_eq!(du.uptime, Some(3600));
+  assert_eq!(du.interface_name.as_deref(), Some("ether1"));
+  assert_eq!(du.ipv4_address, Some(Ipv4Addr::new(192, 168, 88, 1)));
+
+  assert!(DataUnit::decode(&buf[..buf.len() - 1]).is_err());
+}
@@ -0,0 +1,73 @@
+// a small table of the vendors likely to turn up on a network, the full ieee registry is several megabytes.
+// sorted so it can be binary searched
+const VENDORS: &[([u8; 3], &str)] = &[
+  ([0x00, 0x00, 0x0c], "Cisco Systems"),
+  ([0x00, 0x00, 0x5e], "IANA"),
+  ([0x00, 0x01, 0x42], "Cisco Systems"),
+  ([0x00, 0x01, 0xe6], "Hewlett-Packard"),
+  ([0x00, 0x01, 0xe7], "Hewlett-Packard"),
+  ([0x00, 0x01, 0xe8], "Force10 Networks"),
+  ([0x00, 0x02, 0xc9], "Mellanox Technologies"),
+  ([0x00, 0x04, 0x96], "Extreme Networks"),
+  ([0x00, 0x04, 0xf2], "Polycom"),
+  ([0x00, 0x05, 0x85], "Juniper Networks"),
+  ([0x00, 0x08, 0x9b], "QNAP Systems"),
+  ([0x00, 0x09, 0x0f], "Fortinet"),
+  ([0x00, 0x0a, 0xf7], "Broadcom"),
+  ([0x00, 0x0b, 0x82], "Grandstream Networks"),
+  ([0x00, 0x0b, 0x86], "Aruba Networks"),
+  ([0x00, 0x0c, 0x29], "VMware"),
+  ([0x00, 0x0c, 0x42], "MikroTik"),
+  ([0x00, 0x0d, 0x3a], "Microsoft"),
+  ([0x00, 0x0d, 0xb9], "PC Engines"),
+  ([0x00, 0x0e, 0xcf], "PROFIBUS Nutzerorganisation"),
+  ([0x00, 0x0f, 0xe2], "H3C Technologies"),
+  ([0x00, 0x10, 0x18], "Broadcom"),
+  ([0x00, 0x11, 0x32], "Synology"),
+  ([0x00, 0x12, 0x0f], "IEEE 802.3"),
+  ([0x00, 0x12, 0xbb], "TIA TR-41"),
+  ([0x00, 0x14, 0x22], "Dell"),
+  ([0x00, 0x15, 0x5d], "Microsoft"),
+  ([0x00, 0x15, 0x65], "Yealink"),
+  ([0x00, 0x15, 0x6d], "Ubiquiti"),
+  ([0x00, 0x16, 0x3e], "Xensource"),
+  ([0x00, 0x17, 0x88], "Philips Lighting"),
+  ([0x00, 0x1a, 0x1e], "Aruba Networks"),
+  ([0x00, 0x1b, 0x17], "Palo Alto Networks"),
+  ([0x00, 0x1b, 0x21], "Intel"),
+  ([0x00, 0x1c, 0x73], "Arista Networks"),
+  ([0x00, 0x25, 0x90], "Super Micro Computer"),
+  ([0x00, 0x25, 0xb5], "Cisco Systems"),
+  ([0x00, 0x26, 0xb9], "Dell"),
+  ([0x00, 0x30, 0x48], "Super Micro Computer"),
+  ([0x00, 0x50, 0x56], "VMware"),
+  ([0x00, 0x80, 0xc2], "IEEE 802.1"),
+  ([0x00, 0x90, 0x69], "Juniper Networks"),
+  ([0x00, 0x90, 0x7f], "WatchGuard Technologies"),
+  ([0x00, 0xa0, 0x98], "NetApp"),
+  ([0x00, 0xa0, 0xc9], "Intel"),
+  ([0x00, 0xe0, 0x2b], "Extreme Networks"),
+  ([0x00, 0xe0, 0x4c], "Realtek"),
+  ([0x00, 0xe0, 0x52], "Foundry Networks"),
+  ([0x00, 0xe0, 0xfc], "Huawei Technologies"),
+  ([0x08, 0x00, 0x27], "Oracle VirtualBox"),
+  ([0x24, 0xa4, 0x3c], "Ubiquiti"),
+  ([0x4c, 0x5e, 0x0c], "MikroTik"),
+  ([0xb8, 0x27, 0xeb], "Raspberry Pi Foundation"),
+  ([0xdc, 0xa6, 0x32], "Raspberry Pi Trading"),
+];
+
+pub fn vendor(oui: [u8; 3]) -> Option<&'static str> {
+  VENDORS
+    .binary_search_by_key(&oui, |(x, _)| *x)
+    .ok()
+    .map(|i| VENDORS[i].1)
+}
+
+#[test]
+fn looks_up_vendors() {
+  assert!(VENDORS.windows(2).all(|x| x[0].0 < x[1].0));
+  assert_eq!(vendor([0x00, 0x00, 0x0c]), Some("Cisco Systems"));
+  assert_eq!(vendor([0xdc, 0xa6, 0x32]), Some("Raspberry Pi Trading"));
+  assert_eq!(vendor([0x02, 0x00, 0x00]), None);
+}
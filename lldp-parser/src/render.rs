@@ -0,0 +1,242 @@
+use std::fmt::{Debug, Write};
+
+use crate::{
+  cdp,
+  frame::{Frame, VlanTag},
+  lldp, DataUnit, Protocol,
+};
+
+const INDENT: &str = "    ";
+const HEX_PER_LINE: usize = 16;
+
+// a protocol tree like wireshark's packet details pane, every tlv with its type, length, raw bytes and
+// what it decodes to. meant for humans and bug reports, the layout isn't stable.
+pub fn render_frame(buf: &[u8]) -> String {
+  let mut out = String::new();
+  let Some(frame) = Frame::parse(buf) else {
+    writeln!(out, "Not an LLDP, CDP, FDP or SONMP frame").unwrap();
+    raw(&mut out, 1, buf);
+    return out;
+  };
+
+  writeln!(
+    out,
+    "Ethernet, Src: {}, Dst: {}",
+    mac(&frame.source),
+    mac(&frame.destination)
+  )
+  .unwrap();
+  for tag in frame.vlans() {
+    tag_line(&mut out, tag);
+  }
+  // offsets are from the start of the frame, like wireshark's
+  let base = frame.payload.as_ptr() as usize - buf.as_ptr() as usize;
+  du(&mut out, frame.protocol, frame.payload, base);
+  out
+}
+
+pub fn render_du(protocol: Protocol, buf: &[u8]) -> String {
+  let mut out = String::new();
+  du(&mut out, protocol, buf, 0);
+  out
+}
+
+fn du(out: &mut String, protocol: Protocol, buf: &[u8], base: usize) {
+  match protocol {
+    Protocol::Lldp => {
+      writeln!(out, "Link Layer Discovery Protocol").unwrap();
+      lldp_tlvs(out, buf, base);
+    }
+    Protocol::Cdp | Protocol::Fdp => {
+      let name = match protocol {
+        Protocol::Cdp => "Cisco Discovery Protocol",
+        _ => "Foundry Discovery Protocol",
+      };
+      writeln!(out, "{name}").unwrap();
+      cdp_tlvs(out, protocol, buf, base);
+    }
+    // no tlvs to walk, the decoded du is all there is
+    Protocol::Mndp | Protocol::Sonmp => {
+      let name = match protocol {
+        Protocol::Mndp => "MikroTik Neighbor Discovery Protocol",
+        _ => "SynOptics Network Management Protocol",
+      };
+      writeln!(out, "{name}").unwrap();
+      raw(out, 1, buf);
+      match DataUnit::decode(protocol, buf) {
+        Ok(du) => value(out, 1, &du),
+        Err(err) => line(out, 1, &format!("Error: {err}")),
+      }
+    }
+  }
+}
+
+fn line(out: &mut String, depth: usize, text: &str) {
+  writeln!(out, "{}{text}", INDENT.repeat(depth)).unwrap();
+}
+
+fn mac(x: &[u8; 6]) -> String {
+  x.iter().map(|x| format!("{x:02x}")).collect::<Vec<_>>().join(":")
+}
+
+fn tag_line(out: &mut String, tag: VlanTag) {
+  line(
+    out,
+    1,
+    &format!("VLAN {} (TPID: {:#06x}, PCP: {})", tag.vid(), tag.tpid, tag.pcp()),
+  );
+}
+
+fn raw(out: &mut String, depth: usize, buf: &[u8]) {
+  if buf.is_empty() {
+    return line(out, depth, "Raw:");
+  }
+  for (i, chunk) in buf.chunks(HEX_PER_LINE).enumerate() {
+    let hex: Vec<_> = chunk.iter().map(|x| format!("{x:02x}")).collect();
+    let label = if i == 0 { "Raw:" } else { "    " };
+    line(out, depth, &format!("{label} {}", hex.join(" ")));
+  }
+}
+
+// multi-line debug output is indented as a whole so it stays under its tlv
+fn value(out: &mut String, depth: usize, x: &impl Debug) {
+  let text = format!("{x:#?}");
+  let mut lines = text.lines();
+  if let Some(first) = lines.next() {
+    line(out, depth, &format!("Value: {first}"));
+  }
+  for rest in lines {
+    line(out, depth, rest);
+  }
+}
+
+fn tlv(out: &mut String, name: &str, ty: u16, offset: usize, payload: &[u8], decoded: Result<String, String>) {
+  line(out, 1, &format!("{name} ({ty}), Length: {}", payload.len()));
+  line(out, 2, &format!("Offset: {offset}"));
+  raw(out, 2, payload);
+  match decoded {
+    Ok(x) => line(out, 2, &format!("Value: {x}")),
+    Err(err) => line(out, 2, &format!("Error: {err}")),
+  }
+}
+
+fn lldp_name(ty: u8) -> &'static str {
+  match lldp::tlv::TlvKind::from(ty) {
+    lldp::tlv::TlvKind::End => "End of LLDPDU",
+    lldp::tlv::TlvKind::ChassisId => "Chassis Id",
+    lldp::tlv::TlvKind::PortId => "Port Id",
+    lldp::tlv::TlvKind::TimeToLive => "Time To Live",
+    lldp::tlv::TlvKind::PortDescription => "Port Description",
+    lldp::tlv::TlvKind::SystemName => "System Name",
+    lldp::tlv::TlvKind::SystemDescription => "System Description",
+    lldp::tlv::TlvKind::Capabilities => "System Capabilities",
+    lldp::tlv::TlvKind::ManagementAddress => "Management Address",
+    lldp::tlv::TlvKind::Org => "Organization Specific",
+    lldp::tlv::TlvKind::Unknown(_) => "Unknown",
+  }
+}
+
+// fdp only shares the string tlvs with cdp, the rest would decode as something they aren't
+fn cdp_name(protocol: Protocol, ty: u16) -> Option<&'static str> {
+  match (protocol, cdp::tlv::TlvKind::from(ty)) {
+    (_, cdp::tlv::TlvKind::DeviceId) => Some("Device ID"),
+    (_, cdp::tlv::TlvKind::PortId) => Some("Port ID"),
+    (_, cdp::tlv::TlvKind::SoftwareVersion) => Some("Software Version"),
+    (_, cdp::tlv::TlvKind::Platform) => Some("Platform"),
+    (Protocol::Cdp, cdp::tlv::TlvKind::Addresses) => Some("Addresses"),
+    (Protocol::Cdp, cdp::tlv::TlvKind::Capabilities) => Some("Capabilities"),
+    (Protocol::Cdp, cdp::tlv::TlvKind::NativeVlan) => Some("Native VLAN"),
+    (Protocol::Cdp, cdp::tlv::TlvKind::Duplex) => Some("Duplex"),
+    _ => None,
+  }
+}
+
+fn lldp_tlvs(out: &mut String, buf: &[u8], base: usize) {
+  let mut pos = 0;
+  while pos < buf.len() {
+    let raw_tlv = match lldp::tlv::RawTlv::decode(&buf[pos..]) {
+      Ok(x) => x,
+      Err(err) => {
+        line(out, 1, &format!("Malformed TLV at offset {}: {err}", base + pos));
+        raw(out, 2, &buf[pos..]);
+        return;
+      }
+    };
+
+    let len = raw_tlv.total_len();
+    let (ty, payload) = (raw_tlv.ty, raw_tlv.payload);
+    let decoded = lldp::tlv::Tlv::decode(raw_tlv)
+      .map(|x| lldp_value(&x))
+      .map_err(|x| x.to_string());
+    tlv(out, lldp_name(ty), ty.into(), base + pos, payload, decoded);
+    pos += len;
+  }
+}
+
+// identifiers read better with their subtype, and capabilities by name, than as debug output
+fn lldp_value(tlv: &lldp::tlv::Tlv) -> String {
+  match tlv {
+    lldp::tlv::Tlv::ChassisId(x) => format!("{x:#}"),
+    lldp::tlv::Tlv::PortId(x) => format!("{x:#}"),
+    lldp::tlv::Tlv::ManagementAddress(x) => format!("{x:#}"),
+    lldp::tlv::Tlv::Capabilities(x) => x.to_string(),
+    x => format!("{x:?}"),
+  }
+}
+
+fn cdp_tlvs(out: &mut String, protocol: Protocol, buf: &[u8], base: usize) {
+  // version, ttl and checksum come before the first tlv
+  let Some(header) = buf.get(..4) else {
+    line(out, 1, "Malformed header");
+    return raw(out, 2, buf);
+  };
+  line(out, 1, &format!("Version: {}", header[0]));
+  line(out, 1, &format!("TTL: {}", header[1]));
+  line(
+    out,
+    1,
+    &format!("Checksum: {:#06x}", u16::from_be_bytes([header[2], header[3]])),
+  );
+
+  let mut pos = 4;
+  while pos < buf.len() {
+    let raw_tlv = match cdp::tlv::RawTlv::decode(&buf[pos..]) {
+      Ok(x) => x,
+      Err(err) => {
+        line(out, 1, &format!("Malformed TLV at offset {}: {err}", base + pos));
+        raw(out, 2, &buf[pos..]);
+        return;
+      }
+    };
+
+    let len = raw_tlv.total_len();
+    let (ty, payload) = (raw_tlv.ty, raw_tlv.payload);
+    let name = cdp_name(protocol, ty);
+    let decoded = match name {
+      Some(_) => cdp::tlv::Tlv::decode(raw_tlv)
+        .map(|x| format!("{x:?}"))
+        .map_err(|x| x.to_string()),
+      None => Err("unknown tlv".into()),
+    };
+    tlv(out, name.unwrap_or("Unknown"), ty, base + pos, payload, decoded);
+    pos += len;
+  }
+}
+
+#[test]
+fn renders_lldp_tree() {
+  let buf = [
+    0x02, 0x07, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, // chassis id
+    0x04, 0x02, 0x07, 0x70, // port id
+    0x06, 0x02, 0x00, 0x78, // ttl
+    0x12, 0x01, 0xff, // unknown tlv 9
+  ];
+  let out = render_du(Protocol::Lldp, &buf);
+  let lines: Vec<_> = out.lines().collect();
+  assert_eq!(lines[0], "Link Layer Discovery Protocol");
+  assert_eq!(lines[1], "    Chassis Id (1), Length: 7");
+  assert_eq!(lines[2], "        Offset: 0");
+  assert_eq!(lines[3], "        Raw: 04 02 00 00 00 00 01");
+  assert_eq!(lines[4], "        Value: mac 02:00:00:00:00:01");
+  assert_eq!(lines[13], "    Unknown (9), Length: 1");
+}
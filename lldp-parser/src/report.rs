@@ -0,0 +1,28 @@
+use crate::{lldp::du::DecodeWarning, TlvDecodeError};
+
+// what a decode skipped or put up with, enough to keep lldpStatsRxPortTLVsDiscarded/Unrecognized without
+// parsing the du a second time
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DecodeReport {
+  pub tlvs_total: usize,
+  // types or org subtypes this crate doesn't know
+  pub tlvs_unknown: usize,
+  // known, but malformed
+  pub tlvs_errored: usize,
+  pub warnings: Vec<DecodeWarning>,
+  // bytes after the end tlv, usually zeros padding the frame to 60 bytes
+  pub padding: usize,
+}
+
+impl DecodeReport {
+  pub(crate) fn record<T>(&mut self, result: &Result<T, TlvDecodeError>) {
+    self.tlvs_total += 1;
+    match result {
+      Ok(_) => {}
+      Err(
+        TlvDecodeError::UnknownTlv(_) | TlvDecodeError::UnknownOrgSubtype { .. } | TlvDecodeError::UnsupportedOui(_),
+      ) => self.tlvs_unknown += 1,
+      Err(_) => self.tlvs_errored += 1,
+    }
+  }
+}
@@ -0,0 +1,52 @@
+use std::net::Ipv4Addr;
+
+use thiserror::Error;
+
+// sonmp hellos don't carry a hold time, switches age them out after 360 seconds
+pub const TIME_TO_LIVE: u16 = 360;
+
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum DataUnitError {
+  #[error("buffer too short")]
+  BufferTooShort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataUnit {
+  pub ip_address: Ipv4Addr,
+  pub segment_id: u32,
+  pub chassis_type: u8,
+  pub backplane_type: u8,
+  pub state: u8,
+  pub links: u8,
+}
+
+impl DataUnit {
+  pub fn decode(buf: &[u8]) -> Result<Self, DataUnitError> {
+    if buf.len() < 11 {
+      return Err(DataUnitError::BufferTooShort);
+    }
+
+    Ok(Self {
+      ip_address: Ipv4Addr::new(buf[0], buf[1], buf[2], buf[3]),
+      segment_id: u32::from_be_bytes([0, buf[4], buf[5], buf[6]]),
+      chassis_type: buf[7],
+      backplane_type: buf[8],
+      state: buf[9],
+      links: buf[10],
+    })
+  }
+}
+
+#[test]
+fn decode_sonmp() {
+  let du = DataUnit::decode(&[10, 0, 0, 1, 0x00, 0x01, 0x02, 0x39, 0x0c, 0x03, 0x01]).unwrap();
+  assert_eq!(du.ip_address, Ipv4Addr::new(10, 0, 0, 1));
+  assert_eq!(du.segment_id, 0x0102);
+  assert_eq!(du.chassis_type, 0x39);
+  assert_eq!(du.backplane_type, 0x0c);
+  assert_eq!(du.links, 1);
+
+  assert!(DataUnit::decode(&[10, 0, 0, 1]).is_err());
+}
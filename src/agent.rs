@@ -0,0 +1,252 @@
+use std::{
+  collections::HashMap,
+  future::Future,
+  sync::{Arc, RwLock},
+  time::Duration,
+};
+
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::{
+  AgentStats, CaptureError, FilterSpec, Interface, InterfaceConfig, InterfaceSelector, LocalPort, NeighborEntry,
+  NeighborTableSnapshot,
+};
+
+// how often start_all looks for interfaces that came, went, or changed state
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct Agent {
+  inner: Arc<AgentInner>,
+}
+
+#[derive(Debug)]
+struct AgentInner {
+  name: String,
+  members: RwLock<Vec<Interface>>,
+}
+
+impl Agent {
+  pub fn new(name: impl Into<String>) -> Self {
+    Self {
+      inner: Arc::new(AgentInner {
+        name: name.into(),
+        members: Default::default(),
+      }),
+    }
+  }
+
+  // a bond or bridge, with one Interface per port enslaved to it
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  pub fn from_os(name: &str) -> std::io::Result<Self> {
+    let agent = Self::new(name);
+    for member in crate::local::os_lower_devices(name)? {
+      agent.add_member(Interface::from_os(&member)?);
+    }
+    Ok(agent)
+  }
+
+  pub fn name(&self) -> &str {
+    &self.inner.name
+  }
+
+  pub fn add_member(&self, interface: Interface) {
+    let mut members = self.inner.members.write().unwrap();
+    members.retain(|x| x.local_port().name != interface.local_port().name);
+    members.push(interface);
+  }
+
+  pub fn remove_member(&self, name: &str) -> Option<Interface> {
+    let mut members = self.inner.members.write().unwrap();
+    let index = members.iter().position(|x| x.local_port().name == name)?;
+    Some(members.remove(index))
+  }
+
+  pub fn member(&self, name: &str) -> Option<Interface> {
+    let members = self.inner.members.read().unwrap();
+    members.iter().find(|x| x.local_port().name == name).cloned()
+  }
+
+  pub fn members(&self) -> Vec<Interface> {
+    self.inner.members.read().unwrap().clone()
+  }
+
+  pub fn stats(&self) -> AgentStats {
+    self.members().iter().map(Interface::stats).collect()
+  }
+
+  // every entry keeps the local_port of the member it was learned on
+  pub async fn neighbors(&self) -> Vec<NeighborEntry> {
+    let mut out = Vec::new();
+    for member in self.members() {
+      out.extend(member.neighbors().await);
+    }
+    // by identity rather than remote index, so the same neighbors list the same way across restarts
+    out.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+    out
+  }
+
+  pub async fn snapshot(&self) -> NeighborTableSnapshot {
+    NeighborTableSnapshot::new(self.neighbors().await)
+  }
+
+  // captures on every interface that's up and isn't a loopback, as long as selector matches it. interfaces that come
+  // up later are added, and ones that go away or whose capture fails are removed, to be tried again if they're still
+  // around on the next pass. members added some other way are left alone. aborting the handle stops every capture
+  // it started
+  pub fn start_all(&self, selector: InterfaceSelector, filter: FilterSpec, config: InterfaceConfig) -> JoinHandle<()> {
+    self.start_matching(
+      move |name| selector.matches(name),
+      config,
+      move |intf| {
+        let filter = filter.clone();
+        async move {
+          let name = intf.local_port().name.clone();
+          intf.start_socket(&name, &filter).await
+        }
+      },
+    )
+  }
+
+  // start_all with any predicate on the interface name, and start in place of a plain start_socket. anything start
+  // runs alongside the capture is stopped with it when the interface goes away
+  pub fn start_matching<S, F, Fut>(&self, select: S, config: InterfaceConfig, start: F) -> JoinHandle<()>
+  where
+    S: Fn(&str) -> bool + Send + Sync + 'static,
+    F: Fn(Interface) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), CaptureError>> + Send + 'static,
+  {
+    let agent = self.clone();
+    tokio::spawn(async move {
+      let mut captures = Captures::default();
+      loop {
+        agent.rescan(&select, &config, &start, &mut captures).await;
+        tokio::time::sleep(RESCAN_INTERVAL).await;
+      }
+    })
+  }
+
+  async fn rescan<Fut: Future<Output = Result<(), CaptureError>> + Send + 'static>(
+    &self,
+    select: &impl Fn(&str) -> bool,
+    config: &InterfaceConfig,
+    start: &impl Fn(Interface) -> Fut,
+    captures: &mut Captures,
+  ) {
+    let names: Vec<String> = match crate::local::os_up_interfaces() {
+      Ok(names) => names.into_iter().filter(|x| select(x)).collect(),
+      Err(err) => {
+        warn!(%err, "failed to list interfaces");
+        return;
+      }
+    };
+
+    captures.reap(self).await;
+
+    for name in captures
+      .0
+      .keys()
+      .filter(|x| !names.contains(x))
+      .cloned()
+      .collect::<Vec<_>>()
+    {
+      info!(name, "interface went away");
+      captures.0.remove(&name).unwrap().abort();
+      self.remove_member(&name);
+    }
+
+    for name in names {
+      if captures.0.contains_key(&name) || self.member(&name).is_some() {
+        continue;
+      }
+
+      let intf = match LocalPort::from_os(&name) {
+        Ok(local_port) => Interface::with_config(local_port, config.clone()),
+        Err(err) => {
+          warn!(%err, name, "failed to look up interface");
+          continue;
+        }
+      };
+      info!(name, "capturing on interface");
+      self.add_member(intf.clone());
+      captures.0.insert(name, tokio::spawn(start(intf)));
+    }
+  }
+}
+
+// start_all's captures, by interface name. they're aborted along with it
+#[derive(Default)]
+struct Captures(HashMap<String, JoinHandle<Result<(), CaptureError>>>);
+
+impl Captures {
+  // forgets captures that stopped on their own, so the next pass can start them again
+  async fn reap(&mut self, agent: &Agent) {
+    let finished: Vec<_> = self
+      .0
+      .iter()
+      .filter(|(_, x)| x.is_finished())
+      .map(|(x, _)| x.clone())
+      .collect();
+    for name in finished {
+      match self.0.remove(&name).unwrap().await {
+        Ok(Ok(())) => info!(name, "capture stopped"),
+        Ok(Err(err)) => warn!(%err, name, "capture failed"),
+        Err(err) => warn!(%err, name, "capture panicked"),
+      }
+      agent.remove_member(&name);
+    }
+  }
+}
+
+impl Drop for Captures {
+  fn drop(&mut self) {
+    for task in self.0.values() {
+      task.abort();
+    }
+  }
+}
+
+#[tokio::test]
+async fn merges_member_neighbors() {
+  use lldp_parser::DataUnit;
+
+  use crate::{FrameInfo, LocalPort, MacAddress};
+
+  let du = DataUnit::Cdp(crate::fixtures::cdp_du());
+
+  let agent = Agent::new("bond0");
+  for name in ["eth1", "eth0"] {
+    let member = Interface::new(LocalPort::new(name));
+    member
+      .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1])), du.clone())
+      .await;
+    agent.add_member(member);
+  }
+
+  let neighbors = agent.neighbors().await;
+  assert_eq!(neighbors.len(), 2);
+  assert_eq!(neighbors[0].local_port.name, "eth0");
+  assert_eq!(neighbors[1].local_port.name, "eth1");
+  assert_eq!(agent.stats().inserts, 2);
+
+  agent.remove_member("eth0");
+  assert_eq!(agent.neighbors().await.len(), 1);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn start_all_skips_loopback() {
+  let up = crate::local::os_up_interfaces().unwrap();
+  assert!(!up.iter().any(|x| x == "lo" || x == "lo0"));
+
+  // only members start_all added itself are ever removed
+  let agent = Agent::new("host");
+  agent.add_member(Interface::new(LocalPort::new("lo")));
+  let handle = agent.start_all("!*".parse().unwrap(), FilterSpec::default(), InterfaceConfig::default());
+  tokio::time::sleep(Duration::from_millis(50)).await;
+  handle.abort();
+
+  let members: Vec<_> = agent.members().iter().map(|x| x.local_port().name.clone()).collect();
+  assert_eq!(members, ["lo"]);
+}
@@ -0,0 +1,142 @@
+use std::{
+  collections::VecDeque,
+  os::fd::AsRawFd,
+  time::{Duration, UNIX_EPOCH},
+};
+
+use rawsocket::{
+  bpf::{bpf_insn, bpf_program},
+  bsd::tokio::BpfSocket,
+};
+use tracing::instrument;
+
+use super::CaptureError;
+use crate::{CaptureStats, FilterSpec, Frame, Interface, PacketSource};
+
+fn set_read_filter(sock: &BpfSocket, filter: &FilterSpec) -> Result<(), CaptureError> {
+  let mut insns: Vec<_> = filter
+    .compile()
+    .into_iter()
+    .map(|insn| bpf_insn {
+      code: insn.code,
+      jt: insn.jt,
+      jf: insn.jf,
+      k: insn.k,
+    })
+    .collect();
+  // the kernel copies the program, so it only has to outlive the ioctl
+  let program = bpf_program {
+    bf_len: insns.len() as _,
+    bf_insns: insns.as_mut_ptr(),
+  };
+  sock.set_read_filter(program)?;
+  Ok(())
+}
+
+// struct bpf_stat, which libc only has for some of the bsds
+#[repr(C)]
+#[derive(Default)]
+struct BpfStat {
+  bs_recv: libc::c_uint,
+  bs_drop: libc::c_uint,
+}
+
+pub struct BpfSource {
+  sock: BpfSocket,
+  buf: Vec<u8>,
+  pending: VecDeque<Frame>,
+}
+
+impl BpfSource {
+  pub fn open(intf: &str, filter: &FilterSpec, buffer_size: usize) -> Result<Self, CaptureError> {
+    if filter.is_empty() {
+      return Err(CaptureError::NoProtocols);
+    }
+
+    let buf = vec![0; buffer_size];
+    let sock = BpfSocket::open(intf, Some(buf.len() as _))?;
+    sock.set_immediate(true)?;
+    set_read_filter(&sock, filter)?;
+
+    Ok(Self {
+      sock,
+      buf,
+      pending: VecDeque::new(),
+    })
+  }
+}
+
+impl BpfSource {
+  // the interface leaves promiscuous mode again once the bpf device is closed
+  pub fn enable_promiscuous(&mut self) -> Result<(), CaptureError> {
+    if unsafe { libc::ioctl(self.sock.as_raw_fd(), libc::BIOCPROMISC as _) } < 0 {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+  }
+}
+
+impl PacketSource for BpfSource {
+  async fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+    loop {
+      if let Some(frame) = self.pending.pop_front() {
+        return Ok(Some(frame));
+      }
+
+      for packet in self.sock.read_iter(&mut self.buf).await? {
+        let tstamp = packet.header.bh_tstamp;
+        self.pending.push_back(Frame {
+          data: packet.capture.to_vec(),
+          wire_len: packet.header.bh_datalen as _,
+          timestamp: Some(UNIX_EPOCH + Duration::new(tstamp.tv_sec as _, tstamp.tv_usec as u32 * 1000)),
+        });
+      }
+    }
+  }
+
+  // a read hands over everything the kernel buffered since the last one
+  async fn next_batch(&mut self) -> Result<Vec<Frame>, CaptureError> {
+    if self.pending.is_empty() {
+      self
+        .next_frame()
+        .await?
+        .into_iter()
+        .for_each(|x| self.pending.push_front(x));
+    }
+    Ok(self.pending.drain(..).collect())
+  }
+
+  fn set_filter(&mut self, filter: &FilterSpec) -> Result<(), CaptureError> {
+    set_read_filter(&self.sock, filter)
+  }
+
+  fn capture_stats(&mut self) -> Option<CaptureStats> {
+    let mut stat = BpfStat::default();
+    let ret = unsafe { libc::ioctl(self.sock.as_raw_fd(), libc::BIOCGSTATS, &mut stat) };
+    (ret == 0).then_some(CaptureStats {
+      received: stat.bs_recv as _,
+      dropped: stat.bs_drop as _,
+    })
+  }
+}
+
+impl Interface {
+  #[instrument(skip_all, fields(interface = intf))]
+  pub async fn start_socket(&self, intf: &str, filter: &FilterSpec) -> Result<(), CaptureError> {
+    if filter.is_empty() {
+      return Ok(());
+    }
+
+    if self.inner.config.all_multicast {
+      return Err(CaptureError::Unsupported(
+        "bpf can't enable all-multicast mode, use promiscuous mode instead",
+      ));
+    }
+
+    let mut source = BpfSource::open(intf, filter, self.buffer_size())?;
+    if self.inner.config.promiscuous {
+      source.enable_promiscuous()?;
+    }
+    self.run_live(source, filter).await
+  }
+}
@@ -0,0 +1,450 @@
+use std::{
+  ffi::CString,
+  io, mem,
+  os::fd::{AsRawFd, FromRawFd, OwnedFd},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::io::unix::AsyncFd;
+use tracing::{instrument, warn};
+
+use super::CaptureError;
+use crate::{CaptureStats, FilterSpec, Frame, Interface, PacketSink, PacketSource};
+
+pub const LLDP_MULTICAST_GROUPS: [[u8; 6]; 3] = [
+  [0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e],
+  [0x01, 0x80, 0xc2, 0x00, 0x00, 0x03],
+  [0x01, 0x80, 0xc2, 0x00, 0x00, 0x00],
+];
+
+pub const CDP_MULTICAST_GROUP: [u8; 6] = [0x01, 0x00, 0x0c, 0xcc, 0xcc, 0xcc];
+
+pub const FDP_MULTICAST_GROUP: [u8; 6] = [0x01, 0xe0, 0x52, 0xcc, 0xcc, 0xcc];
+
+pub const SONMP_MULTICAST_GROUPS: [[u8; 6]; 2] = [
+  [0x01, 0x00, 0x81, 0x00, 0x01, 0x00],
+  [0x01, 0x00, 0x81, 0x00, 0x01, 0x01],
+];
+
+// struct tpacket_auxdata from linux/if_packet.h
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TpacketAuxdata {
+  tp_status: u32,
+  tp_len: u32,
+  tp_snaplen: u32,
+  tp_mac: u16,
+  tp_net: u16,
+  tp_vlan_tci: u16,
+  tp_vlan_tpid: u16,
+}
+
+// struct tpacket_stats, PACKET_STATISTICS isn't in libc
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TpacketStats {
+  tp_packets: u32,
+  tp_drops: u32,
+}
+
+const PACKET_STATISTICS: libc::c_int = 6;
+
+const TP_STATUS_VLAN_VALID: u32 = 1 << 4;
+const TP_STATUS_VLAN_TPID_VALID: u32 = 1 << 6;
+
+pub(super) fn cvt(ret: libc::c_int) -> io::Result<libc::c_int> {
+  if ret < 0 {
+    Err(io::Error::last_os_error())
+  } else {
+    Ok(ret)
+  }
+}
+
+pub(super) unsafe fn setsockopt<T>(fd: &OwnedFd, level: libc::c_int, name: libc::c_int, value: &T) -> io::Result<()> {
+  cvt(libc::setsockopt(
+    fd.as_raw_fd(),
+    level,
+    name,
+    value as *const T as *const _,
+    mem::size_of::<T>() as _,
+  ))
+  .map(|_| ())
+}
+
+// T has to be plain old data, it starts out zeroed
+pub(super) unsafe fn getsockopt<T>(fd: &OwnedFd, level: libc::c_int, name: libc::c_int) -> io::Result<T> {
+  let mut value: T = mem::zeroed();
+  let mut len = mem::size_of::<T>() as libc::socklen_t;
+  cvt(libc::getsockopt(
+    fd.as_raw_fd(),
+    level,
+    name,
+    &mut value as *mut T as *mut _,
+    &mut len,
+  ))?;
+  Ok(value)
+}
+
+fn membership(ifindex: i32, group: [u8; 6]) -> libc::packet_mreq {
+  let mut mr_address = [0; 8];
+  mr_address[0..6].copy_from_slice(&group);
+  libc::packet_mreq {
+    mr_ifindex: ifindex,
+    mr_type: libc::PACKET_MR_MULTICAST as _,
+    mr_alen: 6,
+    mr_address,
+  }
+}
+
+// PACKET_MR_PROMISC or PACKET_MR_ALLMULTI. the kernel counts these per socket and takes them back when it closes,
+// so the interface ends up however it was before, even if someone else turned the same mode on in the meantime
+pub(super) fn mode(ifindex: i32, mr_type: libc::c_int) -> libc::packet_mreq {
+  libc::packet_mreq {
+    mr_ifindex: ifindex,
+    mr_type: mr_type as _,
+    mr_alen: 0,
+    mr_address: [0; 8],
+  }
+}
+
+// attaching again replaces the old program in one go, nothing unfiltered gets through in between
+fn attach_filter(fd: &OwnedFd, filter: &FilterSpec) -> io::Result<()> {
+  let mut program: Vec<_> = filter
+    .compile()
+    .into_iter()
+    .map(|insn| libc::sock_filter {
+      code: insn.code,
+      jt: insn.jt,
+      jf: insn.jf,
+      k: insn.k,
+    })
+    .collect();
+  let fprog = libc::sock_fprog {
+    len: program.len() as _,
+    filter: program.as_mut_ptr(),
+  };
+  unsafe { setsockopt(fd, libc::SOL_SOCKET, libc::SO_ATTACH_FILTER, &fprog) }
+}
+
+fn groups(filter: &FilterSpec) -> Vec<[u8; 6]> {
+  let mut groups = Vec::new();
+  if filter.lldp {
+    groups.extend(LLDP_MULTICAST_GROUPS);
+  }
+  if filter.cdp {
+    groups.push(CDP_MULTICAST_GROUP);
+  }
+  if filter.fdp {
+    groups.push(FDP_MULTICAST_GROUP);
+  }
+  if filter.sonmp {
+    groups.extend(SONMP_MULTICAST_GROUPS);
+  }
+  groups
+}
+
+pub struct AfPacketSource {
+  fd: AsyncFd<OwnedFd>,
+  ifindex: i32,
+  memberships: Vec<[u8; 6]>,
+  modes: Vec<libc::c_int>,
+  buf: Vec<u8>,
+  // the kernel zeroes its counters every time they're read
+  stats: CaptureStats,
+}
+
+impl AfPacketSource {
+  pub fn open(intf: &str, filter: &FilterSpec, buffer_size: usize) -> Result<Self, CaptureError> {
+    if filter.is_empty() {
+      return Err(CaptureError::NoProtocols);
+    }
+
+    let c_name = CString::new(intf).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let ifindex = unsafe { libc::if_nametoindex(c_name.as_ptr()) } as i32;
+    if ifindex == 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+
+    // protocol 0 receives nothing until bind, so no unfiltered frames sneak in before the filter is attached
+    let fd = cvt(unsafe {
+      libc::socket(
+        libc::AF_PACKET,
+        libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+        0,
+      )
+    })?;
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    attach_filter(&fd, filter)?;
+
+    // the kernel strips vlan tags before handing us the frame, auxdata lets us put them back
+    unsafe { setsockopt(&fd, libc::SOL_PACKET, libc::PACKET_AUXDATA, &1 as &libc::c_int)? };
+    // stamped when the driver handed it over, not when we got around to reading it
+    unsafe { setsockopt(&fd, libc::SOL_SOCKET, libc::SO_TIMESTAMPNS, &1 as &libc::c_int)? };
+
+    let mut addr: libc::sockaddr_ll = unsafe { mem::zeroed() };
+    addr.sll_family = libc::AF_PACKET as _;
+    addr.sll_protocol = (libc::ETH_P_ALL as u16).to_be();
+    addr.sll_ifindex = ifindex;
+    cvt(unsafe {
+      libc::bind(
+        fd.as_raw_fd(),
+        &addr as *const libc::sockaddr_ll as *const _,
+        mem::size_of::<libc::sockaddr_ll>() as _,
+      )
+    })?;
+
+    let memberships = groups(filter);
+    for group in &memberships {
+      unsafe {
+        setsockopt(
+          &fd,
+          libc::SOL_PACKET,
+          libc::PACKET_ADD_MEMBERSHIP,
+          &membership(ifindex, *group),
+        )?
+      };
+    }
+
+    Ok(Self {
+      fd: AsyncFd::new(fd)?,
+      ifindex,
+      memberships,
+      modes: Vec::new(),
+      buf: vec![0; buffer_size],
+      stats: CaptureStats::default(),
+    })
+  }
+}
+
+impl AfPacketSource {
+  // for nics and bridges that filter the discovery groups in hardware, until the source is dropped
+  pub fn enable_promiscuous(&mut self) -> Result<(), CaptureError> {
+    self.enable_mode(libc::PACKET_MR_PROMISC)
+  }
+
+  pub fn enable_all_multicast(&mut self) -> Result<(), CaptureError> {
+    self.enable_mode(libc::PACKET_MR_ALLMULTI)
+  }
+
+  fn enable_mode(&mut self, mr_type: libc::c_int) -> Result<(), CaptureError> {
+    if !self.modes.contains(&mr_type) {
+      unsafe {
+        setsockopt(
+          self.fd.get_ref(),
+          libc::SOL_PACKET,
+          libc::PACKET_ADD_MEMBERSHIP,
+          &mode(self.ifindex, mr_type),
+        )?
+      };
+      self.modes.push(mr_type);
+    }
+    Ok(())
+  }
+}
+
+impl Drop for AfPacketSource {
+  fn drop(&mut self) {
+    // the kernel drops memberships when the socket closes, but be explicit about it
+    let groups = self.memberships.iter().map(|group| membership(self.ifindex, *group));
+    let modes = self.modes.iter().map(|mr_type| mode(self.ifindex, *mr_type));
+    for mreq in groups.chain(modes) {
+      let result = unsafe { setsockopt(self.fd.get_ref(), libc::SOL_PACKET, libc::PACKET_DROP_MEMBERSHIP, &mreq) };
+
+      if let Err(err) = result {
+        warn!(%err, "failed to drop multicast membership");
+      }
+    }
+  }
+}
+
+struct Received {
+  len: usize,
+  auxdata: Option<TpacketAuxdata>,
+  timestamp: Option<SystemTime>,
+}
+
+fn recv(fd: &OwnedFd, buf: &mut [u8]) -> io::Result<Received> {
+  let mut iov = libc::iovec {
+    iov_base: buf.as_mut_ptr() as *mut _,
+    iov_len: buf.len(),
+  };
+  let mut control = [0u64; 16];
+  let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+  msg.msg_iov = &mut iov;
+  msg.msg_iovlen = 1;
+  msg.msg_control = control.as_mut_ptr() as *mut _;
+  msg.msg_controllen = mem::size_of_val(&control) as _;
+
+  // MSG_TRUNC makes recvmsg report the full length of the frame even if it didn't fit
+  let len = cvt(unsafe { libc::recvmsg(fd.as_raw_fd(), &mut msg, libc::MSG_TRUNC) as _ })?;
+
+  let mut auxdata = None;
+  let mut timestamp = None;
+  unsafe {
+    let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+    while !cmsg.is_null() {
+      if (*cmsg).cmsg_level == libc::SOL_PACKET && (*cmsg).cmsg_type == libc::PACKET_AUXDATA {
+        auxdata = Some(std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const TpacketAuxdata));
+      }
+      if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_TIMESTAMPNS {
+        let ts = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::timespec);
+        timestamp = Some(UNIX_EPOCH + Duration::new(ts.tv_sec as _, ts.tv_nsec as _));
+      }
+      cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+    }
+  }
+
+  Ok(Received {
+    len: len as usize,
+    auxdata,
+    timestamp,
+  })
+}
+
+impl PacketSource for AfPacketSource {
+  async fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+    loop {
+      let mut guard = self.fd.readable().await?;
+      let buf = &mut self.buf;
+      let result = guard.try_io(|fd| recv(fd.get_ref(), buf));
+
+      match result {
+        Ok(Ok(Received {
+          len: wire_len,
+          auxdata,
+          timestamp,
+        })) => {
+          let mut data = self.buf[..wire_len.min(self.buf.len())].to_vec();
+          let mut wire_len = wire_len;
+
+          if let Some(aux) = auxdata.filter(|aux| aux.tp_status & TP_STATUS_VLAN_VALID != 0 && data.len() >= 12) {
+            let tpid = if aux.tp_status & TP_STATUS_VLAN_TPID_VALID != 0 {
+              aux.tp_vlan_tpid
+            } else {
+              0x8100
+            };
+
+            let mut tag = [0; 4];
+            tag[0..2].copy_from_slice(&tpid.to_be_bytes());
+            tag[2..4].copy_from_slice(&aux.tp_vlan_tci.to_be_bytes());
+            data.splice(12..12, tag);
+            wire_len += 4;
+          }
+
+          return Ok(Some(Frame {
+            data,
+            wire_len,
+            timestamp,
+          }));
+        }
+        Ok(Err(err)) => return Err(err.into()),
+        Err(_would_block) => continue,
+      }
+    }
+  }
+
+  // groups are only ever joined here, the filter keeps out whatever was switched off
+  fn set_filter(&mut self, filter: &FilterSpec) -> Result<(), CaptureError> {
+    for group in groups(filter) {
+      if !self.memberships.contains(&group) {
+        unsafe {
+          setsockopt(
+            self.fd.get_ref(),
+            libc::SOL_PACKET,
+            libc::PACKET_ADD_MEMBERSHIP,
+            &membership(self.ifindex, group),
+          )?
+        };
+        self.memberships.push(group);
+      }
+    }
+    Ok(attach_filter(self.fd.get_ref(), filter)?)
+  }
+
+  fn capture_stats(&mut self) -> Option<CaptureStats> {
+    let stats: TpacketStats = unsafe { getsockopt(self.fd.get_ref(), libc::SOL_PACKET, PACKET_STATISTICS) }.ok()?;
+    // tp_packets already includes the drops
+    self.stats.received += stats.tp_packets as u64;
+    self.stats.dropped += stats.tp_drops as u64;
+    Some(self.stats)
+  }
+}
+
+pub struct AfPacketSink {
+  fd: AsyncFd<OwnedFd>,
+}
+
+impl AfPacketSink {
+  pub fn open(intf: &str) -> io::Result<Self> {
+    let c_name = CString::new(intf).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let ifindex = unsafe { libc::if_nametoindex(c_name.as_ptr()) } as i32;
+    if ifindex == 0 {
+      return Err(io::Error::last_os_error());
+    }
+
+    // protocol 0 never receives, the socket is only bound so send() knows the interface
+    let fd = cvt(unsafe {
+      libc::socket(
+        libc::AF_PACKET,
+        libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+        0,
+      )
+    })?;
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let mut addr: libc::sockaddr_ll = unsafe { mem::zeroed() };
+    addr.sll_family = libc::AF_PACKET as _;
+    addr.sll_ifindex = ifindex;
+    cvt(unsafe {
+      libc::bind(
+        fd.as_raw_fd(),
+        &addr as *const libc::sockaddr_ll as *const _,
+        mem::size_of::<libc::sockaddr_ll>() as _,
+      )
+    })?;
+
+    Ok(Self { fd: AsyncFd::new(fd)? })
+  }
+}
+
+impl PacketSink for AfPacketSink {
+  async fn send_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+    loop {
+      let mut guard = self.fd.writable().await?;
+      let result = guard.try_io(|fd| {
+        cvt(unsafe { libc::send(fd.get_ref().as_raw_fd(), frame.as_ptr() as *const _, frame.len(), 0) as _ })
+      });
+
+      match result {
+        Ok(result) => return result.map(|_| ()),
+        Err(_would_block) => continue,
+      }
+    }
+  }
+}
+
+impl Interface {
+  #[instrument(skip_all, fields(interface = intf))]
+  pub async fn start_socket(&self, intf: &str, filter: &FilterSpec) -> Result<(), CaptureError> {
+    if filter.is_empty() {
+      return Ok(());
+    }
+
+    let mut source = AfPacketSource::open(intf, filter, self.buffer_size())?;
+    if self.inner.config.promiscuous {
+      source.enable_promiscuous()?;
+    }
+    if self.inner.config.all_multicast {
+      source.enable_all_multicast()?;
+    }
+    self.run_live(source, filter).await
+  }
+
+  #[instrument(skip_all, fields(interface = intf))]
+  pub async fn start_tx(&self, intf: &str) -> io::Result<()> {
+    let lldp = AfPacketSink::open(intf)?;
+    let cdp = AfPacketSink::open(intf)?;
+    tokio::try_join!(self.run_tx(lldp), self.run_cdp_tx(cdp)).map(|_| ())
+  }
+}
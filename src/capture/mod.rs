@@ -0,0 +1,250 @@
+use std::{future::Future, io, time::SystemTime};
+
+use thiserror::Error;
+
+use crate::FilterSpec;
+#[cfg(feature = "capture")]
+use crate::{stats, Interface};
+
+#[cfg(all(feature = "capture", unix, not(any(target_os = "linux", target_os = "android"))))]
+pub mod bsd;
+#[cfg(all(feature = "capture", any(target_os = "linux", target_os = "android")))]
+pub mod linux;
+pub mod pcap;
+#[cfg(feature = "capture")]
+pub mod replay;
+#[cfg(all(windows, feature = "npcap"))]
+pub mod windows;
+#[cfg(all(feature = "xdp", target_os = "linux"))]
+pub mod xdp;
+
+// what every backend fails with, so callers don't care which one they got
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CaptureError {
+  #[error("no protocols enabled")]
+  NoProtocols,
+  #[error("{0}")]
+  Unsupported(&'static str),
+  #[error("filter too large for the kernel to load")]
+  FilterTooLarge,
+  #[cfg(all(windows, feature = "npcap"))]
+  #[error(transparent)]
+  Pcap(#[from] ::pcap::Error),
+  #[error(transparent)]
+  Io(#[from] io::Error),
+}
+
+impl From<CaptureError> for io::Error {
+  fn from(value: CaptureError) -> Self {
+    match value {
+      CaptureError::NoProtocols | CaptureError::FilterTooLarge => {
+        io::Error::new(io::ErrorKind::InvalidInput, value.to_string())
+      }
+      CaptureError::Unsupported(_) => io::Error::new(io::ErrorKind::Unsupported, value.to_string()),
+      CaptureError::Io(err) => err,
+      #[cfg(all(windows, feature = "npcap"))]
+      CaptureError::Pcap(err) => io::Error::other(err),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+  pub data: Vec<u8>,
+  pub wire_len: usize,
+  // from the kernel when the backend gets one
+  pub timestamp: Option<SystemTime>,
+}
+
+impl Frame {
+  pub fn new(data: Vec<u8>) -> Self {
+    let wire_len = data.len();
+    Self {
+      data,
+      wire_len,
+      timestamp: None,
+    }
+  }
+}
+
+// the kernel's side of a capture, counted since the source was opened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CaptureStats {
+  // frames the filter passed, dropped ones included
+  pub received: u64,
+  // passed the filter but thrown away before being read, usually because the buffer was full
+  pub dropped: u64,
+}
+
+pub trait PacketSource {
+  // Ok(None) means the source is exhausted, live captures never return it
+  fn next_frame(&mut self) -> impl Future<Output = Result<Option<Frame>, CaptureError>> + Send;
+
+  // everything one read returned, empty means exhausted like next_frame's None. sources that read one frame at a
+  // time get this for free
+  fn next_batch(&mut self) -> impl Future<Output = Result<Vec<Frame>, CaptureError>> + Send
+  where
+    Self: Send,
+  {
+    async move { Ok(self.next_frame().await?.into_iter().collect()) }
+  }
+
+  // replaces the filter on a live capture. sources that can't keep theirs, the interface drops what's switched off
+  fn set_filter(&mut self, _filter: &FilterSpec) -> Result<(), CaptureError> {
+    Ok(())
+  }
+
+  // None for sources the kernel isn't involved in, or that can't tell
+  fn capture_stats(&mut self) -> Option<CaptureStats> {
+    None
+  }
+}
+
+// how often a live capture's kernel counters are folded into the interface's, drops happen when frames aren't
+// being read so they can't wait for the next batch
+#[cfg(feature = "capture")]
+const CAPTURE_STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[cfg(feature = "capture")]
+impl Interface {
+  pub async fn run<S: PacketSource + Send>(&self, mut source: S) -> Result<(), CaptureError> {
+    let mut last = CaptureStats::default();
+    loop {
+      let frames = source.next_batch().await?;
+      if frames.is_empty() {
+        return Ok(());
+      }
+      self.handle_frames(&frames).await;
+      self.record_capture_stats(&mut source, &mut last);
+    }
+  }
+
+  // last is what the source reported the previous time, only the difference is added so a restarted capture
+  // doesn't count twice
+  fn record_capture_stats<S: PacketSource>(&self, source: &mut S, last: &mut CaptureStats) {
+    if let Some(stats) = source.capture_stats() {
+      let counters = &self.inner.counters;
+      stats::add(
+        &counters.kernel_received,
+        stats.received.saturating_sub(last.received) as _,
+      );
+      stats::add(
+        &counters.kernel_dropped,
+        stats.dropped.saturating_sub(last.dropped) as _,
+      );
+      *last = stats;
+    }
+  }
+
+  // like run, but follows set_protocols, pause and resume by swapping the source's filter
+  #[cfg_attr(all(windows, not(feature = "npcap")), allow(dead_code))]
+  pub(crate) async fn run_live<S: PacketSource + Send>(
+    &self,
+    mut source: S,
+    filter: &FilterSpec,
+  ) -> Result<(), CaptureError> {
+    // before subscribing, so it isn't taken for a change to apply
+    self.inner.rx_state.send_modify(|x| x.capture = Some(filter.clone()));
+    let mut state = self.inner.rx_state.subscribe();
+    let mut last = CaptureStats::default();
+    let mut tick = tokio::time::interval(CAPTURE_STATS_INTERVAL);
+    loop {
+      tokio::select! {
+        frames = source.next_batch() => match frames? {
+          frames if frames.is_empty() => return Ok(()),
+          frames => self.handle_frames(&frames).await,
+        },
+        Ok(()) = state.changed() => {
+          let filter = state.borrow_and_update().apply(filter);
+          source.set_filter(&filter)?;
+        }
+        _ = tick.tick() => self.record_capture_stats(&mut source, &mut last),
+      }
+    }
+  }
+}
+
+#[cfg(all(feature = "capture", windows, not(feature = "npcap")))]
+impl Interface {
+  pub async fn start_socket(&self, _: &str, _: &crate::FilterSpec) -> Result<(), CaptureError> {
+    Err(CaptureError::Unsupported(
+      "capturing on windows requires the npcap feature",
+    ))
+  }
+}
+
+#[cfg(all(test, feature = "capture"))]
+pub(crate) struct VecSource(pub std::collections::VecDeque<Frame>);
+
+#[cfg(all(test, feature = "capture"))]
+impl PacketSource for VecSource {
+  async fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+    Ok(self.0.pop_front())
+  }
+}
+
+#[cfg(feature = "capture")]
+#[tokio::test]
+async fn run_injected_frames() {
+  use lldp_parser::lldp::{
+    du::DataUnit,
+    tlv::{ChassisId, PortId},
+  };
+
+  let mut frame = vec![0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 0x02, 0, 0, 0, 0, 1, 0x88, 0xcc];
+  DataUnit {
+    chassis_id: ChassisId::MacAddress([2, 0, 0, 0, 0, 1]),
+    port_id: PortId::InterfaceName("eth0".into()),
+    system_name: Some("switch".into()),
+    ..crate::fixtures::lldp_du()
+  }
+  .encode(&mut frame);
+
+  let mut truncated = Frame::new(frame.clone());
+  truncated.wire_len += 10;
+
+  let interface = Interface::default();
+  let frames = [Frame::new(frame), truncated, Frame::new(vec![0; 10])];
+  interface.run(VecSource(frames.into())).await.unwrap();
+
+  let neighbors = interface.neighbors().await;
+  assert_eq!(neighbors.len(), 1);
+  assert_eq!(neighbors[0].source, crate::MacAddress([2, 0, 0, 0, 0, 1]));
+  assert_eq!(interface.stats().frames_received, 3);
+  assert_eq!(interface.stats().frames_truncated, 1);
+}
+
+#[cfg(feature = "capture")]
+#[tokio::test]
+async fn records_capture_stats() {
+  // counts like the kernel does, cumulatively with one frame dropped along the way
+  struct CountingSource(VecSource, u64);
+
+  impl PacketSource for CountingSource {
+    async fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+      let frame = self.0.next_frame().await?;
+      self.1 += frame.is_some() as u64;
+      Ok(frame)
+    }
+
+    fn capture_stats(&mut self) -> Option<CaptureStats> {
+      Some(CaptureStats {
+        received: self.1 + 1,
+        dropped: 1,
+      })
+    }
+  }
+
+  let interface = Interface::default();
+  let frames = vec![Frame::new(vec![0; 20]); 3];
+  interface
+    .run(CountingSource(VecSource(frames.into()), 0))
+    .await
+    .unwrap();
+
+  let stats = interface.stats();
+  assert_eq!(stats.frames_received, 3);
+  assert_eq!(stats.kernel_received, 4);
+  assert_eq!(stats.kernel_dropped, 1);
+}
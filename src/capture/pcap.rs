@@ -0,0 +1,479 @@
+use std::{
+  io::{self, Read, Write},
+  time::Duration,
+};
+
+pub const LINKTYPE_ETHERNET: u32 = 1;
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b23c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+
+// anything bigger is a corrupt file rather than a real frame
+const MAX_RECORD_LEN: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PcapPacket {
+  pub timestamp: Option<Duration>,
+  pub link_type: u32,
+  pub data: Vec<u8>,
+  pub wire_len: usize,
+}
+
+#[derive(Debug)]
+enum Format {
+  Pcap {
+    nanos: bool,
+    link_type: u32,
+  },
+  Pcapng {
+    // (link type, timestamp units per second)
+    interfaces: Vec<(u32, u64)>,
+  },
+}
+
+#[derive(Debug)]
+pub struct PcapReader<R> {
+  inner: R,
+  big_endian: bool,
+  format: Format,
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<R: Read> PcapReader<R> {
+  pub fn new(mut inner: R) -> io::Result<Self> {
+    let mut magic = [0; 4];
+    inner.read_exact(&mut magic)?;
+
+    if u32::from_ne_bytes(magic) == PCAPNG_SECTION_HEADER {
+      let mut reader = Self {
+        inner,
+        big_endian: false,
+        format: Format::Pcapng { interfaces: Vec::new() },
+      };
+      reader.read_section_header()?;
+      return Ok(reader);
+    }
+
+    let (big_endian, nanos) = match (u32::from_be_bytes(magic), u32::from_le_bytes(magic)) {
+      (PCAP_MAGIC_MICROS, _) => (true, false),
+      (PCAP_MAGIC_NANOS, _) => (true, true),
+      (_, PCAP_MAGIC_MICROS) => (false, false),
+      (_, PCAP_MAGIC_NANOS) => (false, true),
+      _ => return Err(invalid("not a pcap or pcapng file")),
+    };
+
+    let mut header = [0; 20];
+    inner.read_exact(&mut header)?;
+
+    let mut reader = Self {
+      inner,
+      big_endian,
+      format: Format::Pcap { nanos, link_type: 0 },
+    };
+    // the upper bits of the link type carry fcs information
+    let link_type = reader.u32(&header[16..20]) & 0x0fff_ffff;
+    reader.format = Format::Pcap { nanos, link_type };
+    Ok(reader)
+  }
+
+  fn u16(&self, buf: &[u8]) -> u16 {
+    let buf = buf[0..2].try_into().unwrap();
+    if self.big_endian {
+      u16::from_be_bytes(buf)
+    } else {
+      u16::from_le_bytes(buf)
+    }
+  }
+
+  fn u32(&self, buf: &[u8]) -> u32 {
+    let buf = buf[0..4].try_into().unwrap();
+    if self.big_endian {
+      u32::from_be_bytes(buf)
+    } else {
+      u32::from_le_bytes(buf)
+    }
+  }
+
+  // reads the remainder of a section header block whose type has already been consumed
+  fn read_section_header(&mut self) -> io::Result<()> {
+    let mut buf = [0; 8];
+    self.inner.read_exact(&mut buf)?;
+
+    self.big_endian = match (
+      u32::from_be_bytes(buf[4..8].try_into().unwrap()),
+      u32::from_le_bytes(buf[4..8].try_into().unwrap()),
+    ) {
+      (PCAPNG_BYTE_ORDER_MAGIC, _) => true,
+      (_, PCAPNG_BYTE_ORDER_MAGIC) => false,
+      _ => return Err(invalid("bad pcapng byte order magic")),
+    };
+
+    let total_len = self.u32(&buf[0..4]) as usize;
+    if !(28..=MAX_RECORD_LEN).contains(&total_len) {
+      return Err(invalid("bad pcapng section header length"));
+    }
+
+    // version, section length, options, and the trailing length
+    io::copy(&mut (&mut self.inner).take((total_len - 12) as u64), &mut io::sink())?;
+    self.format = Format::Pcapng { interfaces: Vec::new() };
+    Ok(())
+  }
+
+  // returns false on a clean end of file
+  fn read_or_eof(&mut self, buf: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+      match self.inner.read(&mut buf[read..]) {
+        Ok(0) if read == 0 => return Ok(false),
+        Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+        Ok(n) => read += n,
+        Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+        Err(err) => return Err(err),
+      }
+    }
+    Ok(true)
+  }
+
+  pub fn next_packet(&mut self) -> io::Result<Option<PcapPacket>> {
+    match self.format {
+      Format::Pcap { nanos, link_type } => self.next_pcap_packet(nanos, link_type),
+      Format::Pcapng { .. } => self.next_pcapng_packet(),
+    }
+  }
+
+  fn next_pcap_packet(&mut self, nanos: bool, link_type: u32) -> io::Result<Option<PcapPacket>> {
+    let mut header = [0; 16];
+    if !self.read_or_eof(&mut header)? {
+      return Ok(None);
+    }
+
+    let secs = self.u32(&header[0..4]) as u64;
+    let frac = self.u32(&header[4..8]);
+    let caplen = self.u32(&header[8..12]) as usize;
+    let wire_len = self.u32(&header[12..16]) as usize;
+
+    if caplen > MAX_RECORD_LEN {
+      return Err(invalid("pcap record too large"));
+    }
+
+    let mut data = vec![0; caplen];
+    self.inner.read_exact(&mut data)?;
+
+    let timestamp = if nanos {
+      Duration::new(secs, frac)
+    } else {
+      Duration::new(secs, 0) + Duration::from_micros(frac as u64)
+    };
+
+    Ok(Some(PcapPacket {
+      timestamp: Some(timestamp),
+      link_type,
+      data,
+      wire_len,
+    }))
+  }
+
+  fn next_pcapng_packet(&mut self) -> io::Result<Option<PcapPacket>> {
+    loop {
+      let mut ty = [0; 4];
+      if !self.read_or_eof(&mut ty)? {
+        return Ok(None);
+      }
+
+      if u32::from_ne_bytes(ty) == PCAPNG_SECTION_HEADER {
+        self.read_section_header()?;
+        continue;
+      }
+
+      let ty = self.u32(&ty);
+
+      let mut len = [0; 4];
+      self.inner.read_exact(&mut len)?;
+      let total_len = self.u32(&len) as usize;
+      if total_len < 12 || total_len & 3 != 0 || total_len > MAX_RECORD_LEN {
+        return Err(invalid("bad pcapng block length"));
+      }
+
+      // body followed by the repeated block length
+      let mut body = vec![0; total_len - 8];
+      self.inner.read_exact(&mut body)?;
+      let body = &body[..body.len() - 4];
+
+      match ty {
+        PCAPNG_INTERFACE_DESCRIPTION => {
+          if body.len() < 8 {
+            return Err(invalid("short pcapng interface description"));
+          }
+
+          let link_type = self.u16(&body[0..2]) as u32;
+          let resolution = self.if_tsresol(&body[8..]).unwrap_or(1_000_000);
+          if let Format::Pcapng { interfaces } = &mut self.format {
+            interfaces.push((link_type, resolution));
+          }
+        }
+
+        PCAPNG_ENHANCED_PACKET => {
+          if body.len() < 20 {
+            return Err(invalid("short pcapng enhanced packet"));
+          }
+
+          let interface_id = self.u32(&body[0..4]) as usize;
+          let ts = ((self.u32(&body[4..8]) as u64) << 32) | self.u32(&body[8..12]) as u64;
+          let caplen = self.u32(&body[12..16]) as usize;
+          let wire_len = self.u32(&body[16..20]) as usize;
+          let data = body
+            .get(20..20 + caplen)
+            .ok_or(invalid("pcapng packet overruns block"))?;
+
+          let Some((link_type, resolution)) = self.interface(interface_id) else {
+            return Err(invalid("pcapng packet references unknown interface"));
+          };
+
+          // picosecond and finer resolutions overflow u64 once scaled to nanoseconds
+          let nanos = (ts % resolution) as u128 * 1_000_000_000 / resolution as u128;
+          let timestamp = Duration::new(ts / resolution, nanos as u32);
+
+          return Ok(Some(PcapPacket {
+            timestamp: Some(timestamp),
+            link_type,
+            data: data.to_vec(),
+            wire_len,
+          }));
+        }
+
+        PCAPNG_SIMPLE_PACKET => {
+          if body.len() < 4 {
+            return Err(invalid("short pcapng simple packet"));
+          }
+
+          let wire_len = self.u32(&body[0..4]) as usize;
+          let data = &body[4..4 + wire_len.min(body.len() - 4)];
+          let Some((link_type, _)) = self.interface(0) else {
+            return Err(invalid("pcapng packet references unknown interface"));
+          };
+
+          return Ok(Some(PcapPacket {
+            timestamp: None,
+            link_type,
+            data: data.to_vec(),
+            wire_len,
+          }));
+        }
+
+        _ => {}
+      }
+    }
+  }
+
+  fn interface(&self, id: usize) -> Option<(u32, u64)> {
+    match &self.format {
+      Format::Pcapng { interfaces } => interfaces.get(id).copied(),
+      Format::Pcap { .. } => None,
+    }
+  }
+
+  fn if_tsresol(&self, mut options: &[u8]) -> Option<u64> {
+    while options.len() >= 4 {
+      let code = self.u16(&options[0..2]);
+      let len = self.u16(&options[2..4]) as usize;
+      let value = options.get(4..4 + len)?;
+
+      match code {
+        0 => return None,
+        9 if len == 1 => {
+          let exp = (value[0] & 0x7f) as u32;
+          return if value[0] & 0x80 == 0 {
+            10u64.checked_pow(exp)
+          } else {
+            2u64.checked_pow(exp)
+          };
+        }
+        _ => {}
+      }
+
+      // the last option's padding can be cut off by the end of the block
+      options = options.get((4 + len + 3) & !3..).unwrap_or_default();
+    }
+
+    None
+  }
+}
+
+#[derive(Debug)]
+pub struct PcapngWriter<W: Write> {
+  inner: W,
+  interfaces: u32,
+  written: u64,
+}
+
+fn push_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+  buf.extend(code.to_le_bytes());
+  buf.extend((value.len() as u16).to_le_bytes());
+  buf.extend(value);
+  buf.resize((buf.len() + 3) & !3, 0);
+}
+
+impl<W: Write> PcapngWriter<W> {
+  pub fn new(inner: W) -> io::Result<Self> {
+    let mut writer = Self {
+      inner,
+      interfaces: 0,
+      written: 0,
+    };
+
+    let mut body = Vec::new();
+    body.extend(PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes());
+    body.extend(1u16.to_le_bytes());
+    body.extend(0u16.to_le_bytes());
+    // section length is not known up front
+    body.extend((-1i64).to_le_bytes());
+    writer.write_block(PCAPNG_SECTION_HEADER, &body)?;
+
+    Ok(writer)
+  }
+
+  pub fn bytes_written(&self) -> u64 {
+    self.written
+  }
+
+  pub fn add_interface(&mut self, name: &str, description: Option<&str>, snaplen: u32) -> io::Result<u32> {
+    let mut body = Vec::new();
+    body.extend((LINKTYPE_ETHERNET as u16).to_le_bytes());
+    body.extend(0u16.to_le_bytes());
+    body.extend(snaplen.to_le_bytes());
+    push_option(&mut body, 2, name.as_bytes());
+    if let Some(description) = description {
+      push_option(&mut body, 3, description.as_bytes());
+    }
+    push_option(&mut body, 0, &[]);
+    self.write_block(PCAPNG_INTERFACE_DESCRIPTION, &body)?;
+
+    self.interfaces += 1;
+    Ok(self.interfaces - 1)
+  }
+
+  pub fn write_packet(
+    &mut self,
+    interface_id: u32,
+    timestamp: Duration,
+    data: &[u8],
+    wire_len: usize,
+  ) -> io::Result<()> {
+    let ts = timestamp.as_micros() as u64;
+
+    let mut body = Vec::with_capacity(data.len() + 24);
+    body.extend(interface_id.to_le_bytes());
+    body.extend(((ts >> 32) as u32).to_le_bytes());
+    body.extend((ts as u32).to_le_bytes());
+    body.extend((data.len() as u32).to_le_bytes());
+    body.extend((wire_len as u32).to_le_bytes());
+    body.extend(data);
+    body.resize((body.len() + 3) & !3, 0);
+    self.write_block(PCAPNG_ENHANCED_PACKET, &body)
+  }
+
+  pub fn flush(&mut self) -> io::Result<()> {
+    self.inner.flush()
+  }
+
+  fn write_block(&mut self, ty: u32, body: &[u8]) -> io::Result<()> {
+    let total_len = (body.len() + 12) as u32;
+    self.inner.write_all(&ty.to_le_bytes())?;
+    self.inner.write_all(&total_len.to_le_bytes())?;
+    self.inner.write_all(body)?;
+    self.inner.write_all(&total_len.to_le_bytes())?;
+    self.written += total_len as u64;
+    Ok(())
+  }
+}
+
+#[test]
+fn pcapng_round_trip() {
+  let mut writer = PcapngWriter::new(Vec::new()).unwrap();
+  let id = writer.add_interface("en0", Some("uplink"), 65535).unwrap();
+  writer
+    .write_packet(id, Duration::new(10, 500_000), &[1, 2, 3], 64)
+    .unwrap();
+  writer.write_packet(id, Duration::new(11, 0), &[4; 8], 8).unwrap();
+  assert_eq!(writer.bytes_written(), writer.inner.len() as u64);
+
+  let mut reader = PcapReader::new(&writer.inner[..]).unwrap();
+  let packet = reader.next_packet().unwrap().unwrap();
+  assert_eq!(packet.timestamp, Some(Duration::new(10, 500_000)));
+  assert_eq!(packet.link_type, LINKTYPE_ETHERNET);
+  assert_eq!(packet.data, [1, 2, 3]);
+  assert_eq!(packet.wire_len, 64);
+  assert_eq!(reader.next_packet().unwrap().unwrap().data, [4; 8]);
+  assert!(reader.next_packet().unwrap().is_none());
+}
+
+#[test]
+fn read_pcap() {
+  let mut file = Vec::new();
+  file.extend(PCAP_MAGIC_MICROS.to_le_bytes());
+  file.extend([2, 0, 4, 0]);
+  file.extend([0; 8]);
+  file.extend(65535u32.to_le_bytes());
+  file.extend(LINKTYPE_ETHERNET.to_le_bytes());
+
+  file.extend(10u32.to_le_bytes());
+  file.extend(500u32.to_le_bytes());
+  file.extend(3u32.to_le_bytes());
+  file.extend(64u32.to_le_bytes());
+  file.extend([1, 2, 3]);
+
+  let mut reader = PcapReader::new(&file[..]).unwrap();
+  let packet = reader.next_packet().unwrap().unwrap();
+  assert_eq!(packet.timestamp, Some(Duration::new(10, 500_000)));
+  assert_eq!(packet.link_type, LINKTYPE_ETHERNET);
+  assert_eq!(packet.data, [1, 2, 3]);
+  assert_eq!(packet.wire_len, 64);
+  assert!(reader.next_packet().unwrap().is_none());
+}
+
+#[test]
+fn reads_fine_timestamp_resolutions() {
+  let mut file = PcapngWriter::new(Vec::new()).unwrap();
+  let mut body = Vec::new();
+  body.extend((LINKTYPE_ETHERNET as u16).to_le_bytes());
+  body.extend(0u16.to_le_bytes());
+  body.extend(65535u32.to_le_bytes());
+  // picoseconds
+  push_option(&mut body, 9, &[12]);
+  push_option(&mut body, 0, &[]);
+  file.write_block(PCAPNG_INTERFACE_DESCRIPTION, &body).unwrap();
+
+  let ts = 10_500_000_000_000u64;
+  let mut body = Vec::new();
+  body.extend(0u32.to_le_bytes());
+  body.extend(((ts >> 32) as u32).to_le_bytes());
+  body.extend((ts as u32).to_le_bytes());
+  body.extend(4u32.to_le_bytes());
+  body.extend(4u32.to_le_bytes());
+  body.extend([1, 2, 3, 4]);
+  file.write_block(PCAPNG_ENHANCED_PACKET, &body).unwrap();
+
+  let mut reader = PcapReader::new(&file.inner[..]).unwrap();
+  let packet = reader.next_packet().unwrap().unwrap();
+  assert_eq!(packet.timestamp, Some(Duration::new(10, 500_000_000)));
+}
+
+#[test]
+fn tolerates_unpadded_trailing_option() {
+  let file = PcapngWriter::new(Vec::new()).unwrap().inner;
+  let reader = PcapReader::new(&file[..]).unwrap();
+  // if_name with its padding cut off
+  assert_eq!(reader.if_tsresol(&[2, 0, 1, 0, b'a']), None);
+  assert_eq!(
+    reader.if_tsresol(&[2, 0, 1, 0, b'a', 0, 0, 0, 9, 0, 1, 0, 3]),
+    Some(1000)
+  );
+}
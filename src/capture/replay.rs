@@ -0,0 +1,86 @@
+use std::{io::Cursor, path::Path, time::Duration};
+
+use tokio::time::Instant;
+use tracing::instrument;
+
+use super::{
+  pcap::{PcapReader, LINKTYPE_ETHERNET},
+  CaptureError,
+};
+use crate::{Frame, Interface, PacketSource};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReplaySpeed {
+  #[default]
+  Unpaced,
+  Realtime,
+  Scaled(f64),
+}
+
+impl ReplaySpeed {
+  fn factor(&self) -> Option<f64> {
+    match self {
+      Self::Unpaced => None,
+      Self::Realtime => Some(1.0),
+      Self::Scaled(x) if *x > 0.0 => Some(*x),
+      Self::Scaled(_) => None,
+    }
+  }
+}
+
+pub struct ReplaySource {
+  reader: PcapReader<Cursor<Vec<u8>>>,
+  speed: ReplaySpeed,
+  // capture timestamp of the first frame and when it was replayed
+  origin: Option<(Duration, Instant)>,
+}
+
+impl ReplaySource {
+  pub async fn open(path: impl AsRef<Path>, speed: ReplaySpeed) -> Result<Self, CaptureError> {
+    let file = tokio::fs::read(path).await?;
+    Ok(Self {
+      reader: PcapReader::new(Cursor::new(file))?,
+      speed,
+      origin: None,
+    })
+  }
+
+  async fn pace(&mut self, timestamp: Option<Duration>) {
+    let (Some(factor), Some(timestamp)) = (self.speed.factor(), timestamp) else {
+      return;
+    };
+
+    let (first, start) = *self.origin.get_or_insert((timestamp, Instant::now()));
+    let offset = timestamp.saturating_sub(first).div_f64(factor);
+    tokio::time::sleep_until(start + offset).await;
+  }
+}
+
+impl PacketSource for ReplaySource {
+  async fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+    while let Some(packet) = self.reader.next_packet()? {
+      if packet.link_type != LINKTYPE_ETHERNET {
+        continue;
+      }
+
+      self.pace(packet.timestamp).await;
+
+      return Ok(Some(Frame {
+        data: packet.data,
+        wire_len: packet.wire_len,
+        // recorded in another run, the neighbors age from when they're replayed
+        timestamp: None,
+      }));
+    }
+
+    Ok(None)
+  }
+}
+
+impl Interface {
+  #[instrument(skip_all, fields(path = %path.as_ref().display()))]
+  pub async fn replay_pcap(&self, path: impl AsRef<Path>, speed: ReplaySpeed) -> Result<(), CaptureError> {
+    let source = ReplaySource::open(path, speed).await?;
+    self.run(source).await
+  }
+}
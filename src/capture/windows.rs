@@ -0,0 +1,117 @@
+use std::{
+  io,
+  time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use pcap::{Capture, Error};
+use tokio::{
+  sync::{mpsc, watch},
+  task::JoinHandle,
+};
+use tracing::instrument;
+
+use super::{CaptureError, CAPTURE_STATS_INTERVAL};
+use crate::{CaptureStats, FilterSpec, Frame, Interface, PacketSource};
+
+pub struct NpcapSource {
+  rx: mpsc::Receiver<Frame>,
+  reader: Option<JoinHandle<Result<(), CaptureError>>>,
+  // the capture belongs to the reader thread, which refreshes these as it goes
+  stats: watch::Receiver<Option<CaptureStats>>,
+}
+
+impl NpcapSource {
+  // npcap takes the adapter out of promiscuous mode again when the capture is closed
+  pub fn open(intf: &str, filter: &FilterSpec, buffer_size: usize, promiscuous: bool) -> Result<Self, CaptureError> {
+    if filter.is_empty() {
+      return Err(CaptureError::NoProtocols);
+    }
+
+    let mut capture = Capture::from_device(intf)?
+      .snaplen(buffer_size as _)
+      .promisc(promiscuous)
+      .immediate_mode(true)
+      .timeout(1000)
+      .open()?;
+    capture.filter(&filter.expression(), true)?;
+
+    // npcap only offers a blocking api, so the capture runs on its own thread and hands frames over
+    let (tx, rx) = mpsc::channel(64);
+    let (stats_tx, stats) = watch::channel(None);
+    let mut stats_updated = Instant::now();
+    let reader = tokio::task::spawn_blocking(move || loop {
+      if stats_updated.elapsed() >= CAPTURE_STATS_INTERVAL {
+        stats_updated = Instant::now();
+        if let Ok(stat) = capture.stats() {
+          stats_tx.send_replace(Some(CaptureStats {
+            received: stat.received as _,
+            dropped: stat.dropped as u64 + stat.if_dropped as u64,
+          }));
+        }
+      }
+
+      match capture.next_packet() {
+        Ok(packet) => {
+          let ts = packet.header.ts;
+          let frame = Frame {
+            data: packet.data.to_vec(),
+            wire_len: packet.header.len as _,
+            timestamp: Some(UNIX_EPOCH + Duration::new(ts.tv_sec as _, ts.tv_usec as u32 * 1000)),
+          };
+
+          if tx.blocking_send(frame).is_err() {
+            return Ok(());
+          }
+        }
+        Err(Error::TimeoutExpired) => {
+          if tx.is_closed() {
+            return Ok(());
+          }
+        }
+        Err(err) => return Err(CaptureError::Pcap(err)),
+      }
+    });
+
+    Ok(Self {
+      rx,
+      reader: Some(reader),
+      stats,
+    })
+  }
+}
+
+impl PacketSource for NpcapSource {
+  async fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+    if let Some(frame) = self.rx.recv().await {
+      return Ok(Some(frame));
+    }
+
+    if let Some(reader) = self.reader.take() {
+      reader.await.map_err(|err| CaptureError::Io(io::Error::other(err)))??;
+    }
+
+    Ok(None)
+  }
+
+  fn capture_stats(&mut self) -> Option<CaptureStats> {
+    *self.stats.borrow()
+  }
+}
+
+impl Interface {
+  #[instrument(skip_all, fields(interface = intf))]
+  pub async fn start_socket(&self, intf: &str, filter: &FilterSpec) -> Result<(), CaptureError> {
+    if filter.is_empty() {
+      return Ok(());
+    }
+
+    if self.inner.config.all_multicast {
+      return Err(CaptureError::Unsupported(
+        "npcap can't enable all-multicast mode, use promiscuous mode instead",
+      ));
+    }
+
+    let source = NpcapSource::open(intf, filter, self.buffer_size(), self.inner.config.promiscuous)?;
+    self.run_live(source, filter).await
+  }
+}
@@ -0,0 +1,731 @@
+use std::{
+  collections::VecDeque,
+  ffi::CString,
+  fs, io, mem,
+  os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+  ptr,
+  sync::atomic::{AtomicU32, Ordering},
+  task::Poll,
+  time::SystemTime,
+};
+
+use tokio::io::unix::AsyncFd;
+use tracing::{debug, instrument};
+
+use super::{
+  linux::{cvt, getsockopt, mode, setsockopt},
+  CaptureError,
+};
+use crate::{
+  filter::{Insn, BPF_JMP_JA, BPF_JMP_JEQ_K, BPF_LD_B_ABS, BPF_LD_H_ABS, BPF_LD_W_ABS, BPF_RET_K},
+  CaptureStats, FilterSpec, Frame, Interface, PacketSource,
+};
+
+// enough for a 1500 byte mtu with room to spare, frames that don't fit a chunk never reach the socket
+const CHUNK_SIZE: u32 = 4096;
+// per rx queue, discovery frames come a few a second so this only has to absorb bursts
+const RING_SIZE: u32 = 256;
+
+// from linux/bpf.h
+const BPF_MAP_CREATE: libc::c_int = 0;
+const BPF_MAP_UPDATE_ELEM: libc::c_int = 2;
+const BPF_PROG_LOAD: libc::c_int = 5;
+const BPF_LINK_CREATE: libc::c_int = 28;
+const BPF_LINK_UPDATE: libc::c_int = 29;
+const BPF_MAP_TYPE_XSKMAP: u32 = 17;
+const BPF_PROG_TYPE_XDP: u32 = 6;
+const BPF_XDP: u32 = 37;
+const BPF_PSEUDO_MAP_FD: u8 = 1;
+const BPF_FUNC_REDIRECT_MAP: i32 = 51;
+const XDP_PASS: i32 = 2;
+
+// offsets into struct xdp_md
+const XDP_MD_DATA: i16 = 0;
+const XDP_MD_DATA_END: i16 = 4;
+const XDP_MD_RX_QUEUE_INDEX: i16 = 16;
+
+// struct bpf_insn, dst_reg is the low nibble of regs
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EbpfInsn {
+  code: u8,
+  regs: u8,
+  off: i16,
+  imm: i32,
+}
+
+const LDX_W: u8 = 0x61;
+const LDX_H: u8 = 0x69;
+const LDX_B: u8 = 0x71;
+const LD_IMM64: u8 = 0x18;
+const MOV64_X: u8 = 0xbf;
+const MOV64_K: u8 = 0xb7;
+const ADD64_K: u8 = 0x07;
+const TO_BE: u8 = 0xdc;
+const JA: u8 = 0x05;
+const JGT_X: u8 = 0x2d;
+// jmp32 so immediates with the top bit set aren't sign extended against the 64 bit register
+const JEQ32_K: u8 = 0x16;
+const CALL: u8 = 0x85;
+const EXIT: u8 = 0x95;
+
+fn insn(code: u8, dst: u8, src: u8, off: i16, imm: i32) -> EbpfInsn {
+  EbpfInsn {
+    code,
+    regs: (src << 4) | dst,
+    off,
+    imm,
+  }
+}
+
+// the same classic filter every other backend attaches, translated for xdp. r2 and r3 hold the packet bounds, r7 is
+// the accumulator. what doesn't match goes on to the kernel stack, what does is redirected to the queue's socket
+// ebpf jumps only reach 32767 instructions either way, long source lists can outgrow that
+fn program(filter: &FilterSpec, map: RawFd) -> Result<Vec<EbpfInsn>, CaptureError> {
+  let classic = filter.compile();
+  let len = |insn: &Insn| match insn.code {
+    BPF_LD_B_ABS => 4,
+    BPF_LD_H_ABS | BPF_LD_W_ABS => 5,
+    BPF_JMP_JEQ_K => 2,
+    _ => 1,
+  };
+
+  let mut start = Vec::with_capacity(classic.len());
+  let mut pc = 4;
+  for insn in &classic {
+    start.push(pc);
+    pc += len(insn);
+  }
+  let pass = pc;
+  let redirect = pass + 2;
+  let jump =
+    |from: usize, to: usize| i16::try_from(to as isize - from as isize - 1).map_err(|_| CaptureError::FilterTooLarge);
+
+  let mut out = vec![
+    insn(MOV64_X, 6, 1, 0, 0),
+    insn(MOV64_K, 7, 0, 0, 0),
+    insn(LDX_W, 2, 6, XDP_MD_DATA, 0),
+    insn(LDX_W, 3, 6, XDP_MD_DATA_END, 0),
+  ];
+  for (i, insn_) in classic.iter().enumerate() {
+    let next = i + 1;
+    match insn_.code {
+      BPF_LD_B_ABS | BPF_LD_H_ABS | BPF_LD_W_ABS => {
+        let (code, size) = match insn_.code {
+          BPF_LD_B_ABS => (LDX_B, 1),
+          BPF_LD_H_ABS => (LDX_H, 2),
+          _ => (LDX_W, 4),
+        };
+        // a load past the end fails the classic filter, so it's a pass here too
+        out.push(insn(MOV64_X, 4, 2, 0, 0));
+        out.push(insn(ADD64_K, 4, 0, 0, (insn_.k + size) as i32));
+        out.push(insn(JGT_X, 4, 3, jump(out.len(), pass)?, 0));
+        out.push(insn(code, 7, 2, insn_.k as i16, 0));
+        if size > 1 {
+          out.push(insn(TO_BE, 7, 0, 0, size as i32 * 8));
+        }
+      }
+      BPF_JMP_JEQ_K => {
+        let jt = start[next + insn_.jt as usize];
+        let jf = start[next + insn_.jf as usize];
+        out.push(insn(JEQ32_K, 7, 0, jump(out.len(), jt)?, insn_.k as i32));
+        out.push(insn(JA, 0, 0, jump(out.len(), jf)?, 0));
+      }
+      BPF_JMP_JA => out.push(insn(JA, 0, 0, jump(out.len(), start[next + insn_.k as usize])?, 0)),
+      BPF_RET_K => {
+        let to = if insn_.k == 0 { pass } else { redirect };
+        out.push(insn(JA, 0, 0, jump(out.len(), to)?, 0));
+      }
+      code => unreachable!("unexpected opcode {code:#x}"),
+    }
+  }
+
+  debug_assert_eq!(out.len(), pass);
+  out.extend([
+    insn(MOV64_K, 0, 0, 0, XDP_PASS),
+    insn(EXIT, 0, 0, 0, 0),
+    insn(LDX_W, 2, 6, XDP_MD_RX_QUEUE_INDEX, 0),
+    insn(LD_IMM64, 1, BPF_PSEUDO_MAP_FD, 0, map),
+    insn(0, 0, 0, 0, 0),
+    // queues without a socket fall back to passing the frame
+    insn(MOV64_K, 3, 0, 0, XDP_PASS),
+    insn(CALL, 0, 0, 0, BPF_FUNC_REDIRECT_MAP),
+    insn(EXIT, 0, 0, 0, 0),
+  ]);
+  Ok(out)
+}
+
+// the leading part of union bpf_attr each command uses, the kernel treats what's left out as zero
+#[repr(C)]
+struct MapCreateAttr {
+  map_type: u32,
+  key_size: u32,
+  value_size: u32,
+  max_entries: u32,
+}
+
+#[repr(C)]
+struct MapUpdateAttr {
+  map_fd: u32,
+  key: u64,
+  value: u64,
+  flags: u64,
+}
+
+#[repr(C)]
+struct ProgLoadAttr {
+  prog_type: u32,
+  insn_cnt: u32,
+  insns: u64,
+  license: u64,
+  log_level: u32,
+  log_size: u32,
+  log_buf: u64,
+  kern_version: u32,
+  prog_flags: u32,
+  prog_name: [u8; 16],
+  prog_ifindex: u32,
+  expected_attach_type: u32,
+}
+
+#[repr(C)]
+struct LinkCreateAttr {
+  prog_fd: u32,
+  target_ifindex: u32,
+  attach_type: u32,
+  flags: u32,
+}
+
+#[repr(C)]
+struct LinkUpdateAttr {
+  link_fd: u32,
+  new_prog_fd: u32,
+  flags: u32,
+  old_prog_fd: u32,
+}
+
+unsafe fn bpf<T>(cmd: libc::c_int, attr: &T) -> io::Result<libc::c_int> {
+  cvt(libc::syscall(libc::SYS_bpf, cmd, attr as *const T, mem::size_of::<T>()) as _)
+}
+
+fn load(program: &[EbpfInsn]) -> io::Result<OwnedFd> {
+  let license = c"Dual MIT/GPL";
+  let mut attr = ProgLoadAttr {
+    prog_type: BPF_PROG_TYPE_XDP,
+    insn_cnt: program.len() as _,
+    insns: program.as_ptr() as _,
+    license: license.as_ptr() as _,
+    log_level: 0,
+    log_size: 0,
+    log_buf: 0,
+    kern_version: 0,
+    prog_flags: 0,
+    prog_name: *b"rlldp_filter\0\0\0\0",
+    prog_ifindex: 0,
+    expected_attach_type: BPF_XDP,
+  };
+  match unsafe { bpf(BPF_PROG_LOAD, &attr) } {
+    Ok(fd) => Ok(unsafe { OwnedFd::from_raw_fd(fd) }),
+    Err(err) => {
+      // load again for the verifier's reasoning, it's only worth the buffer when something is wrong
+      let mut log = vec![0u8; 1 << 16];
+      attr.log_level = 1;
+      attr.log_size = log.len() as _;
+      attr.log_buf = log.as_mut_ptr() as _;
+      _ = unsafe { bpf(BPF_PROG_LOAD, &attr) };
+      let log = String::from_utf8_lossy(&log[..log.iter().position(|&x| x == 0).unwrap_or(log.len())]);
+      debug!(%log, "xdp program rejected");
+      Err(err)
+    }
+  }
+}
+
+struct Mmap {
+  ptr: *mut u8,
+  len: usize,
+}
+
+// only ever touched through &mut XdpSource
+unsafe impl Send for Mmap {}
+
+impl Mmap {
+  fn new(fd: RawFd, len: usize, offset: libc::off_t) -> io::Result<Self> {
+    let (flags, fd) = if fd < 0 {
+      (libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1)
+    } else {
+      (libc::MAP_SHARED | libc::MAP_POPULATE, fd)
+    };
+    let ptr = unsafe {
+      libc::mmap(
+        ptr::null_mut(),
+        len,
+        libc::PROT_READ | libc::PROT_WRITE,
+        flags,
+        fd,
+        offset,
+      )
+    };
+    if ptr == libc::MAP_FAILED {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(Self { ptr: ptr as _, len })
+  }
+}
+
+impl Drop for Mmap {
+  fn drop(&mut self) {
+    unsafe { libc::munmap(self.ptr as _, self.len) };
+  }
+}
+
+// a single producer single consumer ring shared with the kernel
+struct Ring {
+  map: Mmap,
+  offsets: libc::xdp_ring_offset,
+}
+
+impl Ring {
+  fn open<T>(fd: RawFd, offsets: libc::xdp_ring_offset, pgoff: libc::off_t) -> io::Result<Self> {
+    let len = offsets.desc as usize + RING_SIZE as usize * mem::size_of::<T>();
+    Ok(Self {
+      map: Mmap::new(fd, len, pgoff)?,
+      offsets,
+    })
+  }
+
+  fn producer(&self) -> &AtomicU32 {
+    unsafe { &*(self.map.ptr.add(self.offsets.producer as _) as *const AtomicU32) }
+  }
+
+  fn consumer(&self) -> &AtomicU32 {
+    unsafe { &*(self.map.ptr.add(self.offsets.consumer as _) as *const AtomicU32) }
+  }
+
+  fn desc<T>(&self, index: u32) -> *mut T {
+    unsafe { (self.map.ptr.add(self.offsets.desc as _) as *mut T).add((index & (RING_SIZE - 1)) as _) }
+  }
+}
+
+struct Rings {
+  umem: Mmap,
+  rx: Ring,
+  fill: Ring,
+}
+
+impl Rings {
+  // hands every chunk the kernel filled back to it once the frame is copied out
+  fn drain(&mut self, frames: &mut VecDeque<Frame>) -> usize {
+    let end = self.rx.producer().load(Ordering::Acquire);
+    let start = self.rx.consumer().load(Ordering::Relaxed);
+    let mut fill = self.fill.producer().load(Ordering::Relaxed);
+    // af_xdp has no receive timestamps, the ring being read is as close as it gets
+    let now = SystemTime::now();
+    for index in start..end {
+      let desc = unsafe { self.rx.desc::<libc::xdp_desc>(index).read() };
+      let data = unsafe { std::slice::from_raw_parts(self.umem.ptr.add(desc.addr as _), desc.len as _) };
+      frames.push_back(Frame {
+        timestamp: Some(now),
+        ..Frame::new(data.to_vec())
+      });
+
+      unsafe { self.fill.desc::<u64>(fill).write(desc.addr & !(CHUNK_SIZE as u64 - 1)) };
+      fill = fill.wrapping_add(1);
+    }
+    self.fill.producer().store(fill, Ordering::Release);
+    self.rx.consumer().store(end, Ordering::Release);
+    end.wrapping_sub(start) as usize
+  }
+}
+
+struct Xsk {
+  fd: AsyncFd<OwnedFd>,
+  rings: Rings,
+}
+
+impl Xsk {
+  fn open(ifindex: u32, queue: u32) -> io::Result<Self> {
+    let fd = cvt(unsafe { libc::socket(libc::AF_XDP, libc::SOCK_RAW | libc::SOCK_CLOEXEC, 0) })?;
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let umem = Mmap::new(-1, (CHUNK_SIZE * RING_SIZE) as _, 0)?;
+    let reg = libc::xdp_umem_reg {
+      addr: umem.ptr as _,
+      len: umem.len as _,
+      chunk_size: CHUNK_SIZE,
+      headroom: 0,
+      flags: 0,
+      tx_metadata_len: 0,
+    };
+    unsafe {
+      setsockopt(&fd, libc::SOL_XDP, libc::XDP_UMEM_REG, &reg)?;
+      setsockopt(&fd, libc::SOL_XDP, libc::XDP_UMEM_FILL_RING, &RING_SIZE)?;
+      // bind wants one even though nothing is ever sent
+      setsockopt(&fd, libc::SOL_XDP, libc::XDP_UMEM_COMPLETION_RING, &RING_SIZE)?;
+      setsockopt(&fd, libc::SOL_XDP, libc::XDP_RX_RING, &RING_SIZE)?;
+    }
+
+    let mut offsets: libc::xdp_mmap_offsets = unsafe { mem::zeroed() };
+    let mut len = mem::size_of_val(&offsets) as libc::socklen_t;
+    cvt(unsafe {
+      libc::getsockopt(
+        fd.as_raw_fd(),
+        libc::SOL_XDP,
+        libc::XDP_MMAP_OFFSETS,
+        &mut offsets as *mut _ as *mut _,
+        &mut len,
+      )
+    })?;
+    let rx = Ring::open::<libc::xdp_desc>(fd.as_raw_fd(), offsets.rx, libc::XDP_PGOFF_RX_RING)?;
+    let fill = Ring::open::<u64>(fd.as_raw_fd(), offsets.fr, libc::XDP_UMEM_PGOFF_FILL_RING as _)?;
+
+    // every chunk starts out with the kernel
+    for index in 0..RING_SIZE {
+      unsafe { fill.desc::<u64>(index).write((index * CHUNK_SIZE) as u64) };
+    }
+    fill.producer().store(RING_SIZE, Ordering::Release);
+
+    // no flags lets the driver pick zero copy when it can and fall back to copying
+    let addr = libc::sockaddr_xdp {
+      sxdp_family: libc::AF_XDP as _,
+      sxdp_flags: 0,
+      sxdp_ifindex: ifindex,
+      sxdp_queue_id: queue,
+      sxdp_shared_umem_fd: 0,
+    };
+    cvt(unsafe {
+      libc::bind(
+        fd.as_raw_fd(),
+        &addr as *const libc::sockaddr_xdp as *const _,
+        mem::size_of::<libc::sockaddr_xdp>() as _,
+      )
+    })?;
+
+    Ok(Self {
+      fd: AsyncFd::new(fd)?,
+      rings: Rings { umem, rx, fill },
+    })
+  }
+}
+
+fn rx_queues(intf: &str) -> usize {
+  fs::read_dir(format!("/sys/class/net/{intf}/queues"))
+    .map(|dir| {
+      dir
+        .filter_map(Result::ok)
+        .filter(|x| x.file_name().to_string_lossy().starts_with("rx-"))
+        .count()
+    })
+    .unwrap_or(0)
+    .max(1)
+}
+
+// for taps where discovery frames share the link with heavy traffic. the filter runs in the driver so everything else
+// stays in the kernel, and only matching frames are copied to userspace. frames the nic untagged in hardware reach
+// the program without their vlan, so they aren't put back like the af_packet source does
+pub struct XdpSource {
+  // dropping the link detaches the program
+  link: OwnedFd,
+  map: OwnedFd,
+  ifindex: u32,
+  sockets: Vec<Xsk>,
+  // an af_packet socket that receives nothing, only there to hold promiscuous and all-multicast mode
+  modes: Option<OwnedFd>,
+  pending: VecDeque<Frame>,
+  // af_xdp only counts what it dropped, what made it is counted here
+  drained: u64,
+}
+
+impl XdpSource {
+  pub fn open(intf: &str, filter: &FilterSpec) -> Result<Self, CaptureError> {
+    if filter.is_empty() {
+      return Err(CaptureError::NoProtocols);
+    }
+
+    let c_name = CString::new(intf).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let ifindex = unsafe { libc::if_nametoindex(c_name.as_ptr()) };
+    if ifindex == 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+
+    // one socket per rx queue, rss can put the frames on any of them
+    let queues = rx_queues(intf);
+    let map = unsafe {
+      bpf(
+        BPF_MAP_CREATE,
+        &MapCreateAttr {
+          map_type: BPF_MAP_TYPE_XSKMAP,
+          key_size: 4,
+          value_size: 4,
+          max_entries: queues as _,
+        },
+      )?
+    };
+    let map = unsafe { OwnedFd::from_raw_fd(map) };
+
+    let mut sockets = Vec::with_capacity(queues);
+    for queue in 0..queues as u32 {
+      let xsk = Xsk::open(ifindex, queue)?;
+      let fd = xsk.fd.as_raw_fd();
+      unsafe {
+        bpf(
+          BPF_MAP_UPDATE_ELEM,
+          &MapUpdateAttr {
+            map_fd: map.as_raw_fd() as _,
+            key: &queue as *const u32 as _,
+            value: &fd as *const RawFd as _,
+            flags: 0,
+          },
+        )?
+      };
+      sockets.push(xsk);
+    }
+
+    let prog = load(&program(filter, map.as_raw_fd())?)?;
+    let link = unsafe {
+      bpf(
+        BPF_LINK_CREATE,
+        &LinkCreateAttr {
+          prog_fd: prog.as_raw_fd() as _,
+          target_ifindex: ifindex,
+          attach_type: BPF_XDP,
+          flags: 0,
+        },
+      )?
+    };
+
+    Ok(Self {
+      link: unsafe { OwnedFd::from_raw_fd(link) },
+      map,
+      ifindex,
+      sockets,
+      modes: None,
+      pending: VecDeque::new(),
+      drained: 0,
+    })
+  }
+}
+
+impl XdpSource {
+  pub fn enable_promiscuous(&mut self) -> Result<(), CaptureError> {
+    self.enable_mode(libc::PACKET_MR_PROMISC)
+  }
+
+  pub fn enable_all_multicast(&mut self) -> Result<(), CaptureError> {
+    self.enable_mode(libc::PACKET_MR_ALLMULTI)
+  }
+
+  fn enable_mode(&mut self, mr_type: libc::c_int) -> Result<(), CaptureError> {
+    let fd = match &self.modes {
+      Some(fd) => fd,
+      None => {
+        let fd = cvt(unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_RAW | libc::SOCK_CLOEXEC, 0) })?;
+        self.modes.insert(unsafe { OwnedFd::from_raw_fd(fd) })
+      }
+    };
+    // adding the same mode twice only bumps the kernel's count, which closing the socket undoes all the same
+    unsafe {
+      setsockopt(
+        fd,
+        libc::SOL_PACKET,
+        libc::PACKET_ADD_MEMBERSHIP,
+        &mode(self.ifindex as _, mr_type),
+      )?
+    };
+    Ok(())
+  }
+}
+
+impl PacketSource for XdpSource {
+  async fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+    let Self {
+      sockets,
+      pending,
+      drained,
+      ..
+    } = self;
+    let frame = std::future::poll_fn(|cx| {
+      if pending.is_empty() {
+        for xsk in sockets.iter_mut() {
+          while let Poll::Ready(guard) = xsk.fd.poll_read_ready(cx) {
+            let mut guard = guard?;
+            let n = xsk.rings.drain(pending);
+            if n > 0 {
+              *drained += n as u64;
+              break;
+            }
+            guard.clear_ready();
+          }
+        }
+      }
+
+      match pending.pop_front() {
+        Some(frame) => Poll::Ready(Ok::<_, io::Error>(frame)),
+        None => Poll::Pending,
+      }
+    })
+    .await?;
+    Ok(Some(frame))
+  }
+
+  // whatever every ring had ready
+  async fn next_batch(&mut self) -> Result<Vec<Frame>, CaptureError> {
+    if self.pending.is_empty() {
+      self
+        .next_frame()
+        .await?
+        .into_iter()
+        .for_each(|x| self.pending.push_front(x));
+    }
+    Ok(self.pending.drain(..).collect())
+  }
+
+  // the link swaps programs atomically, so nothing is let through or lost in between
+  fn set_filter(&mut self, filter: &FilterSpec) -> Result<(), CaptureError> {
+    let prog = load(&program(filter, self.map.as_raw_fd())?)?;
+    unsafe {
+      bpf(
+        BPF_LINK_UPDATE,
+        &LinkUpdateAttr {
+          link_fd: self.link.as_raw_fd() as _,
+          new_prog_fd: prog.as_raw_fd() as _,
+          flags: 0,
+          old_prog_fd: 0,
+        },
+      )?
+    };
+    Ok(())
+  }
+
+  fn capture_stats(&mut self) -> Option<CaptureStats> {
+    let mut dropped = 0;
+    for xsk in &self.sockets {
+      let stats: libc::xdp_statistics =
+        unsafe { getsockopt(xsk.fd.get_ref(), libc::SOL_XDP, libc::XDP_STATISTICS) }.ok()?;
+      dropped += stats.rx_dropped + stats.rx_ring_full;
+    }
+    Some(CaptureStats {
+      received: self.drained + dropped,
+      dropped,
+    })
+  }
+}
+
+impl Interface {
+  #[instrument(skip_all, fields(interface = intf))]
+  pub async fn start_xdp(&self, intf: &str, filter: &FilterSpec) -> Result<(), CaptureError> {
+    if filter.is_empty() {
+      return Ok(());
+    }
+
+    let mut source = XdpSource::open(intf, filter)?;
+    if self.inner.config.promiscuous {
+      source.enable_promiscuous()?;
+    }
+    if self.inner.config.all_multicast {
+      source.enable_all_multicast()?;
+    }
+    self.run_live(source, filter).await
+  }
+}
+
+// runs the translated program the way the verifier would let it, returns the xdp action
+#[cfg(test)]
+fn run(program: &[EbpfInsn], frame: &[u8]) -> i32 {
+  let mut regs = [0u64; 11];
+  let mut pc = 0;
+  loop {
+    let insn = program[pc];
+    let (dst, src) = ((insn.regs & 0xf) as usize, (insn.regs >> 4) as usize);
+    pc += 1;
+    match insn.code {
+      MOV64_X => regs[dst] = regs[src],
+      MOV64_K => regs[dst] = insn.imm as u64,
+      ADD64_K => regs[dst] = regs[dst].wrapping_add(insn.imm as u64),
+      // the context is r1 = 0, where data starts at 0 and ends at the frame's length
+      LDX_W if src == 6 => {
+        regs[dst] = if insn.off == XDP_MD_DATA_END {
+          frame.len() as u64
+        } else {
+          0
+        }
+      }
+      LDX_W | LDX_H | LDX_B => {
+        let size = match insn.code {
+          LDX_W => 4,
+          LDX_H => 2,
+          _ => 1,
+        };
+        let offset = (regs[src] as i64 + insn.off as i64) as usize;
+        let mut bytes = [0; 8];
+        bytes[..size].copy_from_slice(&frame[offset..offset + size]);
+        regs[dst] = u64::from_ne_bytes(bytes);
+      }
+      TO_BE => {
+        regs[dst] = match insn.imm {
+          16 => (regs[dst] as u16).to_be() as u64,
+          _ => (regs[dst] as u32).to_be() as u64,
+        }
+      }
+      LD_IMM64 => pc += 1,
+      JA => pc = (pc as isize + insn.off as isize) as usize,
+      JGT_X if regs[dst] > regs[src] => pc = (pc as isize + insn.off as isize) as usize,
+      JGT_X => {}
+      JEQ32_K if regs[dst] as u32 == insn.imm as u32 => pc = (pc as isize + insn.off as isize) as usize,
+      JEQ32_K => {}
+      CALL => return 4,
+      EXIT => return regs[0] as i32,
+      code => panic!("unexpected opcode {code:#x}"),
+    }
+  }
+}
+
+#[test]
+fn translates_filter() {
+  const XDP_REDIRECT: i32 = 4;
+  let src = [0, 0, 0, 0, 0, 1];
+  let frame = |dst: [u8; 6], rest: &[u8]| [&dst[..], &src[..], rest].concat();
+
+  let lldp = frame([0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e], &[0x88, 0xcc, 0, 0]);
+  let tagged = frame(
+    [0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e],
+    &[0x81, 0x00, 0x00, 0x64, 0x88, 0xcc],
+  );
+  let cdp = frame([0x01, 0x00, 0x0c, 0xcc, 0xcc, 0xcc], &[0x00, 0x20, 0xaa, 0xaa]);
+  let sonmp = frame([0x01, 0x00, 0x81, 0x00, 0x01, 0x01], &[0x00, 0x13, 0xaa, 0xaa]);
+  let ipv4 = frame([0xff; 6], &[0x08, 0x00, 0, 0]);
+
+  let all = program(&FilterSpec::default(), 3).unwrap();
+  assert_eq!(run(&all, &lldp), XDP_REDIRECT);
+  assert_eq!(run(&all, &tagged), XDP_REDIRECT);
+  assert_eq!(run(&all, &cdp), XDP_REDIRECT);
+  assert_eq!(run(&all, &sonmp), XDP_REDIRECT);
+  assert_eq!(run(&all, &ipv4), XDP_PASS);
+  assert_eq!(run(&all, &lldp[..8]), XDP_PASS);
+
+  let lldp_only = program(
+    &FilterSpec {
+      cdp: false,
+      fdp: false,
+      sonmp: false,
+      src_allowlist: vec![crate::MacAddress(src)],
+      ..Default::default()
+    },
+    3,
+  )
+  .unwrap();
+  assert_eq!(run(&lldp_only, &lldp), XDP_REDIRECT);
+  assert_eq!(run(&lldp_only, &cdp), XDP_PASS);
+  let mut other = lldp.clone();
+  other[11] = 2;
+  assert_eq!(run(&lldp_only, &other), XDP_PASS);
+}
+
+#[test]
+fn rejects_filters_past_jump_range() {
+  let filter = |len: u64| FilterSpec {
+    src_allowlist: (0..len).map(crate::MacAddress::from_u64).collect(),
+    ..Default::default()
+  };
+  assert!(program(&filter(1000), 3).is_ok());
+  assert!(matches!(program(&filter(3000), 3), Err(CaptureError::FilterTooLarge)));
+}
@@ -0,0 +1,92 @@
+use lldp_parser::DataUnit;
+
+use crate::MacAddress;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NeighborField {
+  Source,
+  TimeToLive,
+  SystemName,
+  SystemDescription,
+  PortId,
+  PortDescription,
+  ManagementAddress,
+  PortVlanId,
+}
+
+// one field that differed between two dus from the same neighbor, with both values as text
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldChange {
+  pub field: NeighborField,
+  pub old: Option<String>,
+  pub new: Option<String>,
+}
+
+fn port_description(du: &DataUnit) -> Option<String> {
+  match du {
+    DataUnit::Lldp(x) => x.port_description.as_ref().map(|x| x.to_string()),
+    _ => None,
+  }
+}
+
+fn system_description(du: &DataUnit) -> Option<String> {
+  match du {
+    DataUnit::Lldp(x) => x.system_description.as_ref().map(|x| x.to_string()),
+    DataUnit::Cdp(x) => x.software_version.as_ref().map(|x| x.to_string()),
+    _ => None,
+  }
+}
+
+impl FieldChange {
+  // what differs between two dus from the same neighbor, in the order the fields are declared
+  pub fn diff(old_source: &MacAddress, old: &DataUnit, new_source: &MacAddress, new: &DataUnit) -> Vec<Self> {
+    let mut changes = Vec::new();
+    let mut push = |field, old: Option<String>, new: Option<String>| {
+      if old != new {
+        changes.push(Self { field, old, new });
+      }
+    };
+
+    push(
+      NeighborField::Source,
+      Some(old_source.to_string()),
+      Some(new_source.to_string()),
+    );
+    push(
+      NeighborField::TimeToLive,
+      Some(old.time_to_live().to_string()),
+      Some(new.time_to_live().to_string()),
+    );
+    push(
+      NeighborField::SystemName,
+      old.system_name().map(|x| x.to_string()),
+      new.system_name().map(|x| x.to_string()),
+    );
+    push(
+      NeighborField::SystemDescription,
+      system_description(old),
+      system_description(new),
+    );
+    push(
+      NeighborField::PortId,
+      old.port_id().map(|x| x.to_string()),
+      new.port_id().map(|x| x.to_string()),
+    );
+    push(
+      NeighborField::PortDescription,
+      port_description(old),
+      port_description(new),
+    );
+    push(
+      NeighborField::ManagementAddress,
+      old.management_address().map(|x| x.to_string()),
+      new.management_address().map(|x| x.to_string()),
+    );
+    push(
+      NeighborField::PortVlanId,
+      old.port_vlan_id().map(|x| x.to_string()),
+      new.port_vlan_id().map(|x| x.to_string()),
+    );
+    changes
+  }
+}
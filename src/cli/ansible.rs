@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use super::output::NeighborOutput;
+
+// the shape a facts module returns, so the output can be dropped into /etc/ansible/facts.d or returned as is.
+// an interface can see more than one neighbor, so every interface maps to a list.
+pub fn facts(neighbors: &[NeighborOutput]) -> Value {
+  let mut by_interface: BTreeMap<&str, Vec<&NeighborOutput>> = BTreeMap::new();
+  for neighbor in neighbors {
+    by_interface.entry(&neighbor.interface).or_default().push(neighbor);
+  }
+  json!({ "ansible_facts": { "ansible_lldp_neighbors": by_interface } })
+}
+
+#[test]
+fn groups_by_interface() {
+  let neighbor = |interface: &str, chassis: &str| NeighborOutput {
+    interface: interface.into(),
+    protocol: "lldp".into(),
+    source: chassis.into(),
+    chassis: chassis.into(),
+    ttl: 120,
+    ttl_remaining: 120,
+    age: 0,
+    remote_index: 1,
+    chassis_subtype: Some("mac".into()),
+    ..Default::default()
+  };
+
+  let value = facts(&[
+    neighbor("eth1", "02:00:00:00:00:01"),
+    neighbor("eth0", "02:00:00:00:00:02"),
+    neighbor("eth1", "02:00:00:00:00:03"),
+  ]);
+  let neighbors = &value["ansible_facts"]["ansible_lldp_neighbors"];
+  assert_eq!(neighbors["eth0"][0]["chassis"], "02:00:00:00:00:02");
+  assert_eq!(neighbors["eth1"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,57 @@
+use std::io;
+
+use clap::{Args, Subcommand};
+
+use super::{
+  control::{self, AdminStatusArg, Request, ScopeArg},
+  GlobalArgs,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigureCommand {
+  /// Set the admin status of an interface's agent
+  AdminStatus(AdminStatusArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct AdminStatusArgs {
+  interface: String,
+
+  #[arg(value_enum)]
+  admin_status: AdminStatusArg,
+
+  #[arg(long, value_enum, default_value_t)]
+  scope: ScopeArg,
+}
+
+#[derive(Debug, Args)]
+pub struct UpdateArgs {
+  /// Only resend on this interface
+  interface: Option<String>,
+}
+
+pub async fn run(command: &ConfigureCommand, global: &GlobalArgs) -> io::Result<()> {
+  let mut client = control::require(&global.socket).await?;
+  let request = match command {
+    ConfigureCommand::AdminStatus(args) => Request::SetAdminStatus {
+      interface: args.interface.clone(),
+      scope: args.scope,
+      admin_status: args.admin_status,
+    },
+  };
+
+  match client.request(&request).await? {
+    control::Response::Ok => Ok(()),
+    response => Err(control::unexpected(response)),
+  }
+}
+
+pub async fn update(args: &UpdateArgs, global: &GlobalArgs) -> io::Result<()> {
+  let request = Request::Resend {
+    interface: args.interface.clone(),
+  };
+  match control::require(&global.socket).await?.request(&request).await? {
+    control::Response::Ok => Ok(()),
+    response => Err(control::unexpected(response)),
+  }
+}
@@ -0,0 +1,352 @@
+#[cfg(windows)]
+use std::convert::Infallible;
+use std::{
+  io,
+  path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+#[cfg(unix)]
+use rlldp::NeighborEvent;
+use rlldp::{AdminStatus, Agent, Scope};
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use tokio::{
+  io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, Lines},
+  net::{
+    unix::{OwnedReadHalf, OwnedWriteHalf},
+    UnixListener, UnixStream,
+  },
+  sync::broadcast::{self, error::RecvError},
+};
+#[cfg(unix)]
+use tracing::{debug, info, warn};
+
+use super::{
+  output::{EventOutput, NeighborOutput, StatsOutput},
+  Capture,
+};
+
+// same place lldpd keeps lldpd.socket
+pub const DEFAULT_SOCKET: &str = "/var/run/rlldp.socket";
+
+// the control protocol is one json request per line, answered with one json response per line.
+// watch is the exception, after it the daemon streams one EventOutput per line until the client hangs up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+  Neighbors {
+    // every member when empty
+    interfaces: Vec<String>,
+  },
+  Stats,
+  SetAdminStatus {
+    interface: String,
+    scope: ScopeArg,
+    admin_status: AdminStatusArg,
+  },
+  Resend {
+    interface: Option<String>,
+  },
+  Watch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "snake_case")]
+pub enum Response {
+  Neighbors { neighbors: Vec<NeighborOutput> },
+  Stats { interfaces: Vec<StatsOutput> },
+  Ok,
+  Error { message: String },
+}
+
+// mirrors Scope
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScopeArg {
+  #[default]
+  NearestBridge,
+  NearestNonTpmrBridge,
+  NearestCustomerBridge,
+}
+
+impl From<ScopeArg> for Scope {
+  fn from(value: ScopeArg) -> Self {
+    match value {
+      ScopeArg::NearestBridge => Scope::NearestBridge,
+      ScopeArg::NearestNonTpmrBridge => Scope::NearestNonTpmrBridge,
+      ScopeArg::NearestCustomerBridge => Scope::NearestCustomerBridge,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AdminStatusArg {
+  TxOnly,
+  RxOnly,
+  TxAndRx,
+  Disabled,
+}
+
+impl From<AdminStatusArg> for AdminStatus {
+  fn from(value: AdminStatusArg) -> Self {
+    match value {
+      AdminStatusArg::TxOnly => AdminStatus::TxOnly,
+      AdminStatusArg::RxOnly => AdminStatus::RxOnly,
+      AdminStatusArg::TxAndRx => AdminStatus::TxAndRx,
+      AdminStatusArg::Disabled => AdminStatus::Disabled,
+    }
+  }
+}
+
+async fn respond(agent: &Agent, request: Request) -> Response {
+  let member = |name: &str| {
+    agent.member(name).ok_or_else(|| Response::Error {
+      message: format!("no such interface '{name}'"),
+    })
+  };
+
+  match request {
+    Request::Neighbors { interfaces } => Response::Neighbors {
+      neighbors: agent
+        .neighbors()
+        .await
+        .iter()
+        .filter(|x| interfaces.is_empty() || interfaces.contains(&x.local_port.name))
+        .map(NeighborOutput::new)
+        .collect(),
+    },
+    Request::Stats => Response::Stats {
+      interfaces: agent.members().iter().map(StatsOutput::new).collect(),
+    },
+    Request::SetAdminStatus {
+      interface,
+      scope,
+      admin_status,
+    } => match member(&interface) {
+      Ok(intf) => {
+        let mut config = intf.agent(scope.into()).unwrap_or_default();
+        config.admin_status = admin_status.into();
+        intf.set_agent(scope.into(), config).await;
+        intf.resend();
+        Response::Ok
+      }
+      Err(err) => err,
+    },
+    Request::Resend { interface: None } => {
+      agent.members().iter().for_each(|x| x.resend());
+      Response::Ok
+    }
+    Request::Resend {
+      interface: Some(interface),
+    } => match member(&interface) {
+      Ok(intf) => {
+        intf.resend();
+        Response::Ok
+      }
+      Err(err) => err,
+    },
+    Request::Watch => unreachable!("watch is handled by the connection"),
+  }
+}
+
+#[cfg(unix)]
+async fn write_line<W: AsyncWrite + Unpin, T: Serialize>(writer: &mut W, value: &T) -> io::Result<()> {
+  let mut line = serde_json::to_vec(value)?;
+  line.push(b'\n');
+  writer.write_all(&line).await
+}
+
+#[cfg(unix)]
+async fn watch(events: &broadcast::Sender<NeighborEvent>, mut writer: OwnedWriteHalf) -> io::Result<()> {
+  let mut events = events.subscribe();
+  loop {
+    match events.recv().await {
+      Ok(event) => write_line(&mut writer, &EventOutput::new(&event)).await?,
+      Err(RecvError::Lagged(count)) => warn!(count, "watcher missed neighbor events"),
+      Err(RecvError::Closed) => return Ok(()),
+    }
+  }
+}
+
+#[cfg(unix)]
+async fn handle(capture: Capture, stream: UnixStream) -> io::Result<()> {
+  let (reader, mut writer) = stream.into_split();
+  let mut lines = BufReader::new(reader).lines();
+  while let Some(line) = lines.next_line().await? {
+    let response = match serde_json::from_str(&line) {
+      Ok(Request::Watch) => return watch(&capture.events, writer).await,
+      Ok(request) => respond(&capture.agent, request).await,
+      Err(err) => Response::Error {
+        message: err.to_string(),
+      },
+    };
+    write_line(&mut writer, &response).await?;
+  }
+  Ok(())
+}
+
+#[cfg(unix)]
+pub async fn serve(path: PathBuf, capture: Capture) -> io::Result<()> {
+  match UnixStream::connect(&path).await {
+    Ok(_) => {
+      return Err(io::Error::new(
+        io::ErrorKind::AddrInUse,
+        format!("a daemon is already listening on {}", path.display()),
+      ))
+    }
+    // left behind by a daemon that didn't shut down cleanly
+    Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => std::fs::remove_file(&path)?,
+    Err(_) => {}
+  }
+
+  let listener = UnixListener::bind(&path)?;
+  info!(path = %path.display(), "listening for control connections");
+  loop {
+    let (stream, _) = listener.accept().await?;
+    let capture = capture.clone();
+    tokio::spawn(async move {
+      if let Err(err) = handle(capture, stream).await {
+        debug!(%err, "control connection failed");
+      }
+    });
+  }
+}
+
+#[cfg(windows)]
+pub async fn serve(_: PathBuf, _: Capture) -> io::Result<()> {
+  Err(io::Error::new(
+    io::ErrorKind::Unsupported,
+    "the control socket is only supported on unix",
+  ))
+}
+
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Client {
+  lines: Lines<BufReader<OwnedReadHalf>>,
+  writer: OwnedWriteHalf,
+}
+
+#[cfg(unix)]
+impl Client {
+  // None when there's no daemon to talk to
+  pub async fn connect(path: &Path) -> Option<Self> {
+    let stream = UnixStream::connect(path).await.ok()?;
+    let (reader, writer) = stream.into_split();
+    Some(Self {
+      lines: BufReader::new(reader).lines(),
+      writer,
+    })
+  }
+
+  async fn read_line<T: for<'de> Deserialize<'de>>(&mut self) -> io::Result<Option<T>> {
+    match self.lines.next_line().await? {
+      Some(line) => Ok(Some(serde_json::from_str(&line)?)),
+      None => Ok(None),
+    }
+  }
+
+  pub async fn request(&mut self, request: &Request) -> io::Result<Response> {
+    write_line(&mut self.writer, request).await?;
+    match self.read_line().await? {
+      Some(Response::Error { message }) => Err(io::Error::other(message)),
+      Some(response) => Ok(response),
+      None => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+  }
+
+  pub async fn watch(&mut self) -> io::Result<()> {
+    write_line(&mut self.writer, &Request::Watch).await
+  }
+
+  pub async fn next_event(&mut self) -> io::Result<Option<EventOutput>> {
+    self.read_line().await
+  }
+}
+
+#[cfg(windows)]
+#[derive(Debug)]
+pub struct Client(Infallible);
+
+#[cfg(windows)]
+impl Client {
+  pub async fn connect(_: &Path) -> Option<Self> {
+    None
+  }
+
+  pub async fn request(&mut self, _: &Request) -> io::Result<Response> {
+    match self.0 {}
+  }
+
+  pub async fn watch(&mut self) -> io::Result<()> {
+    match self.0 {}
+  }
+
+  pub async fn next_event(&mut self) -> io::Result<Option<EventOutput>> {
+    match self.0 {}
+  }
+}
+
+pub async fn require(path: &Path) -> io::Result<Client> {
+  Client::connect(path).await.ok_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("no daemon is listening on {}", path.display()),
+    )
+  })
+}
+
+// the daemon answers with the same variant it was asked for
+pub fn unexpected(response: Response) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, format!("unexpected response {response:?}"))
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn control_round_trip() {
+  let dir = std::env::temp_dir().join(format!("rlldp-control-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  let path = dir.join("rlldp.socket");
+
+  let agent = Agent::new("test");
+  agent.add_member(rlldp::Interface::new(rlldp::LocalPort::new("eth0")));
+  let capture = Capture {
+    agent: agent.clone(),
+    events: tokio::sync::broadcast::channel(16).0,
+  };
+  tokio::spawn(serve(path.clone(), capture));
+  let mut client = loop {
+    if let Some(client) = Client::connect(&path).await {
+      break client;
+    }
+    tokio::task::yield_now().await;
+  };
+
+  let response = client.request(&Request::Stats).await.unwrap();
+  let Response::Stats { interfaces } = response else {
+    panic!("{response:?}");
+  };
+  assert_eq!(interfaces[0].interface, "eth0");
+
+  let request = Request::SetAdminStatus {
+    interface: "eth0".into(),
+    scope: ScopeArg::NearestBridge,
+    admin_status: AdminStatusArg::RxOnly,
+  };
+  client.request(&request).await.unwrap();
+  let config = agent.member("eth0").unwrap().agent(Scope::NearestBridge).unwrap();
+  assert_eq!(config.admin_status, AdminStatus::RxOnly);
+
+  let err = client
+    .request(&Request::Resend {
+      interface: Some("eth1".into()),
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(err.to_string(), "no such interface 'eth1'");
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
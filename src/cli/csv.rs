@@ -0,0 +1,169 @@
+use clap::ValueEnum;
+
+use super::output::{NeighborOutput, StatsOutput};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+  Interface,
+  Protocol,
+  ChassisId,
+  SystemName,
+  PortId,
+  PortDescr,
+  MgmtIp,
+  Vlan,
+  Capabilities,
+  FirstSeen,
+  LastSeen,
+  Vendor,
+}
+
+pub const DEFAULT_COLUMNS: [Column; 11] = [
+  Column::Interface,
+  Column::Protocol,
+  Column::ChassisId,
+  Column::SystemName,
+  Column::PortId,
+  Column::PortDescr,
+  Column::MgmtIp,
+  Column::Vlan,
+  Column::Capabilities,
+  Column::FirstSeen,
+  Column::LastSeen,
+];
+
+impl Column {
+  fn name(self) -> String {
+    self.to_possible_value().unwrap().get_name().replace('-', "_")
+  }
+
+  fn value(self, neighbor: &NeighborOutput) -> String {
+    let optional = |x: &Option<String>| x.clone().unwrap_or_default();
+    match self {
+      Self::Interface => neighbor.interface.clone(),
+      Self::Protocol => neighbor.protocol.clone(),
+      Self::ChassisId => neighbor.chassis.clone(),
+      Self::SystemName => optional(&neighbor.system_name),
+      Self::PortId => optional(&neighbor.port_id),
+      Self::PortDescr => optional(&neighbor.port_description),
+      Self::MgmtIp => optional(&neighbor.management_address),
+      // the advertised vlan, falling back to the tag the frame came in with
+      Self::Vlan => neighbor
+        .port_vlan_id
+        .or(neighbor.vlans.first().copied())
+        .map(|x| x.to_string())
+        .unwrap_or_default(),
+      Self::Capabilities => neighbor.capabilities.join(" "),
+      Self::FirstSeen => utc(neighbor.first_seen),
+      Self::LastSeen => utc(neighbor.last_seen),
+      Self::Vendor => optional(&neighbor.vendor),
+    }
+  }
+}
+
+// rfc 4180, fields are only quoted when they have to be
+fn field(x: &str) -> String {
+  if x.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", x.replace('"', "\"\""))
+  } else {
+    x.to_string()
+  }
+}
+
+fn row(fields: impl IntoIterator<Item = String>) -> String {
+  let fields: Vec<_> = fields.into_iter().map(|x| field(&x)).collect();
+  fields.join(",") + "\n"
+}
+
+pub fn neighbors(columns: &[Column], neighbors: &[NeighborOutput]) -> String {
+  let mut out = row(columns.iter().map(|x| x.name()));
+  for neighbor in neighbors {
+    out += &row(columns.iter().map(|x| x.value(neighbor)));
+  }
+  out
+}
+
+pub fn stats(stats: &[StatsOutput]) -> String {
+  let columns = [
+    "interface",
+    "frames_received",
+    "frames_truncated",
+    "tlvs_discarded",
+    "tlvs_unrecognized",
+    "inserts",
+    "deletes",
+    "drops",
+    "ageouts",
+    "last_change",
+    "kernel_received",
+    "kernel_dropped",
+  ];
+  let mut out = row(columns.map(String::from));
+  for x in stats {
+    out += &row([
+      x.interface.clone(),
+      x.frames_received.to_string(),
+      x.frames_truncated.to_string(),
+      x.tlvs_discarded.to_string(),
+      x.tlvs_unrecognized.to_string(),
+      x.inserts.to_string(),
+      x.deletes.to_string(),
+      x.drops.to_string(),
+      x.ageouts.to_string(),
+      x.last_change.map(|x| utc(x as u64)).unwrap_or_default(),
+      x.kernel_received.to_string(),
+      x.kernel_dropped.to_string(),
+    ]);
+  }
+  out
+}
+
+// "2024-05-01 12:00:00", which spreadsheets recognize as a date. days to y/m/d is from
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn utc(secs: u64) -> String {
+  let days = (secs / 86400) as i64 + 719468;
+  let era = days.div_euclid(146097);
+  let doe = days.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+  format!(
+    "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+    secs / 3600 % 24,
+    secs / 60 % 60,
+    secs % 60
+  )
+}
+
+#[test]
+fn writes_csv() {
+  let neighbor = NeighborOutput {
+    interface: "eth0".into(),
+    protocol: "lldp".into(),
+    chassis: "02:00:00:00:00:01".into(),
+    system_name: Some("switch, core".into()),
+    port_id: Some("Gi1/0/1".into()),
+    vlans: vec![20],
+    capabilities: vec!["bridge".into(), "router".into()],
+    first_seen: 1714564800,
+    last_seen: 951782400,
+    ..Default::default()
+  };
+
+  let columns = [
+    Column::Interface,
+    Column::SystemName,
+    Column::Vlan,
+    Column::Capabilities,
+    Column::FirstSeen,
+    Column::LastSeen,
+  ];
+  assert_eq!(
+    neighbors(&columns, &[neighbor]),
+    "interface,system_name,vlan,capabilities,first_seen,last_seen\n\
+     eth0,\"switch, core\",20,bridge router,2024-05-01 12:00:00,2000-02-29 00:00:00\n"
+  );
+}
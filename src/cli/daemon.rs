@@ -0,0 +1,126 @@
+use std::io;
+#[cfg(any(feature = "grpc", feature = "gnmi", feature = "http"))]
+use std::net::SocketAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::{future::Future, sync::Arc};
+
+use clap::Args;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use rlldp::{Interface, LocalSystem};
+use tracing::warn;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use super::med::MedArgs;
+use super::{control, Capture, CaptureArgs, GlobalArgs};
+
+#[derive(Debug, Args)]
+pub struct DaemonArgs {
+  #[command(flatten)]
+  capture: CaptureArgs,
+
+  /// Also serve the gRPC API on this address, like 127.0.0.1:50051
+  #[cfg(feature = "grpc")]
+  #[arg(long, value_name = "ADDR")]
+  grpc: Option<SocketAddr>,
+
+  /// Also serve the OpenConfig LLDP model over gNMI on this address, like 127.0.0.1:9339
+  #[cfg(feature = "gnmi")]
+  #[arg(long, value_name = "ADDR")]
+  gnmi: Option<SocketAddr>,
+
+  /// Also serve the HTTP API on this address, like 127.0.0.1:8080
+  #[cfg(feature = "http")]
+  #[arg(long, value_name = "ADDR")]
+  http: Option<SocketAddr>,
+
+  /// Also publish the neighbor table as org.rlldp on the system bus
+  #[cfg(feature = "dbus")]
+  #[arg(long)]
+  dbus: bool,
+
+  #[command(flatten)]
+  sinks: super::sink::SinkArgs,
+
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  #[command(flatten)]
+  med: super::med::MedArgs,
+}
+
+// runs for as long as the interface is captured on
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn start_tx(system: &LocalSystem, med: &MedArgs, intf: &Interface) -> impl Future<Output = ()> {
+  intf.advertise_local_system(system);
+  let med = med.config(&intf.local_port().name);
+  if !med.is_empty() {
+    intf.add_tx_provider(Arc::new(med));
+  }
+  let intf = intf.clone();
+  async move {
+    let name = intf.local_port().name.clone();
+    if let Err(err) = intf.start_tx(&name).await {
+      warn!(%err, name, "transmit failed");
+    }
+  }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn start(args: &DaemonArgs) -> io::Result<Capture> {
+  let system = LocalSystem::from_os()?;
+  let med = args.med.clone();
+  // interfaces matched by --interfaces later on start transmitting as they appear
+  args.capture.start(move |intf| start_tx(&system, &med, intf))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn start(args: &DaemonArgs) -> io::Result<Capture> {
+  warn!("transmitting is only supported on linux, running receive only");
+  args.capture.start(|_| async {})
+}
+
+pub async fn run(args: &DaemonArgs, global: &GlobalArgs) -> io::Result<()> {
+  let capture = start(args)?;
+
+  super::sink::start(&args.sinks, &capture).await?;
+
+  #[cfg(feature = "grpc")]
+  if let Some(addr) = args.grpc {
+    let capture = capture.clone();
+    tokio::spawn(async move {
+      if let Err(err) = super::grpc::serve(addr, capture).await {
+        warn!(%err, "grpc server failed");
+      }
+    });
+  }
+
+  #[cfg(feature = "gnmi")]
+  if let Some(addr) = args.gnmi {
+    let capture = capture.clone();
+    tokio::spawn(async move {
+      if let Err(err) = super::gnmi::serve(addr, capture).await {
+        warn!(%err, "gnmi server failed");
+      }
+    });
+  }
+
+  #[cfg(feature = "http")]
+  if let Some(addr) = args.http {
+    let capture = capture.clone();
+    tokio::spawn(async move {
+      if let Err(err) = super::http::serve(addr, capture).await {
+        warn!(%err, "http server failed");
+      }
+    });
+  }
+
+  #[cfg(feature = "dbus")]
+  if args.dbus {
+    let capture = capture.clone();
+    tokio::spawn(async move {
+      if let Err(err) = super::dbus::serve(capture).await {
+        warn!(%err, "d-bus service failed");
+      }
+    });
+  }
+
+  control::serve(global.socket.clone(), capture).await
+}
@@ -0,0 +1,149 @@
+use rlldp::Agent;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+use zbus::{
+  interface,
+  zvariant::{OwnedObjectPath, OwnedValue, Type, Value},
+  Connection,
+};
+
+use super::{output::NeighborOutput, Capture};
+
+pub const BUS_NAME: &str = "org.rlldp";
+pub const MANAGER_PATH: &str = "/org/rlldp/Manager";
+
+// d-bus has no optional values, absent strings are empty and the ttls are seconds like the json output
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Value, OwnedValue)]
+pub struct Neighbor {
+  pub protocol: String,
+  pub source: String,
+  pub chassis: String,
+  pub system_name: String,
+  pub port_id: String,
+  pub management_address: String,
+  pub ttl: u16,
+  pub ttl_remaining: u64,
+}
+
+impl From<NeighborOutput> for Neighbor {
+  fn from(x: NeighborOutput) -> Self {
+    Self {
+      protocol: x.protocol,
+      source: x.source,
+      chassis: x.chassis,
+      system_name: x.system_name.unwrap_or_default(),
+      port_id: x.port_id.unwrap_or_default(),
+      management_address: x.management_address.unwrap_or_default(),
+      ttl: x.ttl,
+      ttl_remaining: x.ttl_remaining,
+    }
+  }
+}
+
+// object path elements only allow [A-Za-z0-9_], everything else is escaped as _xx like systemd does
+pub fn interface_path(name: &str) -> OwnedObjectPath {
+  let mut path = String::from("/org/rlldp/Interface/");
+  for x in name.bytes() {
+    match x {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => path.push(x as char),
+      x => path.push_str(&format!("_{x:02x}")),
+    }
+  }
+  OwnedObjectPath::try_from(path).unwrap()
+}
+
+#[derive(Debug)]
+struct Manager(Agent);
+
+#[interface(name = "org.rlldp.Manager")]
+impl Manager {
+  #[zbus(property)]
+  fn interfaces(&self) -> Vec<OwnedObjectPath> {
+    self
+      .0
+      .members()
+      .iter()
+      .map(|x| interface_path(&x.local_port().name))
+      .collect()
+  }
+}
+
+#[derive(Debug)]
+struct Interface {
+  name: String,
+  agent: Agent,
+}
+
+#[interface(name = "org.rlldp.Interface")]
+impl Interface {
+  #[zbus(property)]
+  fn name(&self) -> String {
+    self.name.clone()
+  }
+
+  #[zbus(property)]
+  async fn neighbors(&self) -> Vec<Neighbor> {
+    self
+      .agent
+      .neighbors()
+      .await
+      .iter()
+      .filter(|x| x.local_port.name == self.name)
+      .map(|x| NeighborOutput::new(x).into())
+      .collect()
+  }
+}
+
+// false when the object was already there
+async fn add_interface(conn: &Connection, agent: &Agent, name: &str) -> zbus::Result<bool> {
+  let intf = Interface {
+    name: name.into(),
+    agent: agent.clone(),
+  };
+  conn.object_server().at(interface_path(name), intf).await
+}
+
+async fn neighbors_changed(conn: &Connection, capture: &Capture, name: &str) -> zbus::Result<()> {
+  let server = conn.object_server();
+  if add_interface(conn, &capture.agent, name).await? {
+    let manager = server.interface::<_, Manager>(MANAGER_PATH).await?;
+    manager.get().await.interfaces_changed(manager.signal_context()).await?;
+  }
+
+  let intf = server.interface::<_, Interface>(interface_path(name)).await?;
+  intf.get().await.neighbors_changed(intf.signal_context()).await?;
+  Ok(())
+}
+
+// serves the neighbor table on the system bus, emitting PropertiesChanged for every neighbor event
+pub async fn serve(capture: Capture) -> zbus::Result<()> {
+  let mut events = capture.events.subscribe();
+  let conn = zbus::connection::Builder::system()?
+    .name(BUS_NAME)?
+    .serve_at(MANAGER_PATH, Manager(capture.agent.clone()))?
+    .build()
+    .await?;
+  for intf in capture.agent.members() {
+    add_interface(&conn, &capture.agent, &intf.local_port().name).await?;
+  }
+  info!(name = BUS_NAME, "serving on the system bus");
+
+  loop {
+    match events.recv().await {
+      Ok(event) => neighbors_changed(&conn, &capture, &event.neighbor.local_port.name).await?,
+      Err(RecvError::Lagged(count)) => warn!(count, "d-bus missed neighbor events"),
+      Err(RecvError::Closed) => return Ok(()),
+    }
+  }
+}
+
+#[test]
+fn escapes_object_paths() {
+  assert_eq!(interface_path("eth0").as_str(), "/org/rlldp/Interface/eth0");
+  assert_eq!(
+    interface_path("br-lan.10").as_str(),
+    "/org/rlldp/Interface/br_2dlan_2e10"
+  );
+  assert_eq!(interface_path("a_b").as_str(), "/org/rlldp/Interface/a_5fb");
+}
@@ -0,0 +1,306 @@
+use std::{fmt::Debug, fs, io, path::Path};
+
+use clap::{Args, ValueEnum};
+use lldp_parser::{
+  cdp,
+  frame::Frame,
+  lldp,
+  render::{render_du, render_frame},
+  DataUnit, Protocol,
+};
+use rlldp::pcap_file::PcapReader;
+
+use super::{
+  output::{print_structured, reformat_mac, FrameOutput, TlvOutput},
+  protocol_name, Format, GlobalArgs,
+};
+
+// pcap link type for ethernet
+const LINKTYPE_ETHERNET: u32 = 1;
+// version, ttl and checksum come before the first cdp/fdp tlv
+const CDP_HEADER_LEN: usize = 4;
+
+#[derive(Debug, Args)]
+pub struct DecodeArgs {
+  /// Hex string, raw frame file, or pcap/pcapng file
+  input: String,
+
+  /// Decode the input as a bare data unit instead of an ethernet frame
+  #[arg(long, value_enum)]
+  protocol: Option<ProtocolArg>,
+
+  /// Print a wireshark-style tree with the raw bytes of every tlv, text output only
+  #[arg(short, long)]
+  verbose: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ProtocolArg {
+  Lldp,
+  Cdp,
+  Fdp,
+  Mndp,
+  Sonmp,
+}
+
+impl From<ProtocolArg> for Protocol {
+  fn from(value: ProtocolArg) -> Self {
+    match value {
+      ProtocolArg::Lldp => Protocol::Lldp,
+      ProtocolArg::Cdp => Protocol::Cdp,
+      ProtocolArg::Fdp => Protocol::Fdp,
+      ProtocolArg::Mndp => Protocol::Mndp,
+      ProtocolArg::Sonmp => Protocol::Sonmp,
+    }
+  }
+}
+
+pub fn run(args: &DecodeArgs, global: &GlobalArgs) -> io::Result<()> {
+  let format = global.format;
+  for (i, buf) in read_input(&args.input)?.iter().enumerate() {
+    if args.verbose && matches!(format, Format::Text) {
+      println!("Frame {}", i + 1);
+      match args.protocol {
+        Some(protocol) => println!("{}", render_du(protocol.into(), buf)),
+        None => println!("{}", render_frame(buf)),
+      }
+      continue;
+    }
+
+    let mut output = FrameOutput {
+      frame: i + 1,
+      ..match args.protocol {
+        Some(protocol) => decode_du(protocol.into(), buf, 0),
+        None => decode_frame(buf),
+      }
+    };
+    if let Some(source) = &mut output.source {
+      reformat_mac(source, global.mac_format.into());
+    }
+    print_frame(format, &output);
+  }
+  Ok(())
+}
+
+fn read_input(input: &str) -> io::Result<Vec<Vec<u8>>> {
+  if !Path::new(input).exists() {
+    return parse_hex(input).map(|x| vec![x]);
+  }
+
+  let data = fs::read(input)?;
+  let Ok(mut reader) = PcapReader::new(&data[..]) else {
+    return Ok(vec![data]);
+  };
+
+  let mut out = Vec::new();
+  while let Some(packet) = reader.next_packet()? {
+    // keep numbering in line with wireshark even for frames we can't look at
+    out.push(if packet.link_type == LINKTYPE_ETHERNET {
+      packet.data
+    } else {
+      Vec::new()
+    });
+  }
+  Ok(out)
+}
+
+// accepts plain hex as well as the usual "0x", ":", "-" and whitespace separated dumps
+fn parse_hex(input: &str) -> io::Result<Vec<u8>> {
+  let digits: String = input
+    .trim()
+    .trim_start_matches("0x")
+    .chars()
+    .filter(|x| !x.is_whitespace() && *x != ':' && *x != '-')
+    .collect();
+
+  let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "input is neither a file nor a hex string");
+  if !digits.len().is_multiple_of(2) {
+    return Err(invalid());
+  }
+
+  (0..digits.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid()))
+    .collect()
+}
+
+fn decode_frame(buf: &[u8]) -> FrameOutput {
+  let Some(frame) = Frame::parse(buf) else {
+    return FrameOutput {
+      error: Some("not an lldp, cdp, fdp or sonmp frame".into()),
+      ..Default::default()
+    };
+  };
+
+  let offset = frame.payload.as_ptr() as usize - buf.as_ptr() as usize;
+  FrameOutput {
+    source: Some(lldp::tlv::ChassisId::MacAddress(frame.source).to_string()),
+    vlans: frame.vlans().map(|x| x.vid()).collect(),
+    ..decode_du(frame.protocol, frame.payload, offset)
+  }
+}
+
+fn decode_du(protocol: Protocol, buf: &[u8], offset: usize) -> FrameOutput {
+  let tlvs = match protocol {
+    Protocol::Lldp => lldp_tlvs(buf, offset),
+    Protocol::Cdp | Protocol::Fdp => cdp_tlvs(buf, offset),
+    Protocol::Mndp | Protocol::Sonmp => Vec::new(),
+  };
+
+  let (decoded, error) = split(DataUnit::decode(protocol, buf).map(|x| format!("{x:#?}")));
+  FrameOutput {
+    protocol: Some(protocol_name(protocol)),
+    tlvs,
+    decoded,
+    error,
+    ..Default::default()
+  }
+}
+
+fn split<E: ToString>(x: Result<String, E>) -> (Option<String>, Option<String>) {
+  match x {
+    Ok(x) => (Some(x), None),
+    Err(err) => (None, Some(err.to_string())),
+  }
+}
+
+fn tlv_output<T: Debug, E: ToString>(offset: usize, ty: u16, length: usize, tlv: Result<T, E>) -> TlvOutput {
+  let (value, error) = split(tlv.map(|x| format!("{x:?}")));
+  TlvOutput {
+    offset,
+    ty: Some(ty),
+    length: Some(length),
+    value,
+    error,
+  }
+}
+
+fn lldp_tlvs(buf: &[u8], offset: usize) -> Vec<TlvOutput> {
+  let mut out = Vec::new();
+  let mut pos = 0;
+  while pos < buf.len() {
+    let raw = match lldp::tlv::RawTlv::decode(&buf[pos..]) {
+      Ok(raw) => raw,
+      Err(err) => {
+        out.push(TlvOutput {
+          offset: offset + pos,
+          ty: Some((buf[pos] >> 1).into()),
+          length: None,
+          value: None,
+          error: Some(err.to_string()),
+        });
+        break;
+      }
+    };
+
+    let len = raw.total_len();
+    out.push(tlv_output(
+      offset + pos,
+      raw.ty.into(),
+      raw.payload.len(),
+      lldp::tlv::Tlv::decode(raw),
+    ));
+    pos += len;
+  }
+  out
+}
+
+fn cdp_tlvs(buf: &[u8], offset: usize) -> Vec<TlvOutput> {
+  let mut out = Vec::new();
+  let mut pos = CDP_HEADER_LEN;
+  while pos < buf.len() {
+    let raw = match cdp::tlv::RawTlv::decode(&buf[pos..]) {
+      Ok(raw) => raw,
+      Err(err) => {
+        out.push(TlvOutput {
+          offset: offset + pos,
+          ty: None,
+          length: None,
+          value: None,
+          error: Some(err.to_string()),
+        });
+        break;
+      }
+    };
+
+    let len = raw.total_len();
+    out.push(tlv_output(
+      offset + pos,
+      raw.ty,
+      raw.payload.len(),
+      cdp::tlv::Tlv::decode(raw),
+    ));
+    pos += len;
+  }
+  out
+}
+
+fn print_frame(format: Format, output: &FrameOutput) {
+  let Format::Text = format else {
+    return print_structured(format, output);
+  };
+
+  print!("frame {}", output.frame);
+  if let Some(protocol) = output.protocol {
+    print!(": {protocol}");
+  }
+  if let Some(source) = &output.source {
+    print!(" from {source}");
+  }
+  for vid in &output.vlans {
+    print!(" vlan {vid}");
+  }
+  println!();
+
+  for tlv in &output.tlvs {
+    let ty = tlv.ty.map(|x| format!("type {x}")).unwrap_or_default();
+    let len = tlv.length.map(|x| format!("len {x}")).unwrap_or_default();
+    match (&tlv.value, &tlv.error) {
+      (_, Some(err)) => println!("  {:#06x}  {ty:9} {len:8} error: {err}", tlv.offset),
+      (value, None) => println!(
+        "  {:#06x}  {ty:9} {len:8} {}",
+        tlv.offset,
+        value.as_deref().unwrap_or_default()
+      ),
+    }
+  }
+
+  if let Some(decoded) = &output.decoded {
+    println!("{decoded}");
+  }
+  if let Some(err) = &output.error {
+    println!("error: {err}");
+  }
+  println!();
+}
+
+#[test]
+fn parses_hex_dumps() {
+  assert_eq!(parse_hex("0x01:80:c2 00-00-0e").unwrap(), [1, 0x80, 0xc2, 0, 0, 0x0e]);
+  assert!(parse_hex("abc").is_err());
+  assert!(parse_hex("zz").is_err());
+}
+
+#[test]
+fn reports_unknown_tlvs() {
+  // chassis id, port id, ttl, an unknown tlv 9, then a truncated tlv
+  let buf = parse_hex("0207 04 020000000001 0402 07 70 0602 0078 1201 ff 0a05 00").unwrap();
+  let output = decode_du(Protocol::Lldp, &buf, 14);
+  let lines: Vec<_> = output
+    .tlvs
+    .iter()
+    .map(|x| (x.offset, x.ty, x.error.is_none()))
+    .collect();
+  assert_eq!(
+    lines,
+    [
+      (14, Some(1), true),
+      (23, Some(2), true),
+      (27, Some(3), true),
+      (31, Some(9), false),
+      (34, Some(5), false),
+    ]
+  );
+  // the truncated tlv fails the whole du, the tlv list still shows how far it got
+  assert!(output.decoded.is_none() && output.error.is_some());
+}
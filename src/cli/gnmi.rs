@@ -0,0 +1,243 @@
+use std::{
+  net::SocketAddr,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use rlldp::{NeighborEvent, NeighborEventKind};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+use tracing::{info, warn};
+
+use super::{
+  openconfig::{self, Leaf, LeafValue, PathElem},
+  Capture,
+};
+
+// generated from the upstream field names
+#[allow(clippy::enum_variant_names)]
+pub mod proto {
+  tonic::include_proto!("gnmi");
+}
+
+use proto::{
+  g_nmi_server::{GNmi, GNmiServer},
+  subscribe_request, subscribe_response,
+  subscription_list::Mode,
+  typed_value, GetRequest, GetResponse, Notification, Path, SubscribeRequest, SubscribeResponse, TypedValue, Update,
+};
+
+fn to_path(path: &[PathElem]) -> Path {
+  Path {
+    elem: path
+      .iter()
+      .map(|x| proto::PathElem {
+        name: x.name.into(),
+        key: x.key.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+      })
+      .collect(),
+    ..Default::default()
+  }
+}
+
+// prefix of the leaf's path, with * matching any name or key value
+fn matches(pattern: &[proto::PathElem], path: &[PathElem]) -> bool {
+  pattern.len() <= path.len()
+    && pattern.iter().zip(path).all(|(p, x)| {
+      (p.name == "*" || p.name == x.name)
+        && p
+          .key
+          .iter()
+          .all(|(k, v)| x.key.as_ref().is_some_and(|(xk, xv)| xk == k && (v == "*" || v == xv)))
+    })
+}
+
+fn patterns(prefix: Option<&Path>, paths: impl IntoIterator<Item = Path>) -> Vec<Vec<proto::PathElem>> {
+  let prefix = prefix.map(|x| x.elem.clone()).unwrap_or_default();
+  let mut out: Vec<_> = paths
+    .into_iter()
+    .map(|x| prefix.iter().cloned().chain(x.elem).collect())
+    .collect();
+  if out.is_empty() {
+    out.push(prefix);
+  }
+  out
+}
+
+fn timestamp() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|x| x.as_nanos() as i64)
+    .unwrap_or_default()
+}
+
+// values are sent as typed scalars whatever encoding was asked for, which json_ietf clients accept as well
+fn notification(leaves: &[Leaf], patterns: &[Vec<proto::PathElem>]) -> Notification {
+  Notification {
+    timestamp: timestamp(),
+    update: leaves
+      .iter()
+      .filter(|x| patterns.iter().any(|p| matches(p, &x.path)))
+      .map(|x| Update {
+        path: Some(to_path(&x.path)),
+        val: Some(TypedValue {
+          value: Some(match &x.value {
+            LeafValue::String(x) => typed_value::Value::StringVal(x.clone()),
+            LeafValue::Uint(x) => typed_value::Value::UintVal(*x),
+          }),
+        }),
+      })
+      .collect(),
+    ..Default::default()
+  }
+}
+
+fn event_notification(event: &NeighborEvent, patterns: &[Vec<proto::PathElem>]) -> Option<Notification> {
+  let notification = match event.kind {
+    NeighborEventKind::Expired => {
+      let path = openconfig::neighbor_path(&event.neighbor);
+      // a pattern reaching below the neighbor still has to see it go away
+      let wanted = patterns.iter().any(|p| matches(&p[..p.len().min(path.len())], &path));
+      Notification {
+        timestamp: timestamp(),
+        delete: wanted.then(|| to_path(&path)).into_iter().collect(),
+        ..Default::default()
+      }
+    }
+    _ => notification(&openconfig::neighbor_leaves(&event.neighbor), patterns),
+  };
+  (!notification.update.is_empty() || !notification.delete.is_empty()).then_some(notification)
+}
+
+fn update(notification: Notification) -> SubscribeResponse {
+  SubscribeResponse {
+    response: Some(subscribe_response::Response::Update(notification)),
+  }
+}
+
+fn sync() -> SubscribeResponse {
+  SubscribeResponse {
+    response: Some(subscribe_response::Response::SyncResponse(true)),
+  }
+}
+
+#[derive(Debug)]
+struct Service(Capture);
+
+#[tonic::async_trait]
+impl GNmi for Service {
+  async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+    let request = request.into_inner();
+    let patterns = patterns(request.prefix.as_ref(), request.path);
+    let leaves = openconfig::leaves(&self.0.agent).await;
+    Ok(Response::new(GetResponse {
+      notification: vec![notification(&leaves, &patterns)],
+    }))
+  }
+
+  type SubscribeStream = ReceiverStream<Result<SubscribeResponse, Status>>;
+
+  // every subscription is served on change, sample intervals are ignored
+  async fn subscribe(
+    &self,
+    request: Request<Streaming<SubscribeRequest>>,
+  ) -> Result<Response<Self::SubscribeStream>, Status> {
+    let mut requests = request.into_inner();
+    let Some(subscribe_request::Request::Subscribe(list)) = requests.message().await?.and_then(|x| x.request) else {
+      return Err(Status::invalid_argument(
+        "the first request must be a subscription list",
+      ));
+    };
+
+    let capture = self.0.clone();
+    let mode = list.mode();
+    let patterns = patterns(
+      list.prefix.as_ref(),
+      list.subscription.into_iter().filter_map(|x| x.path),
+    );
+    let (tx, rx) = mpsc::channel(64);
+    // subscribing before the initial snapshot so nothing falls in between
+    let mut events = capture.events.subscribe();
+
+    tokio::spawn(async move {
+      if !list.updates_only {
+        let leaves = openconfig::leaves(&capture.agent).await;
+        tx.send(Ok(update(notification(&leaves, &patterns)))).await?;
+      }
+      tx.send(Ok(sync())).await?;
+
+      match mode {
+        Mode::Once => {}
+        Mode::Poll => {
+          while let Some(request) = requests.next().await {
+            if let Ok(SubscribeRequest {
+              request: Some(subscribe_request::Request::Poll(_)),
+            }) = request
+            {
+              let leaves = openconfig::leaves(&capture.agent).await;
+              tx.send(Ok(update(notification(&leaves, &patterns)))).await?;
+              tx.send(Ok(sync())).await?;
+            }
+          }
+        }
+        Mode::Stream => loop {
+          match events.recv().await {
+            Ok(event) => {
+              if let Some(notification) = event_notification(&event, &patterns) {
+                tx.send(Ok(update(notification))).await?;
+              }
+            }
+            Err(RecvError::Lagged(count)) => warn!(count, "gnmi subscriber missed neighbor events"),
+            Err(RecvError::Closed) => break,
+          }
+        },
+      }
+      Ok::<_, mpsc::error::SendError<_>>(())
+    });
+
+    Ok(Response::new(ReceiverStream::new(rx)))
+  }
+}
+
+pub async fn serve(addr: SocketAddr, capture: Capture) -> Result<(), tonic::transport::Error> {
+  info!(%addr, "listening for gnmi connections");
+  Server::builder()
+    .add_service(GNmiServer::new(Service(capture)))
+    .serve(addr)
+    .await
+}
+
+#[test]
+fn matches_wildcards() {
+  let leaves = openconfig::stats_leaves(&super::output::StatsOutput {
+    interface: "eth0".into(),
+    frames_received: 3,
+    ..Default::default()
+  });
+  let path = |elems: &[(&str, Option<(&str, &str)>)]| -> Vec<proto::PathElem> {
+    elems
+      .iter()
+      .map(|(name, key)| proto::PathElem {
+        name: name.to_string(),
+        key: key.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+      })
+      .collect()
+  };
+
+  let all = path(&[("lldp", None)]);
+  let eth0 = path(&[
+    ("lldp", None),
+    ("interfaces", None),
+    ("interface", Some(("name", "eth0"))),
+  ]);
+  let any = path(&[("lldp", None), ("interfaces", None), ("interface", Some(("name", "*")))]);
+  let eth1 = path(&[
+    ("lldp", None),
+    ("interfaces", None),
+    ("interface", Some(("name", "eth1"))),
+  ]);
+  assert_eq!(notification(&leaves, &[all]).update.len(), 4);
+  assert_eq!(notification(&leaves, &[eth0]).update.len(), 4);
+  assert_eq!(notification(&leaves, &[any]).update.len(), 4);
+  assert!(notification(&leaves, &[eth1]).update.is_empty());
+}
@@ -0,0 +1,156 @@
+use std::{net::SocketAddr, pin::Pin};
+
+use tokio_stream::{
+  wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+  Stream, StreamExt,
+};
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{info, warn};
+
+use super::{
+  output::{EventOutput, NeighborOutput, StatsOutput},
+  Capture,
+};
+
+pub mod proto {
+  tonic::include_proto!("rlldp");
+}
+
+use proto::neighbors_server::{Neighbors, NeighborsServer};
+
+impl From<NeighborOutput> for proto::Neighbor {
+  fn from(x: NeighborOutput) -> Self {
+    Self {
+      interface: x.interface,
+      protocol: x.protocol,
+      source: x.source,
+      chassis: x.chassis,
+      system_name: x.system_name,
+      port_id: x.port_id,
+      management_address: x.management_address,
+      vlans: x.vlans.into_iter().map(Into::into).collect(),
+      ttl: x.ttl.into(),
+      ttl_remaining: x.ttl_remaining,
+      age: x.age,
+    }
+  }
+}
+
+impl From<EventOutput> for proto::Event {
+  fn from(x: EventOutput) -> Self {
+    Self {
+      event: x.event,
+      time: x.time,
+      neighbor: Some(x.neighbor.into()),
+    }
+  }
+}
+
+impl From<StatsOutput> for proto::InterfaceStats {
+  fn from(x: StatsOutput) -> Self {
+    Self {
+      interface: x.interface,
+      frames_received: x.frames_received,
+      frames_truncated: x.frames_truncated,
+      tlvs_discarded: x.tlvs_discarded,
+      tlvs_unrecognized: x.tlvs_unrecognized,
+      inserts: x.inserts,
+      deletes: x.deletes,
+      drops: x.drops,
+      ageouts: x.ageouts,
+      last_change: x.last_change,
+      kernel_received: x.kernel_received,
+      kernel_dropped: x.kernel_dropped,
+    }
+  }
+}
+
+#[derive(Debug)]
+struct Service(Capture);
+
+#[tonic::async_trait]
+impl Neighbors for Service {
+  async fn list_neighbors(
+    &self,
+    request: Request<proto::ListNeighborsRequest>,
+  ) -> Result<Response<proto::ListNeighborsResponse>, Status> {
+    let interfaces = request.into_inner().interfaces;
+    let neighbors = self
+      .0
+      .agent
+      .neighbors()
+      .await
+      .iter()
+      .filter(|x| interfaces.is_empty() || interfaces.contains(&x.local_port.name))
+      .map(|x| NeighborOutput::new(x).into())
+      .collect();
+    Ok(Response::new(proto::ListNeighborsResponse { neighbors }))
+  }
+
+  async fn get_interface_stats(
+    &self,
+    _: Request<proto::GetInterfaceStatsRequest>,
+  ) -> Result<Response<proto::GetInterfaceStatsResponse>, Status> {
+    let interfaces = self
+      .0
+      .agent
+      .members()
+      .iter()
+      .map(|x| StatsOutput::new(x).into())
+      .collect();
+    Ok(Response::new(proto::GetInterfaceStatsResponse { interfaces }))
+  }
+
+  type WatchEventsStream = Pin<Box<dyn Stream<Item = Result<proto::Event, Status>> + Send>>;
+
+  async fn watch_events(
+    &self,
+    request: Request<proto::WatchEventsRequest>,
+  ) -> Result<Response<Self::WatchEventsStream>, Status> {
+    let interfaces = request.into_inner().interfaces;
+    let events = BroadcastStream::new(self.0.events.subscribe()).filter_map(move |event| match event {
+      Ok(event) if interfaces.is_empty() || interfaces.contains(&event.neighbor.local_port.name) => {
+        Some(Ok(EventOutput::new(&event).into()))
+      }
+      Ok(_) => None,
+      Err(BroadcastStreamRecvError::Lagged(count)) => {
+        warn!(count, "grpc watcher missed neighbor events");
+        None
+      }
+    });
+    Ok(Response::new(Box::pin(events)))
+  }
+}
+
+pub async fn serve(addr: SocketAddr, capture: Capture) -> Result<(), tonic::transport::Error> {
+  info!(%addr, "listening for grpc connections");
+  Server::builder()
+    .add_service(NeighborsServer::new(Service(capture)))
+    .serve(addr)
+    .await
+}
+
+#[tokio::test]
+async fn lists_interface_stats() {
+  let agent = rlldp::Agent::new("test");
+  agent.add_member(rlldp::Interface::new(rlldp::LocalPort::new("eth0")));
+  let service = Service(Capture {
+    agent,
+    events: tokio::sync::broadcast::channel(16).0,
+  });
+
+  let response = service
+    .get_interface_stats(Request::new(proto::GetInterfaceStatsRequest {}))
+    .await
+    .unwrap()
+    .into_inner();
+  assert_eq!(response.interfaces[0].interface, "eth0");
+  assert_eq!(response.interfaces[0].frames_received, 0);
+
+  let response = service
+    .list_neighbors(Request::new(proto::ListNeighborsRequest::default()))
+    .await
+    .unwrap()
+    .into_inner();
+  assert!(response.neighbors.is_empty());
+}
@@ -0,0 +1,109 @@
+use std::{convert::Infallible, io, net::SocketAddr};
+
+use axum::{
+  extract::{Path, State},
+  response::sse::{Event, KeepAlive, Sse},
+  routing::get,
+  Json, Router,
+};
+use tokio::net::TcpListener;
+use tokio_stream::{
+  wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+  Stream, StreamExt,
+};
+use tracing::{info, warn};
+
+use super::{
+  output::{EventOutput, NeighborOutput, StatsOutput},
+  Capture,
+};
+
+// the bodies are the same structs the cli prints with --format json
+pub fn router(capture: Capture) -> Router {
+  Router::new()
+    .route("/neighbors", get(neighbors))
+    .route("/neighbors/:interface", get(interface_neighbors))
+    .route("/stats", get(stats))
+    .route("/events", get(events))
+    .with_state(capture)
+}
+
+async fn neighbors(State(capture): State<Capture>) -> Json<Vec<NeighborOutput>> {
+  Json(
+    capture
+      .agent
+      .neighbors()
+      .await
+      .iter()
+      .map(NeighborOutput::new)
+      .collect(),
+  )
+}
+
+async fn interface_neighbors(
+  State(capture): State<Capture>,
+  Path(interface): Path<String>,
+) -> Json<Vec<NeighborOutput>> {
+  Json(
+    capture
+      .agent
+      .neighbors()
+      .await
+      .iter()
+      .filter(|x| x.local_port.name == interface)
+      .map(NeighborOutput::new)
+      .collect(),
+  )
+}
+
+async fn stats(State(capture): State<Capture>) -> Json<Vec<StatsOutput>> {
+  Json(capture.agent.members().iter().map(StatsOutput::new).collect())
+}
+
+async fn events(State(capture): State<Capture>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+  let events = BroadcastStream::new(capture.events.subscribe()).filter_map(|event| match event {
+    Ok(event) => Event::default().json_data(EventOutput::new(&event)).ok().map(Ok),
+    Err(BroadcastStreamRecvError::Lagged(count)) => {
+      warn!(count, "http watcher missed neighbor events");
+      None
+    }
+  });
+  Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+pub async fn serve(addr: SocketAddr, capture: Capture) -> io::Result<()> {
+  let listener = TcpListener::bind(addr).await?;
+  info!(%addr, "listening for http connections");
+  axum::serve(listener, router(capture)).await
+}
+
+#[tokio::test]
+async fn serves_stats() {
+  let agent = rlldp::Agent::new("test");
+  agent.add_member(rlldp::Interface::new(rlldp::LocalPort::new("eth0")));
+  let capture = Capture {
+    agent,
+    events: tokio::sync::broadcast::channel(16).0,
+  };
+
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let addr = listener.local_addr().unwrap();
+  tokio::spawn(async move { axum::serve(listener, router(capture)).await });
+
+  // a bare http/1.0 request is enough to check the routes without pulling in a client
+  let get = |path: &'static str| async move {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream
+      .write_all(format!("GET {path} HTTP/1.0\r\n\r\n").as_bytes())
+      .await
+      .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response.split_once("\r\n\r\n").unwrap().1.to_string()
+  };
+
+  let body: Vec<StatsOutput> = serde_json::from_str(&get("/stats").await).unwrap();
+  assert_eq!(body[0].interface, "eth0");
+  assert_eq!(get("/neighbors/eth0").await, "[]");
+}
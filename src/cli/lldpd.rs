@@ -0,0 +1,240 @@
+use serde_json::{json, Map, Value};
+
+use super::output::NeighborOutput;
+
+// `lldpcli show neighbors -f json` from lldpd 1.0. all values are strings, and "interface" is an object
+// for a single neighbor but a list of single-key objects once there are more, which parsers expect.
+pub fn neighbors(neighbors: &[NeighborOutput]) -> Value {
+  let mut interfaces: Vec<_> = neighbors
+    .iter()
+    .map(|x| json!({ x.interface.clone(): interface(x) }))
+    .collect();
+
+  let interfaces = match interfaces.len() {
+    0 => return json!({ "lldp": {} }),
+    1 => interfaces.remove(0),
+    _ => Value::Array(interfaces),
+  };
+  json!({ "lldp": { "interface": interfaces } })
+}
+
+fn interface(neighbor: &NeighborOutput) -> Value {
+  let mut chassis = Map::new();
+  chassis.insert(
+    "id".into(),
+    json!({
+      "type": id_type(neighbor.chassis_subtype.as_deref().unwrap_or("local")),
+      "value": neighbor.chassis,
+    }),
+  );
+  if let Some(x) = &neighbor.system_description {
+    chassis.insert("descr".into(), x.clone().into());
+  }
+  if let Some(x) = &neighbor.management_address {
+    chassis.insert("mgmt-ip".into(), x.clone().into());
+  }
+  let mut capabilities: Vec<_> = capabilities(neighbor)
+    .map(|x| json!({ "type": x, "enabled": true }))
+    .collect();
+  match capabilities.len() {
+    0 => {}
+    1 => _ = chassis.insert("capability".into(), capabilities.remove(0)),
+    _ => _ = chassis.insert("capability".into(), capabilities.into()),
+  }
+  // lldpd nests the chassis under its name when it has one
+  let chassis = match &neighbor.system_name {
+    Some(name) => json!({ name.clone(): chassis }),
+    None => Value::Object(chassis),
+  };
+
+  let mut port = Map::new();
+  if let Some(id) = &neighbor.port_id {
+    port.insert(
+      "id".into(),
+      json!({
+        "type": id_type(neighbor.port_subtype.as_deref().unwrap_or("local")),
+        "value": id,
+      }),
+    );
+  }
+  if let Some(x) = &neighbor.port_description {
+    port.insert("descr".into(), x.clone().into());
+  }
+  port.insert("ttl".into(), neighbor.ttl.to_string().into());
+
+  json!({
+    "via": via(&neighbor.protocol),
+    "rid": neighbor.remote_index.to_string(),
+    "age": age(neighbor.age),
+    "chassis": chassis,
+    "port": port,
+  })
+}
+
+fn via(protocol: &str) -> String {
+  match protocol {
+    "cdp" => "CDPv2".into(),
+    x => x.to_uppercase(),
+  }
+}
+
+// lldpd only names the subtypes it can display, the rest are "unhandled"
+fn id_type(subtype: &str) -> &'static str {
+  match subtype {
+    "mac" => "mac",
+    "network-address" => "ip",
+    "interface-name" => "ifname",
+    "interface-alias" => "ifalias",
+    "local" => "local",
+    _ => "unhandled",
+  }
+}
+
+// only the enabled ones are known, lldpd names a few differently and has no name for the rest
+fn capabilities(neighbor: &NeighborOutput) -> impl Iterator<Item = &'static str> + '_ {
+  neighbor.capabilities.iter().filter_map(|x| match x.as_str() {
+    "other" => Some("Other"),
+    "repeater" => Some("Repeater"),
+    "bridge" | "transparent-bridge" | "switch" => Some("Bridge"),
+    "wlan-access-point" => Some("Wlan"),
+    "router" => Some("Router"),
+    "telephone" => Some("Tel"),
+    "docsis" => Some("Docsis"),
+    "station" | "host" => Some("Station"),
+    _ => None,
+  })
+}
+
+fn age(secs: u64) -> String {
+  let days = secs / 86400;
+  format!(
+    "{days} day{}, {:02}:{:02}:{:02}",
+    if days > 1 { "s" } else { "" },
+    secs / 3600 % 24,
+    secs / 60 % 60,
+    secs % 60
+  )
+}
+
+// `lldpcli show neighbors -f xml`, the same tree as the json with every element labelled
+pub fn neighbors_xml(neighbors: &[NeighborOutput]) -> String {
+  let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<lldp label=\"LLDP neighbors\">\n");
+  for neighbor in neighbors {
+    interface_xml(&mut out, neighbor);
+  }
+  out.push_str("</lldp>\n");
+  out
+}
+
+fn interface_xml(out: &mut String, neighbor: &NeighborOutput) {
+  *out += &format!(
+    " <interface label=\"Interface\" name=\"{}\" via=\"{}\" rid=\"{}\" age=\"{}\">\n",
+    escape(&neighbor.interface),
+    via(&neighbor.protocol),
+    neighbor.remote_index,
+    age(neighbor.age)
+  );
+
+  out.push_str("  <chassis label=\"Chassis\">\n");
+  let chassis_type = id_type(neighbor.chassis_subtype.as_deref().unwrap_or("local"));
+  element(out, "id", "ChassisID", Some(chassis_type), &neighbor.chassis);
+  let optional = [
+    ("name", "SysName", &neighbor.system_name),
+    ("descr", "SysDescr", &neighbor.system_description),
+    ("mgmt-ip", "MgmtIP", &neighbor.management_address),
+  ];
+  for (name, label, value) in optional {
+    if let Some(value) = value {
+      element(out, name, label, None, value);
+    }
+  }
+  for x in capabilities(neighbor) {
+    *out += &format!("   <capability label=\"Capability\" type=\"{x}\" enabled=\"on\"/>\n");
+  }
+  out.push_str("  </chassis>\n");
+
+  out.push_str("  <port label=\"Port\">\n");
+  if let Some(id) = &neighbor.port_id {
+    let port_type = id_type(neighbor.port_subtype.as_deref().unwrap_or("local"));
+    element(out, "id", "PortID", Some(port_type), id);
+  }
+  if let Some(descr) = &neighbor.port_description {
+    element(out, "descr", "PortDescr", None, descr);
+  }
+  element(out, "ttl", "TTL", None, &neighbor.ttl.to_string());
+  out.push_str("  </port>\n");
+  out.push_str(" </interface>\n");
+}
+
+fn element(out: &mut String, name: &str, label: &str, ty: Option<&str>, value: &str) {
+  let ty = ty.map(|x| format!(" type=\"{x}\"")).unwrap_or_default();
+  *out += &format!("   <{name} label=\"{label}\"{ty}>{}</{name}>\n", escape(value));
+}
+
+fn escape(x: &str) -> String {
+  x.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+#[test]
+fn matches_lldpcli() {
+  let neighbor = NeighborOutput {
+    interface: "eth0".into(),
+    protocol: "lldp".into(),
+    source: "02:00:00:00:00:01".into(),
+    chassis: "02:00:00:00:00:01".into(),
+    system_name: Some("switch1".into()),
+    port_id: Some("Gi1/0/1".into()),
+    management_address: Some("10.0.0.1".into()),
+    ttl: 120,
+    ttl_remaining: 100,
+    age: 90061,
+    remote_index: 1,
+    chassis_subtype: Some("mac".into()),
+    port_subtype: Some("interface-name".into()),
+    system_description: Some("IOS".into()),
+    ..Default::default()
+  };
+  assert_eq!(
+    neighbors(std::slice::from_ref(&neighbor)),
+    json!({"lldp": {"interface": {"eth0": {
+      "via": "LLDP",
+      "rid": "1",
+      "age": "1 day, 01:01:01",
+      "chassis": {"switch1": {"id": {"type": "mac", "value": "02:00:00:00:00:01"}, "descr": "IOS", "mgmt-ip": "10.0.0.1"}},
+      "port": {"id": {"type": "ifname", "value": "Gi1/0/1"}, "ttl": "120"},
+    }}}})
+  );
+
+  let both = neighbors(&[neighbor.clone(), neighbor.clone()]);
+  assert_eq!(both["lldp"]["interface"][1]["eth0"]["via"], "LLDP");
+
+  let neighbor = NeighborOutput {
+    system_name: Some("a&b".into()),
+    capabilities: vec!["bridge".into(), "c-vlan".into()],
+    ..neighbor
+  };
+  assert_eq!(
+    neighbors_xml(&[neighbor]),
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<lldp label="LLDP neighbors">
+ <interface label="Interface" name="eth0" via="LLDP" rid="1" age="1 day, 01:01:01">
+  <chassis label="Chassis">
+   <id label="ChassisID" type="mac">02:00:00:00:00:01</id>
+   <name label="SysName">a&amp;b</name>
+   <descr label="SysDescr">IOS</descr>
+   <mgmt-ip label="MgmtIP">10.0.0.1</mgmt-ip>
+   <capability label="Capability" type="Bridge" enabled="on"/>
+  </chassis>
+  <port label="Port">
+   <id label="PortID" type="ifname">Gi1/0/1</id>
+   <ttl label="TTL">120</ttl>
+  </port>
+ </interface>
+</lldp>
+"#
+  );
+}
@@ -0,0 +1,203 @@
+use std::{fmt::Display, str::FromStr};
+
+use clap::Args;
+use rlldp::{CivicLocation, MedApplication, MedConfig, NetworkPolicy};
+
+#[derive(Debug, Clone, Default, Args)]
+pub struct MedArgs {
+  /// LLDP-MED network policy to advertise, like voice,vlan=100,priority=5,dscp=46. Untagged without a vlan, and
+  /// on every interface unless interface=NAME is given
+  #[arg(long, value_name = "POLICY")]
+  med_policy: Vec<PolicyArg>,
+
+  /// LLDP-MED civic address to advertise, like country=US,a1=CA,a3=Berkeley,street=Shattuck. Elements can also be
+  /// given by their catype number, and interface=NAME limits it to one interface
+  #[arg(long, value_name = "LOCATION")]
+  med_location: Vec<LocationArg>,
+}
+
+#[derive(Debug, Clone)]
+struct PolicyArg {
+  interface: Option<String>,
+  policy: NetworkPolicy,
+}
+
+#[derive(Debug, Clone)]
+struct LocationArg {
+  interface: Option<String>,
+  location: CivicLocation,
+}
+
+fn application(s: &str) -> Option<MedApplication> {
+  Some(match s {
+    "voice" => MedApplication::Voice,
+    "voice-signaling" => MedApplication::VoiceSignaling,
+    "guest-voice" => MedApplication::GuestVoice,
+    "guest-voice-signaling" => MedApplication::GuestVoiceSignaling,
+    "softphone-voice" => MedApplication::SoftphoneVoice,
+    "video-conferencing" => MedApplication::VideoConferencing,
+    "streaming-video" => MedApplication::StreamingVideo,
+    "video-signaling" => MedApplication::VideoSignaling,
+    _ => return None,
+  })
+}
+
+// the commonly used rfc 4776 catypes
+fn catype(s: &str) -> Option<u8> {
+  Some(match s {
+    "a1" => 1,
+    "a2" => 2,
+    "a3" => 3,
+    "a4" => 4,
+    "a5" => 5,
+    "a6" => 6,
+    "number" => 19,
+    "name" => 23,
+    "zip" => 24,
+    "building" => 25,
+    "unit" => 26,
+    "floor" => 27,
+    "room" => 28,
+    "street" => 34,
+    _ => return s.parse().ok(),
+  })
+}
+
+fn number<T: FromStr + PartialOrd + Display>(key: &str, value: &str, max: T) -> Result<T, String> {
+  match value.parse() {
+    Ok(x) if x <= max => Ok(x),
+    _ => Err(format!("{key} must be a number up to {max}, not {value:?}")),
+  }
+}
+
+impl FromStr for PolicyArg {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut parts = s.split(',');
+    let name = parts.next().unwrap_or_default();
+    let application = application(name).ok_or_else(|| format!("unknown med application {name:?}"))?;
+    let mut arg = Self {
+      interface: None,
+      policy: NetworkPolicy {
+        application,
+        unknown: false,
+        tagged: false,
+        vlan_id: 0,
+        priority: 0,
+        dscp: 0,
+      },
+    };
+
+    for part in parts {
+      let (key, value) = part
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, not {part:?}"))?;
+      match key {
+        "vlan" => {
+          arg.policy.vlan_id = number(key, value, 4094)?;
+          arg.policy.tagged = true;
+        }
+        "priority" => arg.policy.priority = number(key, value, 7)?,
+        "dscp" => arg.policy.dscp = number(key, value, 63)?,
+        "interface" => arg.interface = Some(value.to_string()),
+        _ => return Err(format!("unknown med policy option {key:?}")),
+      }
+    }
+    Ok(arg)
+  }
+}
+
+impl FromStr for LocationArg {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut interface = None;
+    let mut country = None;
+    let mut elements = Vec::new();
+    for part in s.split(',') {
+      let (key, value) = part
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, not {part:?}"))?;
+      match key {
+        "interface" => interface = Some(value.to_string()),
+        "country" => match value.as_bytes() {
+          [a, b] if value.bytes().all(|x| x.is_ascii_alphabetic()) => {
+            country = Some([a.to_ascii_uppercase(), b.to_ascii_uppercase()])
+          }
+          _ => return Err(format!("country must be a two letter code, not {value:?}")),
+        },
+        _ => {
+          let ty = catype(key).ok_or_else(|| format!("unknown civic address element {key:?}"))?;
+          elements.push((ty, value.to_string()));
+        }
+      }
+    }
+
+    Ok(Self {
+      interface,
+      location: CivicLocation {
+        country: country.ok_or("a civic address needs a country")?,
+        elements,
+      },
+    })
+  }
+}
+
+impl MedArgs {
+  // what's given for the interface itself wins over what's given for every interface
+  pub fn config(&self, interface: &str) -> MedConfig {
+    let matches = |x: &Option<String>| x.as_deref().is_none_or(|x| x == interface);
+    let specific = |x: &Option<String>| x.is_some() as u8;
+
+    let mut policies: Vec<&PolicyArg> = self.med_policy.iter().filter(|x| matches(&x.interface)).collect();
+    policies.sort_by_key(|x| (x.policy.application, std::cmp::Reverse(specific(&x.interface))));
+    policies.dedup_by_key(|x| x.policy.application);
+
+    let location = self
+      .med_location
+      .iter()
+      .filter(|x| matches(&x.interface))
+      .max_by_key(|x| specific(&x.interface))
+      .map(|x| x.location.clone());
+
+    MedConfig {
+      policies: policies.into_iter().map(|x| x.policy).collect(),
+      location,
+    }
+  }
+}
+
+#[test]
+fn per_interface_med_config() {
+  let args = MedArgs {
+    med_policy: vec![
+      "voice,vlan=100,priority=5,dscp=46".parse().unwrap(),
+      "voice,vlan=200,interface=eth1".parse().unwrap(),
+      "video-signaling".parse().unwrap(),
+    ],
+    med_location: vec!["country=us,a1=CA,street=Shattuck,interface=eth1".parse().unwrap()],
+  };
+
+  let config = args.config("eth0");
+  assert_eq!(config.policies.len(), 2);
+  assert_eq!(
+    (
+      config.policies[0].vlan_id,
+      config.policies[0].priority,
+      config.policies[0].dscp
+    ),
+    (100, 5, 46)
+  );
+  assert!(!config.policies[1].tagged);
+  assert_eq!(config.location, None);
+
+  let config = args.config("eth1");
+  assert_eq!(config.policies[0].vlan_id, 200);
+  let location = config.location.unwrap();
+  assert_eq!(location.country, *b"US");
+  assert_eq!(location.elements, vec![(1, "CA".into()), (34, "Shattuck".into())]);
+
+  assert!("voice,vlan=5000".parse::<PolicyArg>().is_err());
+  assert!("a1=CA".parse::<LocationArg>().is_err());
+}
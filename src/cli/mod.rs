@@ -0,0 +1,336 @@
+use std::{future::Future, io, path::PathBuf, str::FromStr};
+
+use clap::{Args, ValueEnum};
+use lldp_parser::Protocol;
+use rlldp::{
+  Agent, CaptureError, FilterSpec, Interface, InterfaceConfig, InterfaceSelector, LocalPort, MacAddress, MacFormat,
+  NeighborEntry, NeighborEvent,
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+pub mod ansible;
+pub mod configure;
+pub mod control;
+pub mod csv;
+pub mod daemon;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+pub mod decode;
+#[cfg(feature = "gnmi")]
+pub mod gnmi;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod lldpd;
+// only the daemon transmits, and only on linux
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod med;
+#[cfg(feature = "netbox")]
+pub mod netbox;
+#[cfg(feature = "gnmi")]
+pub mod openconfig;
+pub mod output;
+pub mod show;
+pub mod sink;
+pub mod syslog;
+pub mod tx;
+pub mod watch;
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum Format {
+  #[default]
+  Text,
+  Json,
+  Yaml,
+  // lldpcli's `-f json` layout for neighbor tables, plain json for everything else
+  LldpdJson,
+  // lldpcli's `-f xml` for neighbor tables, plain json for everything else
+  LldpdXml,
+  // ansible_lldp_neighbors facts for neighbor tables, plain json for everything else
+  AnsibleFacts,
+  // neighbor and stats tables only, plain json for everything else
+  Csv,
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum MacFormatArg {
+  #[default]
+  Colon,
+  Dash,
+  Dotted,
+  Bare,
+}
+
+impl From<MacFormatArg> for MacFormat {
+  fn from(value: MacFormatArg) -> Self {
+    match value {
+      MacFormatArg::Colon => MacFormat::Colon,
+      MacFormatArg::Dash => MacFormat::Dash,
+      MacFormatArg::Dotted => MacFormat::Dotted,
+      MacFormatArg::Bare => MacFormat::Bare,
+    }
+  }
+}
+
+#[derive(Debug, Args)]
+pub struct GlobalArgs {
+  /// Output format, json and yaml print one document per event, table or decoded frame
+  #[arg(long, value_enum, default_value_t, global = true)]
+  pub format: Format,
+
+  /// How MAC addresses are written, in every output format
+  #[arg(long, value_enum, default_value_t, global = true)]
+  pub mac_format: MacFormatArg,
+
+  /// Control socket of a running daemon, used instead of capturing when it's reachable
+  #[arg(long, default_value = control::DEFAULT_SOCKET, global = true)]
+  pub socket: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct CaptureArgs {
+  /// Interfaces to listen on
+  pub interfaces: Vec<String>,
+
+  /// Comma separated interface globs like "eth*,!eth0", re-evaluated as interfaces come and go
+  #[arg(long = "interfaces", value_name = "PATTERNS")]
+  pub selector: Option<InterfaceSelector>,
+
+  /// Listen for LLDP, all protocols are enabled when no protocol is given
+  #[arg(long)]
+  pub lldp: bool,
+
+  /// Listen for CDP
+  #[arg(long)]
+  pub cdp: bool,
+
+  /// Only accept frames from this source MAC, like aa:bb:cc:dd:ee:ff or aabb.ccdd.eeff, or from any MAC in an OUI
+  /// like aa:bb:cc, can be repeated
+  #[arg(long = "source", value_name = "MAC|OUI")]
+  pub sources: Vec<SourceArg>,
+
+  /// Ignore frames from this source MAC or OUI, even if --source allows it, can be repeated
+  #[arg(long = "exclude-source", value_name = "MAC|OUI")]
+  pub excluded_sources: Vec<SourceArg>,
+
+  /// Put the interfaces in promiscuous mode while capturing, for NICs that filter the discovery groups in hardware
+  #[arg(long)]
+  pub promiscuous: bool,
+
+  /// Put the interfaces in all-multicast mode while capturing, a lighter alternative to --promiscuous on Linux
+  #[arg(long)]
+  pub all_multicast: bool,
+
+  /// Capture with AF_XDP, filtering in the driver so other traffic never reaches userspace
+  #[cfg(all(feature = "xdp", target_os = "linux"))]
+  #[arg(long)]
+  pub xdp: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceArg {
+  Mac(MacAddress),
+  Oui([u8; 3]),
+}
+
+impl FromStr for SourceArg {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if let Ok(mac) = s.parse() {
+      return Ok(Self::Mac(mac));
+    }
+
+    // aa:bb:cc, aa-bb-cc or aabbcc
+    let digits: String = s.split([':', '-']).collect();
+    let groups = s.split([':', '-']).count();
+    if digits.len() != 6 || (groups != 1 && groups != 3) || !digits.bytes().all(|x| x.is_ascii_hexdigit()) {
+      return Err(format!("{s:?} is neither a MAC address nor an OUI"));
+    }
+    let byte = |i: usize| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).unwrap();
+    Ok(Self::Oui([byte(0), byte(1), byte(2)]))
+  }
+}
+
+fn split_sources(sources: &[SourceArg]) -> (Vec<MacAddress>, Vec<[u8; 3]>) {
+  let macs = sources
+    .iter()
+    .filter_map(|x| match x {
+      SourceArg::Mac(mac) => Some(mac.clone()),
+      SourceArg::Oui(_) => None,
+    })
+    .collect();
+  let ouis = sources
+    .iter()
+    .filter_map(|x| match x {
+      SourceArg::Mac(_) => None,
+      SourceArg::Oui(oui) => Some(*oui),
+    })
+    .collect();
+  (macs, ouis)
+}
+
+#[derive(Debug, Clone)]
+pub struct Capture {
+  pub agent: Agent,
+  // events of every member, including ones added later
+  pub events: broadcast::Sender<NeighborEvent>,
+}
+
+impl CaptureArgs {
+  fn filter(&self) -> FilterSpec {
+    let (src_allowlist, oui_allowlist) = split_sources(&self.sources);
+    let (src_denylist, oui_denylist) = split_sources(&self.excluded_sources);
+    let filter = FilterSpec {
+      src_allowlist,
+      src_denylist,
+      oui_allowlist,
+      oui_denylist,
+      ..Default::default()
+    };
+    if !self.lldp && !self.cdp {
+      return filter;
+    }
+
+    FilterSpec {
+      lldp: self.lldp,
+      cdp: self.cdp,
+      fdp: false,
+      sonmp: false,
+      ..filter
+    }
+  }
+
+  fn config(&self) -> InterfaceConfig {
+    InterfaceConfig {
+      promiscuous: self.promiscuous,
+      all_multicast: self.all_multicast,
+      ..Default::default()
+    }
+  }
+
+  fn xdp(&self) -> bool {
+    #[cfg(all(feature = "xdp", target_os = "linux"))]
+    return self.xdp;
+    #[cfg(not(all(feature = "xdp", target_os = "linux")))]
+    false
+  }
+
+  // when talking to a daemon there's nothing to start, just a filter on its interfaces
+  pub fn selects(&self, name: &str) -> bool {
+    selects(&self.interfaces, self.selector.as_ref(), name)
+  }
+
+  // on_member runs for every interface as it starts being captured on, what it returns runs alongside the capture
+  // and is stopped with it when the interface goes away
+  pub fn start<F, Fut>(&self, on_member: F) -> io::Result<Capture>
+  where
+    F: Fn(&Interface) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+  {
+    if self.interfaces.is_empty() && self.selector.is_none() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "no interfaces given and no daemon is running",
+      ));
+    }
+    // a typo in a name given outright is an error, rather than an interface that never shows up
+    for name in &self.interfaces {
+      LocalPort::from_os(name)?;
+    }
+
+    let capture = Capture {
+      agent: Agent::new("rlldp"),
+      events: broadcast::channel(1024).0,
+    };
+    let filter = self.filter();
+    let xdp = self.xdp();
+    let (interfaces, selector) = (self.interfaces.clone(), self.selector.clone());
+    let select = move |name: &str| selects(&interfaces, selector.as_ref(), name);
+
+    // follows --interfaces as interfaces come and go, names given outright come back if they go away
+    let events = capture.events.clone();
+    capture.agent.start_matching(select, self.config(), move |intf| {
+      let tx = on_member(&intf);
+      let forward = forward_events(&intf, events.clone());
+      let filter = filter.clone();
+      async move {
+        tokio::select! {
+          result = start_capture(intf, filter, xdp) => result,
+          // neither finishes while the interface is around
+          _ = async { tokio::join!(forward, tx) } => Ok(()),
+        }
+      }
+    });
+
+    Ok(capture)
+  }
+}
+
+fn selects(interfaces: &[String], selector: Option<&InterfaceSelector>, name: &str) -> bool {
+  match selector {
+    None => interfaces.is_empty() || interfaces.iter().any(|x| x == name),
+    Some(selector) => interfaces.iter().any(|x| x == name) || selector.matches(name),
+  }
+}
+
+async fn start_capture(intf: Interface, filter: FilterSpec, xdp: bool) -> Result<(), CaptureError> {
+  let name = intf.local_port().name.clone();
+  #[cfg(all(feature = "xdp", target_os = "linux"))]
+  if xdp {
+    return intf.start_xdp(&name, &filter).await;
+  }
+  #[cfg(not(all(feature = "xdp", target_os = "linux")))]
+  let _ = xdp;
+  intf.start_socket(&name, &filter).await
+}
+
+fn forward_events(intf: &Interface, tx: broadcast::Sender<NeighborEvent>) -> impl Future<Output = ()> {
+  let mut events = intf.subscribe();
+  async move {
+    loop {
+      match events.recv().await {
+        // nobody listening right now is fine
+        Ok(event) => _ = tx.send(event),
+        Err(RecvError::Lagged(count)) => warn!(count, "missed neighbor events"),
+        Err(RecvError::Closed) => return,
+      }
+    }
+  }
+}
+
+pub fn protocol_name(protocol: Protocol) -> &'static str {
+  match protocol {
+    Protocol::Cdp => "cdp",
+    Protocol::Fdp => "fdp",
+    Protocol::Lldp => "lldp",
+    Protocol::Mndp => "mndp",
+    Protocol::Sonmp => "sonmp",
+  }
+}
+
+// lldp has a chassis id, everything else only identifies itself by name
+pub fn chassis(neighbor: &NeighborEntry) -> String {
+  match neighbor.du.get() {
+    lldp_parser::DataUnit::Lldp(x) => x.chassis_id.to_string(),
+    du => du
+      .system_name()
+      .map(|x| x.to_string())
+      .unwrap_or_else(|| neighbor.source.to_string()),
+  }
+}
+
+#[test]
+fn parses_source_args() {
+  assert_eq!(
+    "aabb.ccdd.eeff".parse(),
+    Ok(SourceArg::Mac(MacAddress([0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff])))
+  );
+  assert_eq!("00:1b:21".parse(), Ok(SourceArg::Oui([0, 0x1b, 0x21])));
+  assert_eq!("00-1B-21".parse(), Ok(SourceArg::Oui([0, 0x1b, 0x21])));
+  assert_eq!("001b21".parse(), Ok(SourceArg::Oui([0, 0x1b, 0x21])));
+  assert!("00:1b21".parse::<SourceArg>().is_err());
+  assert!("+0:1b:21".parse::<SourceArg>().is_err());
+}
@@ -0,0 +1,185 @@
+use std::io;
+
+use clap::Args;
+use rlldp::{EventSink, NeighborEvent, NeighborEventKind};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::output::NeighborOutput;
+
+#[derive(Debug, Clone, Args)]
+pub struct NetboxArgs {
+  /// Keep NetBox in sync with discovered neighbors, like https://netbox.example.com
+  #[arg(long = "netbox", value_name = "URL")]
+  pub url: Option<String>,
+
+  /// API token for --netbox
+  #[arg(long = "netbox-token", env = "NETBOX_TOKEN", hide_env_values = true)]
+  pub token: Option<String>,
+
+  /// Name of this host's device in NetBox, the hostname when not given
+  #[arg(long = "netbox-device")]
+  pub device: Option<String>,
+
+  /// Log the changes that would be made to NetBox without making them
+  #[arg(long = "netbox-dry-run")]
+  pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+  results: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NetboxInterface {
+  id: u64,
+  cable: Option<Value>,
+}
+
+// connects the two interfaces with a cable when both ends are known to netbox and the local one isn't cabled
+// yet, everything else ends up as a journal entry on the local interface so nothing is silently dropped
+#[derive(Debug)]
+pub struct NetboxSink {
+  client: reqwest::Client,
+  url: String,
+  token: String,
+  device: String,
+  dry_run: bool,
+}
+
+impl NetboxSink {
+  pub fn new(url: &str, token: &str, device: &str, dry_run: bool) -> Self {
+    Self {
+      client: reqwest::Client::new(),
+      url: url.trim_end_matches('/').into(),
+      token: token.into(),
+      device: device.into(),
+      dry_run,
+    }
+  }
+
+  async fn find_interface(&self, device: &str, name: &str) -> reqwest::Result<Option<NetboxInterface>> {
+    let list: List<NetboxInterface> = self
+      .client
+      .get(format!("{}/api/dcim/interfaces/", self.url))
+      .header("Authorization", format!("Token {}", self.token))
+      .query(&[("device", device), ("name", name)])
+      .send()
+      .await?
+      .error_for_status()?
+      .json()
+      .await?;
+    Ok(list.results.into_iter().next())
+  }
+
+  async fn post(&self, path: &str, body: Value) -> reqwest::Result<()> {
+    if self.dry_run {
+      info!(path, %body, "netbox dry run, not posting");
+      return Ok(());
+    }
+
+    self
+      .client
+      .post(format!("{}{path}", self.url))
+      .header("Authorization", format!("Token {}", self.token))
+      .json(&body)
+      .send()
+      .await?
+      .error_for_status()?;
+    Ok(())
+  }
+
+  async fn sync(&self, event: &NeighborEvent) -> reqwest::Result<()> {
+    let neighbor = NeighborOutput::new(&event.neighbor);
+    let Some(local) = self.find_interface(&self.device, &neighbor.interface).await? else {
+      info!(
+        device = self.device,
+        interface = neighbor.interface,
+        "interface isn't in netbox"
+      );
+      return Ok(());
+    };
+
+    let remote = match (&neighbor.system_name, &neighbor.port_id) {
+      (Some(system), Some(port)) if event.kind == NeighborEventKind::Discovered => {
+        self.find_interface(system, port).await?
+      }
+      _ => None,
+    };
+
+    match remote {
+      Some(remote) if local.cable.is_none() => {
+        let end = |id| json!([{"object_type": "dcim.interface", "object_id": id}]);
+        let body = json!({
+          "a_terminations": end(local.id),
+          "b_terminations": end(remote.id),
+          "status": "connected",
+          "description": format!("discovered by {}", neighbor.protocol),
+        });
+        self.post("/api/dcim/cables/", body).await
+      }
+      _ => {
+        let body = json!({
+          "assigned_object_type": "dcim.interface",
+          "assigned_object_id": local.id,
+          "kind": journal_kind(event.kind),
+          "comments": journal_comment(event.kind, &neighbor),
+        });
+        self.post("/api/extras/journal-entries/", body).await
+      }
+    }
+  }
+}
+
+impl EventSink for NetboxSink {
+  async fn publish(&mut self, event: &NeighborEvent) -> io::Result<()> {
+    // refreshes of a neighbor that's already known would only flood the journal
+    if event.kind == NeighborEventKind::Updated {
+      return Ok(());
+    }
+    self.sync(event).await.map_err(io::Error::other)
+  }
+}
+
+fn journal_kind(kind: NeighborEventKind) -> &'static str {
+  match kind {
+    NeighborEventKind::Expired => "warning",
+    _ => "info",
+  }
+}
+
+fn journal_comment(kind: NeighborEventKind, neighbor: &NeighborOutput) -> String {
+  format!(
+    "{} {} neighbor {} port {}",
+    format!("{kind:?}").to_lowercase(),
+    neighbor.protocol,
+    neighbor.system_name.as_deref().unwrap_or(&neighbor.chassis),
+    neighbor.port_id.as_deref().unwrap_or("-"),
+  )
+}
+
+#[test]
+fn describes_events() {
+  let neighbor = NeighborOutput {
+    interface: "eth0".into(),
+    protocol: "lldp".into(),
+    source: "02:00:00:00:00:01".into(),
+    chassis: "02:00:00:00:00:01".into(),
+    system_name: Some("switch1".into()),
+    port_id: Some("Gi1/0/1".into()),
+    ttl: 120,
+    ttl_remaining: 120,
+    age: 0,
+    remote_index: 1,
+    chassis_subtype: Some("mac".into()),
+    port_subtype: Some("interface-name".into()),
+    ..Default::default()
+  };
+  assert_eq!(
+    journal_comment(NeighborEventKind::Discovered, &neighbor),
+    "discovered lldp neighbor switch1 port Gi1/0/1"
+  );
+  assert_eq!(journal_kind(NeighborEventKind::Expired), "warning");
+}
@@ -0,0 +1,147 @@
+use std::fmt::{self, Display};
+
+use rlldp::{Agent, NeighborEntry};
+
+use super::output::{NeighborOutput, StatsOutput};
+
+// the state leaves of openconfig-lldp that rlldp has data for, one entry per leaf.
+// lldp isn't the only protocol in the table, the other protocols are mapped onto the same leaves.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathElem {
+  pub name: &'static str,
+  pub key: Option<(&'static str, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeafValue {
+  String(String),
+  Uint(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leaf {
+  pub path: Vec<PathElem>,
+  pub value: LeafValue,
+}
+
+// gnmi's string form, /lldp/interfaces/interface[name=eth0]/state/counters/frame-in
+impl Display for Leaf {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for elem in &self.path {
+      write!(f, "/{}", elem.name)?;
+      if let Some((key, value)) = &elem.key {
+        write!(f, "[{key}={value}]")?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn elem(name: &'static str) -> PathElem {
+  PathElem { name, key: None }
+}
+
+fn keyed(name: &'static str, key: &'static str, value: impl Into<String>) -> PathElem {
+  PathElem {
+    name,
+    key: Some((key, value.into())),
+  }
+}
+
+fn interface_path(name: &str) -> Vec<PathElem> {
+  vec![elem("lldp"), elem("interfaces"), keyed("interface", "name", name)]
+}
+
+pub fn stats_leaves(stats: &StatsOutput) -> Vec<Leaf> {
+  let leaf = |name, value| {
+    let mut path = interface_path(&stats.interface);
+    path.extend([elem("state"), elem("counters"), elem(name)]);
+    Leaf {
+      path,
+      value: LeafValue::Uint(value),
+    }
+  };
+  vec![
+    leaf("frame-in", stats.frames_received),
+    leaf("frame-error-in", stats.frames_truncated),
+    leaf("tlv-discard", stats.tlvs_discarded),
+    leaf("tlv-unknown", stats.tlvs_unrecognized),
+    // frames the kernel threw away never got as far as being lldp frames, but they're the ones missing
+    leaf("frame-discard", stats.kernel_dropped),
+  ]
+}
+
+// the container a neighbor's leaves are under, deleted when it expires
+pub fn neighbor_path(neighbor: &NeighborEntry) -> Vec<PathElem> {
+  let mut path = interface_path(&neighbor.local_port.name);
+  path.extend([
+    elem("neighbors"),
+    keyed("neighbor", "id", neighbor.remote_index.to_string()),
+  ]);
+  path
+}
+
+pub fn neighbor_leaves(neighbor: &NeighborEntry) -> Vec<Leaf> {
+  let output = NeighborOutput::new(neighbor);
+  let id = neighbor.remote_index.to_string();
+
+  let mut values = vec![
+    ("id", LeafValue::String(id.clone())),
+    ("chassis-id", LeafValue::String(output.chassis)),
+    ("ttl", LeafValue::Uint(output.ttl.into())),
+    ("age", LeafValue::Uint(output.age)),
+  ];
+  let strings = [
+    ("port-id", output.port_id),
+    ("system-name", output.system_name),
+    ("management-address", output.management_address),
+  ];
+  values.extend(
+    strings
+      .into_iter()
+      .filter_map(|(name, x)| Some((name, LeafValue::String(x?)))),
+  );
+
+  values
+    .into_iter()
+    .map(|(name, value)| {
+      let mut path = neighbor_path(neighbor);
+      path.extend([elem("state"), elem(name)]);
+      Leaf { path, value }
+    })
+    .collect()
+}
+
+pub async fn leaves(agent: &Agent) -> Vec<Leaf> {
+  let mut out: Vec<_> = agent
+    .members()
+    .iter()
+    .flat_map(|x| stats_leaves(&StatsOutput::new(x)))
+    .collect();
+  out.extend(agent.neighbors().await.iter().flat_map(neighbor_leaves));
+  out
+}
+
+#[test]
+fn maps_counters() {
+  let leaves = stats_leaves(&StatsOutput {
+    interface: "eth0".into(),
+    frames_received: 3,
+    frames_truncated: 1,
+    tlvs_discarded: 0,
+    tlvs_unrecognized: 2,
+    ..Default::default()
+  });
+  assert_eq!(
+    leaves[0].to_string(),
+    "/lldp/interfaces/interface[name=eth0]/state/counters/frame-in"
+  );
+  assert_eq!(leaves[0].value, LeafValue::Uint(3));
+  assert_eq!(leaves[1].value, LeafValue::Uint(1));
+  assert_eq!(
+    leaves[3].to_string(),
+    "/lldp/interfaces/interface[name=eth0]/state/counters/tlv-unknown"
+  );
+  assert_eq!(leaves[3].value, LeafValue::Uint(2));
+}
@@ -0,0 +1,304 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use lldp_parser::{
+  lldp::tlv::{ChassisId, PortId},
+  DataUnit,
+};
+use rlldp::{Interface, MacAddress, MacFormat, NeighborEntry, NeighborEvent};
+use serde::{Deserialize, Serialize};
+
+use super::{chassis, protocol_name, Format};
+
+// these structs are the json/yaml schema scripts rely on, fields may be added but are never renamed or removed.
+// durations are whole seconds, times are seconds since the unix epoch and absent values are null.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NeighborOutput {
+  pub interface: String,
+  // "lldp", "cdp", "fdp", "mndp" or "sonmp"
+  pub protocol: String,
+  // source mac of the frame
+  pub source: String,
+  // manufacturer of the source mac, only known when built with the oui feature
+  pub vendor: Option<String>,
+  // lldp chassis id, or the system name for protocols without one
+  pub chassis: String,
+  pub system_name: Option<String>,
+  pub port_id: Option<String>,
+  pub management_address: Option<String>,
+  // vlan ids of the tags the frame arrived with, outermost first
+  pub vlans: Vec<u16>,
+  // advertised ttl and what's left of it
+  pub ttl: u16,
+  pub ttl_remaining: u64,
+  // time since the neighbor was first seen
+  pub age: u64,
+  // lldpRemIndex, stable for as long as the neighbor is known
+  pub remote_index: u32,
+  // "chassis-component", "interface-alias", "port-component", "mac", "network-address", "interface-name" or
+  // "local", null for protocols without a chassis id
+  pub chassis_subtype: Option<String>,
+  // same names as chassis_subtype, with "agent-circuit-id" instead of "chassis-component"
+  pub port_subtype: Option<String>,
+  pub port_description: Option<String>,
+  pub system_description: Option<String>,
+  // advertised port/native vlan, unlike vlans which are the tags on the frame
+  pub port_vlan_id: Option<u16>,
+  // enabled capabilities in lowercase, like "bridge" or "two-port-mac-relay"
+  pub capabilities: Vec<String>,
+  pub first_seen: u64,
+  pub last_seen: u64,
+}
+
+impl NeighborOutput {
+  pub fn new(neighbor: &NeighborEntry) -> Self {
+    let du = neighbor.du.get();
+    let ttl = Duration::from_secs(du.time_to_live().into()).saturating_sub(neighbor.last_detection_time.elapsed());
+    Self {
+      interface: neighbor.local_port.name.clone(),
+      protocol: protocol_name(neighbor.protocol).into(),
+      source: neighbor.source.to_string(),
+      vendor: vendor(&neighbor.source),
+      chassis: chassis(neighbor),
+      system_name: du.system_name().map(|x| x.to_string()),
+      port_id: du.port_id().map(|x| x.to_string()),
+      management_address: du.management_address().map(|x| x.to_string()),
+      vlans: neighbor.vlans.iter().map(|x| x.vid()).collect(),
+      ttl: du.time_to_live(),
+      ttl_remaining: ttl.as_secs(),
+      age: neighbor.first_detection_time.elapsed().as_secs(),
+      remote_index: neighbor.remote_index,
+      chassis_subtype: match du {
+        DataUnit::Lldp(x) => Some(chassis_subtype(&x.chassis_id).into()),
+        _ => None,
+      },
+      port_subtype: du.port_id().map(|x| port_subtype(&x).into()),
+      port_description: match du {
+        DataUnit::Lldp(x) => x.port_description.as_ref().map(|x| x.to_string()),
+        _ => None,
+      },
+      system_description: match du {
+        DataUnit::Lldp(x) => x.system_description.as_ref().map(|x| x.to_string()),
+        DataUnit::Cdp(x) => x.software_version.as_ref().map(|x| x.to_string()),
+        DataUnit::Fdp(x) => x.software_version.as_ref().map(|x| x.to_string()),
+        DataUnit::Mndp(x) => x.version.as_ref().map(|x| x.to_string()),
+        DataUnit::Sonmp(_) => None,
+      },
+      port_vlan_id: du.port_vlan_id(),
+      capabilities: match du {
+        DataUnit::Lldp(x) => x
+          .capabilities
+          .iter()
+          .flat_map(|x| x.enabled_capabilities.names())
+          .map(capability_name)
+          .collect(),
+        DataUnit::Cdp(x) => x
+          .capabilities
+          .iter()
+          .flat_map(|x| x.names())
+          .map(capability_name)
+          .collect(),
+        _ => Vec::new(),
+      },
+      first_seen: epoch_secs(neighbor.first_detection_time),
+      last_seen: epoch_secs(neighbor.last_detection_time),
+    }
+  }
+}
+
+fn capability_name(name: &str) -> String {
+  name.to_lowercase().replace(' ', "-")
+}
+
+#[cfg(feature = "oui")]
+fn vendor(mac: &MacAddress) -> Option<String> {
+  mac.vendor().map(String::from)
+}
+
+#[cfg(not(feature = "oui"))]
+fn vendor(_: &MacAddress) -> Option<String> {
+  None
+}
+
+fn epoch_secs(time: Instant) -> u64 {
+  SystemTime::now()
+    .checked_sub(time.elapsed())
+    .and_then(|x| x.duration_since(SystemTime::UNIX_EPOCH).ok())
+    .map(|x| x.as_secs())
+    .unwrap_or_default()
+}
+
+fn chassis_subtype(id: &ChassisId) -> &'static str {
+  match id {
+    ChassisId::Chassis(_) => "chassis-component",
+    ChassisId::InterfaceAlias(_) => "interface-alias",
+    ChassisId::PortComponent(_) => "port-component",
+    ChassisId::MacAddress(_) => "mac",
+    ChassisId::NetworkAddress(_) => "network-address",
+    ChassisId::InterfaceName(_) => "interface-name",
+    ChassisId::Local(_) => "local",
+  }
+}
+
+fn port_subtype(id: &PortId) -> &'static str {
+  match id {
+    PortId::InterfaceAlias(_) => "interface-alias",
+    PortId::PortComponent(_) => "port-component",
+    PortId::MacAddress(_) => "mac",
+    PortId::NetworkAddress(_) => "network-address",
+    PortId::InterfaceName(_) => "interface-name",
+    PortId::AgentCircuitId(_) => "agent-circuit-id",
+    PortId::Local(_) => "local",
+  }
+}
+
+// rewrites a mac in the default colon form, anything else is left alone
+pub fn reformat_mac(x: &mut String, format: MacFormat) {
+  if let Some(mac) = x.contains(':').then(|| x.parse::<MacAddress>().ok()).flatten() {
+    *x = mac.format(format);
+  }
+}
+
+impl NeighborOutput {
+  // the daemon always sends colons, so this happens wherever the output is printed
+  pub fn with_mac_format(mut self, format: MacFormat) -> Self {
+    reformat_mac(&mut self.source, format);
+    reformat_mac(&mut self.chassis, format);
+    if let Some(port_id) = &mut self.port_id {
+      reformat_mac(port_id, format);
+    }
+    self
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventOutput {
+  // "discovered", "updated" or "expired"
+  pub event: String,
+  pub time: f64,
+  #[serde(flatten)]
+  pub neighbor: NeighborOutput,
+}
+
+impl EventOutput {
+  pub fn new(event: &NeighborEvent) -> Self {
+    Self {
+      event: format!("{:?}", event.kind).to_lowercase(),
+      time: SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|x| x.as_secs_f64())
+        .unwrap_or_default(),
+      neighbor: NeighborOutput::new(&event.neighbor),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsOutput {
+  pub interface: String,
+  pub frames_received: u64,
+  pub frames_truncated: u64,
+  // missing from daemons that predate them
+  #[serde(default)]
+  pub tlvs_discarded: u64,
+  #[serde(default)]
+  pub tlvs_unrecognized: u64,
+  #[serde(default)]
+  pub inserts: u64,
+  #[serde(default)]
+  pub deletes: u64,
+  #[serde(default)]
+  pub drops: u64,
+  #[serde(default)]
+  pub ageouts: u64,
+  // seconds since the epoch, like an event's time
+  #[serde(default)]
+  pub last_change: Option<f64>,
+  // zero where the capture backend doesn't count them
+  #[serde(default)]
+  pub kernel_received: u64,
+  #[serde(default)]
+  pub kernel_dropped: u64,
+}
+
+impl StatsOutput {
+  pub fn new(intf: &Interface) -> Self {
+    let stats = intf.stats();
+    Self {
+      interface: intf.local_port().name.clone(),
+      frames_received: stats.frames_received,
+      frames_truncated: stats.frames_truncated,
+      tlvs_discarded: stats.tlvs_discarded,
+      tlvs_unrecognized: stats.tlvs_unrecognized,
+      inserts: stats.inserts,
+      deletes: stats.deletes,
+      drops: stats.drops,
+      ageouts: stats.ageouts,
+      last_change: stats
+        .last_change
+        .and_then(|x| x.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|x| x.as_secs_f64()),
+      kernel_received: stats.kernel_received,
+      kernel_dropped: stats.kernel_dropped,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FrameOutput {
+  // 1-based, matching wireshark's frame numbers for pcaps
+  pub frame: usize,
+  pub protocol: Option<&'static str>,
+  pub source: Option<String>,
+  pub vlans: Vec<u16>,
+  pub tlvs: Vec<TlvOutput>,
+  // debug rendering of the decoded du, or why it failed to decode
+  pub decoded: Option<String>,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TlvOutput {
+  // from the start of the frame, or of the du with --protocol
+  pub offset: usize,
+  #[serde(rename = "type")]
+  pub ty: Option<u16>,
+  pub length: Option<usize>,
+  pub value: Option<String>,
+  pub error: Option<String>,
+}
+
+// json is one document per line, yaml documents are separated with "---" so both can be streamed
+pub fn print_structured<T: Serialize>(format: Format, value: &T) {
+  match format {
+    Format::Text => unreachable!("text output is rendered by each subcommand"),
+    Format::Json | Format::LldpdJson | Format::LldpdXml | Format::AnsibleFacts | Format::Csv => {
+      println!("{}", serde_json::to_string(value).unwrap())
+    }
+    Format::Yaml => print!("---\n{}", serde_yaml::to_string(value).unwrap()),
+  }
+}
+
+#[test]
+fn schema_field_names() {
+  let value = serde_json::to_value(TlvOutput {
+    offset: 14,
+    ty: Some(1),
+    length: Some(7),
+    value: None,
+    error: Some("buffer too short".into()),
+  })
+  .unwrap();
+  assert_eq!(
+    value,
+    serde_json::json!({"offset": 14, "type": 1, "length": 7, "value": null, "error": "buffer too short"})
+  );
+
+  let yaml = serde_yaml::to_string(&FrameOutput {
+    frame: 1,
+    protocol: Some("lldp"),
+    ..Default::default()
+  })
+  .unwrap();
+  assert!(yaml.starts_with("frame: 1\nprotocol: lldp\n"));
+}
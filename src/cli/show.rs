@@ -0,0 +1,162 @@
+use std::{io, time::Duration};
+
+use clap::{Args, Subcommand};
+
+use super::{
+  control::{self, Client, Request, Response},
+  output::{print_structured, NeighborOutput, StatsOutput},
+  CaptureArgs, Format, GlobalArgs,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum ShowCommand {
+  /// Show the neighbors seen on the given interfaces
+  Neighbors(NeighborsArgs),
+
+  /// Show per-interface counters of a running daemon
+  Stats,
+}
+
+#[derive(Debug, Args)]
+pub struct NeighborsArgs {
+  #[command(flatten)]
+  capture: CaptureArgs,
+
+  /// Seconds to listen before printing the table when there's no daemon
+  #[arg(long, default_value_t = 30)]
+  duration: u64,
+
+  /// Columns of --format csv, comma separated
+  #[arg(long, value_enum, value_delimiter = ',', default_values_t = super::csv::DEFAULT_COLUMNS)]
+  columns: Vec<super::csv::Column>,
+}
+
+pub async fn run(command: &ShowCommand, global: &GlobalArgs) -> io::Result<()> {
+  match command {
+    ShowCommand::Neighbors(args) => neighbors(args, global).await,
+    ShowCommand::Stats => stats(global).await,
+  }
+}
+
+async fn neighbors(args: &NeighborsArgs, global: &GlobalArgs) -> io::Result<()> {
+  let neighbors = match Client::connect(&global.socket).await {
+    Some(mut client) => {
+      // globs are matched here, the daemon only knows exact names
+      let request = Request::Neighbors { interfaces: Vec::new() };
+      match client.request(&request).await? {
+        Response::Neighbors { mut neighbors } => {
+          neighbors.retain(|x| args.capture.selects(&x.interface));
+          neighbors
+        }
+        response => return Err(control::unexpected(response)),
+      }
+    }
+    None => {
+      let agent = args.capture.start(|_| async {})?.agent;
+      tokio::time::sleep(Duration::from_secs(args.duration)).await;
+      agent.neighbors().await.iter().map(NeighborOutput::new).collect()
+    }
+  };
+  let neighbors: Vec<_> = neighbors
+    .into_iter()
+    .map(|x| x.with_mac_format(global.mac_format.into()))
+    .collect();
+
+  match global.format {
+    Format::Text => print_table(&neighbors),
+    Format::LldpdJson => println!("{:#}", super::lldpd::neighbors(&neighbors)),
+    Format::LldpdXml => print!("{}", super::lldpd::neighbors_xml(&neighbors)),
+    Format::AnsibleFacts => println!("{:#}", super::ansible::facts(&neighbors)),
+    Format::Csv => print!("{}", super::csv::neighbors(&args.columns, &neighbors)),
+    format => print_structured(format, &neighbors),
+  }
+  Ok(())
+}
+
+async fn stats(global: &GlobalArgs) -> io::Result<()> {
+  let interfaces = match control::require(&global.socket).await?.request(&Request::Stats).await? {
+    Response::Stats { interfaces } => interfaces,
+    response => return Err(control::unexpected(response)),
+  };
+
+  match global.format {
+    Format::Text => {
+      for StatsOutput {
+        interface,
+        frames_received,
+        frames_truncated,
+        tlvs_discarded,
+        tlvs_unrecognized,
+        inserts,
+        deletes,
+        drops,
+        ageouts,
+        kernel_received,
+        kernel_dropped,
+        ..
+      } in &interfaces
+      {
+        println!(
+          "{interface}: {frames_received} frames received, {frames_truncated} truncated, {tlvs_discarded} tlvs \
+           discarded, {tlvs_unrecognized} unrecognized, {inserts} neighbors inserted, {deletes} deleted, {drops} \
+           dropped, {ageouts} aged out, {kernel_received} passed the kernel filter, {kernel_dropped} dropped by the \
+           kernel"
+        );
+      }
+    }
+    Format::Csv => print!("{}", super::csv::stats(&interfaces)),
+    format => print_structured(format, &interfaces),
+  }
+  Ok(())
+}
+
+const HEADER: [&str; 7] = ["Interface", "Protocol", "System", "Port", "Mgmt IP", "TTL", "Age"];
+
+fn row(output: &NeighborOutput) -> [String; 7] {
+  let output = output.clone();
+  [
+    output.interface,
+    output.protocol,
+    output.system_name.unwrap_or(output.chassis),
+    output.port_id.unwrap_or_else(|| "-".into()),
+    output.management_address.unwrap_or_else(|| "-".into()),
+    format_duration(Duration::from_secs(output.ttl_remaining)),
+    format_duration(Duration::from_secs(output.age)),
+  ]
+}
+
+fn print_table(neighbors: &[NeighborOutput]) {
+  let rows: Vec<_> = neighbors.iter().map(row).collect();
+  let mut widths = HEADER.map(str::len);
+  for row in &rows {
+    for (width, x) in widths.iter_mut().zip(row) {
+      *width = (*width).max(x.chars().count());
+    }
+  }
+
+  let print_row = |row: [&str; 7]| {
+    let line: Vec<_> = row.iter().zip(widths).map(|(x, width)| format!("{x:width$}")).collect();
+    println!("{}", line.join("  ").trim_end());
+  };
+
+  print_row(HEADER);
+  for row in &rows {
+    print_row(row.each_ref().map(String::as_str));
+  }
+}
+
+fn format_duration(duration: Duration) -> String {
+  let secs = duration.as_secs();
+  match secs {
+    0..60 => format!("{secs}s"),
+    60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+    _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+  }
+}
+
+#[test]
+fn formats_durations() {
+  assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+  assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+  assert_eq!(format_duration(Duration::from_secs(7260)), "2h01m");
+}
@@ -0,0 +1,190 @@
+use std::io;
+
+use clap::Args;
+use rlldp::run_sink;
+#[cfg(any(feature = "nats", feature = "kafka"))]
+use rlldp::{EventSink, NeighborEvent};
+use tracing::info;
+
+#[cfg(any(feature = "nats", feature = "kafka"))]
+use super::output::EventOutput;
+use super::{
+  syslog::{LogTarget, SyslogSink},
+  Capture,
+};
+
+// the nats and kafka sinks publish the same json documents `watch --format json` prints
+#[derive(Debug, Args)]
+pub struct SinkArgs {
+  /// Log every neighbor event with structured fields to journald or syslog
+  #[arg(long, value_enum, value_name = "TARGET")]
+  log_events: Option<LogTarget>,
+
+  /// Publish neighbor events to this NATS server, like nats://127.0.0.1:4222
+  #[cfg(feature = "nats")]
+  #[arg(long, value_name = "URL")]
+  nats: Option<String>,
+
+  /// Subject the NATS events are published on
+  #[cfg(feature = "nats")]
+  #[arg(long, default_value = "rlldp.events")]
+  nats_subject: String,
+
+  /// Publish neighbor events to this Kafka broker, like 127.0.0.1:9092
+  #[cfg(feature = "kafka")]
+  #[arg(long, value_name = "BROKER")]
+  kafka: Option<String>,
+
+  /// Topic the Kafka events are produced to, partition 0 so they stay ordered
+  #[cfg(feature = "kafka")]
+  #[arg(long, default_value = "rlldp.events")]
+  kafka_topic: String,
+
+  /// Record every neighbor event into this SQLite database
+  #[cfg(feature = "sqlite")]
+  #[arg(long, value_name = "PATH")]
+  history: Option<std::path::PathBuf>,
+
+  #[cfg(feature = "netbox")]
+  #[command(flatten)]
+  netbox: super::netbox::NetboxArgs,
+}
+
+#[cfg(any(feature = "nats", feature = "kafka"))]
+fn payload(event: &NeighborEvent) -> io::Result<Vec<u8>> {
+  Ok(serde_json::to_vec(&EventOutput::new(event))?)
+}
+
+#[cfg(feature = "nats")]
+#[derive(Debug)]
+pub struct NatsSink {
+  client: async_nats::Client,
+  subject: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+  pub async fn connect(url: &str, subject: &str) -> io::Result<Self> {
+    Ok(Self {
+      client: async_nats::connect(url).await.map_err(io::Error::other)?,
+      subject: subject.into(),
+    })
+  }
+}
+
+#[cfg(feature = "nats")]
+impl EventSink for NatsSink {
+  async fn publish(&mut self, event: &NeighborEvent) -> io::Result<()> {
+    let payload = payload(event)?;
+    self
+      .client
+      .publish(self.subject.clone(), payload.into())
+      .await
+      .map_err(io::Error::other)
+  }
+}
+
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+  client: rskafka::client::partition::PartitionClient,
+}
+
+// PartitionClient isn't Debug
+#[cfg(feature = "kafka")]
+impl std::fmt::Debug for KafkaSink {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("KafkaSink")
+      .field("topic", &self.client.topic())
+      .finish()
+  }
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+  pub async fn connect(broker: &str, topic: &str) -> io::Result<Self> {
+    use rskafka::client::{partition::UnknownTopicHandling, ClientBuilder};
+
+    let client = ClientBuilder::new(vec![broker.into()])
+      .build()
+      .await
+      .map_err(io::Error::other)?;
+    let client = client
+      .partition_client(topic, 0, UnknownTopicHandling::Retry)
+      .await
+      .map_err(io::Error::other)?;
+    Ok(Self { client })
+  }
+}
+
+#[cfg(feature = "kafka")]
+impl EventSink for KafkaSink {
+  async fn publish(&mut self, event: &NeighborEvent) -> io::Result<()> {
+    use rskafka::{client::partition::Compression, record::Record};
+
+    // keyed by interface so compacted topics keep the latest event per port
+    let record = Record {
+      key: Some(event.neighbor.local_port.name.clone().into_bytes()),
+      value: Some(payload(event)?),
+      headers: Default::default(),
+      timestamp: std::time::SystemTime::now().into(),
+    };
+    self
+      .client
+      .produce(vec![record], Compression::NoCompression)
+      .await
+      .map_err(io::Error::other)?;
+    Ok(())
+  }
+}
+
+pub async fn start(args: &SinkArgs, capture: &Capture) -> io::Result<()> {
+  if let Some(target) = args.log_events {
+    let sink = SyslogSink::new(target)?;
+    info!(?target, "logging events");
+    tokio::spawn(run_sink(capture.events.subscribe(), sink));
+  }
+
+  #[cfg(feature = "nats")]
+  if let Some(url) = &args.nats {
+    let sink = NatsSink::connect(url, &args.nats_subject).await?;
+    info!(url, subject = args.nats_subject, "publishing events to nats");
+    tokio::spawn(run_sink(capture.events.subscribe(), sink));
+  }
+
+  #[cfg(feature = "kafka")]
+  if let Some(broker) = &args.kafka {
+    let sink = KafkaSink::connect(broker, &args.kafka_topic).await?;
+    info!(broker, topic = args.kafka_topic, "publishing events to kafka");
+    tokio::spawn(run_sink(capture.events.subscribe(), sink));
+  }
+
+  #[cfg(feature = "sqlite")]
+  if let Some(path) = &args.history {
+    let sink = rlldp::History::open(path).map_err(io::Error::other)?;
+    info!(path = %path.display(), "recording event history");
+    tokio::spawn(run_sink(capture.events.subscribe(), sink));
+  }
+
+  #[cfg(feature = "netbox")]
+  if let Some(url) = &args.netbox.url {
+    let netbox = &args.netbox;
+    let token = netbox
+      .token
+      .as_deref()
+      .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "--netbox needs a token"))?;
+    let device = match &netbox.device {
+      Some(device) => device.clone(),
+      None => rlldp::LocalSystem::from_os()?.hostname.ok_or_else(|| {
+        io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "no hostname, give the device with --netbox-device",
+        )
+      })?,
+    };
+    let sink = super::netbox::NetboxSink::new(url, token, &device, netbox.dry_run);
+    info!(url, device, dry_run = netbox.dry_run, "syncing neighbors to netbox");
+    tokio::spawn(run_sink(capture.events.subscribe(), sink));
+  }
+
+  Ok(())
+}
@@ -0,0 +1,200 @@
+use std::{io, path::PathBuf};
+
+use clap::ValueEnum;
+use rlldp::{EventSink, NeighborEvent, NeighborEventKind};
+#[cfg(unix)]
+use tokio::net::UnixDatagram;
+
+use super::output::NeighborOutput;
+
+#[cfg(unix)]
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+#[cfg(unix)]
+const SYSLOG_SOCKET: &str = "/dev/log";
+// private enterprise number used as the structured data id, per rfc 5424 section 7.2.2
+const SD_ID: &str = "rlldp@32473";
+const FACILITY_DAEMON: u8 = 3;
+
+// one MESSAGE_ID per event kind so `journalctl MESSAGE_ID=` can pick them out
+const MESSAGE_ID_DISCOVERED: &str = "3f7229a084ad421a8930d351a4d741f8";
+const MESSAGE_ID_UPDATED: &str = "ac6548b6a82e490494d49b64116dccb8";
+const MESSAGE_ID_EXPIRED: &str = "4a206c3d75e64e6aa3e0121ec78f8615";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogTarget {
+  Journald,
+  Syslog,
+}
+
+#[derive(Debug)]
+pub struct SyslogSink {
+  target: LogTarget,
+  path: PathBuf,
+  #[cfg(unix)]
+  socket: UnixDatagram,
+}
+
+impl SyslogSink {
+  #[cfg(unix)]
+  pub fn new(target: LogTarget) -> io::Result<Self> {
+    let path = PathBuf::from(match target {
+      LogTarget::Journald => JOURNAL_SOCKET,
+      LogTarget::Syslog => SYSLOG_SOCKET,
+    });
+    Ok(Self {
+      target,
+      path,
+      socket: UnixDatagram::unbound()?,
+    })
+  }
+
+  #[cfg(windows)]
+  pub fn new(_: LogTarget) -> io::Result<Self> {
+    Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "syslog and journald are only available on unix",
+    ))
+  }
+}
+
+impl EventSink for SyslogSink {
+  async fn publish(&mut self, event: &NeighborEvent) -> io::Result<()> {
+    let neighbor = NeighborOutput::new(&event.neighbor);
+    let datagram = match self.target {
+      LogTarget::Journald => journal_entry(event.kind, &neighbor),
+      LogTarget::Syslog => syslog_line(event.kind, &neighbor).into_bytes(),
+    };
+
+    #[cfg(unix)]
+    self.socket.send_to(&datagram, &self.path).await?;
+    #[cfg(windows)]
+    let _ = (datagram, &self.path);
+    Ok(())
+  }
+}
+
+fn message_id(kind: NeighborEventKind) -> &'static str {
+  match kind {
+    NeighborEventKind::Discovered => MESSAGE_ID_DISCOVERED,
+    NeighborEventKind::Updated => MESSAGE_ID_UPDATED,
+    NeighborEventKind::Expired => MESSAGE_ID_EXPIRED,
+  }
+}
+
+// new devices are what gets alerted on, a neighbor going away is worth a look as well
+fn severity(kind: NeighborEventKind) -> u8 {
+  match kind {
+    NeighborEventKind::Discovered => 5,
+    NeighborEventKind::Updated => 6,
+    NeighborEventKind::Expired => 4,
+  }
+}
+
+fn message(kind: NeighborEventKind, neighbor: &NeighborOutput) -> String {
+  format!(
+    "{} {} neighbor {} port {} on {}",
+    format!("{kind:?}").to_lowercase(),
+    neighbor.protocol,
+    neighbor.system_name.as_deref().unwrap_or(&neighbor.chassis),
+    neighbor.port_id.as_deref().unwrap_or("-"),
+    neighbor.interface,
+  )
+}
+
+fn fields(neighbor: &NeighborOutput) -> Vec<(&'static str, &str)> {
+  let mut out = vec![
+    ("INTERFACE", neighbor.interface.as_str()),
+    ("PROTOCOL", &neighbor.protocol),
+    ("CHASSIS", &neighbor.chassis),
+    ("SOURCE", &neighbor.source),
+  ];
+  let optional = [
+    ("PORT", &neighbor.port_id),
+    ("SYSTEM_NAME", &neighbor.system_name),
+    ("MANAGEMENT_ADDRESS", &neighbor.management_address),
+  ];
+  out.extend(optional.into_iter().filter_map(|(k, v)| Some((k, v.as_deref()?))));
+  out
+}
+
+// journald's native protocol, values with newlines are length-prefixed instead of KEY=value
+fn journal_entry(kind: NeighborEventKind, neighbor: &NeighborOutput) -> Vec<u8> {
+  let message = message(kind, neighbor);
+  let priority = severity(kind).to_string();
+  let mut entry = vec![
+    ("MESSAGE", message.as_str()),
+    ("MESSAGE_ID", message_id(kind)),
+    ("PRIORITY", &priority),
+    ("SYSLOG_IDENTIFIER", "rlldp"),
+  ];
+  entry.extend(fields(neighbor));
+
+  let mut out = Vec::new();
+  for (key, value) in entry {
+    out.extend_from_slice(key.as_bytes());
+    if value.contains('\n') {
+      out.push(b'\n');
+      out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+      out.push(b'=');
+    }
+    out.extend_from_slice(value.as_bytes());
+    out.push(b'\n');
+  }
+  out
+}
+
+// rfc 5424 with the fields as structured data, timestamp and hostname are left for the syslog daemon to fill in
+fn syslog_line(kind: NeighborEventKind, neighbor: &NeighborOutput) -> String {
+  let params: Vec<_> = fields(neighbor)
+    .into_iter()
+    .map(|(k, v)| format!("{}=\"{}\"", k.to_lowercase(), escape(v)))
+    .collect();
+  format!(
+    "<{}>1 - - rlldp {} {} [{SD_ID} {}] {}",
+    FACILITY_DAEMON * 8 + severity(kind),
+    std::process::id(),
+    message_id(kind),
+    params.join(" "),
+    message(kind, neighbor)
+  )
+}
+
+fn escape(value: &str) -> String {
+  value.replace('\\', "\\\\").replace('"', "\\\"").replace(']', "\\]")
+}
+
+#[test]
+fn formats_entries() {
+  let neighbor = NeighborOutput {
+    interface: "eth0".into(),
+    protocol: "lldp".into(),
+    source: "02:00:00:00:00:01".into(),
+    chassis: "02:00:00:00:00:01".into(),
+    system_name: Some("sw\"1".into()),
+    port_id: Some("Gi1/0/1".into()),
+    ttl: 120,
+    ttl_remaining: 120,
+    age: 0,
+    remote_index: 1,
+    chassis_subtype: Some("mac".into()),
+    port_subtype: Some("interface-name".into()),
+    ..Default::default()
+  };
+
+  let line = syslog_line(NeighborEventKind::Discovered, &neighbor);
+  assert_eq!(
+    line,
+    format!(
+      "<29>1 - - rlldp {} {MESSAGE_ID_DISCOVERED} [rlldp@32473 interface=\"eth0\" protocol=\"lldp\" \
+       chassis=\"02:00:00:00:00:01\" source=\"02:00:00:00:00:01\" port=\"Gi1/0/1\" system_name=\"sw\\\"1\"] \
+       discovered lldp neighbor sw\"1 port Gi1/0/1 on eth0",
+      std::process::id()
+    )
+  );
+
+  let entry = String::from_utf8(journal_entry(NeighborEventKind::Expired, &neighbor)).unwrap();
+  assert!(entry.starts_with("MESSAGE=expired lldp neighbor sw\"1 port Gi1/0/1 on eth0\n"));
+  assert!(entry.contains(&format!("MESSAGE_ID={MESSAGE_ID_EXPIRED}\nPRIORITY=4\n")));
+  assert!(entry.contains("\nINTERFACE=eth0\n"));
+}
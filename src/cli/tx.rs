@@ -0,0 +1,185 @@
+use std::{borrow::Cow, fs, io, net::IpAddr, path::PathBuf};
+
+use clap::Args;
+use lldp_parser::{
+  cdp::DataUnit as CdpDu,
+  lldp::{
+    du::{DataUnit as LldpDu, Org},
+    tlv::{ChassisId, ManagementAddress, ManagementInterfaceKind, NetworkAddress, PortId},
+  },
+};
+use rlldp::{LocalPort, MacAddress, Scope};
+use serde::Deserialize;
+
+use super::control::ScopeArg;
+
+// also the schema of --from-json, every field is optional
+#[derive(Debug, Clone, Default, Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TxSpec {
+  /// Chassis id, the interface's mac address when not given
+  #[arg(long)]
+  chassis_id: Option<String>,
+
+  /// Port id, the interface name when not given
+  #[arg(long)]
+  port_id: Option<String>,
+
+  #[arg(long)]
+  port_description: Option<String>,
+
+  #[arg(long)]
+  system_name: Option<String>,
+
+  #[arg(long)]
+  system_description: Option<String>,
+
+  /// Time to live in seconds, 120 when not given
+  #[arg(long)]
+  ttl: Option<u16>,
+
+  #[arg(long)]
+  management_address: Vec<IpAddr>,
+
+  /// Port vlan id
+  #[arg(long)]
+  vlan: Option<u16>,
+}
+
+#[derive(Debug, Args)]
+pub struct TxArgs {
+  #[arg(long)]
+  interface: String,
+
+  #[command(flatten)]
+  spec: TxSpec,
+
+  /// Read the data unit fields from a json file instead of the command line
+  #[arg(long)]
+  from_json: Option<PathBuf>,
+
+  /// Send a CDP frame instead of LLDP
+  #[arg(long)]
+  cdp: bool,
+
+  #[arg(long, value_enum, default_value_t)]
+  scope: ScopeArg,
+}
+
+const DEFAULT_TTL: u16 = 120;
+
+impl TxSpec {
+  fn port_id(&self, port: &LocalPort) -> String {
+    self.port_id.clone().unwrap_or_else(|| port.name.clone())
+  }
+
+  fn lldp_du(&self, port: &LocalPort) -> LldpDu<'static> {
+    let chassis_id = match (&self.chassis_id, &port.mac_address) {
+      (Some(x), _) => ChassisId::Local(x.clone().into()),
+      (None, Some(mac)) => ChassisId::MacAddress(mac.0),
+      (None, None) => ChassisId::Local(port.name.clone().into()),
+    };
+
+    LldpDu {
+      chassis_id,
+      port_id: PortId::InterfaceName(self.port_id(port).into()),
+      time_to_live: self.ttl.unwrap_or(DEFAULT_TTL),
+      port_description: self.port_description.clone().map(Cow::Owned),
+      system_name: self.system_name.clone().map(Cow::Owned),
+      system_description: self.system_description.clone().map(Cow::Owned),
+      capabilities: None,
+      management_address: self
+        .management_address
+        .iter()
+        .map(|x| ManagementAddress {
+          address: NetworkAddress::Ip(*x),
+          interface_subtype: ManagementInterfaceKind::IfIndex,
+          interface_number: port.ifindex.unwrap_or_default(),
+          oid: "".into(),
+        })
+        .collect(),
+      org: Org {
+        dot1: lldp_parser::lldp::du::Dot1 {
+          port_vlan_id: self.vlan,
+          ..Default::default()
+        },
+        ..Default::default()
+      },
+    }
+  }
+
+  fn cdp_du(&self, port: &LocalPort) -> CdpDu<'static> {
+    CdpDu {
+      time_to_live: self.ttl.unwrap_or(DEFAULT_TTL).min(u8::MAX.into()) as u8,
+      device_id: self
+        .system_name
+        .clone()
+        .or_else(|| self.chassis_id.clone())
+        .map(Cow::Owned),
+      addresses: self.management_address.clone(),
+      capabilities: None,
+      software_version: self.system_description.clone().map(Cow::Owned),
+      platform: None,
+      port_id: Some(self.port_id(port).into()),
+      duplex: None,
+      native_vlan: self.vlan,
+    }
+  }
+}
+
+impl TxArgs {
+  fn frame(&self, port: &LocalPort) -> io::Result<Vec<u8>> {
+    let spec = match &self.from_json {
+      Some(path) => serde_json::from_slice(&fs::read(path)?)?,
+      None => self.spec.clone(),
+    };
+
+    let source = port.mac_address.clone().unwrap_or(MacAddress([0; 6]));
+    Ok(if self.cdp {
+      rlldp::cdp_frame(&source, spec.cdp_du(port))
+    } else {
+      rlldp::lldp_frame(&Scope::from(self.scope).destination(), &source, spec.lldp_du(port))
+    })
+  }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub async fn run(args: &TxArgs) -> io::Result<()> {
+  use rlldp::{AfPacketSink, PacketSink};
+
+  let port = LocalPort::from_os(&args.interface)?;
+  let frame = args.frame(&port)?;
+  AfPacketSink::open(&args.interface)?.send_frame(&frame).await
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub async fn run(args: &TxArgs) -> io::Result<()> {
+  // still build it so mistakes in the spec are reported everywhere
+  args.frame(&LocalPort::from_os(&args.interface)?)?;
+  Err(io::Error::new(
+    io::ErrorKind::Unsupported,
+    "transmitting is only supported on linux",
+  ))
+}
+
+#[test]
+fn builds_frames_from_json() {
+  let spec: TxSpec =
+    serde_json::from_str(r#"{"system_name": "test", "port_id": "lab1", "ttl": 60, "vlan": 10}"#).unwrap();
+  let port = LocalPort {
+    mac_address: Some(MacAddress([2, 0, 0, 0, 0, 1])),
+    ..LocalPort::new("eth0")
+  };
+
+  let du = spec.lldp_du(&port);
+  assert_eq!(du.chassis_id, ChassisId::MacAddress([2, 0, 0, 0, 0, 1]));
+  assert_eq!(du.port_id, PortId::InterfaceName("lab1".into()));
+  assert_eq!(du.time_to_live, 60);
+  assert_eq!(du.org.dot1.port_vlan_id, Some(10));
+
+  let du = spec.cdp_du(&port);
+  assert_eq!(du.device_id.as_deref(), Some("test"));
+  assert_eq!(du.native_vlan, Some(10));
+
+  assert!(serde_json::from_str::<TxSpec>(r#"{"sytem_name": "typo"}"#).is_err());
+}
@@ -0,0 +1,48 @@
+use std::io;
+
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use super::{
+  control::Client,
+  output::{print_structured, EventOutput},
+  CaptureArgs, Format, GlobalArgs,
+};
+
+pub async fn run(args: &CaptureArgs, global: &GlobalArgs) -> io::Result<()> {
+  if let Some(mut client) = Client::connect(&global.socket).await {
+    client.watch().await?;
+    while let Some(event) = client.next_event().await? {
+      if args.selects(&event.neighbor.interface) {
+        print_event(global, event);
+      }
+    }
+    return Ok(());
+  }
+
+  let mut events = args.start(|_| async {})?.events.subscribe();
+  loop {
+    match events.recv().await {
+      Ok(event) => print_event(global, EventOutput::new(&event)),
+      Err(RecvError::Lagged(count)) => warn!(count, "missed neighbor events"),
+      Err(RecvError::Closed) => return Ok(()),
+    }
+  }
+}
+
+fn print_event(global: &GlobalArgs, mut event: EventOutput) {
+  event.neighbor = event.neighbor.with_mac_format(global.mac_format.into());
+  let neighbor = &event.neighbor;
+  match global.format {
+    Format::Text => println!(
+      "{} {} {} {} {} ttl {}",
+      event.event,
+      neighbor.interface,
+      neighbor.protocol,
+      neighbor.chassis,
+      neighbor.port_id.as_deref().unwrap_or("-"),
+      neighbor.ttl
+    ),
+    format => print_structured(format, &event),
+  }
+}
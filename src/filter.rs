@@ -0,0 +1,596 @@
+use crate::MacAddress;
+
+pub(crate) const BPF_LD_W_ABS: u16 = 0x20;
+pub(crate) const BPF_LD_H_ABS: u16 = 0x28;
+pub(crate) const BPF_LD_B_ABS: u16 = 0x30;
+pub(crate) const BPF_JMP_JA: u16 = 0x05;
+pub(crate) const BPF_JMP_JEQ_K: u16 = 0x15;
+pub(crate) const BPF_RET_K: u16 = 0x06;
+
+const SNAPLEN: u32 = 0x00080000;
+const MAX_VLAN_TAGS: usize = 2;
+// keeps every jump to the matched label within the 8 bit offsets classic bpf has
+pub const MAX_EXTRA_ETHERTYPES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Insn {
+  pub code: u16,
+  pub jt: u8,
+  pub jf: u8,
+  pub k: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterSpec {
+  pub lldp: bool,
+  pub cdp: bool,
+  pub fdp: bool,
+  pub sonmp: bool,
+  pub vlan_ok: bool,
+  pub gre: bool,
+  pub src_allowlist: Vec<MacAddress>,
+  // sources to ignore however they match otherwise, checked before the allowlists
+  pub src_denylist: Vec<MacAddress>,
+  // whole vendors, by the first three bytes of the source. a source on either allowlist gets through
+  pub oui_allowlist: Vec<[u8; 3]>,
+  pub oui_denylist: Vec<[u8; 3]>,
+  // matched like lldp, behind the same vlan tags. only the first MAX_EXTRA_ETHERTYPES are used
+  pub extra_ethertypes: Vec<u16>,
+}
+
+impl Default for FilterSpec {
+  fn default() -> Self {
+    Self {
+      lldp: true,
+      cdp: true,
+      fdp: true,
+      sonmp: true,
+      vlan_ok: true,
+      gre: false,
+      src_allowlist: Vec::new(),
+      src_denylist: Vec::new(),
+      oui_allowlist: Vec::new(),
+      oui_denylist: Vec::new(),
+      extra_ethertypes: Vec::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Target {
+  Next,
+  Skip(u8),
+  Matched,
+  Reject,
+  Accept,
+}
+
+struct Assembler {
+  ops: Vec<(u16, Target, Target, u32)>,
+}
+
+impl Assembler {
+  fn op(&mut self, code: u16, k: u32) {
+    self.ops.push((code, Target::Next, Target::Next, k));
+  }
+
+  fn jeq(&mut self, k: u32, jt: Target, jf: Target) {
+    self.ops.push((BPF_JMP_JEQ_K, jt, jf, k));
+  }
+
+  // the 32 bit offset of an unconditional jump reaches past the 8 bit ones, the lists are still bound by the kernel's
+  // limit on program length
+  fn ja(&mut self, target: Target) {
+    self.ops.push((BPF_JMP_JA, target, Target::Next, 0));
+  }
+
+  // a source matching mac ends up at target, anything else falls through
+  fn mac(&mut self, mac: &MacAddress, target: Target) {
+    let [a, b, c, d, e, f] = mac.0;
+    self.op(BPF_LD_W_ABS, 6);
+    self.jeq(u32::from_be_bytes([a, b, c, d]), Target::Next, Target::Skip(3));
+    self.op(BPF_LD_H_ABS, 10);
+    self.jeq(u16::from_be_bytes([e, f]) as u32, Target::Next, Target::Skip(1));
+    self.ja(target);
+  }
+
+  fn oui(&mut self, oui: [u8; 3], target: Target) {
+    let [a, b, c] = oui;
+    self.op(BPF_LD_H_ABS, 6);
+    self.jeq(u16::from_be_bytes([a, b]) as u32, Target::Next, Target::Skip(3));
+    self.op(BPF_LD_B_ABS, 8);
+    self.jeq(c as u32, Target::Next, Target::Skip(1));
+    self.ja(target);
+  }
+
+  fn finish(self, matched: usize, reject: usize, accept: usize) -> Vec<Insn> {
+    let label = |i: usize, target: Target| match target {
+      Target::Next => 0,
+      Target::Skip(n) => n as usize,
+      Target::Matched => matched - i - 1,
+      Target::Reject => reject - i - 1,
+      Target::Accept => accept - i - 1,
+    };
+    // protocol checks are always emitted right before the matched label, so this can't overflow
+    let offset = |i: usize, target: Target| label(i, target) as u8;
+
+    self
+      .ops
+      .iter()
+      .enumerate()
+      .map(|(i, &(code, jt, jf, k))| match code {
+        BPF_JMP_JA => Insn {
+          code,
+          jt: 0,
+          jf: 0,
+          k: label(i, jt) as u32,
+        },
+        _ => Insn {
+          code,
+          jt: offset(i, jt),
+          jf: offset(i, jf),
+          k,
+        },
+      })
+      .collect()
+  }
+}
+
+impl FilterSpec {
+  pub fn is_empty(&self) -> bool {
+    !self.lldp && !self.cdp && !self.fdp && !self.sonmp && self.extra_ethertypes.is_empty()
+  }
+
+  fn ethertypes(&self) -> Vec<u32> {
+    let extra = self
+      .extra_ethertypes
+      .iter()
+      .take(MAX_EXTRA_ETHERTYPES)
+      .map(|&x| x as u32);
+    self.lldp.then_some(0x88cc).into_iter().chain(extra).collect()
+  }
+
+  // the source lists on their own, for frames from a capture that couldn't apply them
+  pub fn accepts_source(&self, source: &MacAddress) -> bool {
+    let oui = |ouis: &[[u8; 3]]| ouis.iter().any(|x| source.0[..3] == x[..]);
+    if self.src_denylist.contains(source) || oui(&self.oui_denylist) {
+      return false;
+    }
+    let allowlist = !self.src_allowlist.is_empty() || !self.oui_allowlist.is_empty();
+    !allowlist || self.src_allowlist.contains(source) || oui(&self.oui_allowlist)
+  }
+
+  pub fn compile(&self) -> Vec<Insn> {
+    let mut asm = Assembler { ops: Vec::new() };
+    let allowlist = !self.src_allowlist.is_empty() || !self.oui_allowlist.is_empty();
+    let denylist = !self.src_denylist.is_empty() || !self.oui_denylist.is_empty();
+    let matched = if allowlist || denylist {
+      Target::Matched
+    } else {
+      Target::Accept
+    };
+
+    if self.cdp {
+      // cdp is only identified by its destination mac, 01:00:0c:cc:cc:cc
+      asm.op(BPF_LD_W_ABS, 2);
+      asm.jeq(0x0ccccccc, Target::Next, Target::Skip(2));
+      asm.op(BPF_LD_H_ABS, 0);
+      asm.jeq(0x0100, matched, Target::Next);
+    }
+
+    if self.fdp {
+      // same for fdp, 01:e0:52:cc:cc:cc
+      asm.op(BPF_LD_W_ABS, 2);
+      asm.jeq(0x52cccccc, Target::Next, Target::Skip(2));
+      asm.op(BPF_LD_H_ABS, 0);
+      asm.jeq(0x01e0, matched, Target::Next);
+    }
+
+    if self.sonmp {
+      // and sonmp, 01:00:81:00:01:00 or 01:00:81:00:01:01
+      asm.op(BPF_LD_W_ABS, 2);
+      asm.jeq(0x81000100, Target::Skip(1), Target::Next);
+      asm.jeq(0x81000101, Target::Next, Target::Skip(2));
+      asm.op(BPF_LD_H_ABS, 0);
+      asm.jeq(0x0100, matched, Target::Next);
+    }
+
+    let ethertypes = self.ethertypes();
+    if !ethertypes.is_empty() {
+      asm.op(BPF_LD_H_ABS, 12);
+      for &ethertype in &ethertypes {
+        asm.jeq(ethertype, matched, Target::Next);
+      }
+
+      if self.vlan_ok {
+        // peel up to MAX_VLAN_TAGS tags, each one either 802.1q, 802.1ad or the pre-standard qinq tpid
+        let checks = 1 + ethertypes.len();
+        for depth in 0..MAX_VLAN_TAGS {
+          let remaining = (MAX_VLAN_TAGS - depth - 1) * (3 + checks);
+          asm.jeq(0x8100, Target::Skip(2), Target::Next);
+          asm.jeq(0x88a8, Target::Skip(1), Target::Next);
+          asm.jeq(0x9100, Target::Next, Target::Skip((checks + remaining) as u8));
+          asm.op(BPF_LD_H_ABS, 16 + 4 * depth as u32);
+          for &ethertype in &ethertypes {
+            asm.jeq(ethertype, matched, Target::Next);
+          }
+        }
+      }
+    }
+
+    if self.gre {
+      // mirror sessions, the encapsulated frame is checked after decapsulating it in userspace
+      asm.op(BPF_LD_H_ABS, 12);
+      asm.jeq(0x0800, Target::Next, Target::Skip(2));
+      asm.op(BPF_LD_B_ABS, 23);
+      asm.jeq(47, matched, Target::Skip(3));
+      asm.jeq(0x86dd, Target::Next, Target::Skip(2));
+      asm.op(BPF_LD_B_ABS, 20);
+      asm.jeq(47, matched, Target::Next);
+    }
+
+    asm.op(BPF_RET_K, 0);
+    let matched = asm.ops.len();
+
+    for mac in &self.src_denylist {
+      asm.mac(mac, Target::Reject);
+    }
+    for &oui in &self.oui_denylist {
+      asm.oui(oui, Target::Reject);
+    }
+    if denylist && !allowlist {
+      asm.ja(Target::Accept);
+    }
+    for mac in &self.src_allowlist {
+      asm.mac(mac, Target::Accept);
+    }
+    for &oui in &self.oui_allowlist {
+      asm.oui(oui, Target::Accept);
+    }
+
+    let mut reject = matched;
+    if allowlist || denylist {
+      reject = asm.ops.len();
+      asm.op(BPF_RET_K, 0);
+    }
+
+    let accept = asm.ops.len();
+    asm.op(BPF_RET_K, SNAPLEN);
+
+    asm.finish(matched, reject, accept)
+  }
+
+  pub fn expression(&self) -> String {
+    let mut protocols = Vec::new();
+    if self.cdp {
+      protocols.push("ether dst 01:00:0c:cc:cc:cc".to_string());
+    }
+    if self.fdp {
+      protocols.push("ether dst 01:e0:52:cc:cc:cc".to_string());
+    }
+    if self.sonmp {
+      protocols.push("ether dst 01:00:81:00:01:00 or ether dst 01:00:81:00:01:01".to_string());
+    }
+    for ethertype in self.ethertypes() {
+      protocols.push(format!("ether proto {ethertype:#06x}"));
+      if self.vlan_ok {
+        protocols.push(format!("(vlan and ether proto {ethertype:#06x})"));
+        protocols.push(format!("(vlan and vlan and ether proto {ethertype:#06x})"));
+      }
+    }
+
+    if self.gre {
+      protocols.push("ip proto gre or ip6 proto gre".to_string());
+    }
+
+    let sources = |macs: &[MacAddress], ouis: &[[u8; 3]]| {
+      let sources: Vec<_> = macs
+        .iter()
+        .map(|mac| format!("ether src {mac}"))
+        .chain(
+          ouis
+            .iter()
+            .map(|[a, b, c]| format!("(ether[6:2] = 0x{a:02x}{b:02x} and ether[8] = 0x{c:02x})")),
+        )
+        .collect();
+      sources.join(" or ")
+    };
+
+    let mut expression = format!("({})", protocols.join(" or "));
+    if !self.src_denylist.is_empty() || !self.oui_denylist.is_empty() {
+      expression = format!(
+        "{expression} and not ({})",
+        sources(&self.src_denylist, &self.oui_denylist)
+      );
+    }
+    if !self.src_allowlist.is_empty() || !self.oui_allowlist.is_empty() {
+      expression = format!(
+        "{expression} and ({})",
+        sources(&self.src_allowlist, &self.oui_allowlist)
+      );
+    }
+
+    expression
+  }
+}
+
+#[cfg(test)]
+fn run(program: &[Insn], frame: &[u8]) -> u32 {
+  let load = |offset: usize, len: usize| -> Option<u32> {
+    let bytes = frame.get(offset..offset + len)?;
+    Some(bytes.iter().fold(0, |acc, &x| (acc << 8) | x as u32))
+  };
+
+  let mut acc = 0;
+  let mut pc = 0;
+  loop {
+    let insn = program[pc];
+    pc += 1;
+    match insn.code {
+      BPF_LD_W_ABS | BPF_LD_H_ABS | BPF_LD_B_ABS => {
+        let len = match insn.code {
+          BPF_LD_W_ABS => 4,
+          BPF_LD_H_ABS => 2,
+          _ => 1,
+        };
+        match load(insn.k as usize, len) {
+          Some(x) => acc = x,
+          None => return 0,
+        }
+      }
+      BPF_JMP_JA => pc += insn.k as usize,
+      BPF_JMP_JEQ_K => pc += if acc == insn.k { insn.jt } else { insn.jf } as usize,
+      BPF_RET_K => return insn.k,
+      code => panic!("unexpected opcode {code:#x}"),
+    }
+  }
+}
+
+// the checks the kernel's sk_chk_filter does on attach
+#[cfg(test)]
+fn verify(program: &[Insn]) -> bool {
+  let in_bounds = |i: usize, offset: usize| i + 1 + offset < program.len();
+  !program.is_empty()
+    && program.len() <= 4096
+    && program.last().is_some_and(|x| x.code == BPF_RET_K)
+    && program.iter().enumerate().all(|(i, insn)| match insn.code {
+      BPF_JMP_JA => in_bounds(i, insn.k as usize),
+      BPF_JMP_JEQ_K => in_bounds(i, insn.jt as usize) && in_bounds(i, insn.jf as usize),
+      BPF_LD_W_ABS | BPF_LD_H_ABS | BPF_LD_B_ABS | BPF_RET_K => true,
+      _ => false,
+    })
+}
+
+#[cfg(test)]
+fn frame(dst: [u8; 6], src: [u8; 6], rest: &[u8]) -> Vec<u8> {
+  [&dst[..], &src[..], rest].concat()
+}
+
+#[test]
+fn filter_matches_protocols() {
+  const LLDP: [u8; 6] = [0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e];
+  const CDP: [u8; 6] = [0x01, 0x00, 0x0c, 0xcc, 0xcc, 0xcc];
+  let src = [0, 0, 0, 0, 0, 1];
+
+  let lldp = frame(LLDP, src, &[0x88, 0xcc, 0, 0]);
+  let tagged = frame(LLDP, src, &[0x81, 0x00, 0x00, 0x64, 0x88, 0xcc]);
+  let qinq = frame(LLDP, src, &[0x88, 0xa8, 0x00, 0x0a, 0x81, 0x00, 0x00, 0x64, 0x88, 0xcc]);
+  let cdp = frame(CDP, src, &[0x00, 0x20, 0xaa, 0xaa]);
+  let ipv4 = frame([0xff; 6], src, &[0x08, 0x00, 0, 0]);
+
+  let all = FilterSpec::default().compile();
+  assert_eq!(run(&all, &lldp), SNAPLEN);
+  assert_eq!(run(&all, &tagged), SNAPLEN);
+  assert_eq!(run(&all, &qinq), SNAPLEN);
+  assert_eq!(run(&all, &cdp), SNAPLEN);
+  assert_eq!(run(&all, &ipv4), 0);
+
+  let lldp_only = FilterSpec {
+    cdp: false,
+    fdp: false,
+    sonmp: false,
+    vlan_ok: false,
+    ..Default::default()
+  }
+  .compile();
+  assert_eq!(run(&lldp_only, &lldp), SNAPLEN);
+  assert_eq!(run(&lldp_only, &tagged), 0);
+  assert_eq!(run(&lldp_only, &qinq), 0);
+  assert_eq!(run(&lldp_only, &cdp), 0);
+
+  let cdp_only = FilterSpec {
+    lldp: false,
+    fdp: false,
+    sonmp: false,
+    ..Default::default()
+  }
+  .compile();
+  assert_eq!(run(&cdp_only, &lldp), 0);
+  assert_eq!(run(&cdp_only, &cdp), SNAPLEN);
+
+  let fdp = frame([0x01, 0xe0, 0x52, 0xcc, 0xcc, 0xcc], src, &[0x00, 0x20, 0xaa, 0xaa]);
+  assert_eq!(run(&all, &fdp), SNAPLEN);
+  assert_eq!(run(&cdp_only, &fdp), 0);
+
+  let sonmp = frame([0x01, 0x00, 0x81, 0x00, 0x01, 0x01], src, &[0x00, 0x13, 0xaa, 0xaa]);
+  assert_eq!(run(&all, &sonmp), SNAPLEN);
+  assert_eq!(run(&cdp_only, &sonmp), 0);
+
+  let mut gre = frame([2, 0, 0, 0, 0, 2], src, &[0x08, 0x00, 0x45]);
+  gre.resize(34, 0);
+  gre[23] = 47;
+  assert_eq!(run(&all, &gre), 0);
+  let mirror = FilterSpec {
+    gre: true,
+    ..Default::default()
+  }
+  .compile();
+  assert_eq!(run(&mirror, &gre), SNAPLEN);
+  assert_eq!(run(&mirror, &lldp), SNAPLEN);
+  gre[23] = 17;
+  assert_eq!(run(&mirror, &gre), 0);
+}
+
+#[test]
+fn filter_src_allowlist() {
+  let lldp = |src| frame([0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e], src, &[0x88, 0xcc]);
+
+  let spec = FilterSpec {
+    src_allowlist: (1..=100).map(|x| MacAddress([2, 0, 0, 0, 0, x])).collect(),
+    ..Default::default()
+  };
+  let program = spec.compile();
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 0, 1])), SNAPLEN);
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 0, 100])), SNAPLEN);
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 1, 1])), 0);
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 0, 101])), 0);
+}
+
+#[test]
+fn filter_src_lists() {
+  let lldp = |src| frame([0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e], src, &[0x88, 0xcc]);
+
+  let deny = FilterSpec {
+    src_denylist: vec![MacAddress([2, 0, 0, 0, 0, 1])],
+    oui_denylist: vec![[0, 0x1b, 0x21]],
+    ..Default::default()
+  };
+  let program = deny.compile();
+  assert!(verify(&program));
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 0, 1])), 0);
+  assert_eq!(run(&program, &lldp([0, 0x1b, 0x21, 1, 2, 3])), 0);
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 0, 2])), SNAPLEN);
+  assert_eq!(run(&program, &lldp([0, 0x1b, 0x22, 1, 2, 3])), SNAPLEN);
+
+  // the denylist wins over an allowed vendor
+  let scoped = FilterSpec {
+    src_allowlist: vec![MacAddress([2, 0, 0, 0, 0, 1])],
+    src_denylist: vec![MacAddress([0, 0x1b, 0x21, 0, 0, 1])],
+    oui_allowlist: vec![[0, 0x1b, 0x21]],
+    ..Default::default()
+  };
+  let program = scoped.compile();
+  assert!(verify(&program));
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 0, 1])), SNAPLEN);
+  assert_eq!(run(&program, &lldp([0, 0x1b, 0x21, 0, 0, 2])), SNAPLEN);
+  assert_eq!(run(&program, &lldp([0, 0x1b, 0x21, 0, 0, 1])), 0);
+  assert_eq!(run(&program, &lldp([2, 0, 0, 0, 0, 2])), 0);
+  for src in [
+    [2, 0, 0, 0, 0, 1],
+    [0, 0x1b, 0x21, 0, 0, 2],
+    [0, 0x1b, 0x21, 0, 0, 1],
+    [2, 0, 0, 0, 0, 2],
+  ] {
+    let accepted = run(&program, &lldp(src)) == SNAPLEN;
+    assert_eq!(scoped.accepts_source(&MacAddress(src)), accepted);
+    assert_eq!(
+      deny.accepts_source(&MacAddress(src)),
+      run(&deny.compile(), &lldp(src)) == SNAPLEN
+    );
+  }
+  assert!(scoped.expression().ends_with(
+    ") and not (ether src 00:1b:21:00:00:01) and (ether src 02:00:00:00:00:01 or \
+     (ether[6:2] = 0x001b and ether[8] = 0x21))"
+  ));
+}
+
+#[test]
+fn filter_matches_known_programs() {
+  let insn = |code, jt, jf, k| Insn { code, jt, jf, k };
+
+  let lldp = FilterSpec {
+    cdp: false,
+    fdp: false,
+    sonmp: false,
+    vlan_ok: false,
+    ..Default::default()
+  };
+  assert_eq!(
+    lldp.compile(),
+    [
+      insn(BPF_LD_H_ABS, 0, 0, 12),
+      insn(BPF_JMP_JEQ_K, 1, 0, 0x88cc),
+      insn(BPF_RET_K, 0, 0, 0),
+      insn(BPF_RET_K, 0, 0, SNAPLEN),
+    ]
+  );
+
+  let cdp = FilterSpec {
+    lldp: false,
+    fdp: false,
+    sonmp: false,
+    ..Default::default()
+  };
+  assert_eq!(
+    cdp.compile(),
+    [
+      insn(BPF_LD_W_ABS, 0, 0, 2),
+      insn(BPF_JMP_JEQ_K, 0, 2, 0x0ccccccc),
+      insn(BPF_LD_H_ABS, 0, 0, 0),
+      insn(BPF_JMP_JEQ_K, 1, 0, 0x0100),
+      insn(BPF_RET_K, 0, 0, 0),
+      insn(BPF_RET_K, 0, 0, SNAPLEN),
+    ]
+  );
+
+  let tagged = FilterSpec {
+    vlan_ok: true,
+    extra_ethertypes: vec![0x88b5],
+    ..lldp
+  };
+  assert_eq!(
+    tagged.compile(),
+    [
+      insn(BPF_LD_H_ABS, 0, 0, 12),
+      insn(BPF_JMP_JEQ_K, 14, 0, 0x88cc),
+      insn(BPF_JMP_JEQ_K, 13, 0, 0x88b5),
+      insn(BPF_JMP_JEQ_K, 2, 0, 0x8100),
+      insn(BPF_JMP_JEQ_K, 1, 0, 0x88a8),
+      insn(BPF_JMP_JEQ_K, 0, 9, 0x9100),
+      insn(BPF_LD_H_ABS, 0, 0, 16),
+      insn(BPF_JMP_JEQ_K, 8, 0, 0x88cc),
+      insn(BPF_JMP_JEQ_K, 7, 0, 0x88b5),
+      insn(BPF_JMP_JEQ_K, 2, 0, 0x8100),
+      insn(BPF_JMP_JEQ_K, 1, 0, 0x88a8),
+      insn(BPF_JMP_JEQ_K, 0, 3, 0x9100),
+      insn(BPF_LD_H_ABS, 0, 0, 20),
+      insn(BPF_JMP_JEQ_K, 2, 0, 0x88cc),
+      insn(BPF_JMP_JEQ_K, 1, 0, 0x88b5),
+      insn(BPF_RET_K, 0, 0, 0),
+      insn(BPF_RET_K, 0, 0, SNAPLEN),
+    ]
+  );
+}
+
+#[test]
+fn filter_always_verifies() {
+  let src = [0, 0, 0, 0, 0, 1];
+  let tagged = |ethertype: u16| {
+    let [a, b] = ethertype.to_be_bytes();
+    frame([0xff; 6], src, &[0x88, 0xa8, 0, 10, 0x81, 0x00, 0, 100, a, b])
+  };
+
+  for bits in 0..1 << 6 {
+    let flag = |bit: u32| bits & (1 << bit) != 0;
+    let spec = FilterSpec {
+      lldp: flag(0),
+      cdp: flag(1),
+      fdp: flag(2),
+      sonmp: flag(3),
+      vlan_ok: flag(4),
+      gre: flag(5),
+      src_allowlist: vec![MacAddress(src)],
+      src_denylist: vec![MacAddress([0, 0, 0, 0, 0, 2])],
+      oui_allowlist: vec![[0, 0x1b, 0x21]],
+      oui_denylist: vec![[2, 0, 0]],
+      extra_ethertypes: (0..MAX_EXTRA_ETHERTYPES as u16 + 8).map(|x| 0x9000 + x).collect(),
+    };
+    let program = spec.compile();
+    assert!(verify(&program), "{spec:?}");
+
+    // the last extra ethertype that's used still matches behind two tags, the first one left out doesn't
+    let last = 0x9000 + MAX_EXTRA_ETHERTYPES as u16 - 1;
+    assert_eq!(run(&program, &tagged(last)), if spec.vlan_ok { SNAPLEN } else { 0 });
+    assert_eq!(run(&program, &tagged(last + 1)), 0);
+  }
+}
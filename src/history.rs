@@ -0,0 +1,271 @@
+use std::{
+  io,
+  net::IpAddr,
+  path::Path,
+  sync::{Arc, Mutex},
+  time::{Duration, SystemTime},
+};
+
+use lldp_parser::Protocol;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::{EventSink, MacAddress, NeighborEvent, NeighborEventKind};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+  id INTEGER PRIMARY KEY,
+  time INTEGER NOT NULL,
+  kind TEXT NOT NULL,
+  interface TEXT NOT NULL,
+  protocol TEXT NOT NULL,
+  source BLOB NOT NULL,
+  remote_index INTEGER NOT NULL,
+  system_name TEXT,
+  port_id TEXT,
+  management_address TEXT,
+  du TEXT NOT NULL,
+  raw BLOB
+);
+CREATE INDEX IF NOT EXISTS events_interface_time ON events (interface, time);
+";
+
+const COLUMNS: &str =
+  "time, kind, interface, protocol, source, remote_index, system_name, port_id, management_address, du, raw";
+
+// one row of the history, the du is kept as its debug dump plus the raw bytes when the interface stores them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryRecord {
+  pub time: SystemTime,
+  pub kind: NeighborEventKind,
+  pub interface: String,
+  pub protocol: Protocol,
+  pub source: MacAddress,
+  pub remote_index: u32,
+  pub system_name: Option<String>,
+  pub port_id: Option<String>,
+  pub management_address: Option<IpAddr>,
+  pub du: String,
+  pub raw: Option<Vec<u8>>,
+}
+
+impl HistoryRecord {
+  pub fn new(event: &NeighborEvent, time: SystemTime) -> Self {
+    let neighbor = &event.neighbor;
+    let du = neighbor.du.get();
+    Self {
+      time,
+      kind: event.kind,
+      interface: neighbor.local_port.name.clone(),
+      protocol: neighbor.protocol,
+      source: neighbor.source.clone(),
+      remote_index: neighbor.remote_index,
+      system_name: du.system_name().map(|x| x.to_string()),
+      port_id: du.port_id().map(|x| x.to_string()),
+      management_address: du.management_address(),
+      du: format!("{du:#?}"),
+      raw: neighbor.du.raw_bytes().map(Vec::from),
+    }
+  }
+
+  fn from_row(row: &Row) -> rusqlite::Result<Self> {
+    let invalid = |i, x: &str| {
+      rusqlite::Error::FromSqlConversionFailure(i, rusqlite::types::Type::Text, format!("invalid value '{x}'").into())
+    };
+
+    let kind: String = row.get(1)?;
+    let protocol: String = row.get(3)?;
+    let source: Vec<u8> = row.get(4)?;
+    let management_address: Option<String> = row.get(8)?;
+    Ok(Self {
+      time: SystemTime::UNIX_EPOCH + Duration::from_millis(row.get(0)?),
+      kind: parse_kind(&kind).ok_or_else(|| invalid(1, &kind))?,
+      interface: row.get(2)?,
+      protocol: parse_protocol(&protocol).ok_or_else(|| invalid(3, &protocol))?,
+      source: MacAddress(source.try_into().map_err(|_| invalid(4, "source"))?),
+      remote_index: row.get(5)?,
+      system_name: row.get(6)?,
+      port_id: row.get(7)?,
+      management_address: management_address
+        .map(|x| x.parse().map_err(|_| invalid(8, &x)))
+        .transpose()?,
+      du: row.get(9)?,
+      raw: row.get(10)?,
+    })
+  }
+}
+
+fn parse_kind(x: &str) -> Option<NeighborEventKind> {
+  match x {
+    "Discovered" => Some(NeighborEventKind::Discovered),
+    "Updated" => Some(NeighborEventKind::Updated),
+    "Expired" => Some(NeighborEventKind::Expired),
+    _ => None,
+  }
+}
+
+fn parse_protocol(x: &str) -> Option<Protocol> {
+  match x {
+    "Cdp" => Some(Protocol::Cdp),
+    "Fdp" => Some(Protocol::Fdp),
+    "Lldp" => Some(Protocol::Lldp),
+    "Mndp" => Some(Protocol::Mndp),
+    "Sonmp" => Some(Protocol::Sonmp),
+    _ => None,
+  }
+}
+
+fn millis(time: SystemTime) -> i64 {
+  time
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis() as i64
+}
+
+// every neighbor event with a timestamp, for working out after the fact what was plugged in where
+#[derive(Debug, Clone)]
+pub struct History {
+  conn: Arc<Mutex<Connection>>,
+}
+
+impl History {
+  pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+    Self::with_connection(Connection::open(path)?)
+  }
+
+  pub fn open_in_memory() -> rusqlite::Result<Self> {
+    Self::with_connection(Connection::open_in_memory()?)
+  }
+
+  fn with_connection(conn: Connection) -> rusqlite::Result<Self> {
+    conn.execute_batch(SCHEMA)?;
+    Ok(Self {
+      conn: Arc::new(Mutex::new(conn)),
+    })
+  }
+
+  pub fn insert(&self, record: &HistoryRecord) -> rusqlite::Result<()> {
+    self.conn.lock().unwrap().execute(
+      &format!("INSERT INTO events ({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"),
+      params![
+        millis(record.time),
+        format!("{:?}", record.kind),
+        record.interface,
+        format!("{:?}", record.protocol),
+        &record.source.0[..],
+        record.remote_index,
+        record.system_name,
+        record.port_id,
+        record.management_address.map(|x| x.to_string()),
+        record.du,
+        record.raw,
+      ],
+    )?;
+    Ok(())
+  }
+
+  // everything that happened on an interface in [from, to), oldest first
+  pub fn events(&self, interface: &str, from: SystemTime, to: SystemTime) -> rusqlite::Result<Vec<HistoryRecord>> {
+    let conn = self.conn.lock().unwrap();
+    let mut stmt = conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM events WHERE interface = ?1 AND time >= ?2 AND time < ?3 ORDER BY time, id"
+    ))?;
+    let rows = stmt.query_map(params![interface, millis(from), millis(to)], HistoryRecord::from_row)?;
+    rows.collect()
+  }
+
+  // the neighbors an interface had at a point in time: the last event of every neighbor seen before it,
+  // unless that was the neighbor expiring
+  pub fn neighbors_at(&self, interface: &str, time: SystemTime) -> rusqlite::Result<Vec<HistoryRecord>> {
+    let conn = self.conn.lock().unwrap();
+    let mut stmt = conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM events e WHERE interface = ?1 AND kind != 'Expired' AND id = (
+        SELECT id FROM events WHERE interface = e.interface AND protocol = e.protocol AND source = e.source
+          AND time <= ?2 ORDER BY time DESC, id DESC LIMIT 1
+      ) ORDER BY time, id"
+    ))?;
+    let rows = stmt.query_map(params![interface, millis(time)], HistoryRecord::from_row)?;
+    rows.collect()
+  }
+
+  // the most recent event for a neighbor, whichever interface it was seen on
+  pub fn last_seen(&self, source: &MacAddress) -> rusqlite::Result<Option<HistoryRecord>> {
+    let conn = self.conn.lock().unwrap();
+    conn
+      .query_row(
+        &format!("SELECT {COLUMNS} FROM events WHERE source = ?1 ORDER BY time DESC, id DESC LIMIT 1"),
+        params![&source.0[..]],
+        HistoryRecord::from_row,
+      )
+      .optional()
+  }
+
+  // drops everything older than the cutoff, returns how many events went
+  pub fn prune(&self, before: SystemTime) -> rusqlite::Result<usize> {
+    let conn = self.conn.lock().unwrap();
+    conn.execute("DELETE FROM events WHERE time < ?1", params![millis(before)])
+  }
+}
+
+impl EventSink for History {
+  async fn publish(&mut self, event: &NeighborEvent) -> io::Result<()> {
+    let record = HistoryRecord::new(event, SystemTime::now());
+    let history = self.clone();
+    tokio::task::spawn_blocking(move || history.insert(&record))
+      .await?
+      .map_err(io::Error::other)
+  }
+}
+
+#[test]
+fn finds_neighbors_at() {
+  use crate::{LocalPort, NeighborEntry, StoredDu};
+
+  let history = History::open_in_memory().unwrap();
+  let event = |kind, source, name: &str| NeighborEvent {
+    kind,
+    neighbor: NeighborEntry {
+      local_port: Arc::new(LocalPort::new("eth3")),
+      protocol: Protocol::Cdp,
+      scope: None,
+      source: MacAddress([0, 0, 0, 0, 0, source]),
+      vlans: Vec::new(),
+      remote_index: source.into(),
+      first_detection_time: std::time::Instant::now(),
+      last_detection_time: std::time::Instant::now(),
+      capture_time: None,
+      du: StoredDu::decoded(lldp_parser::DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+        device_id: Some(name.to_string().into()),
+        port_id: Some("Gi1/0/1".into()),
+        ..crate::fixtures::cdp_du()
+      })),
+      changes: Vec::new(),
+    },
+  };
+  let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+
+  let events = [
+    (10, event(NeighborEventKind::Discovered, 1, "a")),
+    (20, event(NeighborEventKind::Discovered, 2, "b")),
+    (30, event(NeighborEventKind::Updated, 1, "a2")),
+    (40, event(NeighborEventKind::Expired, 2, "b")),
+  ];
+  for (secs, event) in &events {
+    history.insert(&HistoryRecord::new(event, at(*secs))).unwrap();
+  }
+
+  let names = |records: Vec<HistoryRecord>| records.into_iter().map(|x| x.system_name.unwrap()).collect::<Vec<_>>();
+  assert_eq!(
+    names(history.neighbors_at("eth3", at(5)).unwrap()),
+    Vec::<String>::new()
+  );
+  assert_eq!(names(history.neighbors_at("eth3", at(25)).unwrap()), ["a", "b"]);
+  assert_eq!(names(history.neighbors_at("eth3", at(45)).unwrap()), ["a2"]);
+  assert!(history.neighbors_at("eth0", at(45)).unwrap().is_empty());
+  assert_eq!(history.events("eth3", at(15), at(40)).unwrap().len(), 2);
+
+  let last = history.last_seen(&MacAddress([0, 0, 0, 0, 0, 2])).unwrap().unwrap();
+  assert_eq!((last.kind, last.time), (NeighborEventKind::Expired, at(40)));
+  assert_eq!(last.port_id.as_deref(), Some("Gi1/0/1"));
+
+  assert_eq!(history.prune(at(25)).unwrap(), 2);
+}
@@ -0,0 +1,1127 @@
+use std::{
+  collections::{BTreeMap, HashMap, VecDeque},
+  io,
+  sync::{
+    atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+    Arc, Mutex,
+  },
+  time::{Duration, Instant, SystemTime},
+};
+
+use lldp_parser::{
+  frame::VlanTag,
+  lldp::{du::DataUnit as LldpDu, tlv::CustomOrgTlv, Msap},
+  DataUnit, DataUnitError, Protocol,
+};
+use tokio::{
+  sync::{broadcast, watch, Notify, RwLock},
+  task::AbortHandle,
+};
+use tracing::{debug, info, span, warn, Instrument, Level};
+
+use crate::{
+  machine::{RxAction, RxMachine},
+  mirror::PcapngMirror,
+  neighbor::{raw_msap, split_frame, unchanged_stored, NeighborKey, DEFAULT_MAX_TTL},
+  scope,
+  stats::{self, Counters},
+  AgentConfig, CdpTxConfig, DuStorage, FieldChange, FilterSpec, Frame, FrameInfo, InterfaceStats, LocalPort,
+  MacAddress, MirrorConfig, NeighborEntry, NeighborEvent, NeighborEventKind, NeighborTableSnapshot, Scope, StoredDu,
+  TxTlvProvider,
+};
+
+#[derive(Debug, Clone)]
+pub struct Interface {
+  pub(crate) inner: Arc<InterfaceInner>,
+}
+
+#[derive(Debug)]
+pub(crate) struct InterfaceInner {
+  pub(crate) local_port: Arc<LocalPort>,
+  pub(crate) config: InterfaceConfig,
+  pub(crate) counters: Counters,
+  pub(crate) mirror: Option<Mutex<PcapngMirror>>,
+  pub(crate) agents: Mutex<BTreeMap<Scope, AgentConfig>>,
+  pub(crate) rx_machines: Mutex<BTreeMap<Scope, RxMachine>>,
+  pub(crate) port_enabled: AtomicBool,
+  pub(crate) advertisement: Mutex<Option<LldpDu<'static>>>,
+  pub(crate) cdp_advertisement: Mutex<Option<lldp_parser::cdp::DataUnit<'static>>>,
+  pub(crate) local_change: Notify,
+  pub(crate) tx_providers: Mutex<Vec<Arc<dyn TxTlvProvider>>>,
+  pub(crate) custom_tlvs: Mutex<Vec<CustomOrgTlv<'static>>>,
+  pub(crate) neighbors: RwLock<HashMap<NeighborKey, Neighbor>>,
+  pub(crate) next_remote_index: AtomicU32,
+  // the longest truncated frame seen, when the buffer is allowed to grow
+  pub(crate) grown_buffer_size: AtomicUsize,
+  pub(crate) events: broadcast::Sender<NeighborEvent>,
+  pub(crate) rx_state: watch::Sender<RxState>,
+}
+
+// what's been changed at runtime, on top of the filter the capture was started with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RxState {
+  pub(crate) paused: bool,
+  pub(crate) lldp: bool,
+  pub(crate) cdp: bool,
+  // replaces the one the capture started with
+  pub(crate) filter: Option<FilterSpec>,
+  // the one the capture started with, for what the kernel doesn't enforce like vlan tags af_packet strips first
+  pub(crate) capture: Option<FilterSpec>,
+}
+
+impl Default for RxState {
+  fn default() -> Self {
+    Self {
+      paused: false,
+      lldp: true,
+      cdp: true,
+      filter: None,
+      capture: None,
+    }
+  }
+}
+
+impl RxState {
+  fn accepts(&self, protocol: Protocol, info: &FrameInfo) -> bool {
+    let filter = self.filter.as_ref().or(self.capture.as_ref());
+    let tagged = !info.vlans.is_empty();
+    match protocol {
+      _ if self.paused => false,
+      _ if !filter.is_none_or(|x| x.accepts_source(&info.source)) => false,
+      Protocol::Lldp => self.lldp && filter.is_none_or(|x| x.lldp && (x.vlan_ok || !tagged)),
+      Protocol::Cdp => self.cdp && filter.is_none_or(|x| x.cdp),
+      Protocol::Fdp => filter.is_none_or(|x| x.fdp),
+      Protocol::Sonmp => filter.is_none_or(|x| x.sonmp),
+      Protocol::Mndp => true,
+    }
+  }
+
+  // a paused filter has nothing left in it, which compiles to one that rejects everything
+  pub(crate) fn apply(&self, filter: &FilterSpec) -> FilterSpec {
+    let filter = self.filter.as_ref().unwrap_or(filter);
+    if self.paused {
+      return FilterSpec {
+        lldp: false,
+        cdp: false,
+        fdp: false,
+        sonmp: false,
+        gre: false,
+        extra_ethertypes: Vec::new(),
+        ..filter.clone()
+      };
+    }
+    FilterSpec {
+      lldp: filter.lldp && self.lldp,
+      cdp: filter.cdp && self.cdp,
+      ..filter.clone()
+    }
+  }
+}
+
+impl Default for Interface {
+  fn default() -> Self {
+    Self::new(LocalPort::default())
+  }
+}
+
+// room for the ethernet header, a vlan tag, and the bpf record header on top of the mtu
+const FRAME_OVERHEAD: usize = 64;
+const DEFAULT_MTU: usize = 1500;
+
+#[derive(Debug, Clone)]
+pub struct InterfaceConfig {
+  pub storage: DuStorage,
+  pub buffer_size: Option<usize>,
+  pub mirror: Option<MirrorConfig>,
+  pub agents: BTreeMap<Scope, AgentConfig>,
+  pub cdp_tx: CdpTxConfig,
+  // how many field changes to remember per neighbor, 0 keeps none
+  pub change_history: usize,
+  // bounds on the advertised ttl, and a hold time that replaces it outright. a ttl of 0 is a shutdown and never
+  // changed
+  pub min_ttl: Option<u16>,
+  pub max_ttl: Option<u16>,
+  pub hold_time: Option<u16>,
+  // dus with a bigger payload are dropped and counted in drops, None takes any that fit in the buffer
+  pub max_du_size: Option<usize>,
+  // total bytes of unknown org tlvs kept per lldp neighbor, the ones past it are dropped
+  pub max_unknown_tlv_bytes: Option<usize>,
+  // a truncated frame raises the buffer size to fit it the next time a capture is opened
+  pub grow_buffer: bool,
+  // for nics and bridges that filter the discovery groups in hardware. the capture turns these on while it runs,
+  // and the interface goes back to how it was when it stops
+  pub promiscuous: bool,
+  pub all_multicast: bool,
+}
+
+impl Default for InterfaceConfig {
+  fn default() -> Self {
+    Self {
+      storage: Default::default(),
+      buffer_size: None,
+      mirror: None,
+      agents: scope::default_agents(),
+      cdp_tx: Default::default(),
+      change_history: 0,
+      min_ttl: None,
+      max_ttl: Some(DEFAULT_MAX_TTL),
+      hold_time: None,
+      max_du_size: None,
+      max_unknown_tlv_bytes: None,
+      grow_buffer: false,
+      promiscuous: false,
+      all_multicast: false,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub(crate) struct Neighbor {
+  pub(crate) source: MacAddress,
+  pub(crate) remote_index: u32,
+  pub(crate) vlans: Vec<VlanTag>,
+  pub(crate) first_detection_time: Instant,
+  pub(crate) last_detection_time: Instant,
+  pub(crate) capture_time: Option<SystemTime>,
+  pub(crate) timeout_handle: AbortHandle,
+  pub(crate) du: StoredDu,
+  pub(crate) changes: VecDeque<(SystemTime, FieldChange)>,
+}
+
+impl Neighbor {
+  pub(crate) fn to_entry(&self, key: &NeighborKey, local_port: &Arc<LocalPort>) -> NeighborEntry {
+    NeighborEntry {
+      local_port: local_port.clone(),
+      protocol: key.protocol,
+      scope: key.scope,
+      source: self.source.clone(),
+      vlans: self.vlans.clone(),
+      remote_index: self.remote_index,
+      first_detection_time: self.first_detection_time,
+      last_detection_time: self.last_detection_time,
+      capture_time: self.capture_time,
+      du: self.du.clone(),
+      changes: self.changes.iter().cloned().collect(),
+    }
+  }
+}
+
+impl Interface {
+  pub fn new(local_port: LocalPort) -> Self {
+    Self::with_config(local_port, InterfaceConfig::default())
+  }
+
+  pub fn with_config(local_port: LocalPort, config: InterfaceConfig) -> Self {
+    let (events, _) = broadcast::channel(256);
+    let mirror = config.mirror.clone().map(|x| Mutex::new(PcapngMirror::new(x)));
+    let agents = Mutex::new(config.agents.clone());
+    let rx_machines = config
+      .agents
+      .iter()
+      .map(|(scope, x)| (*scope, RxMachine::new(true, x.admin_status)))
+      .collect();
+
+    Self {
+      inner: Arc::new(InterfaceInner {
+        local_port: Arc::new(local_port),
+        config,
+        counters: Default::default(),
+        mirror,
+        agents,
+        rx_machines: Mutex::new(rx_machines),
+        port_enabled: AtomicBool::new(true),
+        advertisement: Default::default(),
+        cdp_advertisement: Default::default(),
+        local_change: Notify::new(),
+        tx_providers: Default::default(),
+        custom_tlvs: Default::default(),
+        neighbors: Default::default(),
+        next_remote_index: Default::default(),
+        grown_buffer_size: Default::default(),
+        events,
+        rx_state: watch::Sender::new(RxState::default()),
+      }),
+    }
+  }
+
+  pub fn from_os(name: &str) -> io::Result<Self> {
+    Ok(Self::new(LocalPort::from_os(name)?))
+  }
+
+  pub fn local_port(&self) -> &Arc<LocalPort> {
+    &self.inner.local_port
+  }
+
+  pub fn stats(&self) -> InterfaceStats {
+    self.inner.counters.snapshot()
+  }
+
+  pub(crate) fn buffer_size(&self) -> usize {
+    let size = self.inner.config.buffer_size.unwrap_or_else(|| {
+      let mtu = self.inner.local_port.mtu.map(|x| x as usize).unwrap_or(DEFAULT_MTU);
+      mtu + FRAME_OVERHEAD
+    });
+    size.max(self.inner.grown_buffer_size.load(Ordering::Relaxed))
+  }
+
+  // takes effect on a running capture, which swaps its filter rather than restarting
+  pub fn set_protocols(&self, lldp: bool, cdp: bool) {
+    self.inner.rx_state.send_modify(|x| {
+      x.lldp = lldp;
+      x.cdp = cdp;
+    });
+  }
+
+  // neighbors already learned stay until they time out
+  pub fn pause(&self) {
+    self.inner.rx_state.send_modify(|x| x.paused = true);
+  }
+
+  pub fn resume(&self) {
+    self.inner.rx_state.send_modify(|x| x.paused = false);
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.inner.rx_state.borrow().paused
+  }
+
+  // swaps the whole filter of a running capture, set_protocols and pause still apply on top of it. the neighbor
+  // table and stats carry on as they were
+  pub fn set_filter(&self, filter: FilterSpec) {
+    self.inner.rx_state.send_modify(|x| x.filter = Some(filter));
+  }
+
+  pub fn subscribe(&self) -> broadcast::Receiver<NeighborEvent> {
+    self.inner.events.subscribe()
+  }
+
+  pub(crate) fn emit(&self, kind: NeighborEventKind, neighbor: NeighborEntry) {
+    // no receivers is not an error
+    let _ = self.inner.events.send(NeighborEvent { kind, neighbor });
+  }
+
+  pub async fn neighbors(&self) -> Vec<NeighborEntry> {
+    let inner = self.inner.neighbors.read().await;
+    let mut out: Vec<_> = inner
+      .iter()
+      .map(|(key, neighbor)| neighbor.to_entry(key, &self.inner.local_port))
+      .collect();
+    out.sort_by_key(|x| x.remote_index);
+    out
+  }
+
+  pub async fn snapshot(&self) -> NeighborTableSnapshot {
+    NeighborTableSnapshot::new(self.neighbors().await)
+  }
+
+  fn next_remote_index(&self) -> u32 {
+    // lldpRemIndex is an Integer32 in 1..=2147483647 that wraps back to 1
+    loop {
+      let current = self.inner.next_remote_index.load(Ordering::Relaxed);
+      let next = if current >= i32::MAX as u32 { 1 } else { current + 1 };
+      if self
+        .inner
+        .next_remote_index
+        .compare_exchange_weak(current, next, Ordering::Relaxed, Ordering::Relaxed)
+        .is_ok()
+      {
+        return next;
+      }
+    }
+  }
+
+  pub async fn insert_du(&self, info: FrameInfo, du: DataUnit<'static>) {
+    let key = NeighborKey::new(du.protocol(), info.scope, du.msap(), &info.source);
+    self.insert(key, info, du.into()).await
+  }
+
+  pub async fn insert_raw(&self, info: FrameInfo, protocol: Protocol, buf: &[u8]) -> Result<(), DataUnitError> {
+    // the key only needs the leading tlvs, and the same bytes under it are the same neighbor without decoding them
+    let key = match protocol {
+      Protocol::Lldp => raw_msap(buf).map(|msap| NeighborKey::new(protocol, info.scope, Some(msap), &info.source)),
+      _ => Some(NeighborKey::new(protocol, info.scope, None, &info.source)),
+    };
+    let existing = match &key {
+      Some(key) => self.inner.neighbors.read().await.get(key).and_then(|neighbor| {
+        (neighbor.source == info.source && neighbor.du.raw_bytes() == Some(buf)).then(|| neighbor.du.clone())
+      }),
+      None => None,
+    };
+
+    let (key, du) = match (key, existing) {
+      (Some(key), Some(du)) => (key, du),
+      // a du out of order falls back to the full decode for its msap
+      _ => {
+        let du = DataUnit::decode(protocol, buf)?;
+        let key = NeighborKey::new(protocol, info.scope, du.msap().map(Msap::to_static), &info.source);
+        (key, StoredDu::raw_with_ttl(protocol, buf, du.time_to_live()))
+      }
+    };
+
+    self.insert(key, info, du).await;
+    Ok(())
+  }
+
+  async fn insert(&self, key: NeighborKey, info: FrameInfo, du: StoredDu) {
+    let mut inner = self.inner.neighbors.write().await;
+    let event = self.insert_locked(&mut inner, key, info, du);
+    drop(inner);
+    if let Some((kind, entry)) = event {
+      self.emit(kind, entry);
+    }
+  }
+
+  // the caller holds the write lock, and emits the event once it's let go of
+  fn insert_locked(
+    &self,
+    inner: &mut HashMap<NeighborKey, Neighbor>,
+    key: NeighborKey,
+    info: FrameInfo,
+    du: StoredDu,
+  ) -> Option<(NeighborEventKind, NeighborEntry)> {
+    // aged from when the frame was captured, however long it waited to be processed
+    let now = Instant::now();
+    let delay = info.timestamp.and_then(|x| SystemTime::now().duration_since(x).ok());
+    let mut first_detection_time = delay.and_then(|x| now.checked_sub(x)).unwrap_or(now);
+    let last_detection_time = first_detection_time;
+
+    let ttl = self.hold_time(du.time_to_live());
+    let existing = inner.remove(&key);
+    let changed = existing.as_ref().is_none_or(|entry| {
+      entry.source != info.source || entry.vlans != info.vlans || !unchanged_stored(&entry.du, &du)
+    });
+    let action = self.rx_frame(key.scope, ttl, changed);
+
+    if action == RxAction::Delete {
+      // mibDeleteObjects, a shutdown lldpdu takes the neighbor out right away
+      let entry = existing?;
+      entry.timeout_handle.abort();
+      info!(protocol = ?key.protocol, source = %info.source, remote_index = entry.remote_index, "neighbor shut down");
+      let counters = &self.inner.counters;
+      counters.changed(Some(&counters.deletes));
+      return Some((NeighborEventKind::Expired, entry.to_entry(&key, &self.inner.local_port)));
+    }
+
+    let mut changes = VecDeque::new();
+    let (remote_index, event_kind) = if let Some(entry) = existing {
+      first_detection_time = entry.first_detection_time;
+      changes = entry.changes;
+      let limit = self.inner.config.change_history;
+      if limit > 0 {
+        let now = SystemTime::now();
+        changes.extend(
+          FieldChange::diff(&entry.source, entry.du.get(), &info.source, du.get())
+            .into_iter()
+            .map(|x| (now, x)),
+        );
+        changes.drain(..changes.len().saturating_sub(limit));
+      }
+      entry.timeout_handle.abort();
+      // a refresh only restarts the timer, it's an update if anything besides the ttl changed
+      if action == RxAction::Refresh {
+        debug!(protocol = ?key.protocol, source = %info.source, remote_index = entry.remote_index, "refreshed existing neighbor");
+        (entry.remote_index, None)
+      } else {
+        debug!(protocol = ?key.protocol, source = %info.source, remote_index = entry.remote_index, "received update for existing neighbor");
+        self.inner.counters.changed(None);
+        (entry.remote_index, Some(NeighborEventKind::Updated))
+      }
+    } else {
+      let remote_index = self.next_remote_index();
+      info!(protocol = ?key.protocol, source = %info.source, remote_index, "discovered new neighbor");
+      self.inner.counters.changed(Some(&self.inner.counters.inserts));
+      (remote_index, Some(NeighborEventKind::Discovered))
+    };
+
+    let interface = self.clone();
+    let key_clone = key.clone();
+    let span = span!(Level::DEBUG, "neighbor_timeout");
+    let timeout = tokio::task::spawn(
+      async move {
+        tokio::time::sleep_until((last_detection_time + Duration::from_secs(ttl as _)).into()).await;
+        info!(protocol = ?key_clone.protocol, id = ?key_clone.id, "neighbor timed out");
+        let removed = interface.inner.neighbors.write().await.remove(&key_clone);
+        if let Some(neighbor) = removed {
+          interface.rx_info_age(key_clone.scope);
+          let counters = &interface.inner.counters;
+          counters.changed(Some(&counters.deletes));
+          stats::incr(&counters.ageouts);
+          let entry = neighbor.to_entry(&key_clone, &interface.inner.local_port);
+          interface.emit(NeighborEventKind::Expired, entry);
+        }
+      }
+      .instrument(span),
+    );
+
+    let neighbor = Neighbor {
+      source: info.source,
+      remote_index,
+      vlans: info.vlans,
+      first_detection_time,
+      last_detection_time,
+      capture_time: info.timestamp,
+      timeout_handle: timeout.abort_handle(),
+      du,
+      changes,
+    };
+    let entry = neighbor.to_entry(&key, &self.inner.local_port);
+    inner.insert(key, neighbor);
+    event_kind.map(|kind| (kind, entry))
+  }
+
+  fn hold_time(&self, ttl: u16) -> u16 {
+    let config = &self.inner.config;
+    if ttl == 0 {
+      return 0;
+    }
+    let ttl = config.hold_time.unwrap_or(ttl);
+    let ttl = config.max_ttl.map_or(ttl, |max| ttl.min(max));
+    config.min_ttl.map_or(ttl, |min| ttl.max(min))
+  }
+
+  // captures go through handle_frames, this is for feeding single frames in tests
+  #[cfg(test)]
+  pub(crate) async fn handle_frame(&self, frame: &[u8], wire_len: usize, timestamp: Option<SystemTime>) {
+    if let Some((key, info, du)) = self.receive(frame, wire_len, timestamp) {
+      self.insert(key, info, du).await;
+    }
+  }
+
+  // a whole read's worth of frames goes into the table under one lock, and their events go out once it's released
+  pub(crate) async fn handle_frames(&self, frames: &[Frame]) {
+    let received: Vec<_> = frames
+      .iter()
+      .filter_map(|x| self.receive(&x.data, x.wire_len, x.timestamp))
+      .collect();
+    if received.is_empty() {
+      return;
+    }
+
+    let mut inner = self.inner.neighbors.write().await;
+    let events: Vec<_> = received
+      .into_iter()
+      .filter_map(|(key, info, du)| self.insert_locked(&mut inner, key, info, du))
+      .collect();
+    drop(inner);
+    for (kind, entry) in events {
+      self.emit(kind, entry);
+    }
+  }
+
+  // everything short of touching the table: counting, mirroring, filtering and decoding
+  fn receive(
+    &self,
+    frame: &[u8],
+    wire_len: usize,
+    timestamp: Option<SystemTime>,
+  ) -> Option<(NeighborKey, FrameInfo, StoredDu)> {
+    stats::incr(&self.inner.counters.frames_received);
+
+    if let Some(mirror) = &self.inner.mirror {
+      let snaplen = self.buffer_size() as u32;
+      let result = mirror.lock().unwrap().write(
+        &self.inner.local_port,
+        snaplen,
+        frame,
+        wire_len,
+        timestamp.unwrap_or_else(SystemTime::now),
+      );
+      if let Err(err) = result {
+        warn!(%err, "failed to mirror frame");
+      }
+    }
+
+    // a partial du would decode as one missing its last tlvs, so it's never looked at
+    if frame.len() < wire_len {
+      stats::incr(&self.inner.counters.frames_truncated);
+      let interface = &self.inner.local_port.name;
+      if self.inner.config.grow_buffer {
+        self.inner.grown_buffer_size.fetch_max(wire_len, Ordering::Relaxed);
+        warn!(
+          interface,
+          caplen = frame.len(),
+          wire_len,
+          "dropping truncated frame, the buffer grows to fit it when the capture is reopened"
+        );
+      } else {
+        warn!(
+          interface,
+          caplen = frame.len(),
+          wire_len,
+          "dropping truncated frame, consider raising the buffer size"
+        );
+      }
+      return None;
+    }
+
+    let (mut info, protocol, payload) = split_frame(frame)?;
+    info.timestamp = timestamp;
+
+    // frames the filter let through before it was swapped, or that a source without one couldn't drop
+    if !self.inner.rx_state.borrow().accepts(protocol, &info) {
+      return None;
+    }
+
+    if !self.rx_enabled(info.scope) {
+      debug!(scope = ?info.scope, "receive disabled for scope");
+      return None;
+    }
+
+    if self.inner.config.max_du_size.is_some_and(|max| payload.len() > max) {
+      stats::incr(&self.inner.counters.drops);
+      debug!(len = payload.len(), "dropping oversized du");
+      return None;
+    }
+
+    let (mut du, report) = match DataUnit::decode_with_report(protocol, payload) {
+      Ok(x) => x,
+      Err(err) => {
+        warn!(%err, "failed to decode du");
+        return None;
+      }
+    };
+
+    // unknown tlvs aren't discarded as far as the mib is concerned, they're just not understood
+    stats::add(&self.inner.counters.tlvs_discarded, report.tlvs_errored);
+    stats::add(&self.inner.counters.tlvs_unrecognized, report.tlvs_unknown);
+
+    match self.inner.config.storage {
+      DuStorage::Raw => {
+        // the du decoded above already has everything the key and the stored copy need
+        let msap = du.msap().map(Msap::to_static);
+        let stored = StoredDu::raw_with_ttl(protocol, payload, du.time_to_live());
+        Some((NeighborKey::new(protocol, info.scope, msap, &info.source), info, stored))
+      }
+      DuStorage::Decoded => {
+        if let (DataUnit::Lldp(du), Some(max)) = (&mut du, self.inner.config.max_unknown_tlv_bytes) {
+          let mut total = 0;
+          du.org.custom.retain(|x| {
+            total += x.data.len();
+            total <= max
+          });
+        }
+        let du = du.to_static();
+        let key = NeighborKey::new(protocol, info.scope, du.msap(), &info.source);
+        Some((key, info, du.into()))
+      }
+    }
+  }
+}
+
+#[tokio::test]
+async fn remote_index_reused_on_refresh() {
+  let du = |device_id: &'static str| {
+    DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+      device_id: Some(device_id.into()),
+      ..crate::fixtures::cdp_du()
+    })
+  };
+
+  let interface = Interface::default();
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1])), du("a"))
+    .await;
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 2])), du("b"))
+    .await;
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1])), du("a2"))
+    .await;
+
+  let neighbors = interface.neighbors().await;
+  assert_eq!(neighbors.len(), 2);
+  assert_eq!(neighbors[0].remote_index, 1);
+  assert_eq!(neighbors[0].du.system_name().map(|x| x.as_ref()), Some("a2"));
+  assert_eq!(neighbors[1].remote_index, 2);
+}
+
+#[tokio::test]
+async fn sorts_by_identity() {
+  let du = |name: &str, port: &str| {
+    DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+      device_id: Some(name.to_string().into()),
+      port_id: Some(port.to_string().into()),
+      ..crate::fixtures::cdp_du()
+    })
+  };
+
+  let interface = Interface::default();
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1])), du("a", "Gi2"))
+    .await;
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 2])), du("b", "Gi1"))
+    .await;
+
+  let mut neighbors = interface.neighbors().await;
+  neighbors.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+  let names: Vec<_> = neighbors
+    .iter()
+    .map(|x| x.du.system_name().unwrap().to_string())
+    .collect();
+  assert_eq!(names, ["b", "a"]);
+}
+
+#[tokio::test]
+async fn events_carry_local_port() {
+  let interface = Interface::new(LocalPort::new("en0"));
+  let mut events = interface.subscribe();
+
+  let du = DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+    device_id: Some("a".into()),
+    ..crate::fixtures::cdp_du()
+  });
+  let info = FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1]));
+  interface.insert_du(info.clone(), du.clone()).await;
+  // refreshes, the second with only a different ttl, aren't updates
+  interface.insert_du(info.clone(), du.clone()).await;
+  let DataUnit::Cdp(cdp) = du else { unreachable!() };
+  let refreshed = lldp_parser::cdp::DataUnit {
+    time_to_live: 120,
+    ..cdp.clone()
+  };
+  interface.insert_du(info.clone(), refreshed.into()).await;
+  let changed = lldp_parser::cdp::DataUnit {
+    device_id: Some("b".into()),
+    ..cdp
+  };
+  interface.insert_du(info, changed.into()).await;
+
+  let event = events.recv().await.unwrap();
+  assert_eq!(event.kind, NeighborEventKind::Discovered);
+  assert_eq!(event.neighbor.local_port.name, "en0");
+  let event = events.recv().await.unwrap();
+  assert_eq!(event.kind, NeighborEventKind::Updated);
+  assert_eq!(event.neighbor.du.system_name().map(|x| x.as_ref()), Some("b"));
+}
+
+#[tokio::test]
+async fn raw_storage_decodes_lazily() {
+  use lldp_parser::lldp::du::DataUnit as LldpDu;
+
+  let mut buf = Vec::new();
+  LldpDu {
+    system_name: Some("switch".into()),
+    ..crate::fixtures::lldp_du()
+  }
+  .encode(&mut buf);
+
+  let interface = Interface::default();
+  let source = FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1]));
+  interface
+    .insert_raw(source.clone(), Protocol::Lldp, &buf)
+    .await
+    .unwrap();
+  interface.insert_raw(source, Protocol::Lldp, &buf).await.unwrap();
+
+  let neighbors = interface.neighbors().await;
+  assert_eq!(neighbors.len(), 1);
+  assert_eq!(neighbors[0].du.raw_bytes(), Some(&buf[..]));
+  assert_eq!(neighbors[0].du.time_to_live(), 120);
+  assert_eq!(neighbors[0].du.system_name().map(|x| x.as_ref()), Some("switch"));
+
+  assert!(interface
+    .insert_raw(
+      FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 2])),
+      Protocol::Lldp,
+      &buf[..4]
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn scopes_are_separate_neighbors() {
+  let du = DataUnit::Cdp(crate::fixtures::cdp_du());
+
+  let interface = Interface::default();
+  for scope in [Some(Scope::NearestBridge), Some(Scope::NearestCustomerBridge), None] {
+    let info = FrameInfo {
+      scope,
+      ..FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1]))
+    };
+    interface.insert_du(info, du.clone()).await;
+  }
+
+  let neighbors = interface.neighbors().await;
+  assert_eq!(neighbors.len(), 3);
+  assert_eq!(neighbors[1].scope, Some(Scope::NearestCustomerBridge));
+}
+
+#[tokio::test]
+async fn disabling_agent_purges_scope() {
+  let mut frame = vec![0x01, 0x80, 0xc2, 0x00, 0x00, 0x03, 0, 0, 0, 0, 0, 1, 0x88, 0xcc];
+  crate::fixtures::lldp_du().encode(&mut frame);
+
+  let interface = Interface::default();
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert_eq!(interface.neighbors().await.len(), 1);
+
+  let disabled = AgentConfig {
+    admin_status: crate::AdminStatus::TxOnly,
+    ..Default::default()
+  };
+  interface.set_agent(Scope::NearestNonTpmrBridge, disabled).await;
+  assert!(interface.neighbors().await.is_empty());
+
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert!(interface.neighbors().await.is_empty());
+}
+
+#[tokio::test]
+async fn counts_table_changes() {
+  let du = |time_to_live| {
+    DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+      time_to_live,
+      ..crate::fixtures::cdp_du()
+    })
+  };
+
+  let interface = Interface::default();
+  assert_eq!(interface.stats().last_change, None);
+  let info = FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1]));
+  interface.insert_du(info.clone(), du(180)).await;
+  interface.insert_du(info.clone(), du(180)).await;
+  let mut events = interface.subscribe();
+  interface.insert_du(info, du(0)).await;
+  assert_eq!(events.recv().await.unwrap().kind, NeighborEventKind::Expired);
+
+  let stats = interface.stats();
+  assert_eq!((stats.inserts, stats.deletes, stats.ageouts), (1, 1, 0));
+  assert!(stats.last_change.is_some());
+}
+
+#[tokio::test]
+async fn lldp_neighbors_keyed_by_msap() {
+  use lldp_parser::lldp::tlv::{ChassisId, PortId};
+
+  let du = |port: &'static str| {
+    DataUnit::Lldp(LldpDu {
+      chassis_id: ChassisId::Local("stack".into()),
+      port_id: PortId::Local(port.into()),
+      ..crate::fixtures::lldp_du()
+    })
+  };
+
+  // a stack member taking over keeps the msap but not the mac, two ports behind one mac are two neighbors
+  let interface = Interface::default();
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1])), du("1/1"))
+    .await;
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 2])), du("1/1"))
+    .await;
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 2])), du("1/2"))
+    .await;
+
+  let neighbors = interface.neighbors().await;
+  assert_eq!(neighbors.len(), 2);
+  assert_eq!(neighbors[0].source, MacAddress([0, 0, 0, 0, 0, 2]));
+  assert_eq!(neighbors[0].msap().unwrap().to_string(), "stack / 1/1");
+  assert_eq!(neighbors[1].msap().unwrap().port_id, PortId::Local("1/2".into()));
+}
+
+#[tokio::test]
+async fn keeps_change_history() {
+  use crate::NeighborField;
+
+  let du = |name: &'static str, port: &'static str| {
+    DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+      device_id: Some(name.into()),
+      port_id: Some(port.into()),
+      ..crate::fixtures::cdp_du()
+    })
+  };
+
+  let config = InterfaceConfig {
+    change_history: 2,
+    ..Default::default()
+  };
+  let interface = Interface::with_config(LocalPort::default(), config);
+  let info = FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1]));
+  interface.insert_du(info.clone(), du("a", "Gi1")).await;
+  interface.insert_du(info.clone(), du("a", "Gi1")).await;
+  assert!(interface.neighbors().await[0].changes.is_empty());
+
+  interface.insert_du(info.clone(), du("b", "Gi2")).await;
+  interface.insert_du(info, du("c", "Gi2")).await;
+  let changes: Vec<_> = interface.neighbors().await[0]
+    .changes
+    .iter()
+    .map(|(_, x)| (x.field, x.new.clone().unwrap()))
+    .collect();
+  assert_eq!(
+    changes,
+    [
+      (NeighborField::PortId, "Gi2".to_string()),
+      (NeighborField::SystemName, "c".to_string())
+    ]
+  );
+
+  // off by default
+  let interface = Interface::default();
+  let info = FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1]));
+  interface.insert_du(info.clone(), du("a", "Gi1")).await;
+  interface.insert_du(info, du("b", "Gi1")).await;
+  assert!(interface.neighbors().await[0].changes.is_empty());
+}
+
+#[test]
+fn clamps_ttl() {
+  let interface = |config| Interface::with_config(LocalPort::default(), config);
+
+  let clamped = interface(InterfaceConfig {
+    min_ttl: Some(10),
+    max_ttl: Some(300),
+    ..Default::default()
+  });
+  assert_eq!(clamped.hold_time(65535), 300);
+  assert_eq!(clamped.hold_time(1), 10);
+  assert_eq!(clamped.hold_time(120), 120);
+  assert_eq!(clamped.hold_time(0), 0);
+
+  let held = interface(InterfaceConfig {
+    hold_time: Some(30),
+    ..Default::default()
+  });
+  assert_eq!(held.hold_time(120), 30);
+  assert_eq!(Interface::default().hold_time(65535), DEFAULT_MAX_TTL);
+}
+
+#[tokio::test]
+async fn toggles_protocols_at_runtime() {
+  let du = lldp_parser::cdp::DataUnit {
+    device_id: Some("a".into()),
+    ..crate::fixtures::cdp_du()
+  };
+  let frame = crate::cdp_frame(&MacAddress([0, 0, 0, 0, 0, 1]), du);
+
+  let interface = Interface::default();
+  interface.set_protocols(true, false);
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert!(interface.neighbors().await.is_empty());
+
+  interface.set_protocols(true, true);
+  interface.pause();
+  assert!(interface.is_paused());
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert!(interface.neighbors().await.is_empty());
+
+  interface.resume();
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert_eq!(interface.neighbors().await.len(), 1);
+
+  let filter = RxState {
+    cdp: false,
+    ..Default::default()
+  }
+  .apply(&FilterSpec::default());
+  assert!(filter.lldp && !filter.cdp && filter.fdp);
+  let paused = RxState {
+    paused: true,
+    ..Default::default()
+  };
+  assert!(paused.apply(&FilterSpec::default()).is_empty());
+}
+
+#[tokio::test]
+async fn swaps_filter_at_runtime() {
+  let mut frame = vec![
+    0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 0, 0, 0, 0, 0, 1, 0x81, 0x00, 0x00, 0x64, 0x88, 0xcc,
+  ];
+  crate::fixtures::lldp_du().encode(&mut frame);
+
+  let interface = Interface::default();
+  interface.set_filter(FilterSpec {
+    vlan_ok: false,
+    ..Default::default()
+  });
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert!(interface.neighbors().await.is_empty());
+
+  interface.set_filter(FilterSpec::default());
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert_eq!(interface.neighbors().await.len(), 1);
+  assert_eq!(interface.stats().frames_received, 2);
+
+  let state = RxState {
+    cdp: false,
+    filter: Some(FilterSpec {
+      sonmp: false,
+      ..Default::default()
+    }),
+    ..Default::default()
+  };
+  let filter = state.apply(&FilterSpec::default());
+  assert!(filter.lldp && !filter.cdp && !filter.sonmp);
+}
+
+#[tokio::test]
+async fn enforces_capture_vlan_filter() {
+  let frame = |tag: &[u8], port: &'static str| {
+    let mut frame = vec![0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 0, 0, 0, 0, 0, 1];
+    frame.extend_from_slice(tag);
+    frame.extend_from_slice(&[0x88, 0xcc]);
+    lldp_parser::lldp::du::DataUnit {
+      port_id: lldp_parser::lldp::tlv::PortId::Local(port.into()),
+      ..crate::fixtures::lldp_du()
+    }
+    .encode(&mut frame);
+    Frame::new(frame)
+  };
+
+  // af_packet hands the tagged frame over however the kernel filter was compiled
+  let frames = [frame(&[0x81, 0x00, 0x00, 0x64], "tagged"), frame(&[], "untagged")];
+  let interface = Interface::default();
+  let filter = FilterSpec {
+    vlan_ok: false,
+    ..Default::default()
+  };
+  interface
+    .run_live(crate::capture::VecSource(frames.into()), &filter)
+    .await
+    .unwrap();
+
+  let neighbors = interface.neighbors().await;
+  assert_eq!(neighbors.len(), 1);
+  assert!(neighbors[0].vlans.is_empty());
+}
+
+#[tokio::test]
+async fn enforces_capture_source_lists() {
+  let frame = |source| {
+    let mut frame = vec![0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 2, 0, 0, 0, 0, source, 0x88, 0xcc];
+    crate::fixtures::lldp_du().encode(&mut frame);
+    Frame::new(frame)
+  };
+
+  // a source that can't filter in the kernel hands over every frame
+  let frames = [frame(1), frame(2), frame(3)];
+  let interface = Interface::default();
+  let filter = FilterSpec {
+    src_allowlist: vec![MacAddress([2, 0, 0, 0, 0, 1]), MacAddress([2, 0, 0, 0, 0, 2])],
+    src_denylist: vec![MacAddress([2, 0, 0, 0, 0, 2])],
+    ..Default::default()
+  };
+  interface
+    .run_live(crate::capture::VecSource(frames.into()), &filter)
+    .await
+    .unwrap();
+
+  let neighbors = interface.neighbors().await;
+  assert_eq!(neighbors.len(), 1);
+  assert_eq!(neighbors[0].source, MacAddress([2, 0, 0, 0, 0, 1]));
+}
+
+#[tokio::test]
+async fn bounds_du_size() {
+  use lldp_parser::lldp::tlv::CustomOrgTlv;
+
+  let mut frame = vec![0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 0, 0, 0, 0, 0, 1, 0x88, 0xcc];
+  let mut du = crate::fixtures::lldp_du();
+  du.org.custom = (0..4)
+    .map(|subtype| CustomOrgTlv {
+      org: [0x00, 0x12, 0xbb],
+      subtype,
+      data: vec![0; 100].into(),
+    })
+    .collect();
+  du.encode(&mut frame);
+
+  let config = InterfaceConfig {
+    max_unknown_tlv_bytes: Some(250),
+    ..Default::default()
+  };
+  let interface = Interface::with_config(LocalPort::default(), config);
+  interface.handle_frame(&frame, frame.len(), None).await;
+  let neighbors = interface.neighbors().await;
+  let DataUnit::Lldp(du) = neighbors[0].du.get() else {
+    panic!("expected an lldp du");
+  };
+  assert_eq!(du.org.custom.len(), 2);
+
+  let config = InterfaceConfig {
+    max_du_size: Some(256),
+    ..Default::default()
+  };
+  let interface = Interface::with_config(LocalPort::default(), config);
+  interface.handle_frame(&frame, frame.len(), None).await;
+  assert!(interface.neighbors().await.is_empty());
+  assert_eq!(interface.stats().drops, 1);
+}
+
+#[tokio::test]
+async fn ages_from_capture_time() {
+  let du = DataUnit::Cdp(crate::fixtures::cdp_du());
+
+  let interface = Interface::default();
+  let captured = SystemTime::now() - Duration::from_secs(5);
+  let info = FrameInfo {
+    timestamp: Some(captured),
+    ..FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1]))
+  };
+  let mut events = interface.subscribe();
+  interface.insert_du(info, du).await;
+
+  let event = events.recv().await.unwrap();
+  assert_eq!(event.neighbor.capture_time, Some(captured));
+  assert!(event.neighbor.last_detection_time.elapsed() >= Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn grows_buffer_after_truncation() {
+  // never parsed, so any bytes will do
+  let frame = [0; 20];
+
+  for grow_buffer in [false, true] {
+    let config = InterfaceConfig {
+      buffer_size: Some(100),
+      grow_buffer,
+      ..Default::default()
+    };
+    let interface = Interface::with_config(LocalPort::default(), config);
+    interface.handle_frame(&frame, 300, None).await;
+    assert_eq!(interface.stats().frames_truncated, 1);
+    assert!(interface.neighbors().await.is_empty());
+    assert_eq!(interface.buffer_size(), if grow_buffer { 300 } else { 100 });
+  }
+}
+
+#[tokio::test]
+async fn handles_frames_in_batches() {
+  use crate::Frame;
+
+  let du = |name: &'static str| lldp_parser::cdp::DataUnit {
+    device_id: Some(name.into()),
+    ..crate::fixtures::cdp_du()
+  };
+  let frame = |source, name| Frame::new(crate::cdp_frame(&MacAddress([0, 0, 0, 0, 0, source]), du(name)));
+
+  let interface = Interface::default();
+  let mut events = interface.subscribe();
+  let frames = [
+    frame(1, "a"),
+    frame(2, "b"),
+    frame(1, "a"),
+    Frame::new(vec![0; 10]),
+    frame(1, "a2"),
+  ];
+  interface.handle_frames(&frames).await;
+
+  let mut kinds = Vec::new();
+  while let Ok(event) = events.try_recv() {
+    kinds.push((event.kind, event.neighbor.source));
+  }
+  let source = |x| MacAddress([0, 0, 0, 0, 0, x]);
+  assert_eq!(
+    kinds,
+    [
+      (NeighborEventKind::Discovered, source(1)),
+      (NeighborEventKind::Discovered, source(2)),
+      (NeighborEventKind::Updated, source(1)),
+    ]
+  );
+  assert_eq!(interface.neighbors().await.len(), 2);
+  assert_eq!(interface.stats().frames_received, 5);
+}
@@ -1,35 +1,126 @@
 use std::{
-  collections::HashMap,
-  fmt::{Debug, Display},
-  io,
   sync::Arc,
-  time::{Duration, Instant, SystemTime},
+  time::{Instant, SystemTime},
 };
 
-use lldp_parser::{DataUnit, Protocol};
-use rawsocket::{bpf::bpf_program, bpf_filter, bsd::tokio::BpfSocket, EthernetPacket};
-use tokio::{sync::RwLock, task::AbortHandle};
-use tracing::{debug, info, instrument, span, warn, Instrument, Level};
-
-#[derive(Clone, PartialEq, Eq, Hash)]
-#[repr(transparent)]
-pub struct MacAddress(pub [u8; 6]);
-
-impl Display for MacAddress {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(
-      f,
-      "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
-      self.0[0], self.0[1], self.0[2], self.0[3], self.0[4], self.0[5]
-    )
-  }
-}
+// the parser is the only copy of the protocol code, users of the agent shouldn't need a second dependency on it
+pub use lldp_parser;
+use lldp_parser::{
+  frame::VlanTag,
+  lldp::{
+    tlv::{ChassisId, PortId},
+    Msap,
+  },
+  DataUnit, Protocol,
+};
 
-impl Debug for MacAddress {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    Display::fmt(self, f)
-  }
-}
+mod local;
+pub use local::LocalPort;
+
+mod change;
+pub use change::{FieldChange, NeighborField};
+
+mod snapshot;
+
+#[cfg(any(feature = "capture", feature = "sync"))]
+mod neighbor;
+#[cfg(any(feature = "capture", feature = "sync"))]
+pub use neighbor::DEFAULT_MAX_TTL;
+pub use snapshot::{NeighborChange, NeighborTableSnapshot, TableDiff};
+
+#[cfg(feature = "capture")]
+mod interface;
+#[cfg(feature = "capture")]
+pub use interface::{Interface, InterfaceConfig};
+
+mod capture;
+// the capture backends used to live at the top level, pcap_file keeps its old path
+pub use capture::{pcap as pcap_file, CaptureError, CaptureStats, Frame, PacketSource};
+
+mod filter;
+pub use filter::{FilterSpec, Insn, MAX_EXTRA_ETHERTYPES};
+
+#[cfg(feature = "capture")]
+mod mirror;
+#[cfg(all(feature = "capture", unix, not(any(target_os = "linux", target_os = "android"))))]
+pub use capture::bsd::BpfSource;
+#[cfg(all(feature = "capture", any(target_os = "linux", target_os = "android")))]
+pub use capture::linux::{
+  AfPacketSink, AfPacketSource, CDP_MULTICAST_GROUP, FDP_MULTICAST_GROUP, LLDP_MULTICAST_GROUPS, SONMP_MULTICAST_GROUPS,
+};
+#[cfg(feature = "capture")]
+pub use capture::replay::{ReplaySource, ReplaySpeed};
+#[cfg(all(windows, feature = "npcap"))]
+pub use capture::windows::NpcapSource;
+#[cfg(all(feature = "xdp", target_os = "linux"))]
+pub use capture::xdp::XdpSource;
+#[cfg(feature = "capture")]
+pub use mirror::MirrorConfig;
+
+mod stored;
+pub use stored::{DuStorage, StoredDu};
+
+#[cfg(feature = "mndp")]
+mod mndp;
+#[cfg(feature = "mndp")]
+pub use mndp::MNDP_PORT;
+
+#[cfg(feature = "capture")]
+mod machine;
+
+mod scope;
+pub use scope::{AdminStatus, AgentConfig, Scope};
+
+#[cfg(feature = "capture")]
+mod agent;
+#[cfg(feature = "capture")]
+pub use agent::Agent;
+
+mod tx;
+pub use tx::{cdp_frame, lldp_frame, CdpTxConfig, CustomTlvError, PacketSink, TlvSelection, TxConfig, TxTlvProvider};
+
+mod system;
+pub use system::LocalSystem;
+
+mod med;
+pub use med::{CivicLocation, MedApplication, MedConfig, NetworkPolicy};
+
+mod power;
+pub use power::{PowerPriority, PowerSource, PowerState, PowerTlvs};
+
+mod select;
+pub use select::InterfaceSelector;
+
+#[cfg(feature = "sync")]
+pub mod sync;
+
+#[cfg(feature = "capture")]
+mod sink;
+#[cfg(feature = "capture")]
+pub use sink::{run_sink, EventSink};
+
+#[cfg(feature = "capture")]
+mod notify;
+#[cfg(feature = "capture")]
+pub use notify::{run_notifications, ChangeNotification, NotificationSink, DEFAULT_NOTIFICATION_INTERVAL};
+
+#[cfg(feature = "sqlite")]
+mod history;
+#[cfg(feature = "sqlite")]
+pub use history::{History, HistoryRecord};
+
+mod mac;
+pub use mac::{MacAddress, MacAddressParseError, MacFormat};
+
+#[cfg(feature = "capture")]
+mod stats;
+#[cfg(feature = "capture")]
+pub use stats::{AgentStats, InterfaceStats};
+
+pub mod prelude;
+
+#[cfg(test)]
+mod fixtures;
 
 pub const LLDP_TYPE: u16 = 0x88CCu16.to_be();
 
@@ -41,138 +132,85 @@ pub struct MacHeader {
   pub ether_type: u16,
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct Interface {
-  inner: Arc<InterfaceInner>,
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrameInfo {
+  pub source: MacAddress,
+  pub scope: Option<Scope>,
+  pub vlans: Vec<VlanTag>,
+  // when the kernel captured the frame, None means when it's inserted
+  pub timestamp: Option<SystemTime>,
 }
 
-#[derive(Debug, Default)]
-struct InterfaceInner {
-  neighbors: RwLock<HashMap<NeighborKey, Neighbor>>,
+impl FrameInfo {
+  pub fn new(source: MacAddress) -> Self {
+    Self {
+      source,
+      scope: None,
+      vlans: Vec::new(),
+      timestamp: None,
+    }
+  }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct NeighborKey {
-  protocol: Protocol,
-  source: MacAddress,
+#[derive(Debug, Clone)]
+pub struct NeighborEntry {
+  pub local_port: Arc<LocalPort>,
+  pub protocol: Protocol,
+  pub scope: Option<Scope>,
+  pub source: MacAddress,
+  pub vlans: Vec<VlanTag>,
+  pub remote_index: u32,
+  pub first_detection_time: Instant,
+  pub last_detection_time: Instant,
+  // the capture timestamp of the last du, for lining it up with a pcap of the same link
+  pub capture_time: Option<SystemTime>,
+  pub du: StoredDu,
+  // oldest first, empty unless the interface keeps a change history
+  pub changes: Vec<(SystemTime, FieldChange)>,
 }
 
-#[derive(Debug)]
-struct Neighbor {
-  first_detection_time: Instant,
-  last_detection_time: Instant,
-  timeout_handle: AbortHandle,
-  du: DataUnit<'static>,
+// orders neighbors by what they are rather than when they showed up, so snapshots diff cleanly.
+// the source and scope come last so two neighbors never compare equal
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NeighborSortKey<'a> {
+  pub interface: &'a str,
+  pub protocol: Protocol,
+  pub chassis_id: Option<&'a ChassisId<'static>>,
+  pub port_id: Option<PortId<'a>>,
+  pub source: &'a MacAddress,
+  pub scope: Option<Scope>,
 }
 
-impl Interface {
-  pub async fn insert_du(&self, source: MacAddress, du: DataUnit<'static>) {
-    let key = NeighborKey {
-      source,
-      protocol: du.protocol(),
-    };
-
-    let mut first_detection_time = Instant::now();
-    let last_detection_time = first_detection_time;
-
-    let mut inner = self.inner.neighbors.write().await;
-    if let Some(entry) = inner.remove(&key) {
-      first_detection_time = entry.first_detection_time;
-      entry.timeout_handle.abort();
-      debug!(protocol = ?key.protocol, source = %key.source, "received update for existing neighbor");
-    } else {
-      info!(protocol = ?key.protocol, source = %key.source, "discovered new neighbor");
-    }
-
-    let ttl = du.time_to_live();
-    let interface = self.clone();
-    let key_clone = key.clone();
-    let span = span!(Level::DEBUG, "neighbor_timeout");
-    let timeout = tokio::task::spawn(
-      async move {
-        tokio::time::sleep(Duration::from_secs(ttl as _)).await;
-        info!(protocol = ?key_clone.protocol, source = %key_clone.source, "neighbor timed out");
-        interface.inner.neighbors.write().await.remove(&key_clone);
-      }
-      .instrument(span),
-    );
-
-    inner.insert(
-      key,
-      Neighbor {
-        first_detection_time,
-        last_detection_time,
-        timeout_handle: timeout.abort_handle(),
-        du,
-      },
-    );
+impl NeighborEntry {
+  pub fn msap(&self) -> Option<Msap<'static>> {
+    self.du.get().msap()
   }
 
-  #[instrument(skip_all, fields(interface = intf))]
-  pub async fn start_socket(&self, intf: &str, lldp: bool, cdp: bool) -> io::Result<()> {
-    let filter = if cdp && lldp {
-      bpf_filter!(
-        { 0x20, 0, 0, 0x00000002 },
-        { 0x15, 0, 2, 0x0ccccccc },
-        { 0x28, 0, 0, 0x00000000 },
-        { 0x15, 2, 0, 0x00000100 },
-        { 0x28, 0, 0, 0x0000000c },
-        { 0x15, 0, 1, 0x000088cc },
-        { 0x6, 0, 0, 0x00080000 },
-        { 0x6, 0, 0, 0x00000000 },
-      )
-    } else if cdp {
-      bpf_filter!(
-        { 0x20, 0, 0, 0x00000002 },
-        { 0x15, 0, 3, 0x0ccccccc },
-        { 0x28, 0, 0, 0x00000000 },
-        { 0x15, 0, 1, 0x00000100 },
-        { 0x6, 0, 0, 0x00080000 },
-        { 0x6, 0, 0, 0x00000000 },
-      )
-    } else if lldp {
-      bpf_filter!(
-        { 0x28, 0, 0, 0x0000000c },
-        { 0x15, 0, 1, 0x000088cc },
-        { 0x6, 0, 0, 0x00080000 },
-        { 0x6, 0, 0, 0x00000000 },
-      )
-    } else {
-      return Ok(());
-    };
-
-    let mut buf = [0; 1500];
-    let sock = BpfSocket::open(intf, Some(buf.len() as _))?;
-    sock.set_immediate(true)?;
-    sock.set_read_filter(filter)?;
-
-    loop {
-      for packet in sock.read_iter(&mut buf).await.unwrap() {
-        let eth = EthernetPacket::try_decode(packet.capture).unwrap();
-        let du: DataUnit = if eth.header.ether_type == 0xcc88 {
-          match lldp_parser::lldp::du::DataUnit::decode(eth.payload) {
-            Ok(x) => x.into(),
-            Err(err) => {
-              warn!(%err, "failed to decode lldp du");
-              continue;
-            }
-          }
-        } else if eth.header.ether_type == 49665 {
-          match lldp_parser::cdp::DataUnit::decode(&eth.payload[8..]) {
-            Ok(x) => x.into(),
-            Err(err) => {
-              warn!(%err, "failed to decode cdp du");
-              continue;
-            }
-          }
-        } else {
-          continue;
-        };
-
-        self
-          .insert_du(MacAddress(eth.header.source_mac.0), du.to_static())
-          .await;
-      }
+  pub fn sort_key(&self) -> NeighborSortKey<'_> {
+    let du = self.du.get();
+    NeighborSortKey {
+      interface: &self.local_port.name,
+      protocol: self.protocol,
+      chassis_id: match du {
+        DataUnit::Lldp(x) => Some(&x.chassis_id),
+        _ => None,
+      },
+      port_id: du.port_id(),
+      source: &self.source,
+      scope: self.scope,
     }
   }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NeighborEventKind {
+  Discovered,
+  Updated,
+  Expired,
+}
+
+#[derive(Debug, Clone)]
+pub struct NeighborEvent {
+  pub kind: NeighborEventKind,
+  pub neighbor: NeighborEntry,
+}
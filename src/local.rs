@@ -0,0 +1,207 @@
+#[cfg(unix)]
+use std::ffi::{CStr, CString};
+#[cfg(unix)]
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::{io, net::IpAddr};
+
+use crate::MacAddress;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LocalPort {
+  pub name: String,
+  pub ifindex: Option<u32>,
+  pub mac_address: Option<MacAddress>,
+  pub mtu: Option<u32>,
+  pub description: Option<String>,
+  pub addresses: Vec<IpAddr>,
+}
+
+impl LocalPort {
+  pub fn new(name: impl Into<String>) -> Self {
+    Self {
+      name: name.into(),
+      ..Default::default()
+    }
+  }
+
+  #[cfg(windows)]
+  pub fn from_os(name: &str) -> io::Result<Self> {
+    Ok(Self::new(name))
+  }
+
+  #[cfg(unix)]
+  pub fn from_os(name: &str) -> io::Result<Self> {
+    let c_name = CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let ifindex = unsafe { libc::if_nametoindex(c_name.as_ptr()) };
+    if ifindex == 0 {
+      return Err(io::Error::last_os_error());
+    }
+
+    let mut mac_address = None;
+    let mut mtu = None;
+    let mut addresses = Vec::new();
+    for_each_ifaddr(|ifa_name, ifa| {
+      if ifa_name != name {
+        return;
+      }
+
+      if let Some(info) = unsafe { link_info(ifa) } {
+        (mac_address, mtu) = info;
+      } else if let Some(addr) = unsafe { ip_addr(ifa.ifa_addr) } {
+        addresses.push(addr);
+      }
+    })?;
+
+    Ok(Self {
+      name: name.into(),
+      ifindex: Some(ifindex),
+      mac_address,
+      mtu,
+      description: os_description(name),
+      addresses,
+    })
+  }
+}
+
+#[cfg(unix)]
+pub(crate) fn for_each_ifaddr(mut f: impl FnMut(&str, &libc::ifaddrs)) -> io::Result<()> {
+  let mut addrs = std::ptr::null_mut();
+  if unsafe { libc::getifaddrs(&mut addrs) } != 0 {
+    return Err(io::Error::last_os_error());
+  }
+
+  let mut cur = addrs;
+  while !cur.is_null() {
+    let ifa = unsafe { &*cur };
+    cur = ifa.ifa_next;
+
+    if ifa.ifa_addr.is_null() {
+      continue;
+    }
+
+    let name = unsafe { CStr::from_ptr(ifa.ifa_name) }.to_string_lossy();
+    f(&name, ifa);
+  }
+
+  unsafe { libc::freeifaddrs(addrs) };
+  Ok(())
+}
+
+#[cfg(unix)]
+pub(crate) unsafe fn ip_addr(addr: *const libc::sockaddr) -> Option<IpAddr> {
+  match (*addr).sa_family as i32 {
+    libc::AF_INET => {
+      let addr = &*(addr as *const libc::sockaddr_in);
+      Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr))))
+    }
+    libc::AF_INET6 => {
+      let addr = &*(addr as *const libc::sockaddr_in6);
+      Some(IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr)))
+    }
+    _ => None,
+  }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) unsafe fn link_info(ifa: &libc::ifaddrs) -> Option<(Option<MacAddress>, Option<u32>)> {
+  if (*ifa.ifa_addr).sa_family as i32 != libc::AF_PACKET {
+    return None;
+  }
+
+  let addr = &*(ifa.ifa_addr as *const libc::sockaddr_ll);
+  let mac = (addr.sll_halen == 6).then(|| MacAddress(addr.sll_addr[0..6].try_into().unwrap()));
+
+  let name = CStr::from_ptr(ifa.ifa_name).to_string_lossy();
+  let mtu = sysfs_attr(&name, "mtu").and_then(|x| x.parse().ok());
+
+  Some((mac, mtu))
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+pub(crate) unsafe fn link_info(ifa: &libc::ifaddrs) -> Option<(Option<MacAddress>, Option<u32>)> {
+  if (*ifa.ifa_addr).sa_family as i32 != libc::AF_LINK {
+    return None;
+  }
+
+  // equivalent of the LLADDR() macro, the address follows the interface name in sdl_data
+  let addr = &*(ifa.ifa_addr as *const libc::sockaddr_dl);
+  let mac = (addr.sdl_alen == 6).then(|| {
+    let data = (addr.sdl_data.as_ptr() as *const u8).add(addr.sdl_nlen as usize);
+    MacAddress(std::slice::from_raw_parts(data, 6).try_into().unwrap())
+  });
+
+  // for AF_LINK entries ifa_data points at the interface's struct if_data
+  let mtu = (!ifa.ifa_data.is_null()).then(|| (*(ifa.ifa_data as *const libc::if_data)).ifi_mtu);
+
+  Some((mac, mtu))
+}
+
+#[cfg(unix)]
+pub(crate) fn os_interfaces() -> io::Result<Vec<String>> {
+  let mut out = Vec::new();
+  for_each_ifaddr(|name, _| {
+    if !out.iter().any(|x| x == name) {
+      out.push(name.to_string());
+    }
+  })?;
+  out.sort();
+  Ok(out)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn os_interfaces() -> io::Result<Vec<String>> {
+  Err(io::Error::new(
+    io::ErrorKind::Unsupported,
+    "listing interfaces isn't supported on this platform",
+  ))
+}
+
+// the ones worth capturing on when nobody said which
+#[cfg(all(feature = "capture", unix))]
+pub(crate) fn os_up_interfaces() -> io::Result<Vec<String>> {
+  let mut out = Vec::new();
+  for_each_ifaddr(|name, ifa| {
+    let flags = ifa.ifa_flags as libc::c_int;
+    if flags & libc::IFF_UP != 0 && flags & libc::IFF_LOOPBACK == 0 && !out.iter().any(|x| x == name) {
+      out.push(name.to_string());
+    }
+  })?;
+  out.sort();
+  Ok(out)
+}
+
+#[cfg(all(feature = "capture", not(unix)))]
+pub(crate) fn os_up_interfaces() -> io::Result<Vec<String>> {
+  os_interfaces()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn sysfs_attr(name: &str, attr: &str) -> Option<String> {
+  let value = std::fs::read_to_string(format!("/sys/class/net/{name}/{attr}")).ok()?;
+  let value = value.trim();
+  (!value.is_empty()).then(|| value.to_string())
+}
+
+// ports enslaved to a bond or bridge show up as lower_<name> links of the master
+#[cfg(all(feature = "capture", any(target_os = "linux", target_os = "android")))]
+pub(crate) fn os_lower_devices(name: &str) -> io::Result<Vec<String>> {
+  let mut out = Vec::new();
+  for entry in std::fs::read_dir(format!("/sys/class/net/{name}"))? {
+    let entry = entry?;
+    if let Some(lower) = entry.file_name().to_str().and_then(|x| x.strip_prefix("lower_")) {
+      out.push(lower.to_string());
+    }
+  }
+  out.sort();
+  Ok(out)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn os_description(name: &str) -> Option<String> {
+  sysfs_attr(name, "ifalias")
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn os_description(_name: &str) -> Option<String> {
+  None
+}
@@ -0,0 +1,239 @@
+use std::{
+  array::TryFromSliceError,
+  fmt::{Debug, Display},
+  str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct MacAddress(pub [u8; 6]);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MacFormat {
+  // aa:bb:cc:dd:ee:ff
+  #[default]
+  Colon,
+  // aa-bb-cc-dd-ee-ff
+  Dash,
+  // aabb.ccdd.eeff, the way cisco prints them
+  Dotted,
+  // aabbccddeeff
+  Bare,
+}
+
+impl MacAddress {
+  pub fn format(&self, format: MacFormat) -> String {
+    let hex: Vec<_> = self.0.iter().map(|x| format!("{x:02x}")).collect();
+    match format {
+      MacFormat::Colon => hex.join(":"),
+      MacFormat::Dash => hex.join("-"),
+      MacFormat::Dotted => hex.chunks(2).map(|x| x.concat()).collect::<Vec<_>>().join("."),
+      MacFormat::Bare => hex.concat(),
+    }
+  }
+
+  pub fn is_multicast(&self) -> bool {
+    self.0[0] & 0x01 != 0
+  }
+
+  pub fn is_locally_administered(&self) -> bool {
+    self.0[0] & 0x02 != 0
+  }
+
+  // ff:fe in the middle, ipv6 interface ids additionally flip the locally administered bit
+  pub fn to_eui64(&self) -> [u8; 8] {
+    let [a, b, c, d, e, f] = self.0;
+    [a, b, c, 0xff, 0xfe, d, e, f]
+  }
+
+  // big endian in the low 48 bits
+  pub fn to_u64(&self) -> u64 {
+    let mut buf = [0; 8];
+    buf[2..].copy_from_slice(&self.0);
+    u64::from_be_bytes(buf)
+  }
+
+  // anything above the low 48 bits is dropped
+  pub fn from_u64(value: u64) -> Self {
+    Self(value.to_be_bytes()[2..].try_into().unwrap())
+  }
+}
+
+#[cfg(feature = "oui")]
+impl MacAddress {
+  // locally administered and multicast addresses don't carry an oui
+  pub fn vendor(&self) -> Option<&'static str> {
+    if self.is_multicast() || self.is_locally_administered() {
+      return None;
+    }
+    lldp_parser::oui::vendor([self.0[0], self.0[1], self.0[2]])
+  }
+}
+
+impl Display for MacAddress {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.format(MacFormat::Colon))
+  }
+}
+
+impl Debug for MacAddress {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    Display::fmt(self, f)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MacAddressParseError {
+  #[error("expected aa:bb:cc:dd:ee:ff, aa-bb-cc-dd-ee-ff or aabb.ccdd.eeff")]
+  InvalidFormat,
+  #[error("invalid hex digit")]
+  InvalidDigit,
+}
+
+// any of the MacFormat forms, in either case
+impl FromStr for MacAddress {
+  type Err = MacAddressParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    // the length checks below count bytes, so a multibyte char could land a slice mid-char
+    if !s.is_ascii() {
+      return Err(MacAddressParseError::InvalidFormat);
+    }
+
+    let (groups, width): (Vec<_>, _) = if s.contains(':') {
+      (s.split(':').collect(), 2)
+    } else if s.contains('-') {
+      (s.split('-').collect(), 2)
+    } else if s.contains('.') {
+      (s.split('.').collect(), 4)
+    } else {
+      (vec![s], 12)
+    };
+
+    if groups.len() * width != 12 || groups.iter().any(|x| x.len() != width) {
+      return Err(MacAddressParseError::InvalidFormat);
+    }
+
+    let digits = groups.concat();
+    let mut out = [0; 6];
+    for (i, x) in out.iter_mut().enumerate() {
+      // from_str_radix takes a leading '+', so check the digits ourselves
+      let pair = &digits[i * 2..i * 2 + 2];
+      if !pair.bytes().all(|x| x.is_ascii_hexdigit()) {
+        return Err(MacAddressParseError::InvalidDigit);
+      }
+      *x = u8::from_str_radix(pair, 16).unwrap();
+    }
+    Ok(Self(out))
+  }
+}
+
+// the colon form, anything FromStr takes is accepted back
+impl Serialize for MacAddress {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+impl<'de> Deserialize<'de> for MacAddress {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+  }
+}
+
+impl TryFrom<&[u8]> for MacAddress {
+  type Error = TryFromSliceError;
+
+  fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+    value.try_into().map(Self)
+  }
+}
+
+impl From<[u8; 6]> for MacAddress {
+  fn from(value: [u8; 6]) -> Self {
+    Self(value)
+  }
+}
+
+#[test]
+fn parses_mac_addresses() {
+  let mac = MacAddress([0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x0f]);
+  assert_eq!("aa:bb:cc:dd:ee:0f".parse(), Ok(mac.clone()));
+  assert_eq!("AA-BB-CC-DD-EE-0F".parse(), Ok(mac.clone()));
+  assert_eq!("aabb.ccdd.ee0f".parse(), Ok(mac.clone()));
+  assert_eq!(
+    MacAddress::try_from(&[0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x0f][..]).ok(),
+    Some(mac)
+  );
+
+  assert_eq!(
+    "aa:bb:cc:dd:ee".parse::<MacAddress>(),
+    Err(MacAddressParseError::InvalidFormat)
+  );
+  assert_eq!(
+    "aa:bb-cc:dd:ee:ff".parse::<MacAddress>(),
+    Err(MacAddressParseError::InvalidFormat)
+  );
+  assert_eq!(
+    "aabbccddeef".parse::<MacAddress>(),
+    Err(MacAddressParseError::InvalidFormat)
+  );
+  assert_eq!(
+    "aa:bb:cc:dd:ee:+f".parse::<MacAddress>(),
+    Err(MacAddressParseError::InvalidDigit)
+  );
+  assert_eq!(
+    "€a.ccdd.eeff".parse::<MacAddress>(),
+    Err(MacAddressParseError::InvalidFormat)
+  );
+  assert_eq!(
+    "aa:bb:cc:dd:ee:é".parse::<MacAddress>(),
+    Err(MacAddressParseError::InvalidFormat)
+  );
+  assert!(MacAddress::try_from(&[0; 5][..]).is_err());
+}
+
+#[test]
+fn formats_mac_addresses() {
+  let mac = MacAddress([0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x0f]);
+  for (format, expected) in [
+    (MacFormat::Colon, "aa:bb:cc:dd:ee:0f"),
+    (MacFormat::Dash, "aa-bb-cc-dd-ee-0f"),
+    (MacFormat::Dotted, "aabb.ccdd.ee0f"),
+    (MacFormat::Bare, "aabbccddee0f"),
+  ] {
+    assert_eq!(mac.format(format), expected);
+    assert_eq!(expected.parse(), Ok(mac.clone()));
+  }
+  assert_eq!(mac.to_string(), "aa:bb:cc:dd:ee:0f");
+
+  let json = serde_json::to_string(&mac).unwrap();
+  assert_eq!(json, r#""aa:bb:cc:dd:ee:0f""#);
+  assert_eq!(serde_json::from_str::<MacAddress>(r#""aabb.ccdd.ee0f""#).unwrap(), mac);
+  assert!(serde_json::from_str::<MacAddress>(r#""aabb""#).is_err());
+}
+
+#[test]
+fn converts_mac_addresses() {
+  let mac = MacAddress([0x00, 0x1c, 0x73, 0xaa, 0xbb, 0xcc]);
+  assert_eq!(mac.to_u64(), 0x001c_73aa_bbcc);
+  assert_eq!(MacAddress::from_u64(0xffff_001c_73aa_bbcc), mac);
+  assert_eq!(mac.to_eui64(), [0x00, 0x1c, 0x73, 0xff, 0xfe, 0xaa, 0xbb, 0xcc]);
+  assert!(!mac.is_multicast() && !mac.is_locally_administered());
+  assert!(MacAddress([0x01, 0x80, 0xc2, 0, 0, 0x0e]).is_multicast());
+  assert!(MacAddress([0x02, 0, 0, 0, 0, 1]).is_locally_administered());
+}
+
+#[cfg(feature = "oui")]
+#[test]
+fn looks_up_vendors() {
+  assert_eq!(
+    MacAddress([0x00, 0x1c, 0x73, 0, 0, 1]).vendor(),
+    Some("Arista Networks")
+  );
+  assert_eq!(MacAddress([0x02, 0x1c, 0x73, 0, 0, 1]).vendor(), None);
+}
@@ -0,0 +1,482 @@
+// the 802.1AB-2016 state machines from clause 9.2, one set per agent. they don't do any io themselves: events go in,
+// and what the standard's procedures would do to the remote table or the wire comes back out. time only moves with
+// tick, which is called once a second like the standard's timers
+
+use crate::{AdminStatus, TxConfig};
+
+// figure 9-3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum RxState {
+  WaitPortOperational,
+  DeleteAgedInfo,
+  RxLldpInitialize,
+  RxWaitForFrame,
+  RxFrame,
+  DeleteInfo,
+  UpdateInfo,
+}
+
+// what mibDeleteObjects and mibUpdateObjects do to the remote table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum RxAction {
+  // rxInitializeLLDP, everything the agent learned goes
+  DeleteAll,
+  // the msap of the frame, or the one that aged out
+  Delete,
+  Update,
+  // rxProcessFrame restarts rxInfoTTL even when nothing else changed
+  Refresh,
+}
+
+// rxProcessFrame's verdict on a frame that decoded fine
+pub(crate) fn rx_process_frame(ttl: u16, changes: bool) -> RxAction {
+  match ttl {
+    0 => RxAction::Delete,
+    _ if changes => RxAction::Update,
+    _ => RxAction::Refresh,
+  }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct RxMachine {
+  state: RxState,
+  port_enabled: bool,
+  admin_status: AdminStatus,
+  rx_info_age: bool,
+  // rcvFrame, with None for a badFrame
+  rcv_frame: Option<Option<(u16, bool)>>,
+  action: Option<RxAction>,
+}
+
+impl RxMachine {
+  // BEGIN
+  pub(crate) fn new(port_enabled: bool, admin_status: AdminStatus) -> Self {
+    let mut machine = Self {
+      state: RxState::WaitPortOperational,
+      port_enabled,
+      admin_status,
+      rx_info_age: false,
+      rcv_frame: None,
+      action: None,
+    };
+    machine.run();
+    machine
+  }
+
+  #[cfg(test)]
+  pub(crate) fn state(&self) -> RxState {
+    self.state
+  }
+
+  // frames are only looked at while the agent waits for one
+  pub(crate) fn receiving(&self) -> bool {
+    self.state == RxState::RxWaitForFrame
+  }
+
+  pub(crate) fn set_port_enabled(&mut self, port_enabled: bool) -> Option<RxAction> {
+    self.port_enabled = port_enabled;
+    self.run()
+  }
+
+  pub(crate) fn set_admin_status(&mut self, admin_status: AdminStatus) -> Option<RxAction> {
+    self.admin_status = admin_status;
+    self.run()
+  }
+
+  // rcvFrame. None is a badFrame, otherwise the ttl and whether anything besides it changed
+  pub(crate) fn frame(&mut self, frame: Option<(u16, bool)>) -> Option<RxAction> {
+    if !self.receiving() {
+      return None;
+    }
+    self.rcv_frame = Some(frame);
+    self.run()
+  }
+
+  // rxInfoAge, some msap's rxInfoTTL ran out
+  pub(crate) fn info_aged(&mut self) -> Option<RxAction> {
+    self.rx_info_age = true;
+    self.run()
+  }
+
+  fn enter(&mut self, state: RxState) {
+    self.state = state;
+    match state {
+      RxState::WaitPortOperational => {}
+      RxState::DeleteAgedInfo => {
+        self.action = Some(RxAction::Delete);
+        self.rx_info_age = false;
+      }
+      RxState::RxLldpInitialize => {
+        self.action = Some(RxAction::DeleteAll);
+        self.rcv_frame = None;
+      }
+      RxState::RxWaitForFrame => {}
+      RxState::RxFrame => {}
+      RxState::DeleteInfo => {
+        self.action = Some(RxAction::Delete);
+        self.rx_info_age = false;
+      }
+      RxState::UpdateInfo => self.action = Some(RxAction::Update),
+    }
+  }
+
+  fn next(&mut self) -> Option<RxState> {
+    let rx_enabled = self.admin_status.rx_enabled();
+    match self.state {
+      _ if !self.port_enabled && !self.rx_info_age && self.state != RxState::WaitPortOperational => {
+        Some(RxState::WaitPortOperational)
+      }
+      RxState::WaitPortOperational if self.rx_info_age => Some(RxState::DeleteAgedInfo),
+      RxState::WaitPortOperational if self.port_enabled => Some(RxState::RxLldpInitialize),
+      RxState::WaitPortOperational => None,
+      RxState::DeleteAgedInfo => Some(RxState::WaitPortOperational),
+      RxState::RxLldpInitialize if rx_enabled => Some(RxState::RxWaitForFrame),
+      RxState::RxLldpInitialize => None,
+      RxState::RxWaitForFrame if self.rx_info_age => Some(RxState::DeleteInfo),
+      RxState::RxWaitForFrame if self.rcv_frame.is_some() => Some(RxState::RxFrame),
+      RxState::RxWaitForFrame if !rx_enabled => Some(RxState::RxLldpInitialize),
+      RxState::RxWaitForFrame => None,
+      RxState::RxFrame => match self.rcv_frame.take().flatten() {
+        None => Some(RxState::RxWaitForFrame),
+        Some((ttl, changes)) => match rx_process_frame(ttl, changes) {
+          RxAction::Delete => Some(RxState::DeleteInfo),
+          RxAction::Update => Some(RxState::UpdateInfo),
+          action => {
+            self.action = Some(action);
+            Some(RxState::RxWaitForFrame)
+          }
+        },
+      },
+      RxState::DeleteInfo | RxState::UpdateInfo => Some(RxState::RxWaitForFrame),
+    }
+  }
+
+  fn run(&mut self) -> Option<RxAction> {
+    self.action = None;
+    while let Some(state) = self.next() {
+      self.enter(state);
+    }
+    self.action.take()
+  }
+}
+
+// figure 9-1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TxState {
+  LldpInitialize,
+  Idle,
+  ShutdownFrame,
+  InfoFrame,
+}
+
+// figure 9-2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TxTimerState {
+  TxTimerInitialize,
+  TxTimerIdle,
+  TxTimerExpires,
+  TxTick,
+  SignalTx,
+  TxFastStart,
+}
+
+// txFrame, built by mibConstrInfoLLDPDU or mibConstrShutdownLLDPDU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TxFrame {
+  Info,
+  Shutdown,
+}
+
+// the transmit and transmit timer machines together, they share their variables
+#[derive(Debug, Clone)]
+pub(crate) struct TxMachine {
+  state: TxState,
+  timer_state: TxTimerState,
+  port_enabled: bool,
+  admin_status: AdminStatus,
+  tx_now: bool,
+  tx_tick: bool,
+  local_change: bool,
+  new_neighbor: bool,
+  // seconds left on txTTR and txShutdownWhile
+  tx_ttr: u32,
+  tx_shutdown_while: u32,
+  tx_fast: u32,
+  tx_credit: u32,
+  frame: Option<TxFrame>,
+}
+
+fn secs(duration: std::time::Duration) -> u32 {
+  duration.as_secs().clamp(1, u32::MAX as u64) as u32
+}
+
+impl TxMachine {
+  // BEGIN, step runs the machines for the first time
+  pub(crate) fn new(port_enabled: bool, admin_status: AdminStatus, config: &TxConfig) -> Self {
+    let mut machine = Self {
+      state: TxState::LldpInitialize,
+      timer_state: TxTimerState::TxTimerInitialize,
+      port_enabled,
+      admin_status,
+      tx_now: false,
+      tx_tick: false,
+      local_change: false,
+      new_neighbor: false,
+      tx_ttr: 0,
+      tx_shutdown_while: 0,
+      tx_fast: 0,
+      tx_credit: 0,
+      frame: None,
+    };
+    machine.enter_timer(TxTimerState::TxTimerInitialize, config);
+    machine
+  }
+
+  #[cfg(test)]
+  pub(crate) fn state(&self) -> TxState {
+    self.state
+  }
+
+  #[cfg(test)]
+  pub(crate) fn timer_state(&self) -> TxTimerState {
+    self.timer_state
+  }
+
+  pub(crate) fn step(&mut self, config: &TxConfig) -> Option<TxFrame> {
+    self.run(config)
+  }
+
+  pub(crate) fn set_port_enabled(&mut self, port_enabled: bool, config: &TxConfig) -> Option<TxFrame> {
+    self.port_enabled = port_enabled;
+    self.run(config)
+  }
+
+  pub(crate) fn set_admin_status(&mut self, admin_status: AdminStatus, config: &TxConfig) -> Option<TxFrame> {
+    self.admin_status = admin_status;
+    self.run(config)
+  }
+
+  // somethingChangedLocal
+  pub(crate) fn local_change(&mut self, config: &TxConfig) -> Option<TxFrame> {
+    self.local_change = true;
+    self.run(config)
+  }
+
+  // newNeighbor, set by the receive side when a neighbor shows up
+  pub(crate) fn new_neighbor(&mut self, config: &TxConfig) -> Option<TxFrame> {
+    self.new_neighbor = true;
+    self.run(config)
+  }
+
+  // one second, the timers count down and txTick is set
+  pub(crate) fn tick(&mut self, config: &TxConfig) -> Option<TxFrame> {
+    self.tx_ttr = self.tx_ttr.saturating_sub(1);
+    self.tx_shutdown_while = self.tx_shutdown_while.saturating_sub(1);
+    self.tx_tick = true;
+    self.run(config)
+  }
+
+  fn tx_enabled(&self) -> bool {
+    self.port_enabled && self.admin_status.tx_enabled()
+  }
+
+  fn enter(&mut self, state: TxState, config: &TxConfig) {
+    self.state = state;
+    match state {
+      TxState::LldpInitialize | TxState::Idle => {}
+      TxState::ShutdownFrame => {
+        self.frame = Some(TxFrame::Shutdown);
+        self.tx_shutdown_while = secs(config.reinit_delay);
+      }
+      TxState::InfoFrame => {
+        self.frame = Some(TxFrame::Info);
+        self.tx_credit -= 1;
+        self.tx_now = false;
+      }
+    }
+  }
+
+  fn next(&self) -> Option<TxState> {
+    match self.state {
+      _ if !self.port_enabled && self.state != TxState::LldpInitialize => Some(TxState::LldpInitialize),
+      TxState::LldpInitialize if self.tx_enabled() => Some(TxState::Idle),
+      TxState::LldpInitialize => None,
+      TxState::Idle if !self.admin_status.tx_enabled() => Some(TxState::ShutdownFrame),
+      TxState::Idle if self.tx_now && self.tx_credit > 0 => Some(TxState::InfoFrame),
+      TxState::Idle => None,
+      TxState::ShutdownFrame if self.tx_shutdown_while == 0 => Some(TxState::LldpInitialize),
+      TxState::ShutdownFrame => None,
+      TxState::InfoFrame => Some(TxState::Idle),
+    }
+  }
+
+  fn enter_timer(&mut self, state: TxTimerState, config: &TxConfig) {
+    self.timer_state = state;
+    match state {
+      TxTimerState::TxTimerInitialize => {
+        self.tx_tick = false;
+        self.tx_now = false;
+        self.local_change = false;
+        self.tx_ttr = 0;
+        self.tx_fast = 0;
+        self.new_neighbor = false;
+        self.tx_credit = config.tx_credit_max;
+      }
+      TxTimerState::TxTimerIdle => {}
+      TxTimerState::TxTimerExpires => self.tx_fast = self.tx_fast.saturating_sub(1),
+      TxTimerState::TxTick => {
+        self.tx_tick = false;
+        self.tx_credit = (self.tx_credit + 1).min(config.tx_credit_max);
+      }
+      TxTimerState::SignalTx => {
+        self.tx_now = true;
+        self.local_change = false;
+        self.tx_ttr = if self.tx_fast > 0 {
+          secs(config.msg_fast_tx)
+        } else {
+          secs(config.msg_tx_interval)
+        };
+      }
+      TxTimerState::TxFastStart => {
+        self.new_neighbor = false;
+        if self.tx_fast == 0 {
+          self.tx_fast = config.tx_fast_init;
+        }
+      }
+    }
+  }
+
+  fn next_timer(&self) -> Option<TxTimerState> {
+    match self.timer_state {
+      TxTimerState::TxTimerInitialize if self.tx_enabled() => Some(TxTimerState::TxTimerIdle),
+      TxTimerState::TxTimerInitialize => None,
+      _ if !self.tx_enabled() => Some(TxTimerState::TxTimerInitialize),
+      TxTimerState::TxTimerIdle if self.local_change => Some(TxTimerState::SignalTx),
+      TxTimerState::TxTimerIdle if self.tx_ttr == 0 => Some(TxTimerState::TxTimerExpires),
+      TxTimerState::TxTimerIdle if self.new_neighbor => Some(TxTimerState::TxFastStart),
+      TxTimerState::TxTimerIdle if self.tx_tick => Some(TxTimerState::TxTick),
+      TxTimerState::TxTimerIdle => None,
+      TxTimerState::TxTimerExpires => Some(TxTimerState::SignalTx),
+      TxTimerState::SignalTx | TxTimerState::TxTick => Some(TxTimerState::TxTimerIdle),
+      TxTimerState::TxFastStart => Some(TxTimerState::TxTimerExpires),
+    }
+  }
+
+  fn run(&mut self, config: &TxConfig) -> Option<TxFrame> {
+    self.frame = None;
+    loop {
+      // the timer runs first so a signalled txNow goes out in the same step
+      if let Some(state) = self.next_timer() {
+        self.enter_timer(state, config);
+      } else if let Some(state) = self.next() {
+        self.enter(state, config);
+      } else {
+        return self.frame.take();
+      }
+    }
+  }
+}
+
+#[test]
+fn rx_follows_admin_status() {
+  let mut rx = RxMachine::new(true, AdminStatus::TxAndRx);
+  assert_eq!(rx.state(), RxState::RxWaitForFrame);
+
+  assert_eq!(rx.frame(Some((120, true))), Some(RxAction::Update));
+  assert_eq!(rx.frame(Some((120, false))), Some(RxAction::Refresh));
+  assert_eq!(rx.frame(None), None);
+  assert_eq!(rx.frame(Some((0, false))), Some(RxAction::Delete));
+  assert!(rx.receiving());
+
+  // rxInitializeLLDP throws away what was learned, and nothing's received until it's enabled again
+  assert_eq!(rx.set_admin_status(AdminStatus::TxOnly), Some(RxAction::DeleteAll));
+  assert_eq!(rx.state(), RxState::RxLldpInitialize);
+  assert_eq!(rx.frame(Some((120, true))), None);
+  assert_eq!(rx.set_admin_status(AdminStatus::Disabled), None);
+  assert_eq!(rx.set_admin_status(AdminStatus::RxOnly), None);
+  assert!(rx.receiving());
+}
+
+#[test]
+fn rx_info_age() {
+  let mut rx = RxMachine::new(true, AdminStatus::TxAndRx);
+  assert_eq!(rx.info_aged(), Some(RxAction::Delete));
+  assert!(rx.receiving());
+
+  // aged info is still deleted while the port is down, and the table starts over when it comes back
+  assert_eq!(rx.set_port_enabled(false), None);
+  assert_eq!(rx.state(), RxState::WaitPortOperational);
+  assert_eq!(rx.info_aged(), Some(RxAction::Delete));
+  assert_eq!(rx.state(), RxState::WaitPortOperational);
+  assert_eq!(rx.set_port_enabled(true), Some(RxAction::DeleteAll));
+  assert!(rx.receiving());
+}
+
+#[test]
+fn fast_tx_after_new_neighbor() {
+  let config = TxConfig::default();
+  let mut tx = TxMachine::new(true, AdminStatus::TxAndRx, &config);
+  assert_eq!(tx.step(&config), Some(TxFrame::Info));
+  assert_eq!(tx.timer_state(), TxTimerState::TxTimerIdle);
+
+  for _ in 1..config.msg_tx_interval.as_secs() {
+    assert_eq!(tx.tick(&config), None);
+  }
+  assert_eq!(tx.tick(&config), Some(TxFrame::Info));
+
+  // txFastInit frames msgFastTx apart, the first right away
+  assert_eq!(tx.new_neighbor(&config), Some(TxFrame::Info));
+  for _ in 1..config.tx_fast_init {
+    assert_eq!(tx.tick(&config), Some(TxFrame::Info));
+  }
+  assert_eq!(tx.tick(&config), None);
+}
+
+#[test]
+fn tx_credit_limits_bursts() {
+  let config = TxConfig {
+    tx_credit_max: 2,
+    ..Default::default()
+  };
+  let mut tx = TxMachine::new(true, AdminStatus::TxAndRx, &config);
+  assert_eq!(tx.step(&config), Some(TxFrame::Info));
+  assert_eq!(tx.local_change(&config), Some(TxFrame::Info));
+  assert_eq!(tx.local_change(&config), None);
+
+  // the change that was held back goes out with the next credit
+  assert_eq!(tx.tick(&config), Some(TxFrame::Info));
+  assert_eq!(tx.local_change(&config), None);
+
+  // credit never exceeds the maximum no matter how long it's been
+  for _ in 0..20 {
+    tx.tick(&config);
+  }
+  assert_eq!(tx.local_change(&config), Some(TxFrame::Info));
+  assert_eq!(tx.local_change(&config), Some(TxFrame::Info));
+  assert_eq!(tx.local_change(&config), None);
+}
+
+#[test]
+fn tx_shutdown_and_reinit_delay() {
+  let config = TxConfig::default();
+  let mut tx = TxMachine::new(true, AdminStatus::TxAndRx, &config);
+  assert_eq!(tx.step(&config), Some(TxFrame::Info));
+
+  assert_eq!(
+    tx.set_admin_status(AdminStatus::RxOnly, &config),
+    Some(TxFrame::Shutdown)
+  );
+  assert_eq!(tx.local_change(&config), None);
+
+  // enabled again before reinitDelay is up, transmission waits for it
+  assert_eq!(tx.set_admin_status(AdminStatus::TxAndRx, &config), None);
+  assert_eq!(tx.state(), TxState::ShutdownFrame);
+  for _ in 1..config.reinit_delay.as_secs() {
+    assert_eq!(tx.tick(&config), None);
+  }
+  assert_eq!(tx.tick(&config), Some(TxFrame::Info));
+
+  // a port going down has nothing to shut down
+  assert_eq!(tx.set_port_enabled(false, &config), None);
+  assert_eq!(tx.state(), TxState::LldpInitialize);
+}
@@ -1,48 +1,75 @@
-use std::error::Error;
-
-use lldp_parser::lldp::du::DataUnit;
-use rawsocket::{bsd::sync::BpfSocket, EthernetPacket, MacAddress};
-use rlldp::{MacHeader, LLDP_TYPE};
-
-const LLDP_MAC_1: MacAddress = MacAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e]);
-const LLDP_MAC_2: MacAddress = MacAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x03]);
-const LLDP_MAC_3: MacAddress = MacAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x00]);
-
-fn main() {
-  tracing_subscriber::fmt::init();
-  // let packet = include_bytes!("../lldp.1.raw");
-  // let parsed: MacHeader = unsafe { std::ptr::read(packet.as_ptr() as *const _) };
-  // println!("{parsed:#?}");
-
-  // assert!(parsed.ether_type == LLDP_TYPE);
-
-  const ETH_P_LLDP: u16 = 0x88CC;
-
-  // let sock = RawSocket::open(Some(Protocol::from_raw(
-  //   ((ETH_P_LLDP as u16).to_be() as u32).try_into().unwrap(),
-  // )))
-  // .unwrap();
-
-  // sock.bind_to_interface("eth0").unwrap();
-  // sock.set_multicast_membership("eth0", LLDP_MAC_1, true).unwrap();
-  // sock.set_multicast_membership("eth0", LLDP_MAC_2, true).unwrap();
-  // sock.set_multicast_membership("eth0", LLDP_MAC_3, true).unwrap();
-  let sock = BpfSocket::open("en8", Some(1500)).unwrap();
-  sock.set_immediate(true).unwrap();
-  sock
-    .set_read_filter(rawsocket::bpf_filter!(
-      { 0x28, 0, 0, 0x0000000c },
-      { 0x15, 0, 1, 0x000088cc },
-      { 0x6, 0, 0, 0x00080000 },
-      { 0x6, 0, 0, 0x00000000 },
-    ))
-    .unwrap();
-
-  loop {
-    let mut buf = [0; 1500];
-    for packet in sock.read_iter(&mut buf).unwrap() {
-      let eth = EthernetPacket::try_decode(packet.capture).unwrap();
-      dbg!(DataUnit::decode(eth.payload));
-    }
+use std::io;
+
+use clap::{Parser, Subcommand};
+
+mod cli;
+use cli::{
+  configure::{ConfigureCommand, UpdateArgs},
+  daemon::DaemonArgs,
+  decode::DecodeArgs,
+  show::ShowCommand,
+  tx::TxArgs,
+  CaptureArgs, GlobalArgs,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+  version,
+  about = "Listen for LLDP and CDP neighbors",
+  args_conflicts_with_subcommands = true
+)]
+struct Args {
+  #[command(subcommand)]
+  command: Option<Command>,
+
+  #[command(flatten)]
+  capture: CaptureArgs,
+
+  #[command(flatten)]
+  global: GlobalArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// Capture and transmit on the given interfaces, serving the control socket
+  Daemon(DaemonArgs),
+
+  /// Print the neighbor table or counters, from the daemon or a short capture
+  #[command(subcommand)]
+  Show(ShowCommand),
+
+  /// Print neighbor events as they happen
+  Watch(CaptureArgs),
+
+  /// Change the configuration of a running daemon
+  #[command(subcommand)]
+  Configure(ConfigureCommand),
+
+  /// Make a running daemon transmit right away
+  Update(UpdateArgs),
+
+  /// Send a single hand-built LLDP or CDP frame
+  Tx(TxArgs),
+
+  /// Decode a frame from a hex string, raw file or pcap
+  Decode(DecodeArgs),
+}
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+  tracing_subscriber::fmt().with_writer(io::stderr).init();
+  let args = Args::parse();
+  let global = &args.global;
+
+  match &args.command {
+    // running without a subcommand is the same as watch
+    None => cli::watch::run(&args.capture, global).await,
+    Some(Command::Daemon(daemon)) => cli::daemon::run(daemon, global).await,
+    Some(Command::Show(command)) => cli::show::run(command, global).await,
+    Some(Command::Watch(capture)) => cli::watch::run(capture, global).await,
+    Some(Command::Configure(command)) => cli::configure::run(command, global).await,
+    Some(Command::Update(update)) => cli::configure::update(update, global).await,
+    Some(Command::Tx(tx)) => cli::tx::run(tx).await,
+    Some(Command::Decode(decode)) => cli::decode::run(decode, global),
   }
 }
@@ -0,0 +1,164 @@
+use lldp_parser::lldp::{du::DataUnit as LldpDu, tlv::CustomOrgTlv};
+
+use crate::{Scope, TxTlvProvider};
+
+// ansi/tia-1057
+pub(crate) const MED_OUI: [u8; 3] = [0x00, 0x12, 0xbb];
+const SUBTYPE_CAPABILITIES: u8 = 1;
+const SUBTYPE_NETWORK_POLICY: u8 = 2;
+const SUBTYPE_LOCATION: u8 = 3;
+const CAPABILITY_MED: u16 = 1 << 0;
+const CAPABILITY_NETWORK_POLICY: u16 = 1 << 1;
+const CAPABILITY_LOCATION: u16 = 1 << 2;
+const DEVICE_TYPE_NETWORK_CONNECTIVITY: u8 = 4;
+const LOCATION_FORMAT_CIVIC: u8 = 2;
+// rfc 4776, the location is that of the switch closest to the endpoint
+const CIVIC_WHAT_NETWORK_ELEMENT: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MedApplication {
+  Voice = 1,
+  VoiceSignaling = 2,
+  GuestVoice = 3,
+  GuestVoiceSignaling = 4,
+  SoftphoneVoice = 5,
+  VideoConferencing = 6,
+  StreamingVideo = 7,
+  VideoSignaling = 8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NetworkPolicy {
+  pub application: MedApplication,
+  // the policy is required but not known yet, the rest is ignored by the endpoint
+  pub unknown: bool,
+  pub tagged: bool,
+  pub vlan_id: u16,
+  pub priority: u8,
+  pub dscp: u8,
+}
+
+impl NetworkPolicy {
+  fn encode(&self) -> [u8; 4] {
+    let vlan_id = self.vlan_id & 0xfff;
+    let priority = self.priority & 0x7;
+    [
+      self.application as u8,
+      (self.unknown as u8) << 7 | (self.tagged as u8) << 6 | (vlan_id >> 7) as u8,
+      ((vlan_id & 0x7f) as u8) << 1 | priority >> 2,
+      (priority & 0x3) << 6 | self.dscp & 0x3f,
+    ]
+  }
+}
+
+// civic address from rfc 4776, the elements are catype and value pairs like (1, "CA") or (3, "Berkeley")
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CivicLocation {
+  pub country: [u8; 2],
+  pub elements: Vec<(u8, String)>,
+}
+
+impl CivicLocation {
+  fn encode(&self) -> Vec<u8> {
+    let mut lci = vec![CIVIC_WHAT_NETWORK_ELEMENT, self.country[0], self.country[1]];
+    for (ty, value) in &self.elements {
+      // both the element and the whole lci carry a one byte length
+      let value = &value.as_bytes()[..value.len().min(u8::MAX as usize)];
+      if lci.len() + 2 + value.len() > u8::MAX as usize {
+        break;
+      }
+      lci.push(*ty);
+      lci.push(value.len() as u8);
+      lci.extend_from_slice(value);
+    }
+
+    let mut data = vec![LOCATION_FORMAT_CIVIC, lci.len() as u8];
+    data.extend(lci);
+    data
+  }
+}
+
+// what a switch port advertises to the phones plugged into it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MedConfig {
+  pub policies: Vec<NetworkPolicy>,
+  pub location: Option<CivicLocation>,
+}
+
+impl MedConfig {
+  pub fn is_empty(&self) -> bool {
+    self.policies.is_empty() && self.location.is_none()
+  }
+
+  // the capabilities tlv goes first, endpoints ignore the other med tlvs without it
+  pub fn tlvs(&self) -> Vec<CustomOrgTlv<'static>> {
+    if self.is_empty() {
+      return Vec::new();
+    }
+
+    let mut capabilities = CAPABILITY_MED;
+    if !self.policies.is_empty() {
+      capabilities |= CAPABILITY_NETWORK_POLICY;
+    }
+    if self.location.is_some() {
+      capabilities |= CAPABILITY_LOCATION;
+    }
+
+    let tlv = |subtype, data: Vec<u8>| CustomOrgTlv {
+      org: MED_OUI,
+      subtype,
+      data: data.into(),
+    };
+    let mut capabilities = capabilities.to_be_bytes().to_vec();
+    capabilities.push(DEVICE_TYPE_NETWORK_CONNECTIVITY);
+    let mut tlvs = vec![tlv(SUBTYPE_CAPABILITIES, capabilities)];
+    tlvs.extend(
+      self
+        .policies
+        .iter()
+        .map(|x| tlv(SUBTYPE_NETWORK_POLICY, x.encode().to_vec())),
+    );
+    tlvs.extend(self.location.iter().map(|x| tlv(SUBTYPE_LOCATION, x.encode())));
+    tlvs
+  }
+}
+
+impl TxTlvProvider for MedConfig {
+  fn provide(&self, _: Scope, du: &mut LldpDu<'static>) {
+    du.org.custom.extend(self.tlvs());
+  }
+}
+
+#[test]
+fn encodes_network_policy() {
+  let policy = NetworkPolicy {
+    application: MedApplication::Voice,
+    unknown: false,
+    tagged: true,
+    vlan_id: 100,
+    priority: 5,
+    dscp: 46,
+  };
+  assert_eq!(policy.encode(), [0x01, 0x40, 0xc9, 0x6e]);
+
+  let tlvs = MedConfig {
+    policies: vec![policy],
+    location: None,
+  }
+  .tlvs();
+  assert_eq!(tlvs.len(), 2);
+  assert_eq!((tlvs[0].subtype, &tlvs[0].data[..]), (1, &[0x00, 0x03, 0x04][..]));
+  assert_eq!(tlvs[1].subtype, 2);
+  assert!(MedConfig::default().tlvs().is_empty());
+}
+
+#[test]
+fn encodes_civic_location() {
+  let location = CivicLocation {
+    country: *b"US",
+    elements: vec![(1, "CA".into()), (3, "Berkeley".into())],
+  };
+  let mut expected = vec![2, 17, 1, b'U', b'S', 1, 2, b'C', b'A', 3, 8];
+  expected.extend_from_slice(b"Berkeley");
+  assert_eq!(location.encode(), expected);
+}
@@ -0,0 +1,94 @@
+use std::{
+  fs::{self, File},
+  io::{self, BufWriter},
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{pcap_file::PcapngWriter, LocalPort};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MirrorConfig {
+  pub path: PathBuf,
+  pub max_file_size: u64,
+  pub max_files: usize,
+}
+
+impl MirrorConfig {
+  pub fn new(path: impl Into<PathBuf>) -> Self {
+    Self {
+      path: path.into(),
+      max_file_size: 16 * 1024 * 1024,
+      max_files: 4,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub(crate) struct PcapngMirror {
+  config: MirrorConfig,
+  writer: Option<(PcapngWriter<BufWriter<File>>, u32)>,
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+  let mut name = path.as_os_str().to_owned();
+  name.push(format!(".{n}"));
+  name.into()
+}
+
+impl PcapngMirror {
+  pub fn new(config: MirrorConfig) -> Self {
+    Self { config, writer: None }
+  }
+
+  // shifts path -> path.1 -> path.2 ..., dropping whatever falls off the end
+  fn rotate(&self) -> io::Result<()> {
+    let keep = self.config.max_files.max(1);
+    for n in (1..keep).rev() {
+      let from = if n == 1 {
+        self.config.path.clone()
+      } else {
+        rotated_path(&self.config.path, n - 1)
+      };
+
+      match fs::rename(&from, rotated_path(&self.config.path, n)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+      }
+    }
+
+    Ok(())
+  }
+
+  fn open(&self, local_port: &LocalPort, snaplen: u32) -> io::Result<(PcapngWriter<BufWriter<File>>, u32)> {
+    let file = File::create(&self.config.path)?;
+    let mut writer = PcapngWriter::new(BufWriter::new(file))?;
+    let id = writer.add_interface(&local_port.name, local_port.description.as_deref(), snaplen)?;
+    Ok((writer, id))
+  }
+
+  pub fn write(
+    &mut self,
+    local_port: &LocalPort,
+    snaplen: u32,
+    frame: &[u8],
+    wire_len: usize,
+    timestamp: SystemTime,
+  ) -> io::Result<()> {
+    if let Some((writer, _)) = &self.writer {
+      if writer.bytes_written() >= self.config.max_file_size {
+        self.writer = None;
+        self.rotate()?;
+      }
+    }
+
+    let (writer, id) = match &mut self.writer {
+      Some(x) => x,
+      None => self.writer.insert(self.open(local_port, snaplen)?),
+    };
+
+    let timestamp = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+    writer.write_packet(*id, timestamp, frame, wire_len)?;
+    writer.flush()
+  }
+}
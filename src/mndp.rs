@@ -0,0 +1,42 @@
+use std::{io, net::Ipv4Addr};
+
+use lldp_parser::{mndp, DataUnit};
+use tokio::net::UdpSocket;
+use tracing::{instrument, warn};
+
+use crate::{FrameInfo, Interface, MacAddress};
+
+pub const MNDP_PORT: u16 = 5678;
+
+impl Interface {
+  // binds the wildcard address, use run_mndp with a socket bound to the device when listening on several interfaces
+  #[instrument(skip_all, fields(interface = self.local_port().name))]
+  pub async fn start_mndp(&self) -> io::Result<()> {
+    let sock = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, MNDP_PORT)).await?;
+    self.run_mndp(sock).await
+  }
+
+  pub async fn run_mndp(&self, sock: UdpSocket) -> io::Result<()> {
+    let mut buf = vec![0; self.buffer_size()];
+    loop {
+      let (len, addr) = sock.recv_from(&mut buf).await?;
+      let du = match mndp::DataUnit::decode(&buf[..len]) {
+        Ok(du) => du,
+        Err(err) => {
+          warn!(%err, %addr, "failed to decode mndp du");
+          continue;
+        }
+      };
+
+      // there's no ethernet header to take the source from, so rely on the announced mac
+      let Some(mac_address) = du.mac_address else {
+        warn!(%addr, "mndp du without a mac address");
+        continue;
+      };
+
+      self
+        .insert_du(FrameInfo::new(MacAddress(mac_address)), DataUnit::Mndp(du.to_static()))
+        .await;
+    }
+  }
+}
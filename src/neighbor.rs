@@ -0,0 +1,216 @@
+use lldp_parser::{
+  cdp, fdp, frame,
+  lldp::{
+    du::DataUnit as LldpDu,
+    tlv::{RawTlv, Tlv, TlvKind},
+    Msap,
+  },
+  DataUnit, Protocol,
+};
+
+use crate::{FrameInfo, MacAddress, Scope, StoredDu};
+
+// an hour, well past any real lldp or cdp ttl but short of the 18 hours a forged 65535 would hold a neighbor for
+pub const DEFAULT_MAX_TTL: u16 = 3600;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct NeighborKey {
+  pub(crate) protocol: Protocol,
+  pub(crate) scope: Option<Scope>,
+  pub(crate) id: NeighborId,
+}
+
+// lldp neighbors are keyed by msap, which stays the same when a stack fails over to another member's mac
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum NeighborId {
+  Msap(Msap<'static>),
+  Source(MacAddress),
+}
+
+impl NeighborKey {
+  pub(crate) fn new(
+    protocol: Protocol,
+    scope: Option<Scope>,
+    msap: Option<Msap<'static>>,
+    source: &MacAddress,
+  ) -> Self {
+    let id = match msap {
+      Some(msap) => NeighborId::Msap(msap),
+      None => NeighborId::Source(source.clone()),
+    };
+    Self { protocol, scope, id }
+  }
+}
+
+// the msap from the chassis and port id tlvs that lead an lldpdu, without decoding the rest of it
+pub(crate) fn raw_msap(buf: &[u8]) -> Option<Msap<'static>> {
+  let chassis_id = RawTlv::decode(buf).ok()?;
+  let port_id = RawTlv::decode(&buf[chassis_id.total_len()..]).ok()?;
+  match (Tlv::decode(chassis_id), Tlv::decode(port_id)) {
+    (Ok(Tlv::ChassisId(chassis_id)), Ok(Tlv::PortId(port_id))) => Some(Msap { chassis_id, port_id }.to_static()),
+    _ => None,
+  }
+}
+
+// whether two dus from the same neighbor differ in nothing but their ttl
+pub(crate) fn unchanged(old: &DataUnit, new: &DataUnit) -> bool {
+  match (old, new) {
+    (DataUnit::Lldp(a), DataUnit::Lldp(b)) => {
+      // destructured so a new field can't be left out of the comparison
+      let LldpDu {
+        chassis_id,
+        port_id,
+        time_to_live: _,
+        port_description,
+        system_name,
+        system_description,
+        capabilities,
+        management_address,
+        org,
+      } = a;
+      *chassis_id == b.chassis_id
+        && *port_id == b.port_id
+        && *port_description == b.port_description
+        && *system_name == b.system_name
+        && *system_description == b.system_description
+        && *capabilities == b.capabilities
+        && *management_address == b.management_address
+        && *org == b.org
+    }
+    (DataUnit::Cdp(a), DataUnit::Cdp(b)) => {
+      let cdp::DataUnit {
+        time_to_live: _,
+        device_id,
+        addresses,
+        capabilities,
+        software_version,
+        platform,
+        port_id,
+        duplex,
+        native_vlan,
+      } = a;
+      *device_id == b.device_id
+        && *addresses == b.addresses
+        && *capabilities == b.capabilities
+        && *software_version == b.software_version
+        && *platform == b.platform
+        && *port_id == b.port_id
+        && *duplex == b.duplex
+        && *native_vlan == b.native_vlan
+    }
+    (DataUnit::Fdp(a), DataUnit::Fdp(b)) => {
+      let fdp::DataUnit {
+        time_to_live: _,
+        device_id,
+        software_version,
+        platform,
+        port_id,
+        native_vlan,
+      } = a;
+      *device_id == b.device_id
+        && *software_version == b.software_version
+        && *platform == b.platform
+        && *port_id == b.port_id
+        && *native_vlan == b.native_vlan
+    }
+    // mndp and sonmp have no ttl to ignore
+    (a, b) => a == b,
+  }
+}
+
+// the same for stored dus, raw ones are compared as bytes so refreshing them never decodes
+pub(crate) fn unchanged_stored(old: &StoredDu, new: &StoredDu) -> bool {
+  match (old.raw_bytes(), new.raw_bytes()) {
+    (Some(a), Some(b)) if old.protocol() == new.protocol() => {
+      without_ttl(old.protocol(), a) == without_ttl(new.protocol(), b)
+    }
+    _ => unchanged(old.get(), new.get()),
+  }
+}
+
+// the bytes either side of the ttl. cdp and fdp keep it in the header, next to a checksum that covers it
+fn without_ttl(protocol: Protocol, buf: &[u8]) -> (&[u8], &[u8]) {
+  match protocol {
+    Protocol::Cdp | Protocol::Fdp => (&buf[..buf.len().min(1)], buf.get(4..).unwrap_or_default()),
+    Protocol::Lldp => {
+      let mut offset = 0;
+      while let Ok(raw) = RawTlv::decode(&buf[offset..]) {
+        let next = offset + raw.total_len();
+        match TlvKind::from(raw.ty) {
+          TlvKind::TimeToLive => return (&buf[..offset], &buf[next..]),
+          TlvKind::End => break,
+          _ => offset = next,
+        }
+      }
+      (buf, &[])
+    }
+    Protocol::Mndp | Protocol::Sonmp => (buf, &[]),
+  }
+}
+
+pub(crate) fn split_frame(buf: &[u8]) -> Option<(FrameInfo, Protocol, &[u8])> {
+  let frame = frame::Frame::parse(buf)?;
+  let scope = match frame.protocol {
+    Protocol::Lldp => Scope::from_destination(&MacAddress(frame.destination)),
+    _ => None,
+  };
+
+  let info = FrameInfo {
+    source: MacAddress(frame.source),
+    scope,
+    vlans: frame.vlans().collect(),
+    timestamp: None,
+  };
+  Some((info, frame.protocol, frame.payload))
+}
+
+#[test]
+fn split_vlan_tagged_frame() {
+  let mut frame = vec![0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e, 0, 0, 0, 0, 0, 1];
+  frame.extend_from_slice(&[0x81, 0x00, 0x20, 0x64, 0x88, 0xcc, 0xde, 0xad]);
+
+  let (info, protocol, payload) = split_frame(&frame).unwrap();
+  assert_eq!(info.source, MacAddress([0, 0, 0, 0, 0, 1]));
+  assert_eq!(info.scope, Some(Scope::NearestBridge));
+  assert_eq!(info.vlans.iter().map(|x| x.vid()).collect::<Vec<_>>(), [100]);
+  assert_eq!(protocol, Protocol::Lldp);
+  assert_eq!(payload, &[0xde, 0xad]);
+
+  assert!(split_frame(&frame[..16]).is_none());
+}
+
+#[test]
+fn raw_dus_compare_without_ttl() {
+  let raw = |time_to_live, system_name: &'static str| {
+    let mut buf = Vec::new();
+    LldpDu {
+      time_to_live,
+      system_name: Some(system_name.into()),
+      ..crate::fixtures::lldp_du()
+    }
+    .encode(&mut buf);
+    StoredDu::raw(Protocol::Lldp, &buf).unwrap()
+  };
+
+  assert!(unchanged_stored(&raw(120, "a"), &raw(60, "a")));
+  assert!(!unchanged_stored(&raw(120, "a"), &raw(120, "b")));
+  assert!(unchanged(raw(120, "a").get(), raw(60, "a").get()));
+  assert!(!unchanged(raw(120, "a").get(), raw(60, "b").get()));
+}
+
+#[test]
+fn reads_msap_from_leading_tlvs() {
+  use lldp_parser::lldp::tlv::{ChassisId, PortId};
+
+  let mut buf = Vec::new();
+  LldpDu {
+    chassis_id: ChassisId::MacAddress([0, 0x11, 0x22, 0x33, 0x44, 0x55]),
+    port_id: PortId::Local("1/1".into()),
+    ..crate::fixtures::lldp_du()
+  }
+  .encode(&mut buf);
+
+  let du = DataUnit::decode(Protocol::Lldp, &buf).unwrap();
+  assert_eq!(raw_msap(&buf), du.msap().map(Msap::to_static));
+  assert_eq!(raw_msap(&buf[..4]), None);
+}
@@ -0,0 +1,119 @@
+use std::{future::Future, io, time::Duration};
+
+use tokio::{
+  sync::broadcast::{self, error::RecvError},
+  time::Instant,
+};
+use tracing::warn;
+
+use crate::{NeighborEvent, NeighborEventKind};
+
+// lldpNotificationInterval's default
+pub const DEFAULT_NOTIFICATION_INTERVAL: Duration = Duration::from_secs(5);
+
+// the neighbor events of one lldpRemTablesChange, in the order they happened
+#[derive(Debug, Clone)]
+pub struct ChangeNotification {
+  pub events: Vec<NeighborEvent>,
+}
+
+impl ChangeNotification {
+  pub fn count(&self, kind: NeighborEventKind) -> usize {
+    self.events.iter().filter(|x| x.kind == kind).count()
+  }
+}
+
+// like EventSink, for things that should hear about changes at most once an interval, a trap receiver or a webhook
+pub trait NotificationSink {
+  fn notify(&mut self, notification: &ChangeNotification) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+// the first change after a quiet interval goes out straight away, anything after it waits for the interval to pass
+// and goes out together. subscribers of the events themselves still see every one as it happens
+pub async fn run_notifications<S: NotificationSink>(
+  mut events: broadcast::Receiver<NeighborEvent>,
+  interval: Duration,
+  mut sink: S,
+) {
+  let mut next = Instant::now();
+  let mut pending = Vec::new();
+  loop {
+    let closed = if pending.is_empty() {
+      match events.recv().await {
+        Ok(event) => {
+          pending.push(event);
+          false
+        }
+        Err(RecvError::Lagged(count)) => {
+          warn!(count, "notifications missed neighbor events");
+          false
+        }
+        Err(RecvError::Closed) => return,
+      }
+    } else {
+      tokio::select! {
+        _ = tokio::time::sleep_until(next) => false,
+        result = events.recv() => match result {
+          Ok(event) => {
+            pending.push(event);
+            continue;
+          }
+          Err(RecvError::Lagged(count)) => {
+            warn!(count, "notifications missed neighbor events");
+            continue;
+          }
+          Err(RecvError::Closed) => true,
+        },
+      }
+    };
+
+    if !pending.is_empty() && (closed || Instant::now() >= next) {
+      let notification = ChangeNotification {
+        events: std::mem::take(&mut pending),
+      };
+      if let Err(err) = sink.notify(&notification).await {
+        warn!(%err, "failed to send change notification");
+      }
+      next = Instant::now() + interval;
+    }
+    if closed {
+      return;
+    }
+  }
+}
+
+#[tokio::test]
+async fn coalesces_within_interval() {
+  use crate::{FrameInfo, Interface, LocalPort, MacAddress};
+
+  struct Collect(tokio::sync::mpsc::UnboundedSender<usize>);
+
+  impl NotificationSink for Collect {
+    async fn notify(&mut self, notification: &ChangeNotification) -> io::Result<()> {
+      self.0.send(notification.events.len()).map_err(io::Error::other)
+    }
+  }
+
+  let interface = Interface::new(LocalPort::new("eth0"));
+  let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+  let interval = Duration::from_millis(100);
+  tokio::spawn(run_notifications(interface.subscribe(), interval, Collect(tx)));
+
+  let du = |name: &'static str| {
+    lldp_parser::DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+      device_id: Some(name.into()),
+      ..crate::fixtures::cdp_du()
+    })
+  };
+  let info = |x| FrameInfo::new(MacAddress([0, 0, 0, 0, 0, x]));
+
+  interface.insert_du(info(1), du("a")).await;
+  assert_eq!(rx.recv().await, Some(1));
+
+  let start = Instant::now();
+  interface.insert_du(info(2), du("b")).await;
+  interface.insert_du(info(3), du("c")).await;
+  interface.insert_du(info(1), du("a2")).await;
+  assert_eq!(rx.recv().await, Some(3));
+  assert!(start.elapsed() >= interval / 2);
+}
@@ -0,0 +1,174 @@
+use std::{fmt, sync::Arc};
+
+use lldp_parser::lldp::{
+  du::DataUnit as LldpDu,
+  tlv::{
+    org::dot3::{Power, PowerExtension, PowerPortClass, PowerSupport},
+    CustomOrgTlv,
+  },
+};
+
+use crate::{med::MED_OUI, Scope, TxTlvProvider};
+
+const MED_SUBTYPE_CAPABILITIES: u8 = 1;
+const MED_SUBTYPE_EXTENDED_POWER: u8 = 4;
+const MED_CAPABILITY_PSE: u16 = 1 << 3;
+const MED_CAPABILITY_PD: u16 = 1 << 4;
+// the signal pairs, what every af and at pse uses for alternative a
+const PSE_POWER_PAIR_SIGNAL: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PowerPriority {
+  #[default]
+  Unknown,
+  Critical,
+  High,
+  Low,
+}
+
+// what the hardware reports right now, the power values are in 0.1 W like on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PowerState {
+  pub port_class: PowerPortClass,
+  pub enabled: bool,
+  // 0 through 4
+  pub class: u8,
+  pub priority: PowerPriority,
+  pub requested: u16,
+  pub allocated: u16,
+}
+
+impl PowerState {
+  // 802.3at type 2, powered from the primary supply or the pse
+  fn type_source_priority(&self) -> u8 {
+    let ty = match self.port_class {
+      PowerPortClass::Pse => 0b00,
+      PowerPortClass::Pd => 0b01,
+    };
+    ty << 6 | 0b01 << 4 | self.priority as u8
+  }
+
+  pub fn dot3(&self) -> Power {
+    let mut support = PowerSupport::SUPPORTED;
+    support.set(PowerSupport::PSE, self.port_class == PowerPortClass::Pse);
+    support.set(PowerSupport::ENABLED, self.enabled);
+    Power {
+      support,
+      pse_power_pair: PSE_POWER_PAIR_SIGNAL,
+      power_class: self.class.min(4) + 1,
+      extension: Some(PowerExtension {
+        type_source_priority: self.type_source_priority(),
+        requested: self.requested,
+        allocated: self.allocated,
+        bt: Vec::new(),
+      }),
+    }
+  }
+
+  // extended power-via-mdi, a pse advertises what it allocated and a pd what it asks for
+  pub fn med(&self) -> CustomOrgTlv<'static> {
+    let (ty, value) = match self.port_class {
+      PowerPortClass::Pse => (0b00, self.allocated),
+      PowerPortClass::Pd => (0b01, self.requested),
+    };
+    let mut data = vec![ty << 6 | 0b01 << 4 | self.priority as u8];
+    data.extend(value.to_be_bytes());
+    CustomOrgTlv {
+      org: MED_OUI,
+      subtype: MED_SUBTYPE_EXTENDED_POWER,
+      data: data.into(),
+    }
+  }
+}
+
+// asked right before every transmission, None leaves the power tlvs out
+pub trait PowerSource: Send + Sync {
+  fn power(&self, scope: Scope) -> Option<PowerState>;
+}
+
+impl<F: Fn(Scope) -> Option<PowerState> + Send + Sync> PowerSource for F {
+  fn power(&self, scope: Scope) -> Option<PowerState> {
+    self(scope)
+  }
+}
+
+// the tx provider for a power source. the med tlv is only added when med is already being advertised, and the med
+// capabilities then say so too
+#[derive(Clone)]
+pub struct PowerTlvs(pub Arc<dyn PowerSource>);
+
+impl fmt::Debug for PowerTlvs {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("PowerTlvs").finish_non_exhaustive()
+  }
+}
+
+impl TxTlvProvider for PowerTlvs {
+  fn provide(&self, scope: Scope, du: &mut LldpDu<'static>) {
+    let Some(state) = self.0.power(scope) else {
+      return;
+    };
+
+    du.org.dot3.power = Some(state.dot3());
+    let capabilities = du
+      .org
+      .custom
+      .iter_mut()
+      .find(|x| x.org == MED_OUI && x.subtype == MED_SUBTYPE_CAPABILITIES && x.data.len() == 3);
+    if let Some(tlv) = capabilities {
+      let mut bits = u16::from_be_bytes([tlv.data[0], tlv.data[1]]);
+      bits |= match state.port_class {
+        PowerPortClass::Pse => MED_CAPABILITY_PSE,
+        PowerPortClass::Pd => MED_CAPABILITY_PD,
+      };
+      tlv.data.to_mut()[..2].copy_from_slice(&bits.to_be_bytes());
+      du.org
+        .custom
+        .retain(|x| x.org != MED_OUI || x.subtype != MED_SUBTYPE_EXTENDED_POWER);
+      du.org.custom.push(state.med());
+    }
+  }
+}
+
+#[test]
+fn provides_live_power() {
+  use std::sync::atomic::{AtomicU16, Ordering};
+
+  use crate::MedConfig;
+
+  let mut du = crate::fixtures::lldp_du();
+
+  let allocated = Arc::new(AtomicU16::new(154));
+  let source = {
+    let allocated = allocated.clone();
+    move |_| {
+      Some(PowerState {
+        port_class: PowerPortClass::Pse,
+        enabled: true,
+        class: 3,
+        priority: PowerPriority::High,
+        requested: 154,
+        allocated: allocated.load(Ordering::Relaxed),
+      })
+    }
+  };
+  let provider = PowerTlvs(Arc::new(source));
+
+  // no med, just the dot3 tlv
+  provider.provide(Scope::NearestBridge, &mut du);
+  let power = du.org.dot3.power.clone().unwrap();
+  assert_eq!((power.class(), power.allocated_watts()), (Some(3), Some(15.4)));
+  assert!(du.org.custom.is_empty());
+
+  MedConfig {
+    policies: Vec::new(),
+    location: Some(Default::default()),
+  }
+  .provide(Scope::NearestBridge, &mut du);
+  allocated.store(300, Ordering::Relaxed);
+  provider.provide(Scope::NearestBridge, &mut du);
+  assert_eq!(du.org.dot3.power.unwrap().allocated_watts(), Some(30.0));
+  assert_eq!(&du.org.custom[0].data[..2], &[0x00, 0x0d]);
+  let med = du.org.custom.last().unwrap();
+  assert_eq!((med.subtype, &med.data[..]), (4, &[0x12, 0x01, 0x2c][..]));
+}
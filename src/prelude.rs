@@ -0,0 +1,11 @@
+// `use rlldp::prelude::*` for the common case, the per-protocol data units are renamed so they can sit next to
+// the protocol independent one
+pub use lldp_parser::{
+  cdp::DataUnit as CdpDataUnit,
+  lldp::{du::DataUnit as LldpDataUnit, Msap},
+  DataUnit, Protocol,
+};
+
+#[cfg(feature = "capture")]
+pub use crate::{Agent, Interface};
+pub use crate::{LocalPort, MacAddress, NeighborEntry, NeighborEvent, NeighborEventKind};
@@ -0,0 +1,164 @@
+#[cfg(feature = "capture")]
+use std::{collections::BTreeMap, sync::atomic::Ordering};
+
+#[cfg(feature = "capture")]
+use crate::{
+  machine::{self, RxAction, RxMachine},
+  Interface, NeighborEventKind,
+};
+use crate::{MacAddress, TxConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Scope {
+  NearestBridge,
+  NearestNonTpmrBridge,
+  NearestCustomerBridge,
+}
+
+impl Scope {
+  pub const ALL: [Self; 3] = [
+    Self::NearestBridge,
+    Self::NearestNonTpmrBridge,
+    Self::NearestCustomerBridge,
+  ];
+
+  pub fn destination(self) -> MacAddress {
+    match self {
+      Self::NearestBridge => MacAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e]),
+      Self::NearestNonTpmrBridge => MacAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x03]),
+      Self::NearestCustomerBridge => MacAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x00]),
+    }
+  }
+
+  pub fn from_destination(destination: &MacAddress) -> Option<Self> {
+    Self::ALL.into_iter().find(|x| x.destination() == *destination)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AdminStatus {
+  TxOnly,
+  RxOnly,
+  #[default]
+  TxAndRx,
+  Disabled,
+}
+
+impl AdminStatus {
+  pub fn rx_enabled(self) -> bool {
+    matches!(self, Self::RxOnly | Self::TxAndRx)
+  }
+
+  pub fn tx_enabled(self) -> bool {
+    matches!(self, Self::TxOnly | Self::TxAndRx)
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentConfig {
+  pub admin_status: AdminStatus,
+  pub tx: TxConfig,
+}
+
+#[cfg(feature = "capture")]
+pub fn default_agents() -> BTreeMap<Scope, AgentConfig> {
+  Scope::ALL.into_iter().map(|x| (x, AgentConfig::default())).collect()
+}
+
+#[cfg(feature = "capture")]
+impl Interface {
+  pub fn agents(&self) -> BTreeMap<Scope, AgentConfig> {
+    self.inner.agents.lock().unwrap().clone()
+  }
+
+  pub fn agent(&self, scope: Scope) -> Option<AgentConfig> {
+    self.inner.agents.lock().unwrap().get(&scope).cloned()
+  }
+
+  pub async fn set_agent(&self, scope: Scope, config: AgentConfig) {
+    let action = {
+      let mut machines = self.inner.rx_machines.lock().unwrap();
+      match machines.get_mut(&scope) {
+        Some(machine) => machine.set_admin_status(config.admin_status),
+        None => {
+          machines.insert(scope, RxMachine::new(self.port_enabled(), config.admin_status));
+          None
+        }
+      }
+    };
+    self.inner.agents.lock().unwrap().insert(scope, config);
+    if action == Some(RxAction::DeleteAll) {
+      self.purge_scope(scope).await;
+    }
+  }
+
+  pub async fn remove_agent(&self, scope: Scope) -> Option<AgentConfig> {
+    self.inner.rx_machines.lock().unwrap().remove(&scope);
+    let config = self.inner.agents.lock().unwrap().remove(&scope);
+    self.purge_scope(scope).await;
+    config
+  }
+
+  pub fn port_enabled(&self) -> bool {
+    self.inner.port_enabled.load(Ordering::Relaxed)
+  }
+
+  // portEnabled, for whoever's watching the link. agents stop receiving while it's down and start over once it's
+  // back, transmission stops on the next tick
+  pub async fn set_port_enabled(&self, enabled: bool) {
+    self.inner.port_enabled.store(enabled, Ordering::Relaxed);
+    let purge: Vec<_> = {
+      let mut machines = self.inner.rx_machines.lock().unwrap();
+      machines
+        .iter_mut()
+        .filter_map(|(scope, x)| (x.set_port_enabled(enabled) == Some(RxAction::DeleteAll)).then_some(*scope))
+        .collect()
+    };
+    for scope in purge {
+      self.purge_scope(scope).await;
+    }
+  }
+
+  pub(crate) fn rx_enabled(&self, scope: Option<Scope>) -> bool {
+    // frames outside of any lldp scope aren't handled by an agent
+    let Some(scope) = scope else {
+      return true;
+    };
+
+    let machines = self.inner.rx_machines.lock().unwrap();
+    machines.get(&scope).is_some_and(RxMachine::receiving)
+  }
+
+  // rcvFrame, what the agent's rx machine makes of a frame for one of its msaps
+  pub(crate) fn rx_frame(&self, scope: Option<Scope>, ttl: u16, changes: bool) -> RxAction {
+    let mut machines = self.inner.rx_machines.lock().unwrap();
+    scope
+      .and_then(|x| machines.get_mut(&x))
+      .and_then(|x| x.frame(Some((ttl, changes))))
+      .unwrap_or_else(|| machine::rx_process_frame(ttl, changes))
+  }
+
+  // rxInfoAge, the caller has already taken the aged out msap out of the table
+  pub(crate) fn rx_info_age(&self, scope: Option<Scope>) {
+    let mut machines = self.inner.rx_machines.lock().unwrap();
+    if let Some(machine) = scope.and_then(|x| machines.get_mut(&x)) {
+      machine.info_aged();
+    }
+  }
+
+  async fn purge_scope(&self, scope: Scope) {
+    // rxInitializeLLDP drops everything the agent learned once it stops receiving
+    let mut neighbors = self.inner.neighbors.write().await;
+    let keys: Vec<_> = neighbors.keys().filter(|x| x.scope == Some(scope)).cloned().collect();
+    for key in keys {
+      let neighbor = neighbors.remove(&key).unwrap();
+      neighbor.timeout_handle.abort();
+      let counters = &self.inner.counters;
+      counters.changed(Some(&counters.deletes));
+      self.emit(
+        NeighborEventKind::Expired,
+        neighbor.to_entry(&key, &self.inner.local_port),
+      );
+    }
+  }
+}
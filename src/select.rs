@@ -0,0 +1,65 @@
+use std::{convert::Infallible, io, str::FromStr};
+
+// "eth*,!eth0" style lists of globs, a leading ! excludes. with no includes every interface is included.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct InterfaceSelector {
+  pub include: Vec<String>,
+  pub exclude: Vec<String>,
+}
+
+impl FromStr for InterfaceSelector {
+  type Err = Infallible;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut out = Self::default();
+    for pattern in s.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+      match pattern.strip_prefix('!') {
+        Some(x) => out.exclude.push(x.into()),
+        None => out.include.push(pattern.into()),
+      }
+    }
+    Ok(out)
+  }
+}
+
+impl InterfaceSelector {
+  pub fn matches(&self, name: &str) -> bool {
+    let included = self.include.is_empty() || self.include.iter().any(|x| glob(x.as_bytes(), name.as_bytes()));
+    included && !self.exclude.iter().any(|x| glob(x.as_bytes(), name.as_bytes()))
+  }
+
+  pub fn resolve(&self) -> io::Result<Vec<String>> {
+    Ok(
+      crate::local::os_interfaces()?
+        .into_iter()
+        .filter(|x| self.matches(x))
+        .collect(),
+    )
+  }
+}
+
+// shell style, * is any run of characters and ? is any one character
+fn glob(pattern: &[u8], name: &[u8]) -> bool {
+  match (pattern.first(), name.first()) {
+    (None, None) => true,
+    (Some(b'*'), _) => glob(&pattern[1..], name) || (!name.is_empty() && glob(pattern, &name[1..])),
+    (Some(b'?'), Some(_)) => glob(&pattern[1..], &name[1..]),
+    (Some(x), Some(y)) if x == y => glob(&pattern[1..], &name[1..]),
+    _ => false,
+  }
+}
+
+#[test]
+fn selects_interfaces() {
+  let selector: InterfaceSelector = "eth*, !eth0,en?".parse().unwrap();
+  assert!(selector.matches("eth1"));
+  assert!(selector.matches("eth10"));
+  assert!(!selector.matches("eth0"));
+  assert!(selector.matches("en0"));
+  assert!(!selector.matches("en10"));
+  assert!(!selector.matches("lo"));
+
+  let selector: InterfaceSelector = "!lo".parse().unwrap();
+  assert!(selector.matches("eth0"));
+  assert!(!selector.matches("lo"));
+}
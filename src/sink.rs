@@ -0,0 +1,52 @@
+use std::{future::Future, io};
+
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::NeighborEvent;
+
+// somewhere outside the process that neighbor events get published to, a message bus or a database
+pub trait EventSink {
+  fn publish(&mut self, event: &NeighborEvent) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+// publishes until the channel closes. a failed publish only loses that event, the sink is expected to reconnect
+pub async fn run_sink<S: EventSink>(mut events: broadcast::Receiver<NeighborEvent>, mut sink: S) {
+  loop {
+    match events.recv().await {
+      Ok(event) => {
+        if let Err(err) = sink.publish(&event).await {
+          warn!(%err, "failed to publish neighbor event");
+        }
+      }
+      Err(RecvError::Lagged(count)) => warn!(count, "sink missed neighbor events"),
+      Err(RecvError::Closed) => return,
+    }
+  }
+}
+
+#[tokio::test]
+async fn publishes_events() {
+  use crate::{FrameInfo, Interface, LocalPort, MacAddress, NeighborEventKind};
+
+  struct Collect(tokio::sync::mpsc::UnboundedSender<NeighborEventKind>);
+
+  impl EventSink for Collect {
+    async fn publish(&mut self, event: &NeighborEvent) -> io::Result<()> {
+      self.0.send(event.kind).map_err(io::Error::other)
+    }
+  }
+
+  let interface = Interface::new(LocalPort::new("eth0"));
+  let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+  tokio::spawn(run_sink(interface.subscribe(), Collect(tx)));
+
+  let du = lldp_parser::DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+    device_id: Some("a".into()),
+    ..crate::fixtures::cdp_du()
+  });
+  interface
+    .insert_du(FrameInfo::new(MacAddress([0, 0, 0, 0, 0, 1])), du)
+    .await;
+  assert_eq!(rx.recv().await, Some(NeighborEventKind::Discovered));
+}
@@ -0,0 +1,144 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use lldp_parser::{lldp::Msap, Protocol};
+
+use crate::{FieldChange, MacAddress, NeighborEntry, NeighborField, Scope};
+
+// the neighbor table at one point in time, for pollers that compare tables instead of following events
+#[derive(Debug, Clone)]
+pub struct NeighborTableSnapshot {
+  pub time: SystemTime,
+  pub neighbors: Vec<NeighborEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NeighborChange {
+  pub old: NeighborEntry,
+  pub new: NeighborEntry,
+  pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TableDiff {
+  pub added: Vec<NeighborEntry>,
+  pub removed: Vec<NeighborEntry>,
+  pub changed: Vec<NeighborChange>,
+}
+
+impl TableDiff {
+  pub fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+  }
+}
+
+// the same neighbor as far as the table is concerned, see neighbor::NeighborKey
+#[derive(PartialEq, Eq, Hash)]
+struct Identity<'a> {
+  interface: &'a str,
+  protocol: Protocol,
+  scope: Option<Scope>,
+  msap: Option<Msap<'static>>,
+  source: Option<&'a MacAddress>,
+}
+
+impl<'a> Identity<'a> {
+  fn new(neighbor: &'a NeighborEntry) -> Self {
+    let msap = neighbor.msap();
+    Self {
+      interface: &neighbor.local_port.name,
+      protocol: neighbor.protocol,
+      scope: neighbor.scope,
+      source: msap.is_none().then_some(&neighbor.source),
+      msap,
+    }
+  }
+}
+
+impl NeighborTableSnapshot {
+  // sorted by identity, so two snapshots of the same table list the same way
+  pub fn new(mut neighbors: Vec<NeighborEntry>) -> Self {
+    neighbors.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+    Self {
+      time: SystemTime::now(),
+      neighbors,
+    }
+  }
+
+  // what happened between older and this one. a neighbor that only refreshed its ttl hasn't changed
+  pub fn diff(&self, older: &Self) -> TableDiff {
+    let mut old: HashMap<_, _> = older.neighbors.iter().map(|x| (Identity::new(x), x)).collect();
+    let mut diff = TableDiff::default();
+    for new in &self.neighbors {
+      let Some(old) = old.remove(&Identity::new(new)) else {
+        diff.added.push(new.clone());
+        continue;
+      };
+
+      let changes: Vec<_> = FieldChange::diff(&old.source, old.du.get(), &new.source, new.du.get())
+        .into_iter()
+        .filter(|x| x.field != NeighborField::TimeToLive)
+        .collect();
+      if !changes.is_empty() {
+        diff.changed.push(NeighborChange {
+          old: old.clone(),
+          new: new.clone(),
+          changes,
+        });
+      }
+    }
+
+    // in the older snapshot's order
+    diff.removed = older
+      .neighbors
+      .iter()
+      .filter(|x| old.contains_key(&Identity::new(x)))
+      .cloned()
+      .collect();
+    diff
+  }
+}
+
+#[test]
+fn diffs_snapshots() {
+  use std::{sync::Arc, time::Instant};
+
+  use crate::{LocalPort, StoredDu};
+
+  let neighbor = |source: u8, name: &'static str, ttl: u8| NeighborEntry {
+    local_port: Arc::new(LocalPort::new("eth0")),
+    protocol: Protocol::Cdp,
+    scope: None,
+    source: MacAddress([0, 0, 0, 0, 0, source]),
+    vlans: Vec::new(),
+    remote_index: source.into(),
+    first_detection_time: Instant::now(),
+    last_detection_time: Instant::now(),
+    capture_time: None,
+    du: StoredDu::decoded(lldp_parser::DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+      time_to_live: ttl,
+      device_id: Some(name.into()),
+      ..crate::fixtures::cdp_du()
+    })),
+    changes: Vec::new(),
+  };
+
+  let older = NeighborTableSnapshot::new(vec![
+    neighbor(1, "a", 180),
+    neighbor(2, "b", 180),
+    neighbor(3, "c", 180),
+  ]);
+  let newer = NeighborTableSnapshot::new(vec![
+    neighbor(1, "a", 120),
+    neighbor(2, "b2", 180),
+    neighbor(4, "d", 180),
+  ]);
+
+  let diff = newer.diff(&older);
+  assert_eq!(diff.added.len(), 1);
+  assert_eq!(diff.added[0].source, MacAddress([0, 0, 0, 0, 0, 4]));
+  assert_eq!(diff.removed.len(), 1);
+  assert_eq!(diff.removed[0].source, MacAddress([0, 0, 0, 0, 0, 3]));
+  assert_eq!(diff.changed.len(), 1);
+  assert_eq!(diff.changed[0].changes[0].field, NeighborField::SystemName);
+  assert!(newer.diff(&newer).is_empty());
+}
@@ -0,0 +1,97 @@
+use std::{
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+  },
+  time::SystemTime,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct InterfaceStats {
+  pub frames_received: u64,
+  pub frames_truncated: u64,
+  // lldpStatsRxPortTLVsDiscardedTotal and lldpStatsRxPortTLVsUnrecognizedTotal, cdp tlvs count too
+  pub tlvs_discarded: u64,
+  pub tlvs_unrecognized: u64,
+  // lldpStatsRemTables for this interface's part of the table, every protocol included
+  pub inserts: u64,
+  pub deletes: u64,
+  // dus over InterfaceConfig::max_du_size
+  pub drops: u64,
+  pub ageouts: u64,
+  pub last_change: Option<SystemTime>,
+  // from the capture backend where it keeps them, zero otherwise. frames dropped here never reached frames_received
+  pub kernel_received: u64,
+  pub kernel_dropped: u64,
+}
+
+// lldpStatsRemTables, summed over the members of an agent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AgentStats {
+  pub inserts: u64,
+  pub deletes: u64,
+  pub drops: u64,
+  pub ageouts: u64,
+  pub last_change: Option<SystemTime>,
+}
+
+impl FromIterator<InterfaceStats> for AgentStats {
+  fn from_iter<T: IntoIterator<Item = InterfaceStats>>(iter: T) -> Self {
+    iter.into_iter().fold(Self::default(), |acc, x| Self {
+      inserts: acc.inserts + x.inserts,
+      deletes: acc.deletes + x.deletes,
+      drops: acc.drops + x.drops,
+      ageouts: acc.ageouts + x.ageouts,
+      last_change: acc.last_change.max(x.last_change),
+    })
+  }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+  pub frames_received: AtomicU64,
+  pub frames_truncated: AtomicU64,
+  pub tlvs_discarded: AtomicU64,
+  pub tlvs_unrecognized: AtomicU64,
+  pub inserts: AtomicU64,
+  pub deletes: AtomicU64,
+  pub drops: AtomicU64,
+  pub ageouts: AtomicU64,
+  pub last_change: Mutex<Option<SystemTime>>,
+  pub kernel_received: AtomicU64,
+  pub kernel_dropped: AtomicU64,
+}
+
+impl Counters {
+  pub fn snapshot(&self) -> InterfaceStats {
+    InterfaceStats {
+      frames_received: self.frames_received.load(Ordering::Relaxed),
+      frames_truncated: self.frames_truncated.load(Ordering::Relaxed),
+      tlvs_discarded: self.tlvs_discarded.load(Ordering::Relaxed),
+      tlvs_unrecognized: self.tlvs_unrecognized.load(Ordering::Relaxed),
+      inserts: self.inserts.load(Ordering::Relaxed),
+      deletes: self.deletes.load(Ordering::Relaxed),
+      drops: self.drops.load(Ordering::Relaxed),
+      ageouts: self.ageouts.load(Ordering::Relaxed),
+      last_change: *self.last_change.lock().unwrap(),
+      kernel_received: self.kernel_received.load(Ordering::Relaxed),
+      kernel_dropped: self.kernel_dropped.load(Ordering::Relaxed),
+    }
+  }
+
+  // bumps counter, if any, and lldpStatsRemTablesLastChangeTime along with it
+  pub fn changed(&self, counter: Option<&AtomicU64>) {
+    if let Some(counter) = counter {
+      incr(counter);
+    }
+    *self.last_change.lock().unwrap() = Some(SystemTime::now());
+  }
+}
+
+pub(crate) fn incr(counter: &AtomicU64) {
+  add(counter, 1);
+}
+
+pub(crate) fn add(counter: &AtomicU64, n: usize) {
+  counter.fetch_add(n as _, Ordering::Relaxed);
+}
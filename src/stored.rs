@@ -0,0 +1,100 @@
+use std::{
+  ops::Deref,
+  sync::{Arc, OnceLock},
+};
+
+use lldp_parser::{DataUnit, DataUnitError, Protocol};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DuStorage {
+  #[default]
+  Decoded,
+  Raw,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredDu(Arc<Repr>);
+
+#[derive(Debug)]
+enum Repr {
+  Decoded(DataUnit<'static>),
+  Raw {
+    protocol: Protocol,
+    time_to_live: u16,
+    bytes: Box<[u8]>,
+    decoded: OnceLock<DataUnit<'static>>,
+  },
+}
+
+impl StoredDu {
+  pub fn decoded(du: DataUnit<'static>) -> Self {
+    Self(Arc::new(Repr::Decoded(du)))
+  }
+
+  pub fn raw(protocol: Protocol, bytes: &[u8]) -> Result<Self, DataUnitError> {
+    // decoding borrowed is cheap, the owned view is only built when someone asks for it
+    let time_to_live = DataUnit::decode(protocol, bytes)?.time_to_live();
+    Ok(Self::raw_with_ttl(protocol, bytes, time_to_live))
+  }
+
+  // for bytes the caller has already decoded once, get() relies on them decoding again
+  pub(crate) fn raw_with_ttl(protocol: Protocol, bytes: &[u8], time_to_live: u16) -> Self {
+    Self(Arc::new(Repr::Raw {
+      protocol,
+      time_to_live,
+      bytes: bytes.into(),
+      decoded: OnceLock::new(),
+    }))
+  }
+
+  pub fn protocol(&self) -> Protocol {
+    match &*self.0 {
+      Repr::Decoded(du) => du.protocol(),
+      Repr::Raw { protocol, .. } => *protocol,
+    }
+  }
+
+  pub fn time_to_live(&self) -> u16 {
+    match &*self.0 {
+      Repr::Decoded(du) => du.time_to_live(),
+      Repr::Raw { time_to_live, .. } => *time_to_live,
+    }
+  }
+
+  pub fn raw_bytes(&self) -> Option<&[u8]> {
+    match &*self.0 {
+      Repr::Decoded(_) => None,
+      Repr::Raw { bytes, .. } => Some(bytes),
+    }
+  }
+
+  pub fn get(&self) -> &DataUnit<'static> {
+    match &*self.0 {
+      Repr::Decoded(du) => du,
+      Repr::Raw {
+        protocol,
+        bytes,
+        decoded,
+        ..
+      } => decoded.get_or_init(|| {
+        DataUnit::decode(*protocol, bytes)
+          .expect("raw du was validated when stored")
+          .to_static()
+      }),
+    }
+  }
+}
+
+impl Deref for StoredDu {
+  type Target = DataUnit<'static>;
+
+  fn deref(&self) -> &Self::Target {
+    self.get()
+  }
+}
+
+impl From<DataUnit<'static>> for StoredDu {
+  fn from(value: DataUnit<'static>) -> Self {
+    Self::decoded(value)
+  }
+}
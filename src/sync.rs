@@ -0,0 +1,247 @@
+use std::{
+  collections::HashMap,
+  sync::Arc,
+  time::{Duration, Instant, SystemTime},
+};
+
+use lldp_parser::DataUnit;
+use tracing::{debug, info};
+
+use crate::{
+  neighbor::{split_frame, unchanged, NeighborKey, DEFAULT_MAX_TTL},
+  FrameInfo, LocalPort, NeighborEntry, NeighborEvent, NeighborEventKind, StoredDu,
+};
+
+// Interface's neighbor tracking without tokio, for small tools and programs that aren't async. nothing runs in the
+// background, neighbors only age out when expire is called
+#[derive(Debug)]
+pub struct NeighborTable {
+  local_port: Arc<LocalPort>,
+  neighbors: HashMap<NeighborKey, (NeighborEntry, Instant)>,
+  next_remote_index: u32,
+}
+
+impl NeighborTable {
+  pub fn new(local_port: LocalPort) -> Self {
+    Self {
+      local_port: Arc::new(local_port),
+      neighbors: HashMap::new(),
+      next_remote_index: 0,
+    }
+  }
+
+  pub fn local_port(&self) -> &Arc<LocalPort> {
+    &self.local_port
+  }
+
+  pub fn neighbors(&self) -> Vec<NeighborEntry> {
+    let mut out: Vec<_> = self.neighbors.values().map(|(entry, _)| entry.clone()).collect();
+    out.sort_by_key(|x| x.remote_index);
+    out
+  }
+
+  // when expire next has something to do, for callers that sleep or set a read timeout until then
+  pub fn next_expiry(&self) -> Option<Instant> {
+    self.neighbors.values().map(|(_, expires)| *expires).min()
+  }
+
+  pub fn expire(&mut self) -> Vec<NeighborEvent> {
+    let now = Instant::now();
+    let expired: Vec<_> = self
+      .neighbors
+      .iter()
+      .filter(|(_, (_, expires))| *expires <= now)
+      .map(|(key, _)| key.clone())
+      .collect();
+
+    let mut events = Vec::with_capacity(expired.len());
+    for key in expired {
+      if let Some((neighbor, _)) = self.neighbors.remove(&key) {
+        info!(protocol = ?key.protocol, id = ?key.id, "neighbor timed out");
+        events.push(NeighborEvent {
+          kind: NeighborEventKind::Expired,
+          neighbor,
+        });
+      }
+    }
+    events
+  }
+
+  // truncated frames and ones that aren't discovery protocols are ignored
+  pub fn handle_frame(
+    &mut self,
+    frame: &[u8],
+    wire_len: usize,
+    timestamp: Option<SystemTime>,
+  ) -> Option<NeighborEvent> {
+    if frame.len() < wire_len {
+      debug!(caplen = frame.len(), wire_len, "dropping truncated frame");
+      return None;
+    }
+
+    let (mut info, protocol, payload) = split_frame(frame)?;
+    info.timestamp = timestamp;
+    match DataUnit::decode(protocol, payload) {
+      Ok(du) => self.insert_du(info, du.to_static()),
+      Err(err) => {
+        debug!(%err, "failed to decode du");
+        None
+      }
+    }
+  }
+
+  // the event, if the du changed anything. a ttl of 0 removes the neighbor straight away
+  pub fn insert_du(&mut self, info: FrameInfo, du: DataUnit<'static>) -> Option<NeighborEvent> {
+    let key = NeighborKey::new(du.protocol(), info.scope, du.msap(), &info.source);
+    let now = Instant::now();
+    let delay = info.timestamp.and_then(|x| SystemTime::now().duration_since(x).ok());
+    let received = delay.and_then(|x| now.checked_sub(x)).unwrap_or(now);
+    let ttl = du.time_to_live().min(DEFAULT_MAX_TTL);
+
+    let old = self.neighbors.remove(&key).map(|(entry, _)| entry);
+    // a shutdown from a neighbor that was never seen has nothing to expire
+    if ttl == 0 && old.is_none() {
+      return None;
+    }
+    let kind = match &old {
+      Some(old) if old.source == info.source && old.vlans == info.vlans && unchanged(old.du.get(), &du) => None,
+      Some(_) => Some(NeighborEventKind::Updated),
+      None => Some(NeighborEventKind::Discovered),
+    };
+    let remote_index = old.as_ref().map(|x| x.remote_index).unwrap_or_else(|| {
+      self.next_remote_index += 1;
+      self.next_remote_index
+    });
+
+    let entry = NeighborEntry {
+      local_port: self.local_port.clone(),
+      protocol: key.protocol,
+      scope: key.scope,
+      source: info.source,
+      vlans: info.vlans,
+      remote_index,
+      first_detection_time: old.map_or(received, |x| x.first_detection_time),
+      last_detection_time: received,
+      capture_time: info.timestamp,
+      du: StoredDu::decoded(du),
+      changes: Vec::new(),
+    };
+
+    if ttl == 0 {
+      return Some(NeighborEvent {
+        kind: NeighborEventKind::Expired,
+        neighbor: entry,
+      });
+    }
+
+    self
+      .neighbors
+      .insert(key, (entry.clone(), received + Duration::from_secs(ttl as _)));
+    kind.map(|kind| NeighborEvent { kind, neighbor: entry })
+  }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+impl NeighborTable {
+  // blocks the thread capturing on intf, on_event sees every change as it happens. neighbors only expire when a
+  // frame is read, so a quiet link keeps its last neighbors until the next one arrives
+  pub fn run_blocking(
+    &mut self,
+    intf: &str,
+    filter: &crate::FilterSpec,
+    mut on_event: impl FnMut(&NeighborEvent),
+  ) -> Result<(), crate::CaptureError> {
+    use rawsocket::{
+      bpf::{bpf_insn, bpf_program},
+      bsd::sync::BpfSocket,
+    };
+
+    if filter.is_empty() {
+      return Err(crate::CaptureError::NoProtocols);
+    }
+
+    let mut buf = vec![0; self.local_port.mtu.map_or(1500, |x| x as usize) + 64];
+    let sock = BpfSocket::open(intf, Some(buf.len() as _))?;
+    sock.set_immediate(true)?;
+    let mut insns: Vec<_> = filter
+      .compile()
+      .into_iter()
+      .map(|insn| bpf_insn {
+        code: insn.code,
+        jt: insn.jt,
+        jf: insn.jf,
+        k: insn.k,
+      })
+      .collect();
+    sock.set_read_filter(bpf_program {
+      bf_len: insns.len() as _,
+      bf_insns: insns.as_mut_ptr(),
+    })?;
+
+    loop {
+      for packet in sock.read_iter(&mut buf)? {
+        let tstamp = packet.header.bh_tstamp;
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::new(tstamp.tv_sec as _, tstamp.tv_usec as u32 * 1000);
+        if let Some(event) = self.handle_frame(packet.capture, packet.header.bh_datalen as _, Some(timestamp)) {
+          on_event(&event);
+        }
+      }
+      for event in self.expire() {
+        on_event(&event);
+      }
+    }
+  }
+}
+
+#[test]
+fn tracks_neighbors_without_tokio() {
+  use crate::MacAddress;
+
+  let du = |name: &'static str, ttl| {
+    DataUnit::Cdp(lldp_parser::cdp::DataUnit {
+      time_to_live: ttl,
+      device_id: Some(name.into()),
+      ..crate::fixtures::cdp_du()
+    })
+  };
+  let info = |x| FrameInfo::new(MacAddress([0, 0, 0, 0, 0, x]));
+  let kind = |event: Option<NeighborEvent>| event.map(|x| x.kind);
+
+  let mut table = NeighborTable::new(LocalPort::new("eth0"));
+  assert_eq!(
+    kind(table.insert_du(info(1), du("a", 180))),
+    Some(NeighborEventKind::Discovered)
+  );
+  assert_eq!(kind(table.insert_du(info(1), du("a", 120))), None);
+  assert_eq!(
+    kind(table.insert_du(info(1), du("a2", 120))),
+    Some(NeighborEventKind::Updated)
+  );
+  assert_eq!(
+    kind(table.insert_du(info(2), du("b", 180))),
+    Some(NeighborEventKind::Discovered)
+  );
+  assert_eq!(table.neighbors().len(), 2);
+  assert!(table.expire().is_empty());
+
+  // captured long enough ago that it's already past its ttl
+  let stale = FrameInfo {
+    timestamp: Some(SystemTime::now() - Duration::from_secs(10)),
+    ..info(3)
+  };
+  table.insert_du(stale, du("c", 5));
+  let expired = table.expire();
+  assert_eq!(expired.len(), 1);
+  assert_eq!(expired[0].neighbor.source, MacAddress([0, 0, 0, 0, 0, 3]));
+
+  assert_eq!(
+    kind(table.insert_du(info(2), du("b", 0))),
+    Some(NeighborEventKind::Expired)
+  );
+  assert_eq!(table.neighbors().len(), 1);
+  assert_eq!(table.neighbors()[0].remote_index, 1);
+
+  assert_eq!(kind(table.insert_du(info(4), du("d", 0))), None);
+  assert_eq!(table.neighbors().len(), 1);
+  assert_eq!(table.insert_du(info(5), du("e", 180)).unwrap().neighbor.remote_index, 4);
+}
@@ -0,0 +1,330 @@
+use std::{io, net::IpAddr};
+
+use lldp_parser::{
+  cdp::{tlv::CapabilityFlags as CdpCapabilityFlags, DataUnit as CdpDu},
+  lldp::{
+    du::{DataUnit as LldpDu, Org},
+    tlv::{
+      Capabilities, CapabilityFlags, ChassisId, ManagementAddress, ManagementInterfaceKind, NetworkAddress, PortId,
+    },
+  },
+};
+
+#[cfg(feature = "capture")]
+use crate::Interface;
+use crate::{LocalPort, MacAddress};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LocalSystem {
+  pub hostname: Option<String>,
+  pub description: Option<String>,
+  pub chassis_mac: Option<MacAddress>,
+  // advertised instead of the interface's own addresses when set, like lldpd's -m
+  pub management_address: Option<IpAddr>,
+  pub link_local_management: bool,
+}
+
+impl LocalSystem {
+  #[cfg(unix)]
+  pub fn from_os() -> io::Result<Self> {
+    Ok(Self {
+      hostname: os_hostname(),
+      description: os_description(),
+      chassis_mac: os_chassis_mac()?,
+      ..Default::default()
+    })
+  }
+
+  #[cfg(windows)]
+  pub fn from_os() -> io::Result<Self> {
+    Ok(Self {
+      hostname: std::env::var("COMPUTERNAME").ok(),
+      description: Some(format!("Windows {}", std::env::consts::ARCH)),
+      ..Default::default()
+    })
+  }
+
+  // wasm and other targets without an os to ask, the fields can still be filled in by hand
+  #[cfg(not(any(unix, windows)))]
+  pub fn from_os() -> io::Result<Self> {
+    Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "no local system information on this platform",
+    ))
+  }
+
+  fn chassis_id(&self) -> ChassisId<'static> {
+    match (&self.chassis_mac, &self.hostname) {
+      (Some(mac), _) => ChassisId::MacAddress(mac.0),
+      (None, Some(hostname)) => ChassisId::Local(hostname.clone().into()),
+      (None, None) => ChassisId::Local("unknown".into()),
+    }
+  }
+
+  fn usable_address(&self, addr: &IpAddr) -> bool {
+    let link_local = match addr {
+      IpAddr::V4(x) => x.is_link_local(),
+      IpAddr::V6(x) => x.is_unicast_link_local(),
+    };
+    !addr.is_loopback() && !addr.is_unspecified() && !addr.is_multicast() && (self.link_local_management || !link_local)
+  }
+
+  // lldpd advertises the first usable ipv4 and ipv6 address rather than every address on the port
+  pub fn management_addresses(&self, port: &LocalPort) -> Vec<ManagementAddress<'static>> {
+    let selected: Vec<_> = match self.management_address {
+      Some(addr) if port.addresses.contains(&addr) => vec![(addr, port.ifindex)],
+      Some(addr) => vec![(addr, os_ifindex_of(&addr))],
+      None => {
+        let usable = |v4| {
+          port
+            .addresses
+            .iter()
+            .find(|x| x.is_ipv4() == v4 && self.usable_address(x))
+            .map(|x| (*x, port.ifindex))
+        };
+        usable(true).into_iter().chain(usable(false)).collect()
+      }
+    };
+
+    selected
+      .into_iter()
+      .map(|(addr, ifindex)| ManagementAddress {
+        address: NetworkAddress::Ip(addr),
+        interface_subtype: match ifindex {
+          Some(_) => ManagementInterfaceKind::IfIndex,
+          None => ManagementInterfaceKind::Unknown,
+        },
+        interface_number: ifindex.unwrap_or_default(),
+        oid: "".into(),
+      })
+      .collect()
+  }
+
+  pub fn lldp_du(&self, port: &LocalPort) -> LldpDu<'static> {
+    LldpDu {
+      chassis_id: self.chassis_id(),
+      port_id: PortId::InterfaceName(port.name.clone().into()),
+      // replaced with the agent's msgTxInterval * msgTxHold when transmitted
+      time_to_live: 120,
+      port_description: Some(port.description.clone().unwrap_or_else(|| port.name.clone()).into()),
+      system_name: self.hostname.clone().map(Into::into),
+      system_description: self.description.clone().map(Into::into),
+      capabilities: Some(Capabilities {
+        capabilities: CapabilityFlags::STATION,
+        enabled_capabilities: CapabilityFlags::STATION,
+      }),
+      management_address: self.management_addresses(port),
+      org: Org::default(),
+    }
+  }
+
+  pub fn cdp_du(&self, port: &LocalPort) -> CdpDu<'static> {
+    CdpDu {
+      time_to_live: 180,
+      device_id: self.hostname.clone().map(Into::into),
+      addresses: self
+        .management_addresses(port)
+        .into_iter()
+        .filter_map(|x| match x.address {
+          NetworkAddress::Ip(x) => Some(x),
+          NetworkAddress::Other(..) => None,
+        })
+        .collect(),
+      capabilities: Some(CdpCapabilityFlags::HOST),
+      software_version: self.description.clone().map(Into::into),
+      platform: Some(std::env::consts::OS.into()),
+      port_id: Some(port.name.clone().into()),
+      duplex: None,
+      native_vlan: None,
+    }
+  }
+}
+
+#[cfg(feature = "capture")]
+impl Interface {
+  pub fn advertise_local_system(&self, system: &LocalSystem) {
+    self.set_advertisement(Some(system.lldp_du(self.local_port())));
+    self.set_cdp_advertisement(Some(system.cdp_du(self.local_port())));
+  }
+}
+
+#[cfg(unix)]
+fn os_hostname() -> Option<String> {
+  let mut buf = [0u8; 256];
+  if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut _, buf.len()) } != 0 {
+    return None;
+  }
+
+  let len = buf.iter().position(|x| *x == 0).unwrap_or(buf.len());
+  (len > 0).then(|| String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+// same format as lldpd, "<sysname> <release> <version> <machine>"
+#[cfg(unix)]
+fn os_description() -> Option<String> {
+  let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+  if unsafe { libc::uname(&mut uts) } != 0 {
+    return None;
+  }
+
+  let field = |x: &[libc::c_char]| {
+    unsafe { std::ffi::CStr::from_ptr(x.as_ptr()) }
+      .to_string_lossy()
+      .into_owned()
+  };
+  Some(format!(
+    "{} {} {} {}",
+    field(&uts.sysname),
+    field(&uts.release),
+    field(&uts.version),
+    field(&uts.machine)
+  ))
+}
+
+// the mac of the non-loopback interface with the lowest ifindex, so it stays stable across restarts
+#[cfg(unix)]
+fn os_chassis_mac() -> io::Result<Option<MacAddress>> {
+  let mut best: Option<(u32, MacAddress)> = None;
+  crate::local::for_each_ifaddr(|name, ifa| {
+    if ifa.ifa_flags & libc::IFF_LOOPBACK as u32 != 0 {
+      return;
+    }
+
+    let Some((Some(mac), _)) = (unsafe { crate::local::link_info(ifa) }) else {
+      return;
+    };
+    if mac.0 == [0; 6] {
+      return;
+    }
+
+    let Ok(c_name) = std::ffi::CString::new(name) else {
+      return;
+    };
+    let ifindex = unsafe { libc::if_nametoindex(c_name.as_ptr()) };
+    if best.as_ref().is_none_or(|(x, _)| ifindex < *x) {
+      best = Some((ifindex, mac));
+    }
+  })?;
+
+  Ok(best.map(|(_, mac)| mac))
+}
+
+#[cfg(unix)]
+fn os_ifindex_of(addr: &IpAddr) -> Option<u32> {
+  let mut ifindex = None;
+  let _ = crate::local::for_each_ifaddr(|name, ifa| {
+    if ifindex.is_some() || unsafe { crate::local::ip_addr(ifa.ifa_addr) } != Some(*addr) {
+      return;
+    }
+
+    let Ok(c_name) = std::ffi::CString::new(name) else {
+      return;
+    };
+    ifindex = Some(unsafe { libc::if_nametoindex(c_name.as_ptr()) }).filter(|x| *x != 0);
+  });
+  ifindex
+}
+
+#[cfg(not(unix))]
+fn os_ifindex_of(_: &IpAddr) -> Option<u32> {
+  None
+}
+
+#[test]
+fn builds_advertisement() {
+  let system = LocalSystem {
+    hostname: Some("host".into()),
+    description: Some("Linux".into()),
+    chassis_mac: Some(MacAddress([2, 0, 0, 0, 0, 1])),
+    ..Default::default()
+  };
+  let port = LocalPort {
+    ifindex: Some(3),
+    addresses: vec!["10.0.0.1".parse().unwrap()],
+    ..LocalPort::new("eth0")
+  };
+
+  let du = system.lldp_du(&port);
+  assert_eq!(du.chassis_id, ChassisId::MacAddress([2, 0, 0, 0, 0, 1]));
+  assert_eq!(du.port_id, PortId::InterfaceName("eth0".into()));
+  assert_eq!(du.system_name.as_deref(), Some("host"));
+  assert_eq!(du.management_address[0].interface_number, 3);
+
+  let du = system.cdp_du(&port);
+  assert_eq!(du.device_id.as_deref(), Some("host"));
+  assert_eq!(du.addresses, port.addresses);
+}
+
+#[test]
+fn selects_management_addresses() {
+  let port = LocalPort {
+    ifindex: Some(3),
+    addresses: ["fe80::1", "169.254.0.1", "10.0.0.1", "10.0.0.2", "2001:db8::1"]
+      .iter()
+      .map(|x| x.parse().unwrap())
+      .collect(),
+    ..LocalPort::new("eth0")
+  };
+  let addresses = |system: &LocalSystem| -> Vec<_> {
+    system
+      .management_addresses(&port)
+      .into_iter()
+      .map(|x| (x.address, x.interface_subtype, x.interface_number))
+      .collect()
+  };
+
+  let mut system = LocalSystem::default();
+  assert_eq!(
+    addresses(&system),
+    vec![
+      (
+        NetworkAddress::Ip("10.0.0.1".parse().unwrap()),
+        ManagementInterfaceKind::IfIndex,
+        3
+      ),
+      (
+        NetworkAddress::Ip("2001:db8::1".parse().unwrap()),
+        ManagementInterfaceKind::IfIndex,
+        3
+      ),
+    ]
+  );
+
+  system.link_local_management = true;
+  assert_eq!(
+    addresses(&system),
+    vec![
+      (
+        NetworkAddress::Ip("169.254.0.1".parse().unwrap()),
+        ManagementInterfaceKind::IfIndex,
+        3
+      ),
+      (
+        NetworkAddress::Ip("fe80::1".parse().unwrap()),
+        ManagementInterfaceKind::IfIndex,
+        3
+      ),
+    ]
+  );
+
+  system.management_address = Some("10.0.0.2".parse().unwrap());
+  assert_eq!(
+    addresses(&system),
+    vec![(
+      NetworkAddress::Ip("10.0.0.2".parse().unwrap()),
+      ManagementInterfaceKind::IfIndex,
+      3
+    )]
+  );
+
+  // not on any interface, so there's no ifindex to advertise
+  system.management_address = Some("192.0.2.1".parse().unwrap());
+  assert_eq!(
+    addresses(&system),
+    vec![(
+      NetworkAddress::Ip("192.0.2.1".parse().unwrap()),
+      ManagementInterfaceKind::Unknown,
+      0
+    )]
+  );
+}
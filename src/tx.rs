@@ -0,0 +1,717 @@
+#[cfg(feature = "capture")]
+use std::{collections::BTreeMap, sync::Arc};
+use std::{fmt::Debug, future::Future, io, time::Duration};
+
+#[cfg(feature = "capture")]
+use lldp_parser::lldp::tlv::CustomOrgTlv;
+use lldp_parser::{cdp::DataUnit as CdpDu, lldp::du::DataUnit as LldpDu};
+use thiserror::Error;
+#[cfg(feature = "capture")]
+use tokio::{sync::broadcast::error::RecvError, time::MissedTickBehavior};
+#[cfg(feature = "capture")]
+use tracing::debug;
+use tracing::warn;
+
+#[cfg(feature = "capture")]
+use crate::{
+  machine::{TxFrame, TxMachine},
+  AdminStatus, Interface, NeighborEventKind,
+};
+use crate::{med::MED_OUI, MacAddress, Scope};
+
+const ETHER_TYPE_LLDP: u16 = 0x88cc;
+const CDP_DESTINATION: MacAddress = MacAddress([0x01, 0x00, 0x0c, 0xcc, 0xcc, 0xcc]);
+const CDP_SNAP_HEADER: [u8; 8] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x0c, 0x20, 0x00];
+// minimum ethernet frame without the fcs, not every nic pads runts itself
+const MIN_FRAME_LEN: usize = 60;
+// ethernet payload without jumbo frames, the lldpdu goes right after the header
+const LLDP_MTU: usize = 1500;
+// a tlv's 511 byte information string, less the oui and subtype
+#[cfg(feature = "capture")]
+const MAX_CUSTOM_TLV_DATA: usize = 507;
+
+#[cfg(feature = "capture")]
+// the standard's timers all count whole seconds
+const TX_TICK: Duration = Duration::from_secs(1);
+
+pub trait PacketSink {
+  fn send_frame(&mut self, frame: &[u8]) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+// called with a copy of the advertisement right before every transmission, so dynamic tlvs
+// (poe draw, med network policy, ...) don't need the whole du rebuilt with set_advertisement
+pub trait TxTlvProvider: Debug + Send + Sync {
+  fn provide(&self, scope: Scope, du: &mut LldpDu<'static>);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxConfig {
+  pub msg_tx_interval: Duration,
+  pub msg_tx_hold: u16,
+  pub msg_fast_tx: Duration,
+  pub tx_fast_init: u32,
+  pub tx_credit_max: u32,
+  pub reinit_delay: Duration,
+  pub tlvs: TlvSelection,
+}
+
+impl Default for TxConfig {
+  fn default() -> Self {
+    // 802.1AB-2016 defaults
+    Self {
+      msg_tx_interval: Duration::from_secs(30),
+      msg_tx_hold: 4,
+      msg_fast_tx: Duration::from_secs(1),
+      tx_fast_init: 4,
+      tx_credit_max: 5,
+      reinit_delay: Duration::from_secs(2),
+      tlvs: TlvSelection::default(),
+    }
+  }
+}
+
+impl TxConfig {
+  pub fn time_to_live(&self) -> u16 {
+    let ttl = self.msg_tx_interval.as_secs() * self.msg_tx_hold as u64 + 1;
+    ttl.min(u16::MAX as u64) as u16
+  }
+}
+
+// which optional tlvs go out, like lldpPortConfigTLVsTxEnable and its dot1, dot3 and med counterparts. the
+// mandatory ones and other organizationally specific tlvs are always sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TlvSelection {
+  pub port_description: bool,
+  pub system_name: bool,
+  pub system_description: bool,
+  pub capabilities: bool,
+  pub management_address: bool,
+  pub dot1: bool,
+  pub dot3: bool,
+  pub med: bool,
+}
+
+impl Default for TlvSelection {
+  fn default() -> Self {
+    Self::ALL
+  }
+}
+
+impl TlvSelection {
+  pub const ALL: Self = Self {
+    port_description: true,
+    system_name: true,
+    system_description: true,
+    capabilities: true,
+    management_address: true,
+    dot1: true,
+    dot3: true,
+    med: true,
+  };
+
+  pub const NONE: Self = Self {
+    port_description: false,
+    system_name: false,
+    system_description: false,
+    capabilities: false,
+    management_address: false,
+    dot1: false,
+    dot3: false,
+    med: false,
+  };
+
+  pub fn apply(&self, du: &mut LldpDu<'_>) {
+    if !self.port_description {
+      du.port_description = None;
+    }
+    if !self.system_name {
+      du.system_name = None;
+    }
+    if !self.system_description {
+      du.system_description = None;
+    }
+    if !self.capabilities {
+      du.capabilities = None;
+    }
+    if !self.management_address {
+      du.management_address.clear();
+    }
+    if !self.dot1 {
+      du.org.dot1 = Default::default();
+    }
+    if !self.dot3 {
+      du.org.dot3 = Default::default();
+    }
+    if !self.med {
+      du.org.custom.retain(|x| x.org != MED_OUI);
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CdpTxConfig {
+  pub msg_tx_interval: Duration,
+  pub hold_time: u8,
+}
+
+impl Default for CdpTxConfig {
+  fn default() -> Self {
+    // ios defaults, cdp timer 60 and cdp holdtime 180
+    Self {
+      msg_tx_interval: Duration::from_secs(60),
+      hold_time: 180,
+    }
+  }
+}
+
+#[cfg(feature = "capture")]
+#[derive(Debug, Clone, Copy)]
+enum TxInput {
+  Tick,
+  LocalChange,
+  NewNeighbor(Scope),
+}
+
+#[cfg(feature = "capture")]
+fn encoded_len(du: LldpDu<'static>) -> usize {
+  let mut buf = Vec::new();
+  du.encode(&mut buf);
+  buf.len()
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CustomTlvError {
+  #[error("custom tlv {org:02x?}/{subtype} has {len} bytes of data, at most 507 fit")]
+  TooLong { org: [u8; 3], subtype: u8, len: usize },
+  #[error("lldpdu would be {size} bytes, more than the {mtu} that fit in a frame")]
+  TooBig { size: usize, mtu: usize },
+}
+
+pub fn lldp_frame(destination: &MacAddress, source: &MacAddress, du: LldpDu<'static>) -> Vec<u8> {
+  let mut frame = Vec::with_capacity(128);
+  frame.extend_from_slice(&destination.0);
+  frame.extend_from_slice(&source.0);
+  frame.extend_from_slice(&ETHER_TYPE_LLDP.to_be_bytes());
+  let dropped = du.encode_bounded(&mut frame, LLDP_MTU);
+  if !dropped.is_empty() {
+    let kinds: Vec<_> = dropped.iter().map(|x| x.kind()).collect();
+    warn!(?kinds, "lldpdu too big, left out optional tlvs");
+  }
+  frame.resize(frame.len().max(MIN_FRAME_LEN), 0);
+  frame
+}
+
+pub fn cdp_frame(source: &MacAddress, du: CdpDu<'static>) -> Vec<u8> {
+  let mut frame = Vec::with_capacity(128);
+  frame.extend_from_slice(&CDP_DESTINATION.0);
+  frame.extend_from_slice(&source.0);
+  // 802.3 length, filled in once the du is encoded
+  frame.extend_from_slice(&[0, 0]);
+  frame.extend_from_slice(&CDP_SNAP_HEADER);
+  du.encode(&mut frame);
+
+  let len = (frame.len() - 14) as u16;
+  frame[12..14].copy_from_slice(&len.to_be_bytes());
+  frame.resize(frame.len().max(MIN_FRAME_LEN), 0);
+  frame
+}
+
+#[cfg(feature = "capture")]
+impl Interface {
+  pub fn advertisement(&self) -> Option<LldpDu<'static>> {
+    self.inner.advertisement.lock().unwrap().clone()
+  }
+
+  pub fn set_advertisement(&self, du: Option<LldpDu<'static>>) {
+    *self.inner.advertisement.lock().unwrap() = du;
+    // somethingChangedLocal
+    self.inner.local_change.notify_waiters();
+  }
+
+  pub fn cdp_advertisement(&self) -> Option<CdpDu<'static>> {
+    self.inner.cdp_advertisement.lock().unwrap().clone()
+  }
+
+  pub fn set_cdp_advertisement(&self, du: Option<CdpDu<'static>>) {
+    *self.inner.cdp_advertisement.lock().unwrap() = du;
+    self.inner.local_change.notify_waiters();
+  }
+
+  // transmits on every agent right away, like lldpcli update
+  pub fn resend(&self) {
+    self.inner.local_change.notify_waiters();
+  }
+
+  pub fn add_tx_provider(&self, provider: Arc<dyn TxTlvProvider>) {
+    self.inner.tx_providers.lock().unwrap().push(provider);
+  }
+
+  pub fn remove_tx_provider(&self, provider: &Arc<dyn TxTlvProvider>) {
+    self
+      .inner
+      .tx_providers
+      .lock()
+      .unwrap()
+      .retain(|x| !Arc::ptr_eq(x, provider));
+  }
+
+  pub fn custom_tlvs(&self) -> Vec<CustomOrgTlv<'static>> {
+    self.inner.custom_tlvs.lock().unwrap().clone()
+  }
+
+  // sent after the advertisement's own tlvs on every agent. refused when the lldpdu wouldn't fit in a frame
+  // anymore, rather than having optional tlvs left out on every transmission
+  pub fn set_custom_tlvs(&self, tlvs: Vec<CustomOrgTlv<'static>>) -> Result<(), CustomTlvError> {
+    if let Some(tlv) = tlvs.iter().find(|x| x.data.len() > MAX_CUSTOM_TLV_DATA) {
+      return Err(CustomTlvError::TooLong {
+        org: tlv.org,
+        subtype: tlv.subtype,
+        len: tlv.data.len(),
+      });
+    }
+
+    for (scope, agent) in self.agents() {
+      let Some(du) = self.build_du(scope, &agent.tx, &tlvs) else {
+        break;
+      };
+      let size = encoded_len(du);
+      if size > LLDP_MTU {
+        return Err(CustomTlvError::TooBig { size, mtu: LLDP_MTU });
+      }
+    }
+
+    *self.inner.custom_tlvs.lock().unwrap() = tlvs;
+    self.inner.local_change.notify_waiters();
+    Ok(())
+  }
+
+  // how many more bytes of tlvs fit in the scope's lldpdu, None without an advertisement
+  pub fn tx_budget(&self, scope: Scope) -> Option<usize> {
+    let config = self.agent(scope).map(|x| x.tx).unwrap_or_default();
+    let du = self.tx_du(scope, &config)?;
+    Some(LLDP_MTU.saturating_sub(encoded_len(du)))
+  }
+
+  pub(crate) fn tx_du(&self, scope: Scope, config: &TxConfig) -> Option<LldpDu<'static>> {
+    let custom = self.custom_tlvs();
+    self.build_du(scope, config, &custom)
+  }
+
+  fn build_du(&self, scope: Scope, config: &TxConfig, custom: &[CustomOrgTlv<'static>]) -> Option<LldpDu<'static>> {
+    let mut du = self.advertisement()?;
+    du.org.custom.extend_from_slice(custom);
+    let providers = self.inner.tx_providers.lock().unwrap().clone();
+    for provider in providers {
+      provider.provide(scope, &mut du);
+    }
+
+    // providers' tlvs are selected like the rest
+    config.tlvs.apply(&mut du);
+    du.time_to_live = config.time_to_live();
+    Some(du)
+  }
+
+  fn source_mac(&self) -> MacAddress {
+    self.inner.local_port.mac_address.clone().unwrap_or(MacAddress([0; 6]))
+  }
+
+  // mibConstrShutdownLLDPDU, only the msap and a ttl of 0
+  fn shutdown_du(&self) -> Option<LldpDu<'static>> {
+    let du = self.advertisement()?;
+    Some(LldpDu {
+      chassis_id: du.chassis_id,
+      port_id: du.port_id,
+      time_to_live: 0,
+      port_description: None,
+      system_name: None,
+      system_description: None,
+      capabilities: None,
+      management_address: Vec::new(),
+      org: Default::default(),
+    })
+  }
+
+  async fn transmit<S: PacketSink>(
+    &self,
+    sink: &mut S,
+    scope: Scope,
+    frame: TxFrame,
+    config: &TxConfig,
+  ) -> io::Result<()> {
+    let du = match frame {
+      TxFrame::Info => self.tx_du(scope, config),
+      TxFrame::Shutdown => self.shutdown_du(),
+    };
+    let Some(du) = du else {
+      return Ok(());
+    };
+
+    let frame = lldp_frame(&scope.destination(), &self.source_mac(), du);
+    debug!(?scope, len = frame.len(), "transmitting lldpdu");
+    sink.send_frame(&frame).await
+  }
+
+  pub async fn run_tx<S: PacketSink>(&self, mut sink: S) -> io::Result<()> {
+    let mut events = self.subscribe();
+    let mut machines: BTreeMap<Scope, (TxMachine, TxConfig)> = BTreeMap::new();
+    let mut ticks = tokio::time::interval(TX_TICK);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+      let local_change = self.inner.local_change.notified();
+      let input = tokio::select! {
+        _ = ticks.tick() => TxInput::Tick,
+        _ = local_change => TxInput::LocalChange,
+        event = events.recv() => match event {
+          Ok(event) if event.kind == NeighborEventKind::Discovered => match event.neighbor.scope {
+            Some(scope) => TxInput::NewNeighbor(scope),
+            None => continue,
+          },
+          Ok(_) => continue,
+          Err(RecvError::Lagged(count)) => {
+            warn!(count, "tx missed neighbor events");
+            continue;
+          }
+          Err(RecvError::Closed) => return Ok(()),
+        },
+      };
+
+      let port_enabled = self.port_enabled();
+      let agents = self.agents();
+      let mut frames = Vec::new();
+      // removed agents are disabled first, so they still get to send their shutdown lldpdu
+      machines.retain(|scope, (machine, config)| {
+        if agents.contains_key(scope) {
+          return true;
+        }
+        frames.extend(
+          machine
+            .set_admin_status(AdminStatus::Disabled, config)
+            .map(|x| (*scope, x, config.clone())),
+        );
+        false
+      });
+
+      for (scope, agent) in agents {
+        let (machine, config) = machines.entry(scope).or_insert_with(|| {
+          (
+            TxMachine::new(port_enabled, agent.admin_status, &agent.tx),
+            agent.tx.clone(),
+          )
+        });
+        *config = agent.tx;
+        let mut push = |frame: Option<TxFrame>| frames.extend(frame.map(|x| (scope, x, config.clone())));
+        push(machine.set_port_enabled(port_enabled, config));
+        push(machine.set_admin_status(agent.admin_status, config));
+        push(match input {
+          TxInput::Tick => machine.tick(config),
+          TxInput::LocalChange => machine.local_change(config),
+          TxInput::NewNeighbor(x) if x == scope => machine.new_neighbor(config),
+          TxInput::NewNeighbor(_) => machine.step(config),
+        });
+      }
+
+      for (scope, frame, config) in frames {
+        if frame == TxFrame::Shutdown {
+          debug!(?scope, "transmit disabled, sending shutdown lldpdu");
+        }
+        self.transmit(&mut sink, scope, frame, &config).await?;
+      }
+    }
+  }
+}
+
+#[cfg(feature = "capture")]
+impl Interface {
+  pub async fn run_cdp_tx<S: PacketSink>(&self, mut sink: S) -> io::Result<()> {
+    let config = &self.inner.config.cdp_tx;
+    loop {
+      let local_change = self.inner.local_change.notified();
+      if let Some(mut du) = self.cdp_advertisement().filter(|_| self.cdp_tx_enabled()) {
+        du.time_to_live = config.hold_time;
+        let frame = cdp_frame(&self.source_mac(), du);
+        debug!(len = frame.len(), "transmitting cdp du");
+        sink.send_frame(&frame).await?;
+      }
+
+      tokio::select! {
+        _ = tokio::time::sleep(config.msg_tx_interval) => {}
+        _ = local_change => {}
+      }
+    }
+  }
+
+  // cdp has no scope of its own, it goes quiet along with the nearest bridge agent and the port
+  fn cdp_tx_enabled(&self) -> bool {
+    self.port_enabled()
+      && self
+        .agent(Scope::NearestBridge)
+        .is_some_and(|x| x.admin_status.tx_enabled())
+  }
+}
+
+#[test]
+fn ttl_from_tx_config() {
+  assert_eq!(TxConfig::default().time_to_live(), 121);
+  let config = TxConfig {
+    msg_tx_interval: Duration::from_secs(3600),
+    msg_tx_hold: 100,
+    ..Default::default()
+  };
+  assert_eq!(config.time_to_live(), u16::MAX);
+}
+
+#[test]
+fn cdp_frame_round_trip() {
+  let du = CdpDu {
+    device_id: Some("host".into()),
+    port_id: Some("eth0".into()),
+    ..crate::fixtures::cdp_du()
+  };
+
+  let frame = cdp_frame(&MacAddress([2, 0, 0, 0, 0, 1]), du.clone());
+  let (parsed, decoded) = lldp_parser::DataUnit::decode_frame(&frame).unwrap();
+  assert_eq!(parsed.source, [2, 0, 0, 0, 0, 1]);
+  assert_eq!(decoded, lldp_parser::DataUnit::Cdp(du));
+}
+
+#[cfg(feature = "capture")]
+#[test]
+fn providers_add_tlvs() {
+  use lldp_parser::lldp::tlv::CustomOrgTlv;
+
+  #[derive(Debug)]
+  struct Power;
+
+  impl TxTlvProvider for Power {
+    fn provide(&self, _: Scope, du: &mut LldpDu<'static>) {
+      du.org.custom.push(CustomOrgTlv {
+        org: [0x00, 0x12, 0x0f],
+        subtype: 2,
+        data: vec![0x0f, 0x01, 0x02].into(),
+      });
+    }
+  }
+
+  let intf = Interface::default();
+  intf.set_advertisement(Some(LldpDu {
+    time_to_live: 0,
+    ..crate::fixtures::lldp_du()
+  }));
+
+  let provider: Arc<dyn TxTlvProvider> = Arc::new(Power);
+  intf.add_tx_provider(provider.clone());
+  let du = intf.tx_du(Scope::NearestBridge, &TxConfig::default()).unwrap();
+  assert_eq!(du.org.custom.len(), 1);
+  assert_eq!(du.time_to_live, 121);
+  // the stored advertisement is left alone
+  assert!(intf.advertisement().unwrap().org.custom.is_empty());
+
+  intf.remove_tx_provider(&provider);
+  assert!(intf
+    .tx_du(Scope::NearestBridge, &TxConfig::default())
+    .unwrap()
+    .org
+    .custom
+    .is_empty());
+}
+
+#[test]
+fn pads_small_frames() {
+  use lldp_parser::lldp::tlv::{ChassisId, PortId};
+
+  let du = LldpDu {
+    chassis_id: ChassisId::Local("c".into()),
+    port_id: PortId::Local("p".into()),
+    ..crate::fixtures::lldp_du()
+  };
+
+  let frame = lldp_frame(
+    &Scope::NearestBridge.destination(),
+    &MacAddress([2, 0, 0, 0, 0, 1]),
+    du.clone(),
+  );
+  assert_eq!(frame.len(), MIN_FRAME_LEN);
+  // the padding reads as an end of lldpdu tlv
+  let (_, decoded) = lldp_parser::DataUnit::decode_frame(&frame).unwrap();
+  assert_eq!(decoded, lldp_parser::DataUnit::Lldp(du));
+
+  let du = crate::fixtures::cdp_du();
+  let frame = cdp_frame(&MacAddress([2, 0, 0, 0, 0, 1]), du.clone());
+  assert_eq!(frame.len(), MIN_FRAME_LEN);
+  let (_, decoded) = lldp_parser::DataUnit::decode_frame(&frame).unwrap();
+  assert_eq!(decoded, lldp_parser::DataUnit::Cdp(du));
+}
+
+#[cfg(all(test, feature = "capture"))]
+struct Channel(tokio::sync::mpsc::UnboundedSender<Vec<u8>>);
+
+#[cfg(all(test, feature = "capture"))]
+impl PacketSink for Channel {
+  async fn send_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+    self.0.send(frame.to_vec()).unwrap();
+    Ok(())
+  }
+}
+
+#[cfg(feature = "capture")]
+#[tokio::test]
+async fn shutdown_when_tx_disabled() {
+  use tokio::sync::mpsc;
+
+  let intf = Interface::default();
+  intf.set_advertisement(Some(LldpDu {
+    time_to_live: 0,
+    system_name: Some("host".into()),
+    ..crate::fixtures::lldp_du()
+  }));
+  for scope in [Scope::NearestNonTpmrBridge, Scope::NearestCustomerBridge] {
+    intf.remove_agent(scope).await;
+  }
+
+  let (tx, mut rx) = mpsc::unbounded_channel();
+  let task = tokio::spawn({
+    let intf = intf.clone();
+    async move { intf.run_tx(Channel(tx)).await }
+  });
+  let ttl = |frame: Vec<u8>| match lldp_parser::DataUnit::decode_frame(&frame).unwrap().1 {
+    lldp_parser::DataUnit::Lldp(du) => (du.time_to_live, du.system_name.is_some()),
+    x => panic!("unexpected {x:?}"),
+  };
+  assert_eq!(ttl(rx.recv().await.unwrap()), (121, true));
+
+  // just the msap and a ttl of 0, so the neighbor's removed right away
+  let mut agent = intf.agent(Scope::NearestBridge).unwrap();
+  agent.admin_status = AdminStatus::RxOnly;
+  intf.set_agent(Scope::NearestBridge, agent).await;
+  assert_eq!(ttl(rx.recv().await.unwrap()), (0, false));
+  task.abort();
+}
+
+#[cfg(feature = "capture")]
+#[tokio::test]
+async fn cdp_follows_port_and_admin_status() {
+  use tokio::sync::mpsc;
+
+  let config = crate::InterfaceConfig {
+    cdp_tx: CdpTxConfig {
+      msg_tx_interval: Duration::from_millis(10),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let intf = Interface::with_config(Default::default(), config);
+  intf.set_cdp_advertisement(Some(CdpDu {
+    time_to_live: 0,
+    device_id: Some("host".into()),
+    ..crate::fixtures::cdp_du()
+  }));
+  intf.set_port_enabled(false).await;
+
+  let (tx, mut rx) = mpsc::unbounded_channel();
+  let task = tokio::spawn({
+    let intf = intf.clone();
+    async move { intf.run_cdp_tx(Channel(tx)).await }
+  });
+  tokio::time::sleep(Duration::from_millis(50)).await;
+  assert!(rx.try_recv().is_err());
+
+  intf.set_port_enabled(true).await;
+  assert!(rx.recv().await.is_some());
+
+  let mut agent = intf.agent(Scope::NearestBridge).unwrap();
+  agent.admin_status = AdminStatus::RxOnly;
+  intf.set_agent(Scope::NearestBridge, agent).await;
+  tokio::time::sleep(Duration::from_millis(20)).await;
+  while rx.try_recv().is_ok() {}
+  tokio::time::sleep(Duration::from_millis(50)).await;
+  assert!(rx.try_recv().is_err());
+  task.abort();
+}
+
+#[test]
+fn selects_tlvs() {
+  use lldp_parser::lldp::tlv::CustomOrgTlv;
+
+  let du = LldpDu {
+    port_description: Some("uplink".into()),
+    system_name: Some("host".into()),
+    org: lldp_parser::lldp::du::Org {
+      dot1: lldp_parser::lldp::du::Dot1 {
+        port_vlan_id: Some(10),
+        vlan_name: Vec::new(),
+      },
+      dot3: Default::default(),
+      custom: vec![
+        CustomOrgTlv {
+          org: MED_OUI,
+          subtype: 1,
+          data: vec![0x00, 0x33, 0x04].into(),
+        },
+        CustomOrgTlv {
+          org: [0x00, 0x00, 0x5e],
+          subtype: 1,
+          data: vec![0x01].into(),
+        },
+      ],
+    },
+    ..crate::fixtures::lldp_du()
+  };
+
+  let mut all = du.clone();
+  TlvSelection::ALL.apply(&mut all);
+  assert_eq!(all, du);
+
+  let mut selected = du.clone();
+  TlvSelection {
+    system_name: true,
+    ..TlvSelection::NONE
+  }
+  .apply(&mut selected);
+  assert_eq!(selected.port_description, None);
+  assert_eq!(selected.system_name.as_deref(), Some("host"));
+  assert_eq!(selected.org.dot1.port_vlan_id, None);
+  // only the med tlvs are left out of the custom ones
+  assert_eq!(selected.org.custom.len(), 1);
+  assert_eq!(selected.org.custom[0].org, [0x00, 0x00, 0x5e]);
+}
+
+#[cfg(feature = "capture")]
+#[test]
+fn custom_tlvs_fit_the_mtu() {
+  let intf = Interface::default();
+  let tlv = |len| CustomOrgTlv {
+    org: [0x02, 0x00, 0x00],
+    subtype: 1,
+    data: vec![0; len].into(),
+  };
+  assert!(matches!(
+    intf.set_custom_tlvs(vec![tlv(508)]),
+    Err(CustomTlvError::TooLong { len: 508, .. })
+  ));
+
+  intf.set_advertisement(Some(LldpDu {
+    time_to_live: 0,
+    ..crate::fixtures::lldp_du()
+  }));
+  let budget = intf.tx_budget(Scope::NearestBridge).unwrap();
+  intf.set_custom_tlvs(vec![tlv(500), tlv(500)]).unwrap();
+  // each takes its data plus the tlv header, oui and subtype
+  assert_eq!(intf.tx_budget(Scope::NearestBridge), Some(budget - 2 * 506));
+  assert_eq!(
+    intf
+      .tx_du(Scope::NearestBridge, &TxConfig::default())
+      .unwrap()
+      .org
+      .custom
+      .len(),
+    2
+  );
+
+  assert!(matches!(
+    intf.set_custom_tlvs(vec![tlv(500), tlv(500), tlv(500)]),
+    Err(CustomTlvError::TooBig { .. })
+  ));
+  assert_eq!(intf.custom_tlvs().len(), 2);
+}